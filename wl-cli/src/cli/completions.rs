@@ -0,0 +1,57 @@
+//! Shell completion scripts.
+//!
+//! The static part (commands and flags) is generated by clap_complete. For bash, zsh and fish,
+//! a wrapper is appended that completes years for `show` and tags for `--tag` by asking the
+//! hidden `wl __complete` command for candidates from the current worldline.
+
+use clap::Command;
+use clap_complete::Shell;
+use std::io::Write;
+
+const BASH: &str = r#"
+_wl_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" prev="${COMP_WORDS[COMP_CWORD-1]}"
+    if [[ "$prev" == "--tag" ]]; then
+        COMPREPLY=($(compgen -W "$(wl __complete tags 2>/dev/null)" -- "$cur"))
+        return
+    fi
+    if [[ "${COMP_WORDS[1]}" == "show" || "${COMP_WORDS[1]}" == "s" ]] && [[ "$cur" != -* ]]; then
+        COMPREPLY=($(compgen -W "$(wl __complete years 2>/dev/null)" -- "$cur"))
+        return
+    fi
+    _wl "$@"
+}
+complete -F _wl_dynamic -o nosort -o bashdefault -o default wl
+"#;
+
+const ZSH: &str = r#"
+_wl_dynamic() {
+    if [[ ${words[CURRENT-1]} == --tag ]]; then
+        compadd -- ${(f)"$(wl __complete tags 2>/dev/null)"}
+        return
+    fi
+    if [[ ${words[2]} == (show|s) && ${words[CURRENT]} != -* ]]; then
+        compadd -V years -- ${(f)"$(wl __complete years 2>/dev/null)"}
+        return
+    fi
+    _wl "$@"
+}
+compdef _wl_dynamic wl
+"#;
+
+const FISH: &str = r#"
+complete -c wl -n "__fish_seen_subcommand_from show s" -f -a "(wl __complete years 2>/dev/null)"
+complete -c wl -n "__fish_seen_subcommand_from query q add a" -l tag -x -a "(wl __complete tags 2>/dev/null)"
+"#;
+
+/// Write the completion script for a shell.
+pub fn generate(shell: Shell, cmd: &mut Command, out: &mut impl Write) -> std::io::Result<()> {
+    clap_complete::generate(shell, cmd, "wl", out);
+    let dynamic = match shell {
+        Shell::Bash => BASH,
+        Shell::Zsh => ZSH,
+        Shell::Fish => FISH,
+        _ => "",
+    };
+    out.write_all(dynamic.as_bytes())
+}
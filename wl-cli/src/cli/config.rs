@@ -0,0 +1,59 @@
+//! Where the config file and the worldline file are, from the environment. The defaults
+//! follow the XDG base directory specification.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use wl::config::Config;
+
+/// A base directory from an XDG variable, e.g. `$XDG_DATA_HOME`, else `default` under the
+/// home directory. Relative paths in the variable are ignored, as the specification says.
+fn xdg_dir(var: &str, default: &str) -> Option<PathBuf> {
+    match env::var_os(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => Some(dir),
+        _ => Some(Path::new(&env::var_os("HOME")?).join(default)),
+    }
+}
+
+/// Where the config file is expected to be: `$WORLDLINE_CONFIG`, or
+/// `$XDG_CONFIG_HOME/worldline/config.toml` (`~/.config/worldline/config.toml` by default).
+pub fn default_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("WORLDLINE_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    Some(xdg_dir("XDG_CONFIG_HOME", ".config")?.join("worldline/config.toml"))
+}
+
+/// Load the config file from its default location. A missing file is an empty config.
+pub fn load() -> Result<Config, String> {
+    match default_path() {
+        Some(path) if path.exists() => Config::from_file(path),
+        _ => Ok(Config::default()),
+    }
+}
+
+/// The worldline file to use when none is given on the command line: `$WORLDLINE_FILE`,
+/// else `file` in the config, else `~/.worldline` if it exists, for worldlines created
+/// before wl followed the XDG directories, else `$XDG_DATA_HOME/worldline/worldline.txt`
+/// (`~/.local/share/worldline/worldline.txt` by default). `~/` is the home directory, and
+/// `-` is stdin and stdout. None without a home directory to look in.
+pub fn worldline_file(config: &Config) -> Option<String> {
+    if let Ok(file) = env::var("WORLDLINE_FILE") {
+        return Some(file);
+    }
+    let home = env::var("HOME").ok();
+    match config.get("", "file") {
+        Some(file) => match (file.strip_prefix("~/"), home) {
+            (Some(rest), Some(home)) => Some(Path::new(&home).join(rest).display().to_string()),
+            (Some(_), None) => None,
+            (None, _) => Some(file.to_string()),
+        },
+        None => {
+            let legacy = home.map(|home| Path::new(&home).join(".worldline"));
+            let path = match legacy {
+                Some(legacy) if legacy.exists() => legacy,
+                _ => xdg_dir("XDG_DATA_HOME", ".local/share")?.join("worldline/worldline.txt"),
+            };
+            Some(path.display().to_string())
+        }
+    }
+}
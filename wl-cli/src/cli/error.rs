@@ -0,0 +1,99 @@
+//! Reporting errors and exiting, with a distinct exit code for each kind of failure so that
+//! scripts can tell them apart. With `--json-errors`, errors are printed to stderr as JSON
+//! objects, e.g. `{"error":"file_missing","code":4,"message":"..."}`, instead of text.
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static JSON_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// What went wrong, which decides the exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// Anything not covered by the other kinds, e.g. a refused change.
+    Other,
+    /// Invalid arguments, as reported by clap.
+    Usage,
+    /// A date, query, config or the worldline file couldn't be read.
+    Parse,
+    /// The worldline file, or another file given to a command, doesn't exist.
+    FileMissing,
+    /// Nothing matched a query, event reference or dates.
+    NoMatches,
+    /// Another wl process is changing the worldline file.
+    Locked,
+}
+
+impl ErrorKind {
+    pub fn code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::Parse => 3,
+            ErrorKind::FileMissing => 4,
+            ErrorKind::NoMatches => 5,
+            ErrorKind::Locked => 6,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ErrorKind::Other => "other",
+            ErrorKind::Usage => "usage",
+            ErrorKind::Parse => "parse",
+            ErrorKind::FileMissing => "file_missing",
+            ErrorKind::NoMatches => "no_matches",
+            ErrorKind::Locked => "locked",
+        }
+    }
+
+    /// The kind of error reading a file: missing if it doesn't exist, unreadable otherwise.
+    pub fn reading(path: impl AsRef<std::path::Path>) -> Self {
+        if path.as_ref().exists() {
+            ErrorKind::Parse
+        } else {
+            ErrorKind::FileMissing
+        }
+    }
+}
+
+/// Print errors as JSON objects from now on.
+pub fn set_json_errors(json: bool) {
+    JSON_ERRORS.store(json, Ordering::Relaxed);
+}
+
+/// Whether errors are printed as JSON objects.
+pub fn json_errors() -> bool {
+    JSON_ERRORS.load(Ordering::Relaxed)
+}
+
+/// Print an error and exit with its kind's code. Further lines of the message are details,
+/// e.g. the events an ambiguous reference matches.
+pub fn fail(kind: ErrorKind, message: impl Display) -> ! {
+    let message = message.to_string();
+    if json_errors() {
+        eprintln!(
+            "{{\"error\":{},\"code\":{},\"message\":{}}}",
+            wl::json::quote(kind.name()),
+            kind.code(),
+            wl::json::quote(without_colors(&message).trim_end())
+        );
+    } else {
+        eprintln!("Error: {}", message.trim_end());
+    }
+    std::process::exit(kind.code())
+}
+
+/// Text without the escape sequences that color it in a terminal.
+fn without_colors(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{001B}' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
@@ -0,0 +1,188 @@
+//! Prompting for an event one field at a time, for `wl add -i`.
+//!
+//! Each answer is validated as soon as it's entered and asked for again if it's invalid.
+//!
+//! This is also where passphrases are asked for, conflicting events are resolved and files are
+//! opened in the user's editor.
+
+use std::io::{self, BufRead, Write};
+
+/// Ask until `parse` accepts the answer. Exits if stdin is closed.
+pub fn ask<T>(prompt: &str, parse: impl Fn(&str) -> Result<T, String>) -> T {
+    let mut stdin = io::stdin().lock();
+    loop {
+        print!("{}: ", prompt);
+        io::stdout().flush().ok();
+        let mut line = String::new();
+        if stdin.read_line(&mut line).unwrap_or(0) == 0 {
+            eprintln!();
+            eprintln!("Aborted");
+            std::process::exit(1);
+        }
+        match parse(line.trim()) {
+            Ok(value) => return value,
+            Err(e) => eprintln!("  {}", e),
+        }
+    }
+}
+
+/// A year such as "1969", "-44" or "44 BCE".
+fn parse_year(answer: &str) -> Result<i32, String> {
+    let (number, bce) = match answer.strip_suffix("BCE").or(answer.strip_suffix("bce")) {
+        Some(number) => (number.trim(), true),
+        None => (answer.strip_suffix("CE").unwrap_or(answer).trim(), false),
+    };
+    let year: i32 = number
+        .parse()
+        .map_err(|_| "Enter a year, e.g. 1969, -44 or 44 BCE".to_string())?;
+    let year = if bce { -year.saturating_abs() } else { year };
+    wl::Date::new(year, 0, 0).map(|_| year)
+}
+
+/// An optional number, where a blank answer is 0.
+fn parse_optional(answer: &str, what: &str) -> Result<u8, String> {
+    if answer.is_empty() {
+        return Ok(0);
+    }
+    answer
+        .parse()
+        .ok()
+        .filter(|n| *n != 0)
+        .ok_or_else(|| format!("Enter a {} number, or nothing to leave it out", what))
+}
+
+pub fn prompt_date() -> wl::Date {
+    let year = ask("Year (e.g. 1969 or 44 BCE)", parse_year);
+    let month = ask("Month (1-12, blank for none)", |answer| {
+        let month = parse_optional(answer, "month")?;
+        wl::Date::new(year, month, 0).map(|_| month)
+    });
+    if month == 0 {
+        return wl::Date::new(year, 0, 0).unwrap();
+    }
+    let days = wl::Date::days_in_month(year, month);
+    ask(&format!("Day (1-{}, blank for none)", days), |answer| {
+        wl::Date::new(year, month, parse_optional(answer, "day")?)
+    })
+}
+
+pub fn prompt_description() -> String {
+    ask("Description", |answer| {
+        if answer.is_empty() {
+            Err("The description can't be empty".to_string())
+        } else {
+            Ok(answer.to_string())
+        }
+    })
+}
+
+/// Ask a yes/no question, defaulting to yes.
+pub fn confirm(question: &str) -> bool {
+    ask(&format!("{} [Y/n]", question), |answer| {
+        match answer.to_lowercase().as_str() {
+            "" | "y" | "yes" => Ok(true),
+            "n" | "no" => Ok(false),
+            _ => Err("Answer y or n".to_string()),
+        }
+    })
+}
+
+/// Ask how to resolve an imported event conflicting with a similar one in the worldline:
+/// keep mine, take theirs, keep both, or edit theirs in $EDITOR to replace mine.
+pub fn resolve_conflict(
+    mine: &wl::Event,
+    theirs: &wl::Event,
+    display: &wl::display::DisplayOptions,
+) -> wl::similar::Resolution {
+    use wl::similar::Resolution;
+    println!(
+        "Conflicting events on {}:",
+        display.format_date(&mine.date, true).trim()
+    );
+    println!("  mine:   {}", mine.description);
+    println!("  theirs: {}", theirs.description);
+    loop {
+        let answer = ask(
+            "Keep [m]ine, take [t]heirs, keep [b]oth, or [e]dit theirs",
+            |answer| match answer.to_lowercase().as_str() {
+                "m" | "mine" => Ok(Some(Resolution::KeepMine)),
+                "t" | "theirs" => Ok(Some(Resolution::TakeTheirs)),
+                "b" | "both" => Ok(Some(Resolution::KeepBoth)),
+                "e" | "edit" => Ok(None),
+                _ => Err("Answer m, t, b or e".to_string()),
+            },
+        );
+        if let Some(resolution) = answer {
+            return resolution;
+        }
+        match edit_event(theirs) {
+            Ok(event) => return Resolution::Replace(Box::new(event)),
+            Err(e) => eprintln!("  {}", e),
+        }
+    }
+}
+
+/// Edit an event as a line of a v2 file in the user's editor.
+fn edit_event(event: &wl::Event) -> Result<wl::Event, String> {
+    let path = std::env::temp_dir().join(format!("wl-conflict-{}.wl", std::process::id()));
+    let worldline: wl::WorldLine = std::iter::once(event.clone()).collect();
+    std::fs::write(&path, worldline.range_lines(0..1).join("\n") + "\n")
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    let edited = edit_file(&path).and_then(|()| {
+        std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))
+    });
+    let _ = std::fs::remove_file(&path);
+    let mut edited_worldline: wl::WorldLine = std::iter::empty().collect();
+    edited_worldline
+        .add_events(edited?.lines())
+        .map_err(|errors| errors[0].1.clone())?;
+    match edited_worldline.events() {
+        [event] => Ok(event.clone()),
+        _ => Err("Edit the event into exactly one line".to_string()),
+    }
+}
+
+/// Ask for a passphrase without showing it, twice for a new one to catch typos.
+#[cfg(feature = "crypto")]
+pub fn ask_passphrase(new: bool) -> Result<String, String> {
+    let read = |prompt: &str| {
+        rpassword::prompt_password(prompt).map_err(|e| {
+            format!(
+                "Could not ask for the passphrase ({}); give a key file with --keyfile",
+                e
+            )
+        })
+    };
+    let passphrase = read(if new {
+        "New passphrase: "
+    } else {
+        "Passphrase: "
+    })?;
+    if passphrase.is_empty() {
+        return Err("The passphrase can't be empty".to_string());
+    }
+    if new && read("Confirm passphrase: ")? != passphrase {
+        return Err("The passphrases don't match".to_string());
+    }
+    Ok(passphrase)
+}
+
+/// Open a file in the user's editor ($VISUAL or $EDITOR, or vi) and wait for it to close.
+pub fn edit_file(path: &std::path::Path) -> Result<(), String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // the editor may have arguments, e.g. "code --wait"
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or("$EDITOR is empty")?;
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| format!("Could not run {}: {}", editor, e))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", editor, status));
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+//! Keeping two wl processes from changing the same worldline file at once, with an advisory
+//! lock on a `.lock` file next to it. The lock is released when the process exits.
+
+use super::error::{fail, ErrorKind};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+
+/// The lock on a worldline file, held until it's dropped.
+pub struct Lock {
+    _file: Option<File>,
+}
+
+/// The file locked while `worldline` is changed.
+pub fn lock_path(worldline: &Path) -> PathBuf {
+    let mut name = worldline.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// Lock the worldline file for a change, or exit with the code for lock contention if another
+/// process is changing it.
+pub fn lock(worldline: &Path) -> Lock {
+    let path = lock_path(worldline);
+    let file = match OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+    {
+        Ok(file) => file,
+        // the directory is missing, so there's no worldline to change; loading it says so
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Lock { _file: None },
+        Err(e) => fail(
+            ErrorKind::Other,
+            format!("Can't open {}: {}", path.display(), e),
+        ),
+    };
+    match file.try_lock() {
+        Ok(()) => Lock { _file: Some(file) },
+        Err(TryLockError::WouldBlock) => fail(
+            ErrorKind::Locked,
+            format!(
+                "{} is being changed by another wl process; try again when it's done",
+                worldline.display()
+            ),
+        ),
+        Err(TryLockError::Error(e)) => fail(
+            ErrorKind::Other,
+            format!("Can't lock {}: {}", path.display(), e),
+        ),
+    }
+}
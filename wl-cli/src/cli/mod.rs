@@ -0,0 +1,12 @@
+//! Command line interface helpers that aren't part of the library.
+
+pub mod completions;
+pub mod config;
+pub mod error;
+pub mod interactive;
+pub mod lock;
+pub mod output;
+pub mod pager;
+pub mod progress;
+#[cfg(feature = "watch")]
+pub mod watch;
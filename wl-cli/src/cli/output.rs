@@ -0,0 +1,128 @@
+//! How commands that list events print them: as text or a table (see [`wl::table`]) for
+//! people, or as JSON Lines (one object per event, see [`wl::convert`]) for scripts, e.g.
+//! `wl query war --output jsonl | jq`.
+
+use super::pager::print_lines;
+use clap::ValueEnum;
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLORS: AtomicBool = AtomicBool::new(false);
+
+/// When to color output: `Auto` colors it when printing to a terminal, unless $NO_COLOR is set.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+impl Color {
+    /// Whether output is colored, for printing to stdout.
+    pub fn enabled(self) -> bool {
+        match self {
+            // https://no-color.org
+            Color::Auto => {
+                std::io::stdout().is_terminal()
+                    && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+            }
+            Color::Always => true,
+            Color::Never => false,
+        }
+    }
+}
+
+/// Color output from now on, or not.
+pub fn set_colors(colors: bool) {
+    COLORS.store(colors, Ordering::Relaxed);
+}
+
+/// Whether to color output, e.g. diffs.
+pub fn colors() -> bool {
+    COLORS.load(Ordering::Relaxed)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    Text,
+    /// Aligned columns under headers
+    Table,
+    /// A table drawn with box-drawing lines
+    TableBox,
+    Jsonl,
+}
+
+impl Output {
+    /// Print a selection of events, e.g. query results.
+    pub fn print_events(&self, events: &[&wl::Event], options: &wl::display::DisplayOptions) {
+        print_lines(&self.lines(events, options));
+    }
+
+    /// The lines printed for a selection of events.
+    fn lines(&self, events: &[&wl::Event], options: &wl::display::DisplayOptions) -> Vec<String> {
+        match self {
+            Output::Text => options.format_events(events),
+            Output::Table | Output::TableBox => {
+                wl::table::render(events, options, *self == Output::TableBox)
+            }
+            Output::Jsonl => events.iter().map(|event| event.to_json()).collect(),
+        }
+    }
+
+    /// Print events found by a query, highlighting the matches in the text output.
+    pub fn print_matches(
+        &self,
+        events: &[&wl::Event],
+        expr: &wl::query::QueryExpr,
+        options: &wl::display::DisplayOptions,
+    ) {
+        match self {
+            Output::Text => print_lines(&wl::WorldLine::format_matches(events, expr, options)),
+            Output::Table | Output::TableBox | Output::Jsonl => self.print_events(events, options),
+        }
+    }
+
+    /// Print events found by a query with the `context` events before and after each, in
+    /// groups separated by "--" in the text output.
+    pub fn print_in_context(
+        &self,
+        worldline: &wl::WorldLine,
+        found: &[&wl::Event],
+        expr: &wl::query::QueryExpr,
+        context: usize,
+        options: &wl::display::DisplayOptions,
+    ) {
+        let mut lines = Vec::new();
+        for (i, range) in worldline
+            .context_ranges(found, context)
+            .into_iter()
+            .enumerate()
+        {
+            let events: Vec<&wl::Event> = worldline.events()[range].iter().collect();
+            if *self == Output::Jsonl {
+                lines.extend(self.lines(&events, options));
+                continue;
+            }
+            if i > 0 {
+                lines.push("--".to_string());
+            }
+            if *self != Output::Text {
+                lines.extend(self.lines(&events, options));
+                continue;
+            }
+            // only the events that were found are highlighted
+            let spans: Vec<_> = events
+                .iter()
+                .map(|event| {
+                    if found.iter().any(|f| std::ptr::eq(*f, *event)) {
+                        expr.match_spans(&event.description)
+                    } else {
+                        Vec::new()
+                    }
+                })
+                .collect();
+            lines.extend(options.format_highlighted(&events, &spans));
+        }
+        print_lines(&lines);
+    }
+}
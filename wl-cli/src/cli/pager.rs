@@ -0,0 +1,99 @@
+//! Progressive disclosure for `wl show` of huge ranges: a summary first, then the choice of
+//! printing everything, paging through it, or going back to narrow the range.
+//!
+//! Only used when both stdin and stdout are terminals, so piped output is unaffected.
+
+use super::interactive::ask;
+use std::io::{self, IsTerminal, Write};
+use std::ops::Range;
+
+/// Ranges with more events than this get a summary first, unless the config sets
+/// `display.pager_threshold` (0 to never summarize).
+pub const DEFAULT_THRESHOLD: usize = 1000;
+
+const SPARKLINE_WIDTH: usize = 60;
+
+/// Print the lines for a range of events, summarizing first if there are more than
+/// `threshold` of them and the user is at a terminal.
+pub fn show(
+    worldline: &wl::WorldLine,
+    range: Range<usize>,
+    threshold: usize,
+    lines: Vec<String>,
+    display: &wl::display::DisplayOptions,
+) {
+    let interactive = io::stdin().is_terminal() && io::stdout().is_terminal();
+    if !interactive || threshold == 0 || range.len() <= threshold {
+        print_lines(&lines);
+        return;
+    }
+
+    let first = worldline.get(range.start).unwrap();
+    let last = worldline.get(range.end - 1).unwrap();
+    println!(
+        "{} events from {} to {}",
+        range.len(),
+        display.format_date(&first.date, true).trim(),
+        display.format_date(&last.date, true).trim()
+    );
+    println!(
+        "  {}",
+        worldline.sparkline(range.start, range.end, SPARKLINE_WIDTH)
+    );
+    println!("First: {}", first.format_for_display(true, display));
+    println!("Last:  {}", last.format_for_display(true, display));
+
+    let choice = ask(
+        "Show [a]ll, [p]age through them, or [r]efine the range?",
+        |answer| match answer.to_lowercase().as_str() {
+            "a" | "all" => Ok('a'),
+            "p" | "page" => Ok('p'),
+            "r" | "refine" | "q" => Ok('r'),
+            _ => Err("Answer a, p or r".to_string()),
+        },
+    );
+    match choice {
+        'a' => print_lines(&lines),
+        'p' => page(&lines),
+        _ => eprintln!(
+            "Narrow the range with dates, e.g. wl show 1914 1918, or limit it with --first or --last"
+        ),
+    }
+}
+
+/// Print lines to stdout, without paging.
+pub fn print_lines(lines: &[String]) {
+    let mut stdout = io::stdout().lock();
+    for line in lines {
+        // stop quietly if the reader goes away
+        if writeln!(stdout, "{}", line).is_err() {
+            return;
+        }
+    }
+}
+
+/// Print a screenful at a time, waiting for Enter in between.
+fn page(lines: &[String]) {
+    let height = std::env::var("LINES")
+        .ok()
+        .and_then(|lines| lines.parse::<usize>().ok())
+        .unwrap_or(25)
+        .saturating_sub(1)
+        .max(1);
+    let mut chunks = lines.chunks(height).peekable();
+    let mut shown = 0;
+    while let Some(chunk) = chunks.next() {
+        print_lines(chunk);
+        shown += chunk.len();
+        if chunks.peek().is_none() {
+            break;
+        }
+        let more = ask(
+            &format!("-- {}/{} -- Enter for more, q to quit", shown, lines.len()),
+            |answer| Ok(!answer.eq_ignore_ascii_case("q")),
+        );
+        if !more {
+            break;
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Progress bars for long imports and merges, drawn on stderr when it's a terminal. Without
+//! the `progress` feature nothing is drawn.
+
+/// A [`wl::progress::Reporter`] drawing a bar. Clones share the bar, so one can be kept for
+/// [`Progress::suspend`] while the other is reported to.
+#[cfg(feature = "progress")]
+#[derive(Clone)]
+pub struct Progress(indicatif::ProgressBar);
+
+#[cfg(feature = "progress")]
+impl Progress {
+    /// Work on fewer items than this is over too quickly to need a bar.
+    const MIN_ITEMS: usize = 1000;
+
+    pub fn new() -> Self {
+        Progress(indicatif::ProgressBar::hidden())
+    }
+
+    /// Run `f` with the bar cleared, e.g. to ask the user something.
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.0.suspend(f)
+    }
+}
+
+#[cfg(feature = "progress")]
+impl wl::progress::Reporter for Progress {
+    fn start(&mut self, what: &str, total: usize) {
+        use indicatif::{ProgressDrawTarget, ProgressStyle};
+        use std::io::IsTerminal;
+
+        if total < Self::MIN_ITEMS || !std::io::stderr().is_terminal() {
+            return;
+        }
+        let style =
+            ProgressStyle::with_template("{msg} [{bar:40}] {human_pos}/{human_len} ({eta} left)")
+                .unwrap()
+                .progress_chars("=> ");
+        self.0.set_style(style);
+        self.0.set_message(what.to_string());
+        self.0.set_length(total as u64);
+        self.0.reset();
+        self.0.set_draw_target(ProgressDrawTarget::stderr());
+    }
+
+    fn advance(&mut self, n: usize) {
+        self.0.inc(n as u64);
+    }
+
+    fn finish(&mut self) {
+        self.0.finish_and_clear();
+        self.0
+            .set_draw_target(indicatif::ProgressDrawTarget::hidden());
+    }
+}
+
+#[cfg(not(feature = "progress"))]
+#[derive(Clone)]
+pub struct Progress;
+
+#[cfg(not(feature = "progress"))]
+impl Progress {
+    pub fn new() -> Self {
+        Progress
+    }
+
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+}
+
+#[cfg(not(feature = "progress"))]
+impl wl::progress::Reporter for Progress {}
@@ -0,0 +1,55 @@
+//! `wl show --watch`: printing the view again whenever the worldline file changes, e.g. while
+//! editing it in an editor side by side.
+//!
+//! Each view is printed by running wl again with the same arguments but without `--watch`, so
+//! that it reads the file afresh and looks exactly like a plain `wl show`, even when there are
+//! no events to show.
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Changes this close together are printed once, since editors often save in several steps.
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// Print the view, then again after each change to the file at `path`, until interrupted.
+pub fn watch(path: &Path) -> Result<(), String> {
+    let path = std::path::absolute(path).map_err(|e| e.to_string())?;
+    let dir = path.parent().ok_or("The worldline file has no directory")?;
+    let (sender, changes) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+    // editors often replace the file instead of writing to it, which ends a watch on the file
+    // itself, so its directory is watched instead
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Could not watch {}: {}", dir.display(), e))?;
+
+    let wl = std::env::current_exe().map_err(|e| e.to_string())?;
+    let args: Vec<String> = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != "--watch")
+        .collect();
+    loop {
+        if io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+            io::stdout().flush().map_err(|e| e.to_string())?;
+        }
+        Command::new(&wl)
+            .args(&args)
+            .status()
+            .map_err(|e| e.to_string())?;
+        loop {
+            let event = changes
+                .recv()
+                .map_err(|e| e.to_string())?
+                .map_err(|e| e.to_string())?;
+            if !matches!(event.kind, EventKind::Access(_)) && event.paths.contains(&path) {
+                break;
+            }
+        }
+        while changes.recv_timeout(SETTLE).is_ok() {}
+    }
+}
@@ -0,0 +1,3591 @@
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use cli::error::{fail, ErrorKind};
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use wl::filter::EventFilter;
+
+mod cli;
+
+#[derive(Parser)]
+#[command(
+    author,
+    version,
+    about = "Manipulate the worldline",
+    after_help = "Exit codes: 1 for other errors, 2 for invalid arguments, 3 when a date, query, \
+                  config or the worldline file can't be read, 4 when a file is missing, 5 when \
+                  nothing matches, and 6 when another wl process is changing the worldline"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+
+    /// The worldline file. Defaults to $WORLDLINE_FILE, file in the config, or
+    /// $XDG_DATA_HOME/worldline/worldline.txt (see wl paths). - is stdin and stdout
+    #[arg(long = "file", value_name = "FILE", global = true)]
+    worldline_file: Option<String>,
+
+    /// Operate on a named view (a filter defined in the config) instead of the whole worldline
+    #[arg(long, global = true)]
+    view: Option<String>,
+
+    /// How to display dates: padded, iso, long, or compact. Defaults to display.date_format in
+    /// the config, or padded
+    #[arg(long, global = true, value_parser = wl::display::DateFormat::parse)]
+    date_format: Option<wl::display::DateFormat>,
+
+    /// How to display years and months without a day: plain, or descriptive (e.g. "sometime
+    /// in 1845" or "March 1845"). Defaults to display.precision in the config, or plain
+    #[arg(long, global = true, value_parser = wl::display::PrecisionDisplay::parse)]
+    precision: Option<wl::display::PrecisionDisplay>,
+
+    /// When to show eras (BCE/CE) on listed dates: always, never, or auto (when any of them is
+    /// BCE). Defaults to display.era in the config, or auto
+    #[arg(long, global = true, value_parser = wl::display::EraDisplay::parse)]
+    era: Option<wl::display::EraDisplay>,
+
+    /// Fit listed events in N columns. Defaults to the terminal's width when printing to a
+    /// terminal
+    #[arg(long, global = true, value_name = "N")]
+    width: Option<usize>,
+
+    /// Wrap long descriptions onto more lines instead of truncating them
+    #[arg(long, global = true)]
+    wrap: bool,
+
+    /// When to color output: auto (when printing to a terminal and $NO_COLOR isn't set),
+    /// always or never
+    #[arg(long, global = true, value_enum, default_value_t = cli::output::Color::Auto)]
+    color: cli::output::Color,
+
+    /// Count years in the Holocene calendar (he), from a custom epoch (e.g. AUC=753 BCE) or
+    /// era in the config, or in BCE/CE (ce). Defaults to display.epoch in the config, or ce
+    #[arg(long, global = true)]
+    epoch: Option<String>,
+
+    /// Read negative years in dates given as arguments (but not in queries) historically (-44
+    /// is 44 BCE) or astronomically like ISO 8601 (-43 is 44 BCE, 0 is 1 BCE). Defaults to
+    /// display.year_numbering in the config, or historical
+    #[arg(long, global = true, value_parser = wl::epoch::YearNumbering::parse)]
+    year_numbering: Option<wl::epoch::YearNumbering>,
+
+    /// How the worldline is stored: text, sqlite or encrypted. Defaults to encrypted for files
+    /// encrypted with wl encrypt, sqlite for .db and .sqlite files (e.g. history.wl.db), and
+    /// text otherwise
+    #[arg(long, global = true, value_parser = wl::storage::Backend::parse)]
+    backend: Option<wl::storage::Backend>,
+
+    /// A file whose first line is the passphrase of an encrypted worldline. Defaults to
+    /// crypto.keyfile in the config, or else the passphrase is asked for
+    #[cfg(feature = "crypto")]
+    #[arg(long, global = true)]
+    keyfile: Option<PathBuf>,
+
+    /// Print what a command would change in the worldline instead of writing it
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Refuse commands that change the worldline, before they do anything, as when its file
+    /// isn't writable
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Save changes that remove more events at once than storage.max_removed in the config
+    /// allows (50 by default), without asking. Such saves are refused when not run in a
+    /// terminal
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Print errors to stderr as JSON objects with the kind of error, its exit code and a
+    /// message, for scripts
+    #[arg(long, global = true)]
+    json_errors: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Add a new event to the timeline
+    #[command(about = "Add a new event with date and description", alias = "a")]
+    Add {
+        #[arg(required_unless_present_any = ["from_file", "interactive"], allow_hyphen_values = true)]
+        date: Option<String>,
+        #[arg(required_unless_present_any = ["from_file", "interactive", "template"])]
+        description: Option<String>,
+        /// With --template, more values for its placeholders, before the date
+        #[arg(requires = "template")]
+        values: Vec<String>,
+        /// Prompt for the date and description, showing where the event will go before saving
+        #[arg(short, long, conflicts_with_all = ["date", "description", "from_file", "template"])]
+        interactive: bool,
+        /// Add every event line in a file (- for stdin) instead. Nothing is added if any line is invalid
+        #[arg(long, conflicts_with_all = ["date", "description", "id", "tags", "people", "series", "end", "sources", "seq", "force", "template"])]
+        from_file: Option<String>,
+        /// With --from-file, how to read lines that aren't in the file format: strict rejects
+        /// them, lenient reads dates written out like "July 14, 1789" and skips the rest (default:
+        /// strict)
+        #[arg(long, requires = "from_file", conflicts_with_all = ["date", "description", "interactive"], value_parser = wl::ParseMode::parse)]
+        parse_mode: Option<wl::ParseMode>,
+        /// Describe the event with a template from the [templates] section of the config, e.g.
+        /// birth = "Birth of {name}", giving values for its placeholders before the date:
+        /// wl add --template birth "Ada Lovelace" 1815-12-10
+        #[arg(long, value_name = "NAME")]
+        template: Option<String>,
+        /// Unique id for referring to the event
+        #[arg(long)]
+        id: Option<String>,
+        /// Tag the event (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// A person the event is about, e.g. "Julius Caesar" (repeatable)
+        #[arg(long = "person")]
+        people: Vec<String>,
+        /// The series of events the event is part of, e.g. "Punic Wars"
+        #[arg(long)]
+        series: Option<String>,
+        /// Date the event ended
+        #[arg(long, allow_hyphen_values = true)]
+        end: Option<String>,
+        /// Citation key for the event's source (repeatable)
+        #[arg(long = "source")]
+        sources: Vec<String>,
+        /// Place the event among events on the same date: lower numbers come first, and
+        /// before events without a number
+        #[arg(long, value_name = "N")]
+        seq: Option<u32>,
+        /// Add the event even if a similar one is already on the same date, without asking
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Display events from the timeline
+    #[command(
+        about = "Show events. No args = show all. One date = show that date/month/year. Two dates = show range",
+        alias = "s"
+    )]
+    Show {
+        /// A date, month or year to show, e.g. 1066 or -44, or the start of a range
+        #[arg(allow_hyphen_values = true)]
+        start: Option<String>,
+        /// The end of the range
+        #[arg(allow_hyphen_values = true)]
+        end: Option<String>,
+        /// Draw a ruler in the left margin showing how much time passes between events
+        #[arg(long)]
+        ruler: bool,
+        /// With --ruler, mark gaps between events at least this long, e.g. 50y
+        #[arg(long, default_value = "50y", requires = "ruler")]
+        ruler_gap: String,
+        /// Only show the N most recent events (of those in the range)
+        #[arg(long, value_name = "N", conflicts_with = "first")]
+        last: Option<usize>,
+        /// Only show the N oldest events (of those in the range)
+        #[arg(long, value_name = "N")]
+        first: Option<usize>,
+        /// Only show events in this category, e.g. war for events written "[war] ..."
+        #[arg(long)]
+        category: Option<String>,
+        /// Only show events about this person, e.g. "Julius Caesar": their own worldline
+        #[arg(long)]
+        person: Option<String>,
+        /// Only show the events in this series, e.g. "Punic Wars", under a line summing them up
+        #[arg(long)]
+        series: Option<String>,
+        /// Number events, to refer to them in other commands, e.g. wl delete 12. Numbers change
+        /// when events are added or removed
+        #[arg(long, conflicts_with = "ruler")]
+        numbered: bool,
+        /// Show events under a header for each century, decade, or year
+        #[arg(long, value_parser = wl::group::GroupBy::parse, conflicts_with = "ruler")]
+        group_by: Option<wl::group::GroupBy>,
+        /// Show one line for each century, decade, or year, with its number of events and the
+        /// first few of them, as an overview of a long range
+        #[arg(
+            long,
+            value_parser = wl::group::GroupBy::parse,
+            conflicts_with_all = ["ruler", "numbered", "group_by", "output"]
+        )]
+        summarize: Option<wl::group::GroupBy>,
+        /// With --summarize, the number of events to show for each century, decade, or year
+        #[arg(long, value_name = "N", default_value_t = 3, requires = "summarize")]
+        top: usize,
+        /// Print events as text, as a table with aligned columns (table, or table-box with
+        /// box-drawing lines), or as JSON Lines (one object per event) for scripts
+        #[arg(long, value_enum, default_value = "text")]
+        output: cli::output::Output,
+        /// Also show archived events
+        #[arg(long)]
+        archived: bool,
+        /// When there are no events on the dates, show the N nearest events before and after
+        /// them instead
+        #[arg(long, value_name = "N")]
+        context: Option<usize>,
+        /// Show another worldline file's events in a second column, lined up by date
+        #[arg(
+            long,
+            value_name = "OTHER_FILE",
+            conflicts_with_all = ["ruler", "numbered", "group_by", "summarize", "output", "context"]
+        )]
+        compare: Option<PathBuf>,
+        /// Write the events with an exporter instead of showing them, e.g. anki, json or csv
+        /// (see wl export --format)
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            value_parser = wl::export::exporter,
+            conflicts_with_all = ["ruler", "numbered", "group_by", "summarize", "output", "context", "compare"]
+        )]
+        export: Option<std::sync::Arc<dyn wl::export::Exporter>>,
+        /// With --export, the file to write the events to (- for stdout, the default)
+        #[arg(long, value_name = "FILE", requires = "export", default_value = "-")]
+        out: String,
+        /// Print the view again whenever the worldline file changes, e.g. while editing it,
+        /// until interrupted
+        #[cfg(feature = "watch")]
+        #[arg(long)]
+        watch: bool,
+    },
+
+    /// Search for events
+    #[command(
+        about = "Search for events containing text (case-insensitive) or matching an expression",
+        alias = "q"
+    )]
+    Query {
+        /// Text to search for, or an expression, e.g. "caesar AND (senate OR rubicon)"
+        #[arg(value_parser = wl::query::QueryExpr::parse, allow_hyphen_values = true)]
+        query: Option<wl::query::QueryExpr>,
+        /// Only show events with this tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Only show day precision events on this day of the week, e.g. friday or fri
+        #[arg(long, value_parser = wl::weekday::Weekday::parse)]
+        weekday: Option<wl::weekday::Weekday>,
+        /// Only show events within a range of dates, e.g. 1900..2000
+        #[arg(long, value_parser = wl::query::QueryExpr::range, allow_hyphen_values = true)]
+        range: Option<wl::query::QueryExpr>,
+        /// Only show events before (bce) or after (ce) the start of the Common Era
+        #[arg(long, value_name = "ERA", value_parser = wl::filter::Era::parse)]
+        in_era: Option<wl::filter::Era>,
+        /// Only show events in a century, e.g. 20 for 1901-2000, or -1 for 100-1 BCE
+        #[arg(long, allow_negative_numbers = true, value_parser = wl::filter::Century::parse)]
+        century: Option<wl::filter::Century>,
+        /// Also show the N events before and after each match, like grep -C
+        #[arg(long, short = 'C', value_name = "N")]
+        context: Option<usize>,
+        /// Print events as text, as a table with aligned columns (table, or table-box with
+        /// box-drawing lines), or as JSON Lines (one object per event) for scripts
+        #[arg(long, value_enum, default_value = "text")]
+        output: cli::output::Output,
+        /// Write the matching events with an exporter instead of showing them, e.g. anki, json
+        /// or csv (see wl export --format)
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            value_parser = wl::export::exporter,
+            conflicts_with_all = ["context", "output"]
+        )]
+        export: Option<std::sync::Arc<dyn wl::export::Exporter>>,
+        /// With --export, the file to write the events to (- for stdout, the default)
+        #[arg(long, value_name = "FILE", requires = "export", default_value = "-")]
+        out: String,
+    },
+
+    /// Compute the time between two events or dates
+    #[command(
+        about = "Show the time elapsed between two events (found by query) or dates",
+        alias = "b"
+    )]
+    Between {
+        #[arg(allow_hyphen_values = true)]
+        from: String,
+        #[arg(allow_hyphen_values = true)]
+        to: String,
+    },
+
+    /// Find periods with no events
+    #[command(about = "List the longest periods with no events, to find under-covered history")]
+    Gaps {
+        /// Only list gaps at least this many years long
+        #[arg(long, default_value_t = 50)]
+        min_years: i32,
+    },
+
+    /// Chart the density of events over time
+    #[command(
+        about = "Chart how many events there are over time, e.g. ▁▂█▅▃, with one bar per decade, century or other round number of years that fits the width"
+    )]
+    Sparkline {
+        /// One date = chart that date/month/year. Two dates = chart the range
+        #[arg(allow_hyphen_values = true)]
+        start: Option<String>,
+        /// The end of the range
+        #[arg(allow_hyphen_values = true)]
+        end: Option<String>,
+    },
+
+    /// Show an overview of the worldline
+    #[command(
+        about = "Show pinned events, the latest additions, anniversaries coming up and how many events there are, on one screen"
+    )]
+    Dashboard {
+        /// How many days ahead to look for anniversaries
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+        /// How many of the latest additions to show
+        #[arg(long, default_value_t = 5)]
+        recent: usize,
+    },
+
+    /// Attach a note to an event
+    #[command(
+        about = "Append a timestamped note to an event (found by number, id or query), or edit its Markdown note in $EDITOR without text"
+    )]
+    Note { event: String, text: Option<String> },
+
+    /// Show details of an event
+    #[command(
+        about = "Show all details of an event (found by number, id or query), including notes"
+    )]
+    Info { event: String },
+
+    /// Show the history of an event
+    #[command(
+        about = "Show when an event (found by number, id or query) was added and its earlier versions, from the journal of a v2 file"
+    )]
+    History { event: String },
+
+    /// Delete an event
+    #[command(about = "Delete an event (found by number, id or query)")]
+    Delete { event: String },
+
+    /// Archive an event
+    #[command(
+        about = "Hide an event from wl show without deleting it (found by number, id or query)"
+    )]
+    Archive {
+        event: String,
+        /// Show the event again
+        #[arg(long)]
+        restore: bool,
+    },
+
+    /// Pin an event to the dashboard
+    #[command(about = "Pin an event (found by number, id or query) to the top of wl dashboard")]
+    Pin {
+        event: String,
+        /// Unpin the event
+        #[arg(long)]
+        unpin: bool,
+    },
+
+    /// Change an event's date or description
+    #[command(
+        about = "Change the date, description or --seq number of an event (found by number, id or query)",
+        group = clap::ArgGroup::new("change").required(true).multiple(true)
+    )]
+    Edit {
+        event: String,
+        /// The new date
+        #[arg(long, group = "change", allow_hyphen_values = true)]
+        date: Option<String>,
+        /// The new description, which may include a category and place
+        #[arg(long, group = "change")]
+        description: Option<String>,
+        /// The event's new place among events on the same date (see wl add --seq)
+        #[arg(long, value_name = "N", group = "change")]
+        seq: Option<u32>,
+    },
+
+    /// Edit events as text in $EDITOR
+    #[command(
+        about = "Edit the events on the dates given (all for none, a date/month/year for one, or a range for two) in $EDITOR, then apply the changed, deleted and added lines"
+    )]
+    EditFile {
+        #[arg(allow_hyphen_values = true)]
+        start: Option<String>,
+        #[arg(allow_hyphen_values = true)]
+        end: Option<String>,
+        /// Apply the changes without showing them and asking first
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Move events' dates
+    #[command(
+        about = "Move the events matching a query by a number of years, months or days, e.g. to correct a systematic dating error"
+    )]
+    Shift {
+        /// Text to search for, or an expression, e.g. "dynasty X AND NOT tag:disputed"
+        #[arg(long, value_parser = wl::query::QueryExpr::parse)]
+        query: wl::query::QueryExpr,
+        /// Years to move by, e.g. +12 or -12
+        #[arg(long, allow_negative_numbers = true, default_value_t = 0)]
+        years: i32,
+        /// Months to move by
+        #[arg(long, allow_negative_numbers = true, default_value_t = 0)]
+        months: i32,
+        /// Days to move by
+        #[arg(long, allow_negative_numbers = true, default_value_t = 0)]
+        days: i32,
+        /// Move the events without showing them and asking first
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Choose how events on the same date are ordered
+    #[command(
+        about = "Show or set how events on the same date without --seq numbers are ordered: alphabetical or insertion (the order they were added in)"
+    )]
+    Order {
+        #[arg(value_parser = wl::order::SameDayOrder::parse)]
+        order: Option<wl::order::SameDayOrder>,
+        /// Sort years, months, quarters and weeks before the dates within them (first, the
+        /// default) or after them (last)
+        #[arg(long, value_name = "ORDER", value_parser = wl::order::PartialDateOrder::parse)]
+        partial_dates: Option<wl::order::PartialDateOrder>,
+    },
+
+    /// Validate the worldline file
+    #[command(
+        about = "Check the worldline file for unreadable lines, impossible dates, events out of order, duplicate ids, empty descriptions and broken links"
+    )]
+    Check {
+        /// Sort events that are out of order
+        #[arg(long)]
+        fix: bool,
+        /// Go through the events with blank descriptions, asking for a description for each
+        /// or deleting it
+        #[arg(long, conflicts_with = "fix")]
+        empty_descriptions: bool,
+    },
+
+    /// Find events that happened around the same time in two timelines
+    #[command(
+        about = "List pairs of events from this and another worldline that are close in time"
+    )]
+    Correlate {
+        /// The other worldline file
+        #[arg(long)]
+        file: String,
+        /// How close events must be, e.g. 10y, 6m, 2w, 30d
+        #[arg(long, default_value = "1y")]
+        window: String,
+    },
+
+    /// Compare the worldline to another file
+    #[command(
+        about = "Show events only in this worldline (-), only in another file (+), or changed"
+    )]
+    Diff {
+        /// The other worldline file
+        other_file: PathBuf,
+    },
+
+    /// Manage branches of the timeline
+    #[command(about = "Manage alternate branches of the worldline for drafts and counterfactuals")]
+    Branch {
+        #[command(subcommand)]
+        command: BranchCommand,
+    },
+
+    /// Manage the bibliography for events' sources
+    #[command(
+        about = "Resolve events' citation keys against a BibTeX file (e.g. a Zotero export)"
+    )]
+    Sources {
+        #[command(subcommand)]
+        command: SourcesCommand,
+    },
+
+    /// Import events from another worldline file
+    #[command(
+        about = "Add all events from another worldline file (- for stdin)",
+        args_conflicts_with_subcommands = true,
+        subcommand_negates_reqs = true
+    )]
+    Import {
+        #[command(subcommand)]
+        format: Option<ImportFormat>,
+        #[arg(required = true)]
+        file: Option<String>,
+        /// How to read lines of the file that aren't in the file format: strict rejects them,
+        /// lenient reads dates written out like "July 14, 1789" and skips the rest
+        #[arg(long, value_parser = wl::ParseMode::parse, default_value = "strict")]
+        parse_mode: wl::ParseMode,
+        /// Resolve events conflicting with similar ones on the same date without asking:
+        /// mine, theirs, or both. Without it, wl asks about each conflict in a terminal, and
+        /// keeps both otherwise
+        #[arg(long, global = true, value_parser = wl::similar::Strategy::parse)]
+        strategy: Option<wl::similar::Strategy>,
+    },
+
+    /// Generate a worksheet for students
+    #[command(
+        about = "Write a printable Markdown worksheet of random events with dates or descriptions blanked out, plus an answer key"
+    )]
+    Worksheet {
+        /// Only use events in a range, e.g. 1750..1800
+        #[arg(long, value_parser = wl::query::QueryExpr::range, allow_hyphen_values = true)]
+        range: Option<wl::query::QueryExpr>,
+        /// How many events to include
+        #[arg(short = 'n', long, default_value_t = 20)]
+        count: usize,
+        /// What to blank out
+        #[arg(long, value_enum, default_value = "dates")]
+        blank: WorksheetBlank,
+        /// Where to write the worksheet (default: stdout)
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// Where to write the answer key
+        #[arg(long)]
+        key: Option<PathBuf>,
+        /// Seed for choosing events, to reproduce a worksheet (default: random)
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+
+    /// List astronomical events
+    #[cfg(feature = "astro")]
+    #[command(
+        about = "List notable solar eclipses and appearances of Halley's comet, or add them to the worldline"
+    )]
+    Astro {
+        /// Only list events in a range, e.g. 1900..2000
+        #[arg(long, value_parser = wl::query::QueryExpr::range, allow_hyphen_values = true)]
+        range: Option<wl::query::QueryExpr>,
+        /// Add the events to the worldline instead of listing them
+        #[arg(long)]
+        import: bool,
+    },
+
+    /// Copy part of the worldline into a new file
+    #[command(
+        about = "Write the events in a date range (inclusive) to a new worldline file, leaving this one untouched"
+    )]
+    Extract {
+        #[arg(allow_hyphen_values = true)]
+        start: String,
+        #[arg(allow_hyphen_values = true)]
+        end: String,
+        /// The file to write, e.g. crusades.wl
+        #[arg(long)]
+        out: PathBuf,
+        /// Only extract events with this tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+        /// Only extract events matching a query, e.g. "crusade AND category:war"
+        #[arg(long, value_parser = wl::query::QueryExpr::parse)]
+        query: Option<wl::query::QueryExpr>,
+        /// Overwrite the file if it exists
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Split the worldline into topical files
+    #[command(
+        about = "Write a worldline file per tag or category to a directory, with an index.md linking to them, leaving this one untouched"
+    )]
+    Split {
+        /// Split by tag or category
+        #[arg(long, default_value = "tag", value_parser = wl::split::SplitBy::parse)]
+        by: wl::split::SplitBy,
+        /// The directory to write the files to
+        #[arg(long)]
+        out_dir: PathBuf,
+        /// Overwrite files that exist
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Convert between file formats
+    #[command(
+        about = "Convert a file to another format, chosen by extension (wl, json, jsonl, csv, ics)"
+    )]
+    Convert { input: PathBuf, output: PathBuf },
+
+    /// Create a new worldline file
+    #[command(
+        about = "Create a new worldline file, where --file, $WORLDLINE_FILE or the config say, or in $XDG_DATA_HOME/worldline"
+    )]
+    Init {
+        /// Start with the events of a template: a bundled one (see --list-templates), a
+        /// template file, or with the net feature a URL
+        #[arg(long, default_value = "blank")]
+        template: String,
+        /// List the bundled templates
+        #[arg(long, conflicts_with = "template")]
+        list_templates: bool,
+    },
+
+    /// Print a shell completion script
+    #[command(about = "Print a completion script for a shell, e.g. source <(wl completions bash)")]
+    Completions { shell: clap_complete::Shell },
+
+    /// Print a JSON Schema for the json export format
+    #[command(
+        about = "Print a JSON Schema describing the json export and import format, for checking generated files"
+    )]
+    Schema,
+
+    /// Print where the config and worldline files are
+    #[command(
+        about = "Print where the config file and the worldline file are, after following --file and the environment"
+    )]
+    Paths,
+
+    /// Print wl's version and the file format version it writes
+    Version {
+        /// Print them as a JSON object
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Completion candidates from the worldline, used by the completion scripts
+    #[command(name = "__complete", hide = true)]
+    Complete { kind: CompletionKind },
+
+    /// Upgrade the file format
+    #[command(about = "Upgrade the worldline file to the latest format version")]
+    Migrate,
+
+    /// Encrypt the worldline file with a passphrase, asked for or read from --keyfile
+    #[cfg(feature = "crypto")]
+    Encrypt,
+
+    /// Decrypt the worldline file, storing it as plain text again
+    #[cfg(feature = "crypto")]
+    Decrypt,
+
+    /// Export to anki file
+    #[command(
+        about = "Export to a file (- for stdout), by default one which is easily importable with Anki",
+        args_conflicts_with_subcommands = true,
+        subcommand_negates_reqs = true
+    )]
+    Export {
+        #[command(subcommand)]
+        format: Option<ExportFormat>,
+        #[arg(required = true)]
+        outfile: Option<String>,
+        /// The exporter to write the file with: anki, text, json, jsonl, csv or ics
+        #[arg(
+            long = "format",
+            value_name = "FORMAT",
+            default_value = "anki",
+            value_parser = wl::export::exporter
+        )]
+        exporter: std::sync::Arc<dyn wl::export::Exporter>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportFormat {
+    /// Import events from Markdown bullet lists, e.g. "- 1969-07-20: Moon landing" or
+    /// "* 44 BCE — Caesar assassinated"
+    Md {
+        /// The Markdown file (- for stdin)
+        file: String,
+    },
+    /// Import cards from an Anki text export, e.g. a deck written by wl export. The front of
+    /// each card is the date and the back is the description
+    Anki {
+        /// The exported deck (- for stdin)
+        file: String,
+    },
+    /// Import events from lines of a plain-text timeline matched by a regular expression, e.g.
+    /// --pattern '(?<y>\d+)\s*-\s*(?<desc>.+)' for "1066 - Battle of Hastings". Named groups
+    /// are the date (date, or y with optional m, d and era) and the description (desc)
+    Regex {
+        /// The text file (- for stdin)
+        file: String,
+        /// The regular expression, with named groups for the date and the description
+        #[arg(long, value_parser = wl::pattern::LinePattern::new)]
+        pattern: wl::pattern::LinePattern,
+        /// Show the event read from each matched line, and the lines skipped, without
+        /// importing anything
+        #[arg(long)]
+        preview: bool,
+    },
+    /// Import births, deaths and marriages from a GEDCOM genealogy file, with the names of the
+    /// people in them
+    #[cfg(feature = "gedcom")]
+    Gedcom {
+        /// The GEDCOM file (- for stdin)
+        file: String,
+        /// Tag each event with the people in it, e.g. ada-lovelace
+        #[arg(long)]
+        tag_people: bool,
+    },
+    /// Run a SPARQL query on Wikidata and import the results, e.g. treaties or eclipses. Each
+    /// result needs a ?date and an ?itemLabel, and optionally a ?precision
+    #[cfg(feature = "net")]
+    Wikidata {
+        /// The file with the query, e.g. treaties.rq
+        #[arg(long)]
+        query: PathBuf,
+        /// Add the events without showing them and asking first
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportFormat {
+    /// Export events with places as a GeoJSON FeatureCollection, to plot them on a map.
+    /// Named places are located with the [places] section of the config
+    Geojson {
+        /// The file to write (- for stdout)
+        outfile: String,
+    },
+    /// Export events as Markdown notes for Obsidian, with front matter and wiki-links between
+    /// adjacent notes
+    Obsidian {
+        /// The directory to write the notes to, e.g. a folder in a vault
+        #[arg(long)]
+        dir: PathBuf,
+        /// Write a note per year or per event
+        #[arg(long, default_value = "year", value_parser = wl::obsidian::NoteUnit::parse)]
+        per: wl::obsidian::NoteUnit,
+    },
+    /// Export events as a TikZ timeline for LaTeX documents, e.g. figures in papers
+    Latex {
+        /// The file to write (- for stdout)
+        outfile: String,
+        /// Only export events in a range, e.g. 1914..1918, which the axis covers
+        #[arg(long, value_parser = wl::query::QueryExpr::range, allow_hyphen_values = true)]
+        range: Option<wl::query::QueryExpr>,
+        /// Centimetres per year (default: fit the timeline to 15cm)
+        #[arg(long)]
+        scale: Option<f64>,
+        /// How far to rotate the labels from the axis, in degrees
+        #[arg(long, default_value_t = 45.0)]
+        rotate: f64,
+    },
+    /// Export events as an outline of centuries, decades and years with the events in each,
+    /// e.g. for course handouts
+    Outline {
+        /// The file to write (- for stdout)
+        outfile: String,
+        /// Only export events in a range, e.g. 1900..1999
+        #[arg(long, value_parser = wl::query::QueryExpr::range, allow_hyphen_values = true)]
+        range: Option<wl::query::QueryExpr>,
+        /// Write Markdown headings and lists instead of indented plain text
+        #[arg(long)]
+        markdown: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CompletionKind {
+    Tags,
+    Years,
+}
+
+impl Commands {
+    /// Whether the command writes to the worldline file.
+    fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            Commands::Add { .. }
+                | Commands::Note { .. }
+                | Commands::Delete { .. }
+                | Commands::Archive { .. }
+                | Commands::Pin { .. }
+                | Commands::Edit { .. }
+                | Commands::EditFile { .. }
+                | Commands::Shift { .. }
+                | Commands::Order { order: Some(_), .. }
+                | Commands::Order {
+                    partial_dates: Some(_),
+                    ..
+                }
+                | Commands::Import { .. }
+                | Commands::Migrate
+                | Commands::Sources {
+                    command: SourcesCommand::Link { .. }
+                }
+                | Commands::Check { fix: true, .. }
+                | Commands::Check {
+                    empty_descriptions: true,
+                    ..
+                }
+        ) || matches!(self, Commands::Branch { command } if command.is_mutating())
+            || self.is_astro_import()
+            || self.is_crypto_command()
+    }
+
+    #[cfg(feature = "astro")]
+    fn is_astro_import(&self) -> bool {
+        matches!(self, Commands::Astro { import: true, .. })
+    }
+
+    #[cfg(not(feature = "astro"))]
+    fn is_astro_import(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "crypto")]
+    fn is_crypto_command(&self) -> bool {
+        matches!(self, Commands::Encrypt | Commands::Decrypt)
+    }
+
+    #[cfg(not(feature = "crypto"))]
+    fn is_crypto_command(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Subcommand)]
+enum BranchCommand {
+    /// Fork the checked-out branch into a new branch
+    Create { name: String },
+    /// List branches, marking the checked-out one
+    List,
+    /// Check out a branch ("main" is the original file)
+    Switch { name: String },
+    /// Show events that differ between the checked-out branch and another
+    Diff { name: String },
+    /// Apply another branch's changes to the checked-out branch
+    Merge {
+        name: String,
+        /// Resolve events conflicting with similar ones on the same date without asking:
+        /// mine, theirs, or both. Without it, wl asks about each conflict in a terminal, and
+        /// keeps both otherwise
+        #[arg(long, value_parser = wl::similar::Strategy::parse)]
+        strategy: Option<wl::similar::Strategy>,
+    },
+}
+
+impl BranchCommand {
+    /// Whether the command writes to a branch's worldline file.
+    fn is_mutating(&self) -> bool {
+        matches!(
+            self,
+            BranchCommand::Create { .. } | BranchCommand::Merge { .. }
+        )
+    }
+}
+
+#[derive(Subcommand)]
+enum SourcesCommand {
+    /// Use a BibTeX file for resolving citation keys
+    Link { file: PathBuf },
+    /// List cited keys with their full references
+    List,
+    /// Report cited keys missing from the bibliography
+    Check,
+    /// Print a bibliography of all cited sources
+    Bibliography {
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: BibliographyFormat,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BibliographyFormat {
+    Markdown,
+    Latex,
+    Html,
+}
+
+impl From<BibliographyFormat> for wl::bib::Markup {
+    fn from(format: BibliographyFormat) -> Self {
+        match format {
+            BibliographyFormat::Markdown => wl::bib::Markup::Markdown,
+            BibliographyFormat::Latex => wl::bib::Markup::Latex,
+            BibliographyFormat::Html => wl::bib::Markup::Html,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum WorksheetBlank {
+    Dates,
+    Descriptions,
+}
+
+impl From<WorksheetBlank> for wl::worksheet::Blank {
+    fn from(blank: WorksheetBlank) -> Self {
+        match blank {
+            WorksheetBlank::Dates => wl::worksheet::Blank::Dates,
+            WorksheetBlank::Descriptions => wl::worksheet::Blank::Descriptions,
+        }
+    }
+}
+
+/// Where the worldline is read from and written back to. A file name of "-" means the
+/// worldline is read from stdin and written to stdout.
+enum Location {
+    Stdio,
+    File {
+        path: PathBuf,
+        backend: wl::storage::Backend,
+        storage: Box<dyn wl::storage::Storage>,
+    },
+    /// With --dry-run, saving prints the changes from the worldline as it was loaded instead.
+    DryRun {
+        location: Box<Location>,
+        original: wl::WorldLine,
+    },
+    /// A v2 file whose changes are stamped on the events and recorded in its journal.
+    Journaled {
+        location: Box<Location>,
+        original: wl::WorldLine,
+        journal: wl::journal::Journal,
+    },
+}
+
+impl Location {
+    fn load(&self) -> Result<wl::WorldLine, String> {
+        match self {
+            Location::Stdio => wl::WorldLine::from_reader(io::stdin().lock()),
+            Location::File { storage, .. } => storage.load(),
+            Location::DryRun { location, .. } | Location::Journaled { location, .. } => {
+                location.load()
+            }
+        }
+    }
+
+    fn save(&self, worldline: &wl::WorldLine) -> Result<(), String> {
+        match self {
+            Location::Stdio => worldline
+                .write_to(io::stdout().lock())
+                .map_err(|e| e.to_string()),
+            Location::File { storage, .. } => storage.save(worldline),
+            Location::DryRun { location, original } => {
+                print_changes(&wl::diff::ChangeSet::between(original, worldline));
+                eprintln!("Dry run: {} was not changed", location);
+                Ok(())
+            }
+            Location::Journaled {
+                location,
+                original,
+                journal,
+            } => {
+                let now = wl::timestamp::Timestamp::now();
+                let mut stamped = worldline.clone();
+                stamped.stamp_changes(original, now);
+                location.save(&stamped)?;
+                journal.record(&original.diff(&stamped), now)
+            }
+        }
+    }
+
+    /// Check that the worldline could be saved, before changing it.
+    fn check_writable(&self) -> Result<(), String> {
+        match self {
+            Location::Stdio | Location::DryRun { .. } => Ok(()),
+            Location::File { storage, .. } => storage.check_writable(),
+            Location::Journaled { location, .. } => location.check_writable(),
+        }
+    }
+
+    /// Whether the worldline is in a file rather than piped through stdin and stdout, so
+    /// commands can print to stdout after saving it.
+    fn is_file(&self) -> bool {
+        matches!(self, Location::File { .. } | Location::Journaled { .. })
+    }
+
+    /// How to say what a command did: `done`, or `would` with --dry-run, when nothing is saved.
+    fn said<'a>(&self, done: &'a str, would: &'a str) -> &'a str {
+        if matches!(self, Location::DryRun { .. }) {
+            would
+        } else {
+            done
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::Stdio => write!(f, "stdin"),
+            Location::File { path, .. } => write!(f, "{}", path.display()),
+            Location::DryRun { location, .. } | Location::Journaled { location, .. } => {
+                write!(f, "{}", location)
+            }
+        }
+    }
+}
+
+/// Save the worldline, or exit with an error if it can't be written.
+fn save_or_fail(location: &Location, worldline: &wl::WorldLine) {
+    if let Err(e) = location.save(worldline) {
+        fail(
+            ErrorKind::Other,
+            format!("Could not write worldline file: {}", e),
+        )
+    }
+}
+
+/// Read a file to import, or stdin for "-".
+fn read_import_text(location: &Location, file: &str) -> String {
+    let mut contents = String::new();
+    let result = if file == "-" {
+        if let Location::Stdio = location {
+            fail(
+                ErrorKind::Other,
+                "Can't import from stdin when the worldline is read from it",
+            )
+        }
+        io::stdin().lock().read_to_string(&mut contents)
+    } else {
+        std::fs::File::open(file).and_then(|mut f| f.read_to_string(&mut contents))
+    };
+    result.map(|_| contents).unwrap_or_else(|e| {
+        fail(
+            ErrorKind::reading(file),
+            format!("Could not read {}: {}", file, e),
+        )
+    })
+}
+
+/// The bundled astronomical events, optionally only those in a range.
+#[cfg(feature = "astro")]
+fn astro_events(range: Option<&wl::query::QueryExpr>) -> Vec<wl::Event> {
+    wl::astro::events()
+        .into_iter()
+        .filter(|e| range.is_none_or(|range| range.matches(e)))
+        .collect()
+}
+
+/// How dates given on the command line are read: negative years with the --year-numbering
+/// chosen, and month names and eras with the display locale and the eras in the config.
+#[derive(Debug, Clone, Default)]
+struct DateInput {
+    numbering: wl::epoch::YearNumbering,
+    locale: wl::locale::Locale,
+    eras: Vec<wl::epoch::Epoch>,
+}
+
+/// Parse a string starting with a date in the file format.
+fn parse_strict(date_str: &str, input: &DateInput) -> Result<(wl::Date, usize), String> {
+    wl::Date::parse_in(date_str, input.numbering)
+}
+
+/// Parse a date given on the command line. With the holidays feature, symbolic dates like
+/// "easter 1525" are accepted too, and with the calendars feature Hebrew and Islamic dates like
+/// "15 Nisan 5784 AM".
+fn parse_date(date_str: &str, input: &DateInput) -> wl::Date {
+    let date = parse_strict(date_str, input).map(|(date, _)| date);
+    #[cfg(feature = "holidays")]
+    let date = date.or_else(|e| wl::holidays::parse_symbolic(date_str).map_err(|_| e));
+    // before the strict format, which would read "30 Iyar 5785 AM" as the year 30
+    #[cfg(feature = "calendars")]
+    let date = wl::calendars::parse(date_str).unwrap_or(date);
+    date.unwrap_or_else(|e| {
+        fail(
+            ErrorKind::Parse,
+            format!("Could not parse date '{}': {}", date_str, e),
+        )
+    })
+}
+
+/// The dates given to `wl show`, `wl sparkline` or `wl edit-file`: none, one or two. They are
+/// separate arguments rather than one with two values so that flags can follow a negative
+/// year, as in `wl show -44 --last 3`.
+fn date_args(start: &Option<String>, end: &Option<String>) -> Vec<String> {
+    start.iter().chain(end).cloned().collect()
+}
+
+/// The events `wl show` shows: those on the dates given (all for none, a date/month/year for
+/// one, or a range for two), limited by --first or --last. `date_range` finds the events
+/// between two dates, of `len` events.
+fn show_range(
+    dates: &[String],
+    first: Option<usize>,
+    last: Option<usize>,
+    len: usize,
+    input: &DateInput,
+    date_range: impl Fn(&wl::range::DateRange) -> std::ops::Range<usize>,
+) -> std::ops::Range<usize> {
+    let mut range = match dates {
+        [] => 0..len,
+        [date] => date_range(&wl::range::DateRange::of(parse_date(date, input))),
+        [start, end] => date_range(&wl::range::DateRange::new(
+            parse_date(start, input),
+            parse_date(end, input),
+        )),
+        _ => unreachable!(),
+    };
+    if let Some(n) = last {
+        range.start = range.start.max(range.end.saturating_sub(n));
+    }
+    if let Some(n) = first {
+        range.end = range.end.min(range.start + n);
+    }
+    range
+}
+
+/// Say that there are no events to show, and exit with the code for no matches.
+fn no_events() -> ! {
+    if cli::error::json_errors() {
+        fail(ErrorKind::NoMatches, "No events")
+    }
+    println!("No events");
+    std::process::exit(ErrorKind::NoMatches.code())
+}
+
+/// How many events `wl show` prints before asking whether to page, from the config.
+fn pager_threshold() -> usize {
+    let threshold = cli::config::load().and_then(|config| {
+        config.get("display", "pager_threshold").map_or(
+            Ok(cli::pager::DEFAULT_THRESHOLD),
+            |threshold| {
+                threshold
+                    .parse()
+                    .map_err(|_| format!("Invalid display.pager_threshold: {}", threshold))
+            },
+        )
+    });
+    threshold.unwrap_or_else(|e| fail(ErrorKind::Parse, e))
+}
+
+/// `wl show` for a text worldline file read through a memory map, which only parses the
+/// events it shows. Only plain listings (without --ruler, --category and so on) are shown
+/// this way.
+#[cfg(feature = "mmap")]
+fn show_mapped(
+    path: &std::path::Path,
+    dates: &[String],
+    first: Option<usize>,
+    last: Option<usize>,
+    archived: bool,
+    input: &DateInput,
+    display: &wl::display::DisplayOptions,
+) -> Result<(), String> {
+    let file = wl::mapped::MappedFile::open(path)?;
+    let mut events = file.events()?;
+    if !archived {
+        events.retain(|event| !event.is_archived());
+    }
+    let order = file.partial_date_order();
+    let range = show_range(dates, first, last, events.len(), input, |range| {
+        wl::mapped::date_range(&events, range, order)
+    });
+    let shown = file.worldline(&events[range])?;
+    if shown.is_empty() {
+        no_events();
+    }
+    let lines = shown.format_range(0, shown.len(), display);
+    cli::pager::show(&shown, 0..shown.len(), pager_threshold(), lines, display);
+    Ok(())
+}
+
+/// Parse the date of a new event. Besides what [`parse_date`] accepts, dates written out in
+/// English or the display locale like "March 15, 44 BC" are read with
+/// [`wl::Date::parse_natural_in`], after confirming
+/// how they were read if possible. Returns None if the reading was rejected.
+fn parse_new_date(
+    date_str: &str,
+    can_prompt: bool,
+    input: &DateInput,
+    display: &wl::display::DisplayOptions,
+) -> Option<wl::Date> {
+    let strict = parse_strict(date_str, input)
+        .ok()
+        .filter(|(_, idx)| date_str[*idx..].trim().is_empty());
+    if let Some((date, _)) = strict {
+        return Some(date);
+    }
+    let date = wl::Date::parse_natural_in(date_str, input.locale, &input.eras);
+    #[cfg(feature = "calendars")]
+    let date = match wl::calendars::parse(date_str) {
+        Some(Err(e)) => fail(
+            ErrorKind::Parse,
+            format!("Could not parse date '{}': {}", date_str, e),
+        ),
+        parsed => parsed.unwrap_or(date),
+    };
+    let Ok(date) = date else {
+        return Some(parse_date(date_str, input));
+    };
+    let reading = format!(
+        "Read '{}' as {}",
+        date_str,
+        display.format_date(&date, true).trim()
+    );
+    if can_prompt {
+        cli::interactive::confirm(&format!("{}. Add it?", reading)).then_some(date)
+    } else {
+        eprintln!("{}", reading);
+        Some(date)
+    }
+}
+
+/// Resolve a command line argument to a single event, by id or by description (see
+/// [`wl::WorldLine::find`]), returning its index.
+fn resolve_event(
+    worldline: &wl::WorldLine,
+    reference: &str,
+    display: &wl::display::DisplayOptions,
+) -> usize {
+    let matches = worldline.find(reference);
+    match matches.as_slice() {
+        [idx] => *idx,
+        [] => fail(
+            ErrorKind::NoMatches,
+            format!("'{}' matches no events", reference),
+        ),
+        _ => {
+            let mut message = format!("'{}' matches {} events:", reference, matches.len());
+            for idx in matches {
+                message += "\n";
+                message += &worldline
+                    .get(idx)
+                    .unwrap()
+                    .format_for_display(true, display);
+            }
+            fail(ErrorKind::Other, message)
+        }
+    }
+}
+
+/// Resolve a command line argument to a date, either by parsing it as one or by finding the
+/// single event it refers to.
+fn resolve_date(
+    worldline: &wl::WorldLine,
+    arg: &str,
+    input: &DateInput,
+    display: &wl::display::DisplayOptions,
+) -> wl::Date {
+    if let Ok((date, idx)) = parse_strict(arg, input) {
+        if arg[idx..].trim().is_empty() {
+            println!("{}", display.format_date(&date, true).trim_end());
+            return date;
+        }
+    }
+
+    let event = worldline
+        .get(resolve_event(worldline, arg, display))
+        .unwrap();
+    println!("{}", event.format_for_display(true, display));
+    event.date.clone()
+}
+
+/// Print everything known about an event.
+fn print_info(event: &wl::Event, display: &wl::display::DisplayOptions) {
+    println!("{}", event.format_for_display(true, display));
+    if let Some(id) = &event.id {
+        println!("  id:      {}", id);
+    }
+    if !event.tags.is_empty() {
+        println!("  tags:    {}", event.tags.join(", "));
+    }
+    if !event.people.is_empty() {
+        println!("  people:  {}", event.people.join(", "));
+    }
+    if let Some(series) = &event.series {
+        println!("  series:  {}", series);
+    }
+    if let Some(weekday) = event.date.weekday() {
+        println!("  weekday: {}", weekday);
+    }
+    #[cfg(feature = "holidays")]
+    for holiday in wl::holidays::on(&event.date) {
+        println!("  holiday: {}", holiday);
+    }
+    if let Some(end) = &event.end {
+        println!("  end:     {}", display.format_date(end, true).trim_end());
+    }
+    if !event.sources.is_empty() {
+        println!("  sources: {}", event.sources.join(", "));
+    }
+    if let Some(seq) = event.seq {
+        println!("  seq:     {}", seq);
+    }
+    if event.archived {
+        println!("  archived (hidden from wl show)");
+    }
+    if event.pinned {
+        println!("  pinned (shown by wl dashboard)");
+    }
+    if let Some(created) = event.created {
+        println!("  created: {}", created);
+    }
+    if let Some(modified) = event.modified {
+        println!("  changed: {}", modified);
+    }
+    if let Some(id) = &event.id {
+        if display.has_note(id) {
+            println!(
+                "  note:    {} (open it with wl note {})",
+                wl::display::NOTE_MARKER,
+                id
+            );
+        }
+    }
+    if !event.notes.is_empty() {
+        println!("  notes:");
+        for note in &event.notes {
+            println!("    {}  {}", note.timestamp, note.text);
+        }
+    }
+}
+
+/// Print `wl dashboard`: counts of the worldline's events, then its pinned events, the latest
+/// additions and the anniversaries in the next `days` days, leaving out archived events.
+fn print_dashboard(
+    worldline: &wl::WorldLine,
+    days: u32,
+    recent: usize,
+    display: &wl::display::DisplayOptions,
+) {
+    use wl::display::DateFormatter;
+
+    let plural =
+        |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+    let counts = worldline.counts();
+    println!(
+        "{}: {} pinned, {} archived, {}, {}, {}, {}",
+        plural(counts.events, "event", "events"),
+        counts.pinned,
+        counts.archived,
+        plural(counts.categories, "category", "categories"),
+        plural(counts.tags, "tag", "tags"),
+        plural(counts.people, "person", "people"),
+        plural(counts.series, "series", "series"),
+    );
+
+    let worldline = worldline.without_archived();
+    let section = |title: &str, lines: Vec<String>, none: &str| {
+        println!();
+        println!("── {} ──", title);
+        if lines.is_empty() {
+            println!("{}", none);
+        }
+        cli::pager::print_lines(&lines);
+    };
+    section(
+        "Pinned",
+        display.format_events(&worldline.pinned()),
+        "No pinned events; pin one with wl pin",
+    );
+    section(
+        "Latest additions",
+        display.format_events(&worldline.recently_added(recent)),
+        "No events with the time they were added, which v2 files record",
+    );
+    let today = wl::timestamp::Timestamp::now().date();
+    let anniversaries = worldline
+        .upcoming_anniversaries(&today, days)
+        .iter()
+        .map(|a| {
+            let date = wl::display::DateFormat::Long.format(&a.date, false, display);
+            let years = plural(a.years as usize, "year", "years");
+            format!("{}: {} since {}", date, years, a.event.description)
+        })
+        .collect();
+    section(
+        "Coming up",
+        anniversaries,
+        &format!("No anniversaries in the next {} days", days),
+    );
+}
+
+/// Print a line of a diff, marked as removed (left, in red at a terminal) or added (right, in
+/// green).
+fn print_diff_line(side: wl::diff::Side, line: &str) {
+    let (marker, ansi) = match side {
+        wl::diff::Side::Left => ("-", "\u{001B}[31m"),
+        wl::diff::Side::Right => ("+", "\u{001B}[32m"),
+    };
+    if cli::output::colors() {
+        println!("{}{}\u{001B}[0m {}", ansi, marker, line);
+    } else {
+        println!("{} {}", marker, line);
+    }
+}
+
+/// Print a diff in date order.
+fn print_diff(report: &wl::diff::DiffReport, display: &wl::display::DisplayOptions) {
+    if report.is_empty() {
+        println!("No differences");
+    }
+    for (side, event) in report.unified() {
+        print_diff_line(side, &event.format_for_display(true, display));
+    }
+}
+
+/// Print the changes a command would make to the worldline file, for --dry-run.
+fn print_changes(changes: &wl::diff::ChangeSet) {
+    if changes.is_empty() {
+        println!("No changes");
+    }
+    for (side, line) in changes.lines() {
+        print_diff_line(side, &line);
+    }
+}
+
+/// How `wl import` and `wl branch merge` resolve events conflicting with similar ones: with
+/// the --strategy given, else by asking if they can, else by keeping both.
+fn conflict_resolver(
+    strategy: Option<wl::similar::Strategy>,
+    can_prompt: bool,
+    display: &wl::display::DisplayOptions,
+) -> impl FnMut(&wl::Event, &wl::Event) -> wl::similar::Resolution + '_ {
+    move |mine, theirs| match strategy {
+        Some(strategy) => strategy.resolution(),
+        None if can_prompt => cli::interactive::resolve_conflict(mine, theirs, display),
+        None => wl::similar::Resolution::KeepBoth,
+    }
+}
+
+/// Import events for `wl import`, resolving conflicts (see [`conflict_resolver`]) with a
+/// progress bar for long imports, and save the worldline.
+fn import_events(
+    location: &Location,
+    mut worldline: wl::WorldLine,
+    events: wl::WorldLine,
+    strategy: Option<wl::similar::Strategy>,
+    display: &wl::display::DisplayOptions,
+) {
+    let can_prompt = io::stdin().is_terminal() && !matches!(location, Location::Stdio);
+    let mut resolve = conflict_resolver(strategy, can_prompt, display);
+    let mut progress = cli::progress::Progress::new();
+    let bar = progress.clone();
+    let report = worldline.import_reporting(
+        events,
+        |mine, theirs| bar.suspend(|| resolve(mine, theirs)),
+        &mut progress,
+    );
+    save_or_fail(location, &worldline);
+    if report.replaced > 0 {
+        eprintln!(
+            "{} {} events, replacing {}",
+            location.said("Imported", "Would import"),
+            report.added,
+            report.replaced
+        );
+    } else {
+        eprintln!(
+            "{} {} events",
+            location.said("Imported", "Would import"),
+            report.added
+        );
+    }
+    let mut skipped = Vec::new();
+    if report.duplicates > 0 {
+        skipped.push(format!("{} duplicates", report.duplicates));
+    }
+    if report.skipped > 0 {
+        skipped.push(format!("{} conflicting events", report.skipped));
+    }
+    if !skipped.is_empty() {
+        eprintln!("Skipped {}", skipped.join(" and "));
+    }
+}
+
+/// Print the warnings about lines of a file read in [`wl::ParseMode::Lenient`].
+fn print_parse_warnings(file: &str, warnings: Vec<(usize, String)>) {
+    if warnings.is_empty() {
+        return;
+    }
+    eprintln!("{} lines weren't in the file format:", warnings.len());
+    for (line, warning) in warnings {
+        eprintln!("  {}:{}: {}", file, line, warning);
+    }
+}
+
+fn print_branch_diff(
+    branches: &wl::branch::Branches,
+    name: &str,
+    display: &wl::display::DisplayOptions,
+) -> Result<(), String> {
+    let current = branches.load(&branches.current())?;
+    let other = branches.load(name)?;
+    print_diff(&current.diff(&other), display);
+    Ok(())
+}
+
+fn run_branch_command(
+    branches: &wl::branch::Branches,
+    command: BranchCommand,
+    dry_run: bool,
+    display: &wl::display::DisplayOptions,
+) {
+    let result = match command {
+        BranchCommand::Merge { name, strategy } if dry_run => {
+            let resolve = conflict_resolver(strategy, io::stdin().is_terminal(), display);
+            branches.merged(&name, resolve).and_then(|(merged, _)| {
+                let current = branches.load(&branches.current())?;
+                print_changes(&wl::diff::ChangeSet::between(&current, &merged));
+                eprintln!("Dry run: nothing was merged");
+                Ok(())
+            })
+        }
+        BranchCommand::Create { .. } | BranchCommand::Switch { .. } if dry_run => {
+            Err("--dry-run only applies to branch merge".to_string())
+        }
+        BranchCommand::Create { name } => branches.create(&name).map(|_| {
+            println!(
+                "Created branch '{}'. Check it out with: wl branch switch {}",
+                name, name
+            )
+        }),
+        BranchCommand::List => {
+            let current = branches.current();
+            for name in branches.list() {
+                let marker = if name == current { "*" } else { " " };
+                println!("{} {}", marker, name);
+            }
+            Ok(())
+        }
+        BranchCommand::Switch { name } => branches
+            .switch(&name)
+            .map(|_| println!("Switched to branch '{}'", name)),
+        BranchCommand::Diff { name } => print_branch_diff(branches, &name, display),
+        BranchCommand::Merge { name, strategy } => {
+            let mut resolve = conflict_resolver(strategy, io::stdin().is_terminal(), display);
+            let mut progress = cli::progress::Progress::new();
+            let bar = progress.clone();
+            let resolve =
+                |mine: &wl::Event, theirs: &wl::Event| bar.suspend(|| resolve(mine, theirs));
+            branches
+                .merge_reporting(&name, resolve, &mut progress)
+                .map(|summary| {
+                    print!(
+                        "Merged '{}': {} events added, {} removed",
+                        name, summary.added, summary.removed
+                    );
+                    if summary.replaced > 0 {
+                        print!(", {} replaced", summary.replaced);
+                    }
+                    println!();
+                })
+        }
+    };
+
+    if let Err(e) = result {
+        fail(ErrorKind::Other, e)
+    }
+}
+
+/// Open the worldline file with a backend, asking for the passphrase of encrypted ones.
+#[cfg(feature = "crypto")]
+fn open_storage(
+    backend: wl::storage::Backend,
+    path: &std::path::Path,
+    cli: &Cli,
+) -> Result<Box<dyn wl::storage::Storage>, String> {
+    match backend {
+        wl::storage::Backend::Encrypted => Ok(Box::new(wl::crypto::EncryptedFile::new(
+            path.to_path_buf(),
+            passphrase(cli.keyfile.as_deref(), false)?,
+        ))),
+        _ => backend.open(path),
+    }
+}
+
+#[cfg(not(feature = "crypto"))]
+fn open_storage(
+    backend: wl::storage::Backend,
+    path: &std::path::Path,
+    _cli: &Cli,
+) -> Result<Box<dyn wl::storage::Storage>, String> {
+    backend.open(path)
+}
+
+/// Guard storage against saves removing more events than storage.max_removed in the config
+/// allows, asking whether to go ahead with them in a terminal and refusing them otherwise.
+fn guard_truncation(storage: Box<dyn wl::storage::Storage>) -> wl::storage::TruncationGuard {
+    let max_removed = cli::config::load().and_then(|config| {
+        config.get("storage", "max_removed").map_or(
+            Ok(wl::storage::DEFAULT_MAX_REMOVED),
+            |max_removed| {
+                max_removed
+                    .parse()
+                    .map_err(|_| format!("Invalid storage.max_removed: {}", max_removed))
+            },
+        )
+    });
+    let max_removed = max_removed.unwrap_or_else(|e| fail(ErrorKind::Parse, e));
+    wl::storage::TruncationGuard::new(storage, max_removed).confirm_with(|truncation| {
+        if !io::stdin().is_terminal() {
+            return Err(format!("{}; use --force to save anyway", truncation));
+        }
+        eprintln!("Warning: {}", truncation);
+        let question = format!(
+            "Save anyway, leaving {} of {} events?",
+            truncation.saved, truncation.stored
+        );
+        if cli::interactive::confirm(&question) {
+            Ok(())
+        } else {
+            Err("cancelled, nothing was changed".to_string())
+        }
+    })
+}
+
+/// Write a worldline with an exporter to a file, or stdout for "-".
+fn export_to(worldline: &wl::WorldLine, exporter: &dyn wl::export::Exporter, outfile: &str) {
+    let result = if outfile == "-" {
+        exporter.export(worldline, &mut io::stdout().lock())
+    } else {
+        std::fs::File::create(outfile)
+            .map_err(|e| e.to_string())
+            .and_then(|file| exporter.export(worldline, &mut io::BufWriter::new(file)))
+    };
+    if let Err(e) = result {
+        fail(
+            ErrorKind::Other,
+            format!("Could not export to {}: {}", outfile, e),
+        )
+    }
+}
+
+/// Exit with an error reading the worldline file, with a hint if it's missing.
+fn fail_reading(location: &Location, worldline_file: &str, e: String) -> ! {
+    let mut message = format!(
+        "Could not read worldline file: {}\nExpected to find a worldline file at {}",
+        e, location
+    );
+    let kind = ErrorKind::reading(worldline_file);
+    if kind == ErrorKind::FileMissing {
+        message += "\nCreate one with wl init, or choose another with --file";
+    }
+    fail(kind, message)
+}
+
+/// `wl edit-file`: edit the events on some dates in $EDITOR as lines of a v2 file, then
+/// replace them with the edited lines, after showing the changes unless `yes` is set.
+fn run_edit_file(
+    location: &Location,
+    mut worldline: wl::WorldLine,
+    dates: &[String],
+    input: &DateInput,
+    yes: bool,
+) {
+    if let Location::Stdio = location {
+        fail(
+            ErrorKind::Other,
+            "Can't edit events in an editor when the worldline is read from stdin",
+        )
+    }
+    let range = show_range(dates, None, None, worldline.len(), input, |range| {
+        worldline.date_range(range)
+    });
+    let path = std::env::temp_dir().join(format!("wl-edit-{}.wl", std::process::id()));
+    let mut contents = format!(
+        "# {} events. Change, delete or add lines, then save and close to apply them.\n\
+         # Lines starting with # are ignored.\n",
+        range.len()
+    );
+    for line in worldline.range_lines(range.clone()) {
+        contents += &line;
+        contents += "\n";
+    }
+    if let Err(e) = std::fs::write(&path, contents) {
+        fail(
+            ErrorKind::Other,
+            format!("Could not write {}: {}", path.display(), e),
+        )
+    }
+    let original = worldline.clone();
+    let edited = loop {
+        let text = cli::interactive::edit_file(&path)
+            .and_then(|()| std::fs::read_to_string(&path).map_err(|e| e.to_string()));
+        let text = text.unwrap_or_else(|e| {
+            let _ = std::fs::remove_file(&path);
+            fail(ErrorKind::Other, e)
+        });
+        let mut edited = original.clone();
+        match edited.replace_range(range.clone(), text.lines()) {
+            Ok(_) => break edited,
+            Err(errors) => {
+                for (line, e) in errors {
+                    eprintln!("{}:{}: {}", path.display(), line, e);
+                }
+                if !cli::interactive::confirm("Edit again?") {
+                    let _ = std::fs::remove_file(&path);
+                    fail(ErrorKind::Parse, "Nothing was changed")
+                }
+            }
+        }
+    };
+    let _ = std::fs::remove_file(&path);
+    worldline = edited;
+
+    let changes = wl::diff::ChangeSet::between(&original, &worldline);
+    if changes.is_empty() {
+        println!("No changes");
+        return;
+    }
+    if !yes {
+        print_changes(&changes);
+        if !cli::interactive::confirm("Apply these changes?") {
+            eprintln!("Nothing was changed");
+            return;
+        }
+    }
+    save_or_fail(location, &worldline);
+}
+
+/// Print a line of `wl paths`.
+fn print_path(name: &str, path: Option<PathBuf>) {
+    match path {
+        None => println!("{:<10} none, there's no home directory", name),
+        Some(path) if path == Path::new("-") => println!("{:<10} stdin and stdout", name),
+        Some(path) if !path.exists() => println!("{:<10} {} (missing)", name, path.display()),
+        Some(path) => println!("{:<10} {}", name, path.display()),
+    }
+}
+
+/// Create a new worldline file with the events of a template, refusing to replace an existing
+/// one.
+fn run_init(worldline_file: &str, template: &str, cli: &Cli) {
+    if worldline_file == "-" {
+        fail(ErrorKind::Other, "wl init needs a file, not stdin")
+    }
+    let path = Path::new(worldline_file);
+    if path.exists() {
+        fail(
+            ErrorKind::Other,
+            format!("{} already exists", path.display()),
+        )
+    }
+    let pack = wl::templates::load(template).unwrap_or_else(|e| fail(ErrorKind::Parse, e));
+    let mut worldline = wl::WorldLine::default();
+    let added = pack.apply(&mut worldline);
+    let result = cli
+        .backend
+        .map_or_else(|| wl::storage::Backend::from_path(path), Ok)
+        .and_then(|backend| {
+            // e.g. the worldline directory under $XDG_DATA_HOME
+            match path.parent() {
+                Some(dir) if !cli.dry_run && !dir.as_os_str().is_empty() => {
+                    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?
+                }
+                _ => {}
+            }
+            open_storage(backend, path, cli)
+        })
+        .and_then(|storage| {
+            if cli.dry_run {
+                return Ok(());
+            }
+            storage.save(&worldline)
+        });
+    let events = match added {
+        0 => String::new(),
+        1 => " with 1 event".to_string(),
+        n => format!(" with {} events", n),
+    };
+    match result {
+        Ok(()) if cli.dry_run => eprintln!("Dry run: {} was not created", path.display()),
+        Ok(()) => eprintln!("Created {}{}", path.display(), events),
+        Err(e) => fail(
+            ErrorKind::Other,
+            format!("Could not create {}: {}", path.display(), e),
+        ),
+    }
+}
+
+/// The passphrase of an encrypted worldline, from the key file given with --keyfile or in
+/// the config, or else asked for (twice for a new one).
+#[cfg(feature = "crypto")]
+fn passphrase(keyfile: Option<&std::path::Path>, new: bool) -> Result<String, String> {
+    let keyfile = match keyfile {
+        Some(keyfile) => Some(keyfile.to_path_buf()),
+        None => cli::config::load()?
+            .get("crypto", "keyfile")
+            .map(PathBuf::from),
+    };
+    match keyfile {
+        Some(keyfile) => wl::crypto::read_keyfile(keyfile),
+        None => cli::interactive::ask_passphrase(new),
+    }
+}
+
+/// Encrypt the worldline file with a new passphrase, or decrypt it back to plain text.
+#[cfg(feature = "crypto")]
+fn run_crypto_command(
+    location: &Location,
+    worldline: &wl::WorldLine,
+    encrypt: bool,
+    main_file: &str,
+    keyfile: Option<&std::path::Path>,
+    dry_run: bool,
+) {
+    use wl::storage::{Backend, Storage};
+
+    let fail = |e: String| -> ! { fail(ErrorKind::Other, e) };
+    let Location::File { path, backend, .. } = location else {
+        fail("Only worldline files can be encrypted".to_string());
+    };
+    let storage: Box<dyn Storage> = match (encrypt, backend) {
+        (true, Backend::Text) if *path != std::path::Path::new(main_file) => {
+            fail("Check out the main branch before encrypting".to_string())
+        }
+        (true, Backend::Text) => Box::new(wl::crypto::EncryptedFile::new(
+            path.clone(),
+            passphrase(keyfile, true).unwrap_or_else(|e| fail(e)),
+        )),
+        (true, Backend::Encrypted) => fail(format!("{} is already encrypted", location)),
+        (true, _) => fail("Only text worldline files can be encrypted".to_string()),
+        (false, Backend::Encrypted) => Box::new(wl::storage::TextFile { path: path.clone() }),
+        (false, _) => fail(format!("{} isn't encrypted", location)),
+    };
+    let action = if encrypt { "encrypt" } else { "decrypt" };
+    if dry_run {
+        eprintln!("Would {} {}", action, location);
+        return;
+    }
+    if let Err(e) = storage.save(worldline) {
+        fail(format!("Could not {} {}: {}", action, location, e));
+    }
+    if encrypt && wl::branch::Branches::for_file(main_file).list().len() > 1 {
+        eprintln!(
+            "Warning: The other branches of {} aren't encrypted",
+            location
+        );
+    }
+    eprintln!(
+        "{} {}",
+        if encrypt { "Encrypted" } else { "Decrypted" },
+        location
+    );
+}
+
+/// Report problems in the worldline file, and with `fix`, sort its events if they're out of
+/// order. Exits with an error if any problems remain.
+fn run_check(location: Location, fix: bool, dry_run: bool) {
+    let contents = match &location {
+        Location::Stdio => {
+            let mut contents = String::new();
+            io::stdin()
+                .read_to_string(&mut contents)
+                .map(|_| Some(contents))
+        }
+        Location::File {
+            path,
+            backend: wl::storage::Backend::Text,
+            ..
+        } => std::fs::read_to_string(path).map(Some),
+        _ => Ok(None),
+    };
+    let contents = contents.unwrap_or_else(|e| {
+        let kind = match e.kind() {
+            io::ErrorKind::NotFound => ErrorKind::FileMissing,
+            _ => ErrorKind::Other,
+        };
+        fail(kind, format!("Could not read worldline file: {}", e))
+    });
+    let loaded = contents
+        .as_deref()
+        .map_or_else(|| location.load(), str::parse::<wl::WorldLine>);
+    let issues = match &contents {
+        Some(contents) => wl::check::check(contents),
+        None => match &loaded {
+            Ok(worldline) => worldline.validate(),
+            Err(e) => fail(
+                ErrorKind::Parse,
+                format!("Could not read worldline file: {}", e),
+            ),
+        },
+    };
+    let out_of_order = issues
+        .iter()
+        .filter(|issue| matches!(issue, wl::check::Issue::OutOfOrder { .. }))
+        .count();
+    // stdout has the worldline itself when piping
+    let to_stderr = matches!(location, Location::Stdio);
+    let report = |issues: &[wl::check::Issue]| {
+        for issue in issues {
+            if to_stderr {
+                eprintln!("{}", issue);
+            } else {
+                println!("{}", issue);
+            }
+        }
+    };
+    // the worldline is always written back to stdout when piping
+    if fix && (out_of_order > 0 || to_stderr) {
+        let Ok(mut worldline) = loaded else {
+            report(&issues);
+            fail(
+                ErrorKind::Parse,
+                "Can't sort the events until every line can be read",
+            )
+        };
+        worldline.sort_by_date();
+        if dry_run {
+            // sorting isn't a change to the set of events, so there's no diff to show
+            eprintln!(
+                "Dry run: {} events that are out of order were not sorted",
+                out_of_order
+            );
+        } else {
+            save_or_fail(&location, &worldline);
+        }
+        if !dry_run && out_of_order > 0 {
+            eprintln!("Sorted {} events that were out of order", out_of_order);
+        }
+        // with the line numbers after sorting
+        let remaining = worldline.validate();
+        if !remaining.is_empty() {
+            report(&remaining);
+            std::process::exit(1);
+        }
+        return;
+    }
+    if issues.is_empty() {
+        eprintln!("No problems found in {}", location);
+        return;
+    }
+    report(&issues);
+    std::process::exit(1);
+}
+
+fn run_sources_command(
+    worldline: &mut wl::WorldLine,
+    location: &Location,
+    command: SourcesCommand,
+    display: &wl::display::DisplayOptions,
+) -> Result<(), String> {
+    if let SourcesCommand::Link { file } = command {
+        let path = file
+            .canonicalize()
+            .map_err(|e| format!("Could not find {}: {}", file.display(), e))?;
+        let bibliography = wl::bib::Bibliography::from_file(&path)?;
+        worldline.set_front_matter(wl::bib::FRONT_MATTER_KEY, path.display().to_string());
+        location
+            .save(worldline)
+            .map_err(|e| format!("Could not write worldline file: {}", e))?;
+        let cited = worldline.cited_keys().len();
+        let dangling = worldline.dangling_keys(&bibliography);
+        eprintln!(
+            "Linked {} ({} entries): {} of {} cited keys resolved",
+            path.display(),
+            bibliography.len(),
+            cited - dangling.len(),
+            cited
+        );
+        for key in dangling {
+            eprintln!("Warning: '{}' is not in the bibliography", key);
+        }
+        return Ok(());
+    }
+
+    let bibliography = worldline.bibliography()?;
+    match command {
+        SourcesCommand::List => {
+            for key in worldline.cited_keys() {
+                match bibliography.get(key) {
+                    Some(entry) => println!("{}: {}", key, entry.format(wl::bib::Markup::Markdown)),
+                    None => println!("{}: (missing)", key),
+                }
+            }
+        }
+        SourcesCommand::Check => {
+            let dangling = worldline.dangling_keys(&bibliography);
+            if !dangling.is_empty() {
+                for key in &dangling {
+                    for event in worldline.citing(key) {
+                        eprintln!("{}: {}", key, event.format_for_display(true, display));
+                    }
+                }
+                return Err(format!(
+                    "{} citation keys are missing from the bibliography",
+                    dangling.len()
+                ));
+            }
+            println!("All citation keys resolve");
+        }
+        SourcesCommand::Bibliography { format } => {
+            print!(
+                "{}",
+                bibliography.render(&worldline.cited_keys(), format.into())
+            );
+        }
+        SourcesCommand::Link { .. } => unreachable!(),
+    }
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::try_parse().unwrap_or_else(|e| {
+        // the flag can't be read from arguments that don't parse
+        if e.use_stderr() && std::env::args().any(|arg| arg == "--json-errors") {
+            cli::error::set_json_errors(true);
+            let message = e.to_string();
+            let first_line = message.lines().next().unwrap_or_default();
+            fail(
+                ErrorKind::Usage,
+                first_line.strip_prefix("error: ").unwrap_or(first_line),
+            )
+        }
+        e.exit()
+    });
+    cli::error::set_json_errors(cli.json_errors);
+    cli::output::set_colors(cli.color.enabled());
+
+    if let Commands::Completions { shell } = cli.command {
+        if let Err(e) = cli::completions::generate(shell, &mut Cli::command(), &mut io::stdout()) {
+            fail(
+                ErrorKind::Other,
+                format!("Could not write completions: {}", e),
+            )
+        }
+        return;
+    }
+    match cli.command {
+        Commands::Schema => {
+            print!("{}", wl::schema::json_schema());
+            return;
+        }
+        Commands::Version { json } => {
+            let version = env!("CARGO_PKG_VERSION");
+            let format_version = wl::FormatVersion::LATEST.number();
+            if json {
+                println!(
+                    "{{\"version\":\"{}\",\"format_version\":{}}}",
+                    version, format_version
+                );
+            } else {
+                println!("wl {}", version);
+                println!("File format: v{}", format_version);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let display = cli::config::load().and_then(|config| {
+        let date_format = match cli.date_format {
+            Some(format) => format,
+            None => config
+                .get("display", "date_format")
+                .map_or(Ok(wl::display::DateFormat::default()), |format| {
+                    wl::display::DateFormat::parse(format)
+                })?,
+        };
+        let precision = match cli.precision {
+            Some(precision) => precision,
+            None => config
+                .get("display", "precision")
+                .map_or(Ok(wl::display::PrecisionDisplay::default()), |precision| {
+                    wl::display::PrecisionDisplay::parse(precision)
+                })?,
+        };
+        let eras = config.eras()?;
+        let epoch = cli
+            .epoch
+            .as_deref()
+            .or_else(|| config.get("display", "epoch"))
+            .map_or(Ok(wl::epoch::Epoch::default()), |epoch| {
+                wl::epoch::Epoch::parse_in(epoch, &eras)
+            })?;
+        let year_numbering = match cli.year_numbering {
+            Some(numbering) => numbering,
+            None => config.get("display", "year_numbering").map_or(
+                Ok(wl::epoch::YearNumbering::default()),
+                wl::epoch::YearNumbering::parse,
+            )?,
+        };
+        let locale = config
+            .get("display", "locale")
+            .map_or(Ok(wl::locale::Locale::default()), wl::locale::Locale::parse)?;
+        let category_colors = config
+            .category_colors()?
+            .into_iter()
+            .map(|(category, color)| (category.to_lowercase(), color))
+            .collect();
+        let era = match cli.era {
+            Some(era) => era,
+            None => config
+                .get("display", "era")
+                .map_or(Ok(wl::display::EraDisplay::default()), |era| {
+                    wl::display::EraDisplay::parse(era)
+                })?,
+        };
+        let width = cli.width.or_else(|| {
+            io::stdout()
+                .is_terminal()
+                .then(terminal_size::terminal_size)
+                .flatten()
+                .map(|(terminal_size::Width(width), _)| width as usize)
+        });
+        let highlight = config
+            .get("display", "highlight")
+            .map_or(Ok(wl::display::Highlight::default()), |highlight| {
+                wl::display::Highlight::parse(highlight)
+            })?;
+        let display = wl::display::DisplayOptions {
+            era,
+            width,
+            wrap: cli.wrap,
+            highlight,
+            formatter: std::sync::Arc::new(date_format),
+            epoch,
+            precision,
+            locale,
+            ansi: cli::output::colors(),
+            category_colors,
+            noted_ids: Default::default(),
+        };
+        let input = DateInput {
+            numbering: year_numbering,
+            locale,
+            eras,
+        };
+        Ok((display, input))
+    });
+    let (mut display, input) = display.unwrap_or_else(|e| fail(ErrorKind::Parse, e));
+
+    if let Commands::Convert { input, output } = &cli.command {
+        match wl::convert::convert(input, output) {
+            Ok(losses) => {
+                for loss in losses {
+                    eprintln!("Warning: {} in {}", loss, output.display());
+                }
+            }
+            Err(e) => fail(ErrorKind::Other, e),
+        }
+        return;
+    }
+
+    #[cfg(feature = "astro")]
+    if let Commands::Astro {
+        range,
+        import: false,
+    } = &cli.command
+    {
+        for event in astro_events(range.as_ref()) {
+            println!("{}", event.format_for_display(true, &display));
+        }
+        return;
+    }
+
+    if let Commands::Paths = cli.command {
+        let worldline_file = cli.worldline_file.clone().or_else(|| {
+            cli::config::load()
+                .ok()
+                .and_then(|config| cli::config::worldline_file(&config))
+        });
+        print_path("config", cli::config::default_path());
+        print_path("worldline", worldline_file.map(PathBuf::from));
+        return;
+    }
+
+    let worldline_file = match cli.worldline_file.clone() {
+        Some(file) => file,
+        None => cli::config::load()
+            .ok()
+            .and_then(|config| cli::config::worldline_file(&config))
+            .unwrap_or_else(|| {
+                fail(
+                    ErrorKind::FileMissing,
+                    "No worldline file; set one with --file or $WORLDLINE_FILE",
+                )
+            }),
+    };
+
+    if cli.read_only && !cli.dry_run && cli.command.is_mutating() {
+        fail(
+            ErrorKind::Usage,
+            "This command changes the worldline, which --read-only prevents",
+        )
+    }
+
+    if let Commands::Init {
+        template,
+        list_templates,
+    } = &cli.command
+    {
+        if *list_templates {
+            for template in wl::templates::TEMPLATES {
+                println!("{:<16} {}", template.name, template.description);
+            }
+            return;
+        }
+        return run_init(&worldline_file, template, &cli);
+    }
+
+    #[cfg(feature = "watch")]
+    if let Commands::Show { watch: true, .. } = &cli.command {
+        if worldline_file == "-" {
+            fail(ErrorKind::Usage, "--watch needs a file, not stdin")
+        }
+        if let Err(e) = cli::watch::watch(Path::new(&worldline_file)) {
+            fail(ErrorKind::Other, e)
+        }
+        return;
+    }
+
+    let location = if worldline_file == "-" {
+        Location::Stdio
+    } else {
+        let backend = cli
+            .backend
+            .map_or_else(|| wl::storage::Backend::from_path(&worldline_file), Ok)
+            .unwrap_or_else(|e| fail(ErrorKind::Other, e));
+        let path = if backend == wl::storage::Backend::Text {
+            let branches = wl::branch::Branches::for_file(&worldline_file);
+            if let Commands::Branch { command } = cli.command {
+                return run_branch_command(&branches, command, cli.dry_run, &display);
+            }
+            branches.current_file()
+        } else if let Commands::Branch { .. } = cli.command {
+            fail(
+                ErrorKind::Other,
+                "Branches are only supported for text worldline files",
+            )
+        } else {
+            PathBuf::from(&worldline_file)
+        };
+        let mut storage =
+            open_storage(backend, &path, &cli).unwrap_or_else(|e| fail(ErrorKind::Other, e));
+        if !cli.force {
+            storage = Box::new(guard_truncation(storage));
+        }
+        Location::File {
+            path,
+            backend,
+            storage,
+        }
+    };
+    let command = cli.command;
+    if command.is_mutating() && !cli.dry_run {
+        if let Err(e) = location.check_writable() {
+            fail(
+                ErrorKind::Other,
+                format!("Can't change the worldline: {}", e),
+            )
+        }
+    }
+    let _lock = match &location {
+        Location::File { path, .. } if command.is_mutating() && !cli.dry_run => {
+            Some(cli::lock::lock(path))
+        }
+        _ => None,
+    };
+
+    // check reads the file itself, to report lines that would stop it from loading
+    if let Commands::Check {
+        fix,
+        empty_descriptions: false,
+    } = command
+    {
+        return run_check(location, fix, cli.dry_run);
+    }
+
+    if worldline_file != "-" {
+        display.noted_ids = wl::notes::NoteDir::for_file(&worldline_file).ids();
+    }
+
+    #[cfg(feature = "mmap")]
+    if let (
+        Location::File {
+            path,
+            backend: wl::storage::Backend::Text,
+            ..
+        },
+        Commands::Show {
+            start,
+            end,
+            ruler: false,
+            last,
+            first,
+            category: None,
+            person: None,
+            series: None,
+            numbered: false,
+            group_by: None,
+            summarize: None,
+            output: cli::output::Output::Text,
+            archived,
+            context: None,
+            compare: None,
+            export: None,
+            ..
+        },
+        None,
+    ) = (&location, &command, &cli.view)
+    {
+        if let Err(e) = show_mapped(
+            path,
+            &date_args(start, end),
+            *first,
+            *last,
+            *archived,
+            &input,
+            &display,
+        ) {
+            fail_reading(&location, &worldline_file, e);
+        }
+        return;
+    }
+
+    let mut worldline = location
+        .load()
+        .unwrap_or_else(|e| fail_reading(&location, &worldline_file, e));
+
+    if let Some(view) = &cli.view {
+        if command.is_mutating() {
+            fail(
+                ErrorKind::Other,
+                "Views are read-only; run without --view to modify the worldline",
+            )
+        }
+        let expr = cli::config::load().and_then(|config| config.view(view));
+        match expr {
+            Ok(expr) => worldline = worldline.filtered(&expr),
+            Err(e) => fail(ErrorKind::Parse, e),
+        }
+    }
+
+    #[cfg(feature = "crypto")]
+    if let Commands::Encrypt | Commands::Decrypt = command {
+        let encrypt = matches!(command, Commands::Encrypt);
+        let keyfile = cli.keyfile.as_deref();
+        return run_crypto_command(
+            &location,
+            &worldline,
+            encrypt,
+            &worldline_file,
+            keyfile,
+            cli.dry_run,
+        );
+    }
+
+    let location = match location {
+        location if cli.dry_run && command.is_mutating() => Location::DryRun {
+            location: Box::new(location),
+            original: worldline.clone(),
+        },
+        Location::File { ref path, .. }
+            if command.is_mutating() && worldline.version() == wl::format::FormatVersion::V2 =>
+        {
+            let journal = wl::journal::Journal::for_file(path);
+            Location::Journaled {
+                location: Box::new(location),
+                original: worldline.clone(),
+                journal,
+            }
+        }
+        location => location,
+    };
+
+    match command {
+        Commands::Add {
+            from_file: Some(file),
+            parse_mode,
+            ..
+        } => {
+            let contents = if file == "-" {
+                let mut contents = String::new();
+                io::stdin().read_to_string(&mut contents).map(|_| contents)
+            } else {
+                std::fs::read_to_string(&file)
+            };
+            let contents = contents.unwrap_or_else(|e| {
+                fail(
+                    ErrorKind::reading(&file),
+                    format!("Could not read {}: {}", file, e),
+                )
+            });
+            match worldline.add_events_with(contents.lines(), parse_mode.unwrap_or_default()) {
+                Ok(report) => {
+                    save_or_fail(&location, &worldline);
+                    eprintln!(
+                        "{} {} events",
+                        location.said("Added", "Would add"),
+                        report.added
+                    );
+                    print_parse_warnings(&file, report.warnings);
+                }
+                Err(errors) => {
+                    for (line, e) in errors {
+                        eprintln!("{}:{}: {}", file, line, e);
+                    }
+                    fail(ErrorKind::Parse, "No events were added")
+                }
+            }
+        }
+        Commands::Add {
+            date,
+            description,
+            values,
+            template,
+            interactive,
+            id,
+            tags,
+            people,
+            series,
+            end,
+            sources,
+            seq,
+            force,
+            ..
+        } => {
+            let can_prompt = io::stdin().is_terminal() && !matches!(location, Location::Stdio);
+            if interactive {
+                if let Location::Stdio = location {
+                    fail(
+                        ErrorKind::Other,
+                        "Can't prompt for an event when the worldline is read from stdin",
+                    )
+                }
+            }
+            if let Some(id) = &id {
+                if worldline.find_id(id).is_some() {
+                    fail(
+                        ErrorKind::Other,
+                        format!("An event with id '{}' already exists", id),
+                    )
+                }
+            }
+            let (date, description) = match template {
+                Some(template) => {
+                    let mut values: Vec<String> =
+                        date.into_iter().chain(description).chain(values).collect();
+                    let date = values.pop();
+                    let text = cli::config::load()
+                        .and_then(|config| config.fill_template(&template, &values))
+                        .unwrap_or_else(|e| fail(ErrorKind::Usage, e));
+                    (date, Some(text))
+                }
+                None => (date, description),
+            };
+            let mut event = if interactive {
+                let date = cli::interactive::prompt_date();
+                wl::Event::from_text(date, &cli::interactive::prompt_description())
+            } else {
+                let Some(date) = parse_new_date(&date.unwrap(), can_prompt, &input, &display)
+                else {
+                    eprintln!("Nothing was added");
+                    return;
+                };
+                wl::Event::from_text(date, &description.unwrap())
+            };
+            event.id = id;
+            event.tags = tags;
+            event.people = people;
+            event.series = series;
+            event.sources = sources;
+            event.seq = seq;
+            if let Some(end) = end {
+                let end = parse_date(&end, &input);
+                if end < event.date {
+                    fail(ErrorKind::Other, "The end date is before the start date")
+                }
+                event.end = Some(end);
+            }
+            let similar = worldline.find_similar(&event, wl::similar::DEFAULT_THRESHOLD);
+            if !force && !similar.is_empty() {
+                eprintln!("Similar events are already on {}:", event.date);
+                let width = worldline.len().to_string().len();
+                for (i, _) in &similar {
+                    let existing = &worldline.events()[*i];
+                    eprintln!(
+                        "{:>width$} {}",
+                        i + 1,
+                        existing.format_for_display(true, &display)
+                    );
+                }
+                if !can_prompt {
+                    fail(
+                        ErrorKind::Other,
+                        "Not adding a possible duplicate; use --force to add it anyway",
+                    )
+                }
+                if !cli::interactive::confirm("Add it anyway?") {
+                    eprintln!("Nothing was added");
+                    return;
+                }
+            }
+            let idx = worldline.add_event(event);
+            let preview: Vec<&wl::Event> = worldline.context(idx, 1).iter().collect();
+            let preview = display.format_events(&preview);
+            if interactive {
+                println!();
+                cli::pager::print_lines(&preview);
+                if !cli::interactive::confirm("Save?") {
+                    eprintln!("Nothing was added");
+                    return;
+                }
+            }
+            save_or_fail(&location, &worldline);
+            // stdout has the worldline itself when piping
+            if location.is_file() && !interactive {
+                cli::pager::print_lines(&preview);
+            }
+        }
+        Commands::Show {
+            start,
+            end,
+            ruler,
+            ruler_gap,
+            last,
+            first,
+            category,
+            person,
+            series,
+            numbered,
+            group_by,
+            summarize,
+            top,
+            output,
+            archived,
+            context,
+            compare,
+            export,
+            out,
+            ..
+        } => {
+            let dates = date_args(&start, &end);
+            let hide_archived = !archived && worldline.has_archived();
+            // numbers are positions in the whole file, so they refer to the same events with a
+            // view, category, person, series or hidden archived events
+            let filtered =
+                cli.view.is_some() || category.is_some() || person.is_some() || series.is_some();
+            let whole = if numbered && (filtered || hide_archived) {
+                Some(location.load().unwrap_or_else(|e| {
+                    fail(
+                        ErrorKind::Parse,
+                        format!("Could not read worldline file: {}", e),
+                    )
+                }))
+            } else {
+                None
+            };
+            if let Some(category) = &category {
+                worldline =
+                    worldline.filtered(&wl::query::QueryExpr::Category(category.to_lowercase()));
+            }
+            if let Some(person) = &person {
+                worldline = worldline.person(person);
+            }
+            if let Some(series) = &series {
+                worldline = worldline.series(series);
+            }
+            if hide_archived {
+                worldline = worldline.without_archived();
+            }
+            let range_of = |worldline: &wl::WorldLine| {
+                show_range(&dates, first, last, worldline.len(), &input, |range| {
+                    worldline.date_range(range)
+                })
+            };
+            let range = range_of(&worldline);
+            if let Some(exporter) = export {
+                if range.is_empty() {
+                    no_events();
+                }
+                let events = worldline.with_events(&worldline.events()[range]);
+                return export_to(&events, exporter.as_ref(), &out);
+            }
+            if let Some(other_file) = compare {
+                let mut other = wl::storage::Backend::from_path(&other_file)
+                    .and_then(|backend| backend.open(&other_file)?.load())
+                    .unwrap_or_else(|e| {
+                        fail(
+                            ErrorKind::reading(&other_file),
+                            format!("Could not read {}: {}", other_file.display(), e),
+                        )
+                    });
+                if let Some(category) = category {
+                    other =
+                        other.filtered(&wl::query::QueryExpr::Category(category.to_lowercase()));
+                }
+                if let Some(person) = &person {
+                    other = other.person(person);
+                }
+                if let Some(series) = &series {
+                    other = other.series(series);
+                }
+                if !archived {
+                    other = other.without_archived();
+                }
+                let other_range = range_of(&other);
+                if range.is_empty() && other_range.is_empty() {
+                    no_events();
+                }
+                let name = |path: &Path| {
+                    path.file_name()
+                        .map_or(path.to_string_lossy(), |name| name.to_string_lossy())
+                        .into_owned()
+                };
+                let title = if worldline_file == "-" {
+                    "stdin".to_string()
+                } else {
+                    name(Path::new(&worldline_file))
+                };
+                let lines = wl::compare::render(
+                    &worldline.events()[range],
+                    &other.events()[other_range],
+                    (&title, &name(&other_file)),
+                    &display,
+                );
+                cli::pager::print_lines(&lines);
+                return;
+            }
+            if output != cli::output::Output::Text {
+                let events: Vec<&wl::Event> = worldline.events()[range].iter().collect();
+                return output.print_events(&events, &display);
+            }
+            if range.is_empty() {
+                let parsed: Vec<wl::Date> =
+                    dates.iter().map(|date| parse_date(date, &input)).collect();
+                match (context, parsed.first(), parsed.last()) {
+                    (Some(n), Some(start), Some(end)) if n > 0 => {
+                        let (before, _) = worldline.neighbors(start, n);
+                        let (_, after) = worldline.neighbors(end, n);
+                        if before.is_empty() && after.is_empty() {
+                            no_events();
+                        }
+                        println!("No events; the nearest are:");
+                        let mut lines = worldline.format_range(before.start, after.end, &display);
+                        // where the dates would be
+                        lines.insert(before.len(), format!("  ⋯ {} ⋯", dates.join(" to ")));
+                        cli::pager::print_lines(&lines);
+                    }
+                    _ => no_events(),
+                }
+                return;
+            }
+            if let Some(summary) = series.and_then(|series| worldline.series_summary(&series)) {
+                println!("{}\n", summary);
+            }
+            if let Some(group_by) = summarize {
+                let lines =
+                    wl::group::summarize(&worldline.events()[range], group_by, top, &display);
+                cli::pager::print_lines(&lines);
+                return;
+            }
+            let positions: Vec<usize> = match &whole {
+                Some(whole) => worldline.positions_in(whole)[range.clone()].to_vec(),
+                None if numbered => range.clone().collect(),
+                None => Vec::new(),
+            };
+            let number_width = positions.last().map_or(1, |p| (p + 1).to_string().len());
+            let display = if numbered {
+                display.narrowed(number_width + 1)
+            } else {
+                display
+            };
+            let mut lines = if ruler {
+                let min_gap =
+                    wl::span::Span::parse(&ruler_gap).unwrap_or_else(|e| fail(ErrorKind::Parse, e));
+                worldline.format_range_with_ruler(range.start, range.end, &min_gap, &display)
+            } else {
+                worldline.format_range(range.start, range.end, &display)
+            };
+            if numbered {
+                let continued = format!("\n{:number_width$} ", "");
+                for (line, position) in lines.iter_mut().zip(positions) {
+                    *line = format!(
+                        "{:>number_width$} {}",
+                        position + 1,
+                        line.replace('\n', &continued)
+                    );
+                }
+            }
+            if let Some(group_by) = group_by {
+                lines =
+                    wl::group::with_headers(&worldline.events()[range.clone()], lines, group_by);
+            }
+            cli::pager::show(&worldline, range, pager_threshold(), lines, &display);
+        }
+        Commands::Query {
+            query,
+            tags,
+            weekday,
+            range,
+            in_era,
+            century,
+            context,
+            output,
+            export,
+            out,
+        } => {
+            let mut filters: Vec<Box<dyn EventFilter>> = Vec::new();
+            filters.extend(in_era.map(|era| Box::new(era) as Box<dyn EventFilter>));
+            filters.extend(century.map(|century| Box::new(century) as Box<dyn EventFilter>));
+            let index;
+            let mut terms: Vec<wl::query::QueryExpr> = query.into_iter().collect();
+            terms.extend(tags.into_iter().map(wl::query::QueryExpr::Tag));
+            terms.extend(weekday.map(wl::query::QueryExpr::Weekday));
+            terms.extend(range);
+            // --read-only and --dry-run leave the file's index cache alone too
+            let text_index = |path: &PathBuf, worldline: &wl::WorldLine| {
+                if cli.read_only || cli.dry_run {
+                    wl::index::TextIndex::for_file_uncached(path, worldline)
+                } else {
+                    wl::index::TextIndex::for_file(path, worldline)
+                }
+            };
+            let events = match (&terms[..], &location) {
+                // views change the events' indices
+                (
+                    [wl::query::QueryExpr::Text(text)],
+                    Location::File {
+                        path,
+                        backend: wl::storage::Backend::Text,
+                        ..
+                    },
+                ) if cli.view.is_none() => {
+                    index = text_index(path, &worldline);
+                    let mut events = worldline.query_indexed(&index, text);
+                    events.retain(|e| filters.matches(e));
+                    events
+                }
+                _ => worldline.select(&wl::query::QueryExpr::And(terms.clone()).and(filters)),
+            };
+            let expr = wl::query::QueryExpr::And(terms);
+            if events.is_empty() {
+                let index = match &location {
+                    Location::File {
+                        path,
+                        backend: wl::storage::Backend::Text,
+                        ..
+                    } if cli.view.is_none() => text_index(path, &worldline),
+                    _ => wl::index::TextIndex::build(&worldline),
+                };
+                for text in expr.texts() {
+                    if let Some(suggestion) = index.did_you_mean(text) {
+                        eprintln!("No matches for '{}'; did you mean '{}'?", text, suggestion);
+                    }
+                }
+                std::process::exit(ErrorKind::NoMatches.code());
+            }
+            if let Some(exporter) = export {
+                return export_to(&worldline.with_events(events), exporter.as_ref(), &out);
+            }
+            match context {
+                Some(n) => output.print_in_context(&worldline, &events, &expr, n, &display),
+                None => output.print_matches(&events, &expr, &display),
+            }
+        }
+        Commands::Between { from, to } => {
+            let from = resolve_date(&worldline, &from, &input, &display);
+            let to = resolve_date(&worldline, &to, &input, &display);
+            println!("{}", from.difference(&to));
+        }
+        Commands::Gaps { min_years } => {
+            let gaps = worldline.gaps(&wl::span::Span::years(min_years));
+            if gaps.is_empty() {
+                println!("No gaps of {} years or more", min_years);
+            }
+            for (start, end) in gaps {
+                println!(
+                    "{} to {}: {}",
+                    display.format_date(&start, true).trim(),
+                    display.format_date(&end, true).trim(),
+                    start.difference(&end)
+                );
+            }
+        }
+        Commands::Sparkline { start, end } => {
+            let dates = date_args(&start, &end);
+            let range = show_range(&dates, None, None, worldline.len(), &input, |range| {
+                worldline.date_range(range)
+            });
+            let width = display.width.unwrap_or(80);
+            let Some(chart) = wl::sparkline::YearChart::new(&worldline.events()[range], width)
+            else {
+                no_events();
+            };
+            let first = wl::group::GroupBy::Year.label(chart.first_year);
+            let last = wl::group::GroupBy::Year.label(chart.last_year);
+            println!("{}", chart.bars);
+            if chart.first_year == chart.last_year {
+                println!("{}", first);
+            } else {
+                // the last year under the end of the chart, if there's room
+                let bars = chart.bars.chars().count();
+                let padding = bars.saturating_sub(first.len()).max(last.len() + 1);
+                println!("{}{:>padding$}", first, last);
+            }
+            let years = match chart.years_per_bar {
+                1 => "1 year".to_string(),
+                years => format!("{} years", years),
+            };
+            let events = if chart.max == 1 { "event" } else { "events" };
+            println!("{} per bar, up to {} {}", years, chart.max, events);
+        }
+        Commands::Import {
+            format: Some(ImportFormat::Md { file }),
+            strategy,
+            ..
+        } => {
+            let contents = read_import_text(&location, &file);
+            let import = wl::markdown::parse(&contents);
+            import_events(
+                &location,
+                worldline,
+                import.events.into_iter().collect(),
+                strategy,
+                &display,
+            );
+            if !import.skipped.is_empty() {
+                eprintln!(
+                    "Skipped {} list items without a date:",
+                    import.skipped.len()
+                );
+                for (line, text) in import.skipped {
+                    eprintln!("  {}:{}: {}", file, line, text.trim());
+                }
+            }
+        }
+        Commands::Import {
+            format: Some(ImportFormat::Anki { file }),
+            strategy,
+            ..
+        } => {
+            let contents = read_import_text(&location, &file);
+            let import = wl::anki::parse(&contents);
+            import_events(
+                &location,
+                worldline,
+                import.events.into_iter().collect(),
+                strategy,
+                &display,
+            );
+            if !import.skipped.is_empty() {
+                eprintln!("Skipped {} cards without a date:", import.skipped.len());
+                for (line, text) in import.skipped {
+                    eprintln!("  {}:{}: {}", file, line, text.trim());
+                }
+            }
+        }
+        Commands::Import {
+            format:
+                Some(ImportFormat::Regex {
+                    file,
+                    pattern,
+                    preview,
+                }),
+            strategy,
+            ..
+        } => {
+            let contents = read_import_text(&location, &file);
+            let import = pattern
+                .with_locale(input.locale)
+                .with_eras(input.eras.clone())
+                .parse(&contents);
+            if preview {
+                for (line, event) in &import.events {
+                    println!(
+                        "{}:{}: {}",
+                        file,
+                        line,
+                        event.format_for_display(true, &display)
+                    );
+                }
+            } else {
+                let events = import.events.into_iter().map(|(_, event)| event).collect();
+                import_events(&location, worldline, events, strategy, &display);
+            }
+            if !import.skipped.is_empty() {
+                eprintln!(
+                    "Skipped {} matched lines without a usable date:",
+                    import.skipped.len()
+                );
+                for (line, reason) in import.skipped {
+                    eprintln!("  {}:{}: {}", file, line, reason);
+                }
+            }
+            if import.unmatched > 0 {
+                eprintln!("{} lines didn't match the pattern", import.unmatched);
+            }
+            if preview {
+                eprintln!("Preview: nothing was imported");
+            }
+        }
+        #[cfg(feature = "gedcom")]
+        Commands::Import {
+            format: Some(ImportFormat::Gedcom { file, tag_people }),
+            strategy,
+            ..
+        } => {
+            let contents = read_import_text(&location, &file);
+            let import = wl::gedcom::parse(&contents, tag_people);
+            import_events(
+                &location,
+                worldline,
+                import.events.into_iter().collect(),
+                strategy,
+                &display,
+            );
+            if !import.skipped.is_empty() {
+                eprintln!(
+                    "Skipped {} events without a usable date:",
+                    import.skipped.len()
+                );
+                for (line, reason) in import.skipped {
+                    eprintln!("  {}:{}: {}", file, line, reason);
+                }
+            }
+        }
+        #[cfg(feature = "net")]
+        Commands::Import {
+            format: Some(ImportFormat::Wikidata { query, yes }),
+            strategy,
+            ..
+        } => {
+            let query = std::fs::read_to_string(&query).unwrap_or_else(|e| {
+                fail(
+                    ErrorKind::reading(&query),
+                    format!("Could not read {}: {}", query.display(), e),
+                )
+            });
+            eprintln!("Querying Wikidata...");
+            let import = wl::wikidata::run_query(&query)
+                .and_then(|results| wl::wikidata::parse_results(&results))
+                .unwrap_or_else(|e| fail(ErrorKind::Other, e));
+            if !import.skipped.is_empty() {
+                eprintln!(
+                    "Skipped {} results that aren't events:",
+                    import.skipped.len()
+                );
+                for (result, reason) in &import.skipped {
+                    eprintln!("  result {}: {}", result, reason);
+                }
+            }
+            let events: wl::WorldLine = import.events.into_iter().collect();
+            if events.is_empty() {
+                eprintln!("No events to import");
+                return;
+            }
+            if !yes {
+                cli::pager::print_lines(&events.format_range(0, events.len(), &display));
+                let can_prompt = io::stdin().is_terminal() && !matches!(location, Location::Stdio);
+                if !can_prompt {
+                    fail(
+                        ErrorKind::Other,
+                        "Pass --yes to import the events without asking",
+                    )
+                }
+                if !cli::interactive::confirm(&format!("Import these {} events?", events.len())) {
+                    eprintln!("Nothing was imported");
+                    return;
+                }
+            }
+            import_events(&location, worldline, events, strategy, &display);
+        }
+        Commands::Import {
+            file,
+            parse_mode,
+            strategy,
+            ..
+        } => {
+            // required by clap without a format
+            let file = file.unwrap();
+            let contents = read_import_text(&location, &file);
+            let (other, warnings) = wl::WorldLine::parse_with(&contents, parse_mode)
+                .unwrap_or_else(|e| {
+                    fail(ErrorKind::Parse, format!("Could not read {}: {}", file, e))
+                });
+            import_events(&location, worldline, other, strategy, &display);
+            print_parse_warnings(&file, warnings);
+        }
+        Commands::Extract {
+            start,
+            end,
+            out,
+            tags,
+            query,
+            force,
+        } => {
+            let (start, end) = (parse_date(&start, &input), parse_date(&end, &input));
+            if end < start {
+                fail(ErrorKind::Other, "The end date is before the start date")
+            }
+            if out.exists() && !force {
+                fail(
+                    ErrorKind::Other,
+                    format!(
+                        "{} already exists. Use --force to overwrite it",
+                        out.display()
+                    ),
+                )
+            }
+            let mut terms: Vec<wl::query::QueryExpr> =
+                tags.into_iter().map(wl::query::QueryExpr::Tag).collect();
+            terms.extend(query);
+            let filter = (!terms.is_empty()).then_some(wl::query::QueryExpr::And(terms));
+            let slice = worldline.slice(&wl::range::DateRange::new(start, end), filter.as_ref());
+            if cli.dry_run {
+                eprintln!(
+                    "Dry run: would extract {} events to {}",
+                    slice.len(),
+                    out.display()
+                );
+                return;
+            }
+            let result = wl::storage::Backend::from_path(&out)
+                .and_then(|backend| backend.open(&out)?.save(&slice));
+            if let Err(e) = result {
+                fail(
+                    ErrorKind::Other,
+                    format!("Could not write {}: {}", out.display(), e),
+                )
+            }
+            eprintln!("Extracted {} events to {}", slice.len(), out.display());
+        }
+        Commands::Split { by, out_dir, force } => {
+            let parts = worldline.split(by);
+            if parts.is_empty() {
+                no_events();
+            }
+            let index = out_dir.join("index.md");
+            let paths: Vec<PathBuf> = parts.iter().map(|p| out_dir.join(&p.file_name)).collect();
+            let existing = paths.iter().chain([&index]).find(|p| p.exists());
+            if let Some(existing) = existing.filter(|_| !force) {
+                fail(
+                    ErrorKind::Other,
+                    format!(
+                        "{} already exists. Use --force to overwrite it",
+                        existing.display()
+                    ),
+                )
+            }
+            if cli.dry_run {
+                for (part, path) in parts.iter().zip(&paths) {
+                    eprintln!(
+                        "Dry run: would write {} events to {}",
+                        part.worldline.len(),
+                        path.display()
+                    );
+                }
+                return;
+            }
+            let written = std::fs::create_dir_all(&out_dir)
+                .map_err(|e| e.to_string())
+                .and_then(|()| {
+                    parts.iter().zip(&paths).try_for_each(|(part, path)| {
+                        wl::storage::Backend::Text.open(path)?.save(&part.worldline)
+                    })
+                })
+                .and_then(|()| {
+                    std::fs::write(&index, wl::split::index(&parts, by)).map_err(|e| e.to_string())
+                });
+            if let Err(e) = written {
+                fail(
+                    ErrorKind::Other,
+                    format!("Could not write to {}: {}", out_dir.display(), e),
+                )
+            }
+            eprintln!("Wrote {} files to {}", parts.len(), out_dir.display());
+        }
+        Commands::Worksheet {
+            range,
+            count,
+            blank,
+            out,
+            key,
+            seed,
+        } => {
+            let seed = seed.unwrap_or_else(|| {
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_nanos() as u64)
+            });
+            let title = match &range {
+                Some(wl::query::QueryExpr::Range(range)) => {
+                    format!("Worksheet: {} to {}", range.start(), range.end())
+                }
+                _ => "Worksheet".to_string(),
+            };
+            let sheet =
+                worldline.worksheet(&title, range.as_ref(), count, blank.into(), seed, &display);
+            let write = |path: &PathBuf, contents: &str| {
+                if let Err(e) = std::fs::write(path, contents) {
+                    fail(
+                        ErrorKind::Other,
+                        format!("Could not write {}: {}", path.display(), e),
+                    )
+                }
+            };
+            match &out {
+                Some(path) => write(path, &sheet.questions),
+                None => print!("{}", sheet.questions),
+            }
+            if let Some(path) = &key {
+                write(path, &sheet.key);
+            }
+        }
+        #[cfg(feature = "astro")]
+        Commands::Astro { range, .. } => {
+            let added = worldline.import(astro_events(range.as_ref()).into_iter().collect());
+            save_or_fail(&location, &worldline);
+            eprintln!(
+                "{} {} events",
+                location.said("Imported", "Would import"),
+                added
+            );
+        }
+        Commands::Migrate => {
+            if !worldline.migrate() {
+                eprintln!("{} is already in the latest format", location);
+            } else {
+                save_or_fail(&location, &worldline);
+                eprintln!("Migrated {} to the v2 format", location);
+            }
+        }
+        Commands::Note { event, text: None } => {
+            if let Location::Stdio = location {
+                fail(
+                    ErrorKind::Other,
+                    "Notes are kept next to the worldline file, so it can't be read from stdin",
+                )
+            }
+            let idx = resolve_event(&worldline, &event, &display);
+            let (id, new_id) = worldline.ensure_id(idx);
+            let notes = wl::notes::NoteDir::for_file(&worldline_file);
+            let path = notes
+                .note_path(&id)
+                .unwrap_or_else(|e| fail(ErrorKind::Other, e));
+            if cli.dry_run {
+                eprintln!("Dry run: would edit {}", path.display());
+                return;
+            }
+            if new_id {
+                save_or_fail(&location, &worldline);
+                eprintln!("Gave the event the id '{}'", id);
+            }
+            let template = format!("# {}\n\n", worldline.get(idx).unwrap().description);
+            let written = std::fs::create_dir_all(&notes.path).and_then(|()| {
+                if path.exists() {
+                    Ok(())
+                } else {
+                    std::fs::write(&path, &template)
+                }
+            });
+            if let Err(e) = written {
+                fail(
+                    ErrorKind::Other,
+                    format!("Could not write {}: {}", path.display(), e),
+                )
+            }
+            let edited = cli::interactive::edit_file(&path);
+            // an untouched new note isn't kept, so the event isn't marked as having one
+            if std::fs::read_to_string(&path).is_ok_and(|contents| contents == template) {
+                std::fs::remove_file(&path).ok();
+                if edited.is_ok() {
+                    eprintln!("The note was left empty, so it wasn't kept");
+                }
+            }
+            if let Err(e) = edited {
+                fail(ErrorKind::Other, e)
+            }
+        }
+        Commands::Note {
+            event,
+            text: Some(text),
+        } => {
+            let idx = resolve_event(&worldline, &event, &display);
+            worldline.annotate(idx, text);
+            save_or_fail(&location, &worldline);
+            if location.is_file() {
+                print_info(worldline.get(idx).unwrap(), &display);
+            }
+        }
+        Commands::Delete { event } => {
+            let idx = resolve_event(&worldline, &event, &display);
+            let deleted = worldline.remove(idx);
+            save_or_fail(&location, &worldline);
+            eprintln!(
+                "{} {}",
+                location.said("Deleted", "Would delete"),
+                deleted.format_for_display(true, &display)
+            );
+        }
+        Commands::Archive { event, restore } => {
+            let idx = resolve_event(&worldline, &event, &display);
+            let changed = worldline.set_archived(idx, !restore);
+            let event = worldline
+                .get(idx)
+                .unwrap()
+                .format_for_display(true, &display);
+            if !changed {
+                let state = if restore { "isn't" } else { "is already" };
+                eprintln!("{} {} archived", event, state);
+                return;
+            }
+            save_or_fail(&location, &worldline);
+            let action = if restore { "Restored" } else { "Archived" };
+            eprintln!("{} {}", action, event);
+        }
+        Commands::Pin { event, unpin } => {
+            let idx = resolve_event(&worldline, &event, &display);
+            let changed = worldline.set_pinned(idx, !unpin);
+            let event = worldline
+                .get(idx)
+                .unwrap()
+                .format_for_display(true, &display);
+            if !changed {
+                let state = if unpin { "isn't" } else { "is already" };
+                eprintln!("{} {} pinned", event, state);
+                return;
+            }
+            save_or_fail(&location, &worldline);
+            let action = if unpin { "Unpinned" } else { "Pinned" };
+            eprintln!("{} {}", action, event);
+        }
+        Commands::Dashboard { days, recent } => print_dashboard(&worldline, days, recent, &display),
+        Commands::Edit {
+            event,
+            date,
+            description,
+            seq,
+        } => {
+            let idx = resolve_event(&worldline, &event, &display);
+            let mut event = worldline.get(idx).unwrap().clone();
+            if seq.is_some() {
+                event.seq = seq;
+            }
+            if let Some(description) = description {
+                let edited = wl::Event::from_text(event.date.clone(), &description);
+                event.description = edited.description;
+                event.category = edited.category;
+                event.place = edited.place;
+            }
+            if let Some(date) = date {
+                event.date = parse_date(&date, &input);
+                if event.end.as_ref().is_some_and(|end| *end < event.date) {
+                    fail(
+                        ErrorKind::Other,
+                        "The new date is after the event's end date",
+                    )
+                }
+            }
+            let idx = worldline.replace(idx, event);
+            save_or_fail(&location, &worldline);
+            if location.is_file() {
+                println!(
+                    "{}",
+                    worldline
+                        .get(idx)
+                        .unwrap()
+                        .format_for_display(true, &display)
+                );
+            }
+        }
+        Commands::EditFile { start, end, yes } => {
+            let dates = date_args(&start, &end);
+            run_edit_file(&location, worldline, &dates, &input, yes || cli.dry_run)
+        }
+        Commands::Shift {
+            query,
+            years,
+            months,
+            days,
+            yes,
+        } => {
+            let delta = wl::span::Span {
+                years,
+                months,
+                days,
+            };
+            if delta == wl::span::Span::default() {
+                fail(
+                    ErrorKind::Usage,
+                    "Give --years, --months or --days to move by",
+                )
+            }
+            let original = worldline.clone();
+            let moved = worldline
+                .shift_events(&query, &delta)
+                .unwrap_or_else(|e| fail(ErrorKind::Other, e));
+            if moved == 0 {
+                no_events();
+            }
+            let events = format!("{} event{}", moved, if moved == 1 { "" } else { "s" });
+            if !(yes || cli.dry_run) {
+                print_changes(&wl::diff::ChangeSet::between(&original, &worldline));
+                if !cli::interactive::confirm(&format!("Move {} by {}?", events, delta)) {
+                    eprintln!("Nothing was changed");
+                    return;
+                }
+            }
+            save_or_fail(&location, &worldline);
+            eprintln!(
+                "{} {} by {}",
+                location.said("Moved", "Would move"),
+                events,
+                delta
+            );
+        }
+        Commands::Check {
+            empty_descriptions: true,
+            ..
+        } => {
+            if let Location::Stdio = location {
+                fail(
+                    ErrorKind::Other,
+                    "Can't ask about events when the worldline is read from stdin",
+                )
+            }
+            let blank = wl::query::QueryExpr::Length(0..=0);
+            let empty: Vec<wl::Event> = worldline.query_expr(&blank).into_iter().cloned().collect();
+            if empty.is_empty() {
+                eprintln!("No events with empty descriptions in {}", location);
+                return;
+            }
+            let original = worldline.clone();
+            let (mut described, mut deleted) = (0, 0);
+            for event in empty {
+                println!();
+                println!("{}", event.format_for_display(true, &display));
+                let description =
+                    cli::interactive::ask("Description (blank to delete it)", |answer| {
+                        Ok::<_, String>(answer.to_string())
+                    });
+                // earlier answers can move events, so find it again
+                let i = worldline.events().iter().position(|e| *e == event).unwrap();
+                if description.is_empty() {
+                    worldline.remove(i);
+                    deleted += 1;
+                } else {
+                    worldline.replace(
+                        i,
+                        wl::Event {
+                            description,
+                            ..event
+                        },
+                    );
+                    described += 1;
+                }
+            }
+            if !cli.dry_run {
+                println!();
+                print_changes(&wl::diff::ChangeSet::between(&original, &worldline));
+                if !cli::interactive::confirm("Save?") {
+                    eprintln!("Nothing was changed");
+                    return;
+                }
+            }
+            save_or_fail(&location, &worldline);
+            let events = |n: usize| format!("{} event{}", n, if n == 1 { "" } else { "s" });
+            eprintln!(
+                "Described {} and deleted {} with empty descriptions",
+                events(described),
+                events(deleted)
+            );
+        }
+        Commands::Order {
+            order: None,
+            partial_dates: None,
+        } => {
+            let same_day = match worldline.same_day_order() {
+                wl::order::SameDayOrder::Alphabetical => "alphabetical",
+                wl::order::SameDayOrder::Insertion => "insertion",
+            };
+            let partial_dates = match worldline.partial_date_order() {
+                wl::order::PartialDateOrder::First => "first",
+                wl::order::PartialDateOrder::Last => "last",
+            };
+            println!("same day:      {}", same_day);
+            println!("partial dates: {}", partial_dates);
+        }
+        Commands::Order {
+            order,
+            partial_dates,
+        } => {
+            if let Some(order) = order {
+                worldline.set_same_day_order(order);
+            }
+            if let Some(order) = partial_dates {
+                worldline.set_partial_date_order(order);
+            }
+            save_or_fail(&location, &worldline);
+        }
+        Commands::Info { event } => {
+            let event = worldline.get(resolve_event(&worldline, &event, &display));
+            print_info(event.unwrap(), &display);
+        }
+        Commands::History { event } => {
+            let Location::File { path, .. } = &location else {
+                fail(
+                    ErrorKind::Other,
+                    "The journal is kept next to the worldline file, so it can't be read from stdin",
+                )
+            };
+            if worldline.version() != wl::format::FormatVersion::V2 {
+                fail(
+                    ErrorKind::Other,
+                    "History is only recorded for v2 files; upgrade with wl migrate",
+                )
+            }
+            let event = worldline
+                .get(resolve_event(&worldline, &event, &display))
+                .unwrap();
+            let history = wl::journal::Journal::for_file(path)
+                .history(event)
+                .unwrap_or_else(|e| fail(ErrorKind::Parse, e));
+            if history.is_empty() {
+                match event.created {
+                    Some(created) => println!("{}  added", created),
+                    None => fail(
+                        ErrorKind::NoMatches,
+                        "No history was recorded for this event",
+                    ),
+                }
+            }
+            for (i, entry) in history.iter().enumerate() {
+                let change = if i == 0 && entry.event.modified.is_none() {
+                    "added  "
+                } else {
+                    "changed"
+                };
+                println!(
+                    "{}  {}  {}",
+                    entry.timestamp,
+                    change,
+                    entry.event.format_for_display(true, &display)
+                );
+            }
+        }
+        Commands::Correlate { file, window } => {
+            let window =
+                wl::span::Span::parse(&window).unwrap_or_else(|e| fail(ErrorKind::Parse, e));
+            let other = wl::WorldLine::from_file(&file).unwrap_or_else(|e| {
+                fail(
+                    ErrorKind::reading(&file),
+                    format!("Could not read {}: {}", file, e),
+                )
+            });
+
+            let pairs = worldline.correlate(&other, &window);
+            if pairs.is_empty() {
+                println!("No events within {} of each other", window);
+            }
+            let mut last = None;
+            for (event, other_event) in pairs {
+                if last != Some(event) {
+                    println!("{}", event.format_for_display(true, &display));
+                    last = Some(event);
+                }
+                println!("    {}", other_event.format_for_display(true, &display));
+            }
+        }
+        Commands::Diff { other_file } => {
+            let other = wl::storage::Backend::from_path(&other_file)
+                .and_then(|backend| backend.open(&other_file)?.load())
+                .unwrap_or_else(|e| {
+                    fail(
+                        ErrorKind::Other,
+                        format!("Could not read {}: {}", other_file.display(), e),
+                    )
+                });
+            print_diff(&worldline.diff(&other), &display);
+        }
+        Commands::Sources { command } => {
+            if let Err(e) = run_sources_command(&mut worldline, &location, command, &display) {
+                fail(ErrorKind::Other, e)
+            }
+        }
+        Commands::Complete { kind } => match kind {
+            CompletionKind::Tags => worldline.tags().iter().for_each(|t| println!("{}", t)),
+            CompletionKind::Years => worldline.years().iter().for_each(|y| println!("{}", y)),
+        },
+        Commands::Completions { .. }
+        | Commands::Schema
+        | Commands::Paths
+        | Commands::Version { .. }
+        | Commands::Convert { .. }
+        | Commands::Check { .. }
+        | Commands::Init { .. } => {
+            unreachable!()
+        }
+        #[cfg(feature = "crypto")]
+        Commands::Encrypt | Commands::Decrypt => unreachable!(),
+        Commands::Branch { .. } => fail(
+            ErrorKind::Other,
+            "Branches aren't available when the worldline is read from stdin",
+        ),
+        Commands::Export {
+            format: Some(ExportFormat::Geojson { outfile }),
+            ..
+        } => {
+            let places = cli::config::load()
+                .and_then(|config| config.places())
+                .unwrap_or_else(|e| fail(ErrorKind::Parse, e));
+            let geojson = worldline.to_geojson(&places);
+            if outfile == "-" {
+                print!("{}", geojson);
+            } else if let Err(e) = std::fs::write(&outfile, geojson) {
+                fail(
+                    ErrorKind::Other,
+                    format!("Could not export to {}: {}", outfile, e),
+                )
+            }
+        }
+        Commands::Export {
+            format: Some(ExportFormat::Obsidian { dir, per }),
+            ..
+        } => {
+            let notes = worldline.to_obsidian(per);
+            let written = std::fs::create_dir_all(&dir).and_then(|()| {
+                notes.iter().try_for_each(|note| {
+                    std::fs::write(dir.join(format!("{}.md", note.name)), &note.contents)
+                })
+            });
+            if let Err(e) = written {
+                fail(
+                    ErrorKind::Other,
+                    format!("Could not export to {}: {}", dir.display(), e),
+                )
+            }
+            eprintln!("Exported {} notes to {}", notes.len(), dir.display());
+        }
+        Commands::Export {
+            format:
+                Some(ExportFormat::Latex {
+                    outfile,
+                    range,
+                    scale,
+                    rotate,
+                }),
+            ..
+        } => {
+            let options = wl::latex::LatexOptions { scale, rotate };
+            let tex = worldline
+                .to_latex(range.as_ref(), &options)
+                .unwrap_or_else(|e| fail(ErrorKind::Other, e));
+            if outfile == "-" {
+                print!("{}", tex);
+            } else if let Err(e) = std::fs::write(&outfile, tex) {
+                fail(
+                    ErrorKind::Other,
+                    format!("Could not export to {}: {}", outfile, e),
+                )
+            }
+        }
+        Commands::Export {
+            format:
+                Some(ExportFormat::Outline {
+                    outfile,
+                    range,
+                    markdown,
+                }),
+            ..
+        } => {
+            let format = if markdown {
+                wl::outline::OutlineFormat::Markdown
+            } else {
+                wl::outline::OutlineFormat::Text
+            };
+            let outline = worldline.to_outline(range.as_ref(), format);
+            if outline.is_empty() {
+                no_events();
+            }
+            if outfile == "-" {
+                print!("{}", outline);
+            } else if let Err(e) = std::fs::write(&outfile, outline) {
+                fail(
+                    ErrorKind::Other,
+                    format!("Could not export to {}: {}", outfile, e),
+                )
+            }
+        }
+        Commands::Export {
+            outfile, exporter, ..
+        } => {
+            // required by clap without a format
+            export_to(&worldline, exporter.as_ref(), &outfile.unwrap());
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// A worldline file of its own for a test, in the temp directory.
+fn worldline_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("wl-test-{}-{}.wl", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// Run wl on a worldline file, whether or not it succeeds.
+fn run(file: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_wl"))
+        .args(args)
+        .env("WORLDLINE_FILE", file)
+        .env("WORLDLINE_CONFIG", file.with_extension("toml"))
+        .output()
+        .unwrap()
+}
+
+fn wl(file: &PathBuf, args: &[&str]) -> Output {
+    let output = run(file, args);
+    assert!(
+        output.status.success(),
+        "wl {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn test_negative_years() {
+    let file = worldline_file("negative-years");
+    wl(&file, &["init"]);
+    wl(&file, &["add", "-0043-03-15", "Ides of March"]);
+    wl(&file, &["add", "-44", "Caesar dictator for life"]);
+    wl(&file, &["add", "1066", "Battle of Hastings"]);
+
+    let show = |args: &[&str]| String::from_utf8(wl(&file, args).stdout).unwrap();
+    let shown = show(&["show", "-44"]);
+    assert!(shown.contains("Caesar dictator for life"));
+    assert!(!shown.contains("Ides of March"));
+    let shown = show(&["show", "-44", "1066", "--last", "1"]);
+    assert!(shown.contains("Battle of Hastings"));
+    assert!(!shown.contains("Ides of March"));
+    let shown = show(&["show", "-43", "--first", "1"]);
+    assert!(shown.contains("BCE 0043-03-15"));
+    let shown = show(&["query", "--range", "-50..-44"]);
+    assert!(shown.contains("Caesar dictator for life"));
+    assert!(!shown.contains("Ides of March"));
+
+    assert!(show(&["between", "-1", "1"]).ends_with("1 year\n"));
+
+    let extracted = file.with_extension("extract.wl");
+    let out = extracted.to_str().unwrap();
+    wl(&file, &["extract", "-50", "-44", "--out", out, "--force"]);
+    let contents = std::fs::read_to_string(&extracted).unwrap();
+    assert!(contents.contains("Caesar dictator for life"));
+    assert!(!contents.contains("Ides of March"));
+
+    std::fs::remove_file(&extracted).unwrap();
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn test_exit_codes() {
+    let file = worldline_file("exit-codes");
+    let code = |args: &[&str]| run(&file, args).status.code().unwrap();
+    assert_eq!(code(&["show"]), 4);
+    wl(&file, &["init"]);
+    wl(&file, &["add", "1066", "Battle of Hastings"]);
+    assert_eq!(code(&["init"]), 1);
+    assert_eq!(code(&["no-such-command"]), 2);
+    assert_eq!(code(&["show", "1066-02-30"]), 3);
+    assert_eq!(code(&["show", "1800"]), 5);
+
+    let output = run(&file, &["--json-errors", "show", "1800"]);
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "{\"error\":\"no_matches\",\"code\":5,\"message\":\"No events\"}\n"
+    );
+
+    // another wl process changing the file holds the lock
+    let lock = std::fs::File::create(file.with_extension("wl.lock")).unwrap();
+    lock.try_lock().unwrap();
+    assert_eq!(code(&["add", "1067", "Hereward's revolt"]), 6);
+    assert_eq!(code(&["show", "1066"]), 0);
+    drop(lock);
+    wl(&file, &["add", "1067", "Hereward's revolt"]);
+
+    std::fs::remove_file(file.with_extension("wl.lock")).unwrap();
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn test_dry_run_says_what_would_change() {
+    let file = worldline_file("dry-run");
+    wl(&file, &["init"]);
+    wl(&file, &["add", "1066", "Battle of Hastings"]);
+    let said = |args: &[&str]| String::from_utf8(wl(&file, args).stderr).unwrap();
+    assert!(said(&["--dry-run", "delete", "1"]).contains("Would delete"));
+    assert!(said(&[
+        "--dry-run",
+        "shift",
+        "--query",
+        "Hastings",
+        "--years=-1",
+        "--yes"
+    ])
+    .contains("Would move 1 event by -1y"));
+    assert!(
+        said(&["shift", "--query", "Hastings", "--years=-1", "--yes"])
+            .contains("Moved 1 event by -1y")
+    );
+    std::fs::remove_file(&file).unwrap();
+}
+
+#[test]
+fn test_colors_only_at_a_terminal() {
+    let file = worldline_file("colors");
+    wl(&file, &["init"]);
+    wl(&file, &["add", "1066", "Battle of Hastings"]);
+    let shown = |args: &[&str]| String::from_utf8(wl(&file, args).stdout).unwrap();
+    assert!(!shown(&["show"]).contains('\u{001B}'));
+    assert!(shown(&["show", "--color", "always"]).contains('\u{001B}'));
+    std::fs::remove_file(&file).unwrap();
+}
+
+/// `wl show` reads plain listings of text files through a memory map with the mmap feature.
+#[cfg(feature = "mmap")]
+#[test]
+fn test_mapped_show_keeps_same_day_order() {
+    let file = worldline_file("same-day-order");
+    std::fs::write(
+        &file,
+        "#worldline v2\n#same_day_order: insertion\n CE 1950 Zeta\n CE 1950 Alpha\n",
+    )
+    .unwrap();
+    for args in [&["show"][..], &["show", "1950"]] {
+        let shown = String::from_utf8(wl(&file, args).stdout).unwrap();
+        assert!(shown.find("Zeta").unwrap() < shown.find("Alpha").unwrap());
+    }
+    std::fs::remove_file(&file).unwrap();
+}
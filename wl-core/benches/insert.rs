@@ -0,0 +1,57 @@
+//! Adding events to a large worldline one at a time with `add_event`, compared with a single
+//! `add_events_sorted`. Run with `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::time::Duration;
+use wl_core::{Date, Event, WorldLine};
+
+const EXISTING: usize = 100_000;
+const ADDED: usize = 10_000;
+
+/// `n` events spread over the years 1 to 9999 CE, in a scrambled order.
+fn events(n: usize, seed: usize) -> Vec<Event> {
+    (0..n)
+        .map(|i| {
+            let x = (i * 7919 + seed) % 9999;
+            let date = Date::new(x as i32 + 1, (x % 12) as u8 + 1, (x % 28) as u8 + 1).unwrap();
+            Event::new(date, format!("Event {}", i))
+        })
+        .collect()
+}
+
+fn bench_insert(c: &mut Criterion) {
+    let worldline: WorldLine = events(EXISTING, 0).into_iter().collect();
+    let added = events(ADDED, 1);
+
+    let mut group = c.benchmark_group("insert 10k into 100k");
+    // inserting one at a time takes seconds, so keep the run short
+    group.sample_size(10);
+    group.warm_up_time(Duration::from_secs(1));
+    group.measurement_time(Duration::from_secs(50));
+    group.bench_function("add_event", |b| {
+        b.iter_batched(
+            || (worldline.clone(), added.clone()),
+            |(mut worldline, added)| {
+                for event in added {
+                    worldline.add_event(event);
+                }
+                worldline
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("add_events_sorted", |b| {
+        b.iter_batched(
+            || (worldline.clone(), added.clone()),
+            |(mut worldline, added)| {
+                worldline.add_events_sorted(added);
+                worldline
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_insert);
+criterion_main!(benches);
@@ -0,0 +1,43 @@
+//! Querying a worldline of a million events, to compare builds with and without the
+//! `parallel` feature. Run with `cargo bench --bench query [--features parallel]`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use wl_core::query::QueryExpr;
+use wl_core::{Date, Event, WorldLine};
+
+const EVENTS: usize = 1_000_000;
+
+/// `n` events spread over the years 1 to 9999 CE, with a few tagged.
+fn worldline(n: usize) -> WorldLine {
+    (0..n)
+        .map(|i| {
+            let x = (i * 7919) % 9999;
+            let date = Date::new(x as i32 + 1, (x % 12) as u8 + 1, (x % 28) as u8 + 1).unwrap();
+            let mut event = Event::new(date, format!("Event {} in the archive", i));
+            if i % 100 == 0 {
+                event.tags.push("rare".to_string());
+            }
+            event
+        })
+        .collect()
+}
+
+fn bench_query(c: &mut Criterion) {
+    let worldline = worldline(EVENTS);
+    let expr = QueryExpr::parse("archive AND tag:rare").unwrap();
+
+    let mut group = c.benchmark_group("query 1M");
+    group.sample_size(10);
+    group.bench_function("query", |b| b.iter(|| worldline.query("event 4242").len()));
+    group.bench_function("query_expr", |b| {
+        b.iter(|| worldline.query_expr(&expr).len())
+    });
+    group.bench_function("find_similar", |b| {
+        let event = worldline.events()[EVENTS / 2].clone();
+        b.iter(|| worldline.find_similar(&event, 0.6).len())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_query);
+criterion_main!(benches);
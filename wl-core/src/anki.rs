@@ -0,0 +1,227 @@
+//! Importing events from Anki's text export, the reverse of [`WorldLine::write_anki`]:
+//!
+//! ```text
+//! #separator:Semicolon
+//! #tags column:3
+//!  CE 1914       ;WWI begins;war europe
+//! July 14, 1789;Storming of the Bastille
+//! ```
+//!
+//! Header lines set the separator (`Tab`, `Comma`, `Semicolon`, `Space`, `Pipe`, `Colon` or a
+//! single character), whether fields are HTML, and which columns hold the tags, note type, deck
+//! and guid. Of the other columns, the first (the front of the card) is the date, in the file
+//! format or written in English (see [`Date::parse_natural`]), and the second is the
+//! description. Rows without a date are skipped.
+//!
+//! [`WorldLine::write_anki`]: crate::WorldLine::write_anki
+
+use crate::query::parse_whole_date;
+use crate::{Date, Event};
+
+/// The events read from an Anki export by [`parse`].
+#[derive(Debug, Default)]
+pub struct AnkiImport {
+    pub events: Vec<Event>,
+    /// Rows that couldn't be read as events, with their (1-based) line numbers.
+    pub skipped: Vec<(usize, String)>,
+}
+
+/// The options set by the header lines of an export.
+struct Header {
+    separator: char,
+    html: bool,
+    /// 0-based
+    tags_column: Option<usize>,
+    /// Columns that aren't fields of the card, e.g. the deck.
+    other_columns: Vec<usize>,
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self {
+            separator: '\t',
+            html: false,
+            tags_column: None,
+            other_columns: Vec::new(),
+        }
+    }
+}
+
+impl Header {
+    /// Read a `#key:value` header line, ignoring ones that don't matter here.
+    fn read(&mut self, line: &str) {
+        let Some((key, value)) = line[1..].split_once(':') else {
+            return;
+        };
+        let column = || value.trim().parse::<usize>().ok()?.checked_sub(1);
+        match key.trim() {
+            "separator" => {
+                self.separator = match value.trim().to_lowercase().as_str() {
+                    "tab" => '\t',
+                    "comma" => ',',
+                    "semicolon" => ';',
+                    "space" => ' ',
+                    "pipe" => '|',
+                    "colon" => ':',
+                    _ => value.chars().next().unwrap_or('\t'),
+                }
+            }
+            "html" => self.html = value.trim() == "true",
+            "tags column" => self.tags_column = column(),
+            "notetype column" | "deck column" | "guid column" => {
+                self.other_columns.extend(column())
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Split a row into fields. Fields may be quoted, with `""` for a quote, so that they can
+/// contain the separator.
+fn split_fields(row: &str, separator: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut chars = row.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            c if c == separator && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// The text of an HTML field, without tags and with the common entities decoded.
+fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut tag: Option<String> = None;
+    for c in html.chars() {
+        match (c, &mut tag) {
+            ('<', None) => tag = Some(String::new()),
+            ('>', Some(name)) => {
+                // lines and paragraphs are separated by spaces in the description
+                let name = name.trim_start_matches('/').to_lowercase();
+                if ["br", "div", "p"].contains(&name.split([' ', '/']).next().unwrap_or("")) {
+                    text.push(' ');
+                }
+                tag = None;
+            }
+            (c, Some(name)) => name.push(c),
+            (c, None) => text.push(c),
+        }
+    }
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Read a card as an event. Fronts in the file format are joined back to the description
+/// like a line of a worldline file, which keeps times split off by the export.
+fn card_event(front: &str, back: &str) -> Option<Event> {
+    let front = front.trim();
+    let back = back.trim();
+    if back.is_empty() {
+        return None;
+    }
+    if parse_whole_date(front).is_ok() {
+        return Event::parse(&format!("{} {}", front, back)).ok();
+    }
+    let date = Date::parse_natural(front).ok()?;
+    Some(Event::from_text(date, back))
+}
+
+/// Read the events in an Anki text export.
+pub fn parse(contents: &str) -> AnkiImport {
+    let mut import = AnkiImport::default();
+    let mut header = Header::default();
+    for (i, line) in contents.lines().enumerate() {
+        if line.starts_with('#') {
+            header.read(line);
+            continue;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut fields = split_fields(line, header.separator);
+        if header.html {
+            fields = fields.iter().map(|field| html_text(field)).collect();
+        }
+        let tags = header
+            .tags_column
+            .and_then(|column| fields.get(column))
+            .map(|tags| tags.split_whitespace().map(str::to_string).collect());
+        let card: Vec<&String> = fields
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| Some(*i) != header.tags_column && !header.other_columns.contains(i))
+            .map(|(_, field)| field)
+            .collect();
+        let event = match card.as_slice() {
+            [front, back, ..] => card_event(front, back),
+            _ => None,
+        };
+        match event {
+            Some(mut event) => {
+                event.tags = tags.unwrap_or_default();
+                import.events.push(event);
+            }
+            None => import.skipped.push((i + 1, line.to_string())),
+        }
+    }
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldLine;
+
+    #[test]
+    fn test_anki_import() {
+        let worldline: WorldLine =
+            "-44-03-15 Ides of March\n1914 [war] WWI begins\n2023-12-25 14:30 Flight landed\n"
+                .parse()
+                .unwrap();
+        let mut export = Vec::new();
+        worldline.write_anki(&mut export).unwrap();
+        let export = String::from_utf8(export).unwrap();
+        // the time of day is on the front of the card
+        assert!(export.ends_with("\n CE 2023-12-25 14:30\tFlight landed\n"));
+        let import = parse(&export);
+        assert_eq!(import.events, worldline.events());
+        assert!(import.skipped.is_empty());
+
+        let import = parse(
+            "#separator:Semicolon\n\
+             #html:true\n\
+             #deck column:1\n\
+             #tags column:4\n\
+             History;July 14, 1789;\"Storming of the <b>Bastille</b>; Paris\";france revolution\n\
+             History;1815<br>;Waterloo<div>Napoleon defeated</div>\n\
+             History;What year?;1789\n",
+        );
+        let event = &import.events[0];
+        assert_eq!(
+            event.format_for_file(),
+            " CE 1789-07-14 Storming of the Bastille; Paris"
+        );
+        assert_eq!(event.tags, vec!["france", "revolution"]);
+        assert_eq!(import.events[1].description, "Waterloo Napoleon defeated");
+        assert_eq!(
+            import.skipped,
+            vec![(7, "History;What year?;1789".to_string())]
+        );
+    }
+}
@@ -0,0 +1,71 @@
+//! Archiving events: keeping them in the file but hiding them from `wl show`, for superseded
+//! or doubtful events that shouldn't be deleted outright.
+//!
+//! Archived events are written with an `archived=true` field, so only v2 files can have them.
+
+use crate::WorldLine;
+
+impl WorldLine {
+    /// Archive or restore the event at an index. Returns false if it already was.
+    pub fn set_archived(&mut self, idx: usize, archived: bool) -> bool {
+        let event = &mut self.events[idx];
+        let changed = event.archived != archived;
+        event.archived = archived;
+        changed
+    }
+
+    /// Whether any events are archived.
+    pub fn has_archived(&self) -> bool {
+        self.events.iter().any(|e| e.archived)
+    }
+
+    /// A read-only copy of the worldline without its archived events.
+    pub fn without_archived(&self) -> WorldLine {
+        WorldLine {
+            events: self
+                .events
+                .iter()
+                .filter(|e| !e.archived)
+                .cloned()
+                .collect(),
+            version: self.version,
+            front_matter: self.front_matter.clone(),
+            read_only: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::Format;
+
+    #[test]
+    fn test_archive() {
+        let mut worldline: WorldLine = "1914 WWI begins\n1915 Doubtful rumour\n".parse().unwrap();
+        assert!(!worldline.has_archived());
+        assert!(worldline.set_archived(1, true));
+        assert!(!worldline.set_archived(1, true));
+        assert_eq!(worldline.without_archived().len(), 1);
+
+        // archived events need a v2 file
+        let written = worldline.to_string();
+        assert!(written.starts_with("#worldline v2\n"));
+        assert!(written.contains("Doubtful rumour\tarchived=true\n"));
+        let read: WorldLine = written.parse().unwrap();
+        assert!(read.events()[1].archived);
+        let json = Format::Json.write(&worldline);
+        assert!(json.contains(r#""archived":true"#));
+        assert_eq!(
+            Format::Json.read(&json).unwrap().events(),
+            worldline.events()
+        );
+        assert_eq!(
+            Format::Csv.losses(&worldline),
+            vec!["1 archived flags can't be stored"]
+        );
+
+        assert!(worldline.set_archived(1, false));
+        assert!(!worldline.to_string().contains("archived"));
+    }
+}
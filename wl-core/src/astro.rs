@@ -0,0 +1,144 @@
+//! Notable astronomical events, behind the `astro` feature: historically significant total
+//! solar eclipses and the perihelia of Halley's comet.
+//!
+//! Sources give dates before 1582 in the Julian calendar; they're converted to the proleptic
+//! Gregorian calendar used everywhere else.
+
+use crate::{Date, Event};
+
+enum Calendar {
+    Julian,
+    Gregorian,
+}
+use Calendar::*;
+
+/// (year, month, day, calendar, description) of total solar eclipses
+#[rustfmt::skip]
+const ECLIPSES: &[(i32, u8, u8, Calendar, &str)] = &[
+    (-763, 6, 15, Julian, "Assyrian eclipse, recorded in the eponym lists"),
+    (-585, 5, 28, Julian, "Eclipse of Thales, said to halt a battle between the Lydians and Medes"),
+    (1133, 8, 2, Julian, "Eclipse at the departure of Henry I of England for Normandy"),
+    (1715, 5, 3, Gregorian, "Halley's eclipse over England, whose path he predicted"),
+    (1806, 6, 16, Gregorian, "Total eclipse across the United States"),
+    (1851, 7, 28, Gregorian, "First photograph of a total solar eclipse"),
+    (1919, 5, 29, Gregorian, "Eddington's eclipse, confirming light bending by gravity"),
+    (1999, 8, 11, Gregorian, "Total eclipse across Europe"),
+    (2017, 8, 21, Gregorian, "Great American Eclipse"),
+    (2024, 4, 8, Gregorian, "Total eclipse across North America"),
+    (2026, 8, 12, Gregorian, "Total eclipse over Greenland, Iceland and Spain"),
+];
+
+/// (year, month, day, calendar) of perihelia of Halley's comet
+const HALLEY: &[(i32, u8, u8, Calendar)] = &[
+    (-240, 5, 25, Julian),
+    (-164, 11, 12, Julian),
+    (-87, 8, 6, Julian),
+    (-12, 10, 10, Julian),
+    (66, 1, 25, Julian),
+    (141, 3, 22, Julian),
+    (218, 5, 18, Julian),
+    (295, 4, 20, Julian),
+    (374, 2, 16, Julian),
+    (451, 6, 28, Julian),
+    (530, 9, 27, Julian),
+    (607, 3, 15, Julian),
+    (684, 10, 2, Julian),
+    (760, 5, 20, Julian),
+    (837, 2, 28, Julian),
+    (912, 7, 18, Julian),
+    (989, 9, 5, Julian),
+    (1066, 3, 20, Julian),
+    (1145, 4, 18, Julian),
+    (1222, 9, 28, Julian),
+    (1301, 10, 25, Julian),
+    (1378, 11, 10, Julian),
+    (1456, 6, 9, Julian),
+    (1531, 8, 26, Julian),
+    (1607, 10, 27, Gregorian),
+    (1682, 9, 15, Gregorian),
+    (1759, 3, 13, Gregorian),
+    (1835, 11, 16, Gregorian),
+    (1910, 4, 20, Gregorian),
+    (1986, 2, 9, Gregorian),
+    (2061, 7, 28, Gregorian),
+];
+
+fn to_date(year: i32, month: u8, day: u8, calendar: &Calendar) -> Date {
+    match calendar {
+        Gregorian => Date::new(year, month, day).unwrap(),
+        Julian => {
+            // Julian day number, then days since 1970-01-01
+            let (a, y) = (
+                (14 - month as i64) / 12,
+                Date::astronomical_year(year) as i64,
+            );
+            let y = y + 4800 - a;
+            let m = month as i64 + 12 * a - 3;
+            let jdn = day as i64 + (153 * m + 2) / 5 + 365 * y + y / 4 - 32083;
+            Date::from_day_number(jdn - 2440588)
+        }
+    }
+}
+
+/// A year for use in ids, e.g. "1066" or "240bce".
+fn id_year(year: i32) -> String {
+    if year < 0 {
+        format!("{}bce", -year)
+    } else {
+        year.to_string()
+    }
+}
+
+/// All the astronomical events, sorted, tagged "astronomy" and "eclipse" or "comet".
+pub fn events() -> Vec<Event> {
+    let tags = |kind: &str| vec!["astronomy".to_string(), kind.to_string()];
+    let eclipses = ECLIPSES
+        .iter()
+        .map(|(year, month, day, calendar, description)| {
+            let date = to_date(*year, *month, *day, calendar);
+            let mut event = Event::new(
+                date.clone(),
+                format!("Total solar eclipse: {}", description),
+            );
+            event.id = Some(format!("eclipse-{}", id_year(*year)));
+            event.tags = tags("eclipse");
+            event
+        });
+    let halley = HALLEY.iter().map(|(year, month, day, calendar)| {
+        let date = to_date(*year, *month, *day, calendar);
+        let mut event = Event::new(date, "Halley's comet at perihelion".to_string());
+        event.id = Some(format!("halley-{}", id_year(*year)));
+        event.tags = tags("comet");
+        event
+    });
+    let mut events: Vec<Event> = eclipses.chain(halley).collect();
+    events.sort();
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_astro_events() {
+        // the Julian and Gregorian calendars agree in the 3rd century
+        assert_eq!(to_date(250, 3, 1, &Julian), Date::new(250, 3, 1).unwrap());
+        assert_eq!(
+            to_date(1582, 10, 5, &Julian),
+            Date::new(1582, 10, 15).unwrap()
+        );
+        assert_eq!(
+            to_date(-585, 5, 28, &Julian),
+            Date::new(-585, 5, 22).unwrap()
+        );
+
+        let events = events();
+        assert_eq!(events.len(), ECLIPSES.len() + HALLEY.len());
+        let hastings = events
+            .iter()
+            .find(|e| e.id.as_deref() == Some("halley-1066"))
+            .unwrap();
+        assert_eq!(hastings.date, Date::new(1066, 3, 26).unwrap());
+    }
+}
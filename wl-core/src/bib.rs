@@ -0,0 +1,324 @@
+//! BibTeX bibliographies, for resolving the citation keys in events' `sources`.
+//!
+//! A worldline is linked to a `.bib` file through the `bibliography` front matter key.
+
+use crate::{Event, WorldLine};
+use std::collections::BTreeMap;
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+/// The front matter key naming the linked bibliography file.
+pub const FRONT_MATTER_KEY: &str = "bibliography";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// e.g. "book" or "article"
+    pub kind: String,
+    pub key: String,
+    /// lowercase field name -> value, with braces removed
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Markup languages bibliographies can be rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Markup {
+    Markdown,
+    Latex,
+    Html,
+}
+
+#[derive(Debug, Default)]
+pub struct Bibliography {
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Bibliography {
+    #[cfg(feature = "std-fs")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        Self::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut entries = BTreeMap::new();
+        let mut rest = contents;
+        while let Some(at) = rest.find('@') {
+            let (entry, remainder) = parse_entry(&rest[at + 1..])?;
+            rest = remainder;
+            if let Some(entry) = entry {
+                entries.insert(entry.key.clone(), entry);
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Render the entries for the given keys as a bibliography, in key order. Unknown keys are
+    /// skipped.
+    pub fn render(&self, keys: &[&str], markup: Markup) -> String {
+        let entries = keys.iter().filter_map(|k| self.get(k));
+        let mut out = String::new();
+        match markup {
+            Markup::Markdown => {
+                out += "## Bibliography\n\n";
+                for entry in entries {
+                    out += &format!("- **[{}]** {}\n", entry.key, entry.format(markup));
+                }
+            }
+            Markup::Latex => {
+                out += "\\begin{thebibliography}{99}\n";
+                for entry in entries {
+                    out += &format!("\\bibitem{{{}}} {}\n", entry.key, entry.format(markup));
+                }
+                out += "\\end{thebibliography}\n";
+            }
+            Markup::Html => {
+                out += "<h2>Bibliography</h2>\n<ol>\n";
+                for entry in entries {
+                    out += &format!(
+                        "  <li id=\"{}\">{}</li>\n",
+                        html_escape(&entry.key),
+                        entry.format(markup)
+                    );
+                }
+                out += "</ol>\n";
+            }
+        }
+        out
+    }
+}
+
+impl Entry {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(String::as_str)
+    }
+
+    /// A human-readable reference, e.g. "Mary Beard (2015). *SPQR*. Profile Books."
+    pub fn format(&self, markup: Markup) -> String {
+        let escape = |s: &str| match markup {
+            Markup::Markdown => s.to_string(),
+            Markup::Latex => latex_escape(s),
+            Markup::Html => html_escape(s),
+        };
+        let mut parts = Vec::new();
+        if let Some(author) = self.field("author") {
+            let year = self.field("year").map(|y| format!(" ({})", y));
+            parts.push(format!("{}{}", escape(author), year.unwrap_or_default()));
+        }
+        if let Some(title) = self.field("title") {
+            let title = escape(title);
+            parts.push(match markup {
+                Markup::Markdown => format!("*{}*", title),
+                Markup::Latex => format!("\\textit{{{}}}", title),
+                Markup::Html => format!("<i>{}</i>", title),
+            });
+        }
+        let venue = ["journal", "booktitle", "publisher", "howpublished", "url"]
+            .iter()
+            .find_map(|f| self.field(f));
+        if let Some(venue) = venue {
+            parts.push(escape(venue));
+        }
+        if parts.is_empty() {
+            return escape(&self.key);
+        }
+        parts.join(". ") + "."
+    }
+}
+
+impl WorldLine {
+    /// All citation keys used in events' sources, sorted.
+    pub fn cited_keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self
+            .events
+            .iter()
+            .flat_map(|e| e.sources.iter().map(String::as_str))
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Events citing the key.
+    pub fn citing(&self, key: &str) -> Vec<&Event> {
+        self.events
+            .iter()
+            .filter(|e| e.sources.iter().any(|s| s == key))
+            .collect()
+    }
+
+    /// The path of the linked bibliography file, if any.
+    pub fn bibliography_path(&self) -> Option<&str> {
+        self.front_matter
+            .iter()
+            .find(|(k, _)| k == FRONT_MATTER_KEY)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Load the linked bibliography.
+    #[cfg(feature = "std-fs")]
+    pub fn bibliography(&self) -> Result<Bibliography, String> {
+        let path = self
+            .bibliography_path()
+            .ok_or("No bibliography is linked; link one with `wl sources link FILE`")?;
+        Bibliography::from_file(path)
+    }
+
+    /// Citation keys that are used but missing from the bibliography.
+    pub fn dangling_keys<'a>(&'a self, bibliography: &Bibliography) -> Vec<&'a str> {
+        self.cited_keys()
+            .into_iter()
+            .filter(|k| bibliography.get(k).is_none())
+            .collect()
+    }
+}
+
+/// Parse one entry, starting just after its `@`. Returns the entry (None for @comment,
+/// @string, and @preamble) and the rest of the input.
+fn parse_entry(input: &str) -> Result<(Option<Entry>, &str), String> {
+    let open = input
+        .find(['{', '('])
+        .ok_or("Expected '{' after entry type")?;
+    let kind = input[..open].trim().to_lowercase();
+    let close = matching_close(input, open).ok_or(format!("Unterminated @{} entry", kind))?;
+    let body = &input[open + 1..close];
+    let rest = &input[close + 1..];
+    if matches!(kind.as_str(), "comment" | "string" | "preamble") {
+        return Ok((None, rest));
+    }
+
+    let (key, mut fields_str) = body.split_once(',').unwrap_or((body, ""));
+    let mut fields = BTreeMap::new();
+    loop {
+        fields_str = fields_str.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if fields_str.is_empty() {
+            break;
+        }
+        let (name, after) = fields_str
+            .split_once('=')
+            .ok_or(format!("Expected 'field = value' in entry {}", key.trim()))?;
+        let after = after.trim_start();
+        let (value, remainder) = if after.starts_with('{') {
+            let end = matching_close(after, 0).ok_or("Unbalanced braces")?;
+            (&after[1..end], &after[end + 1..])
+        } else if let Some(quoted) = after.strip_prefix('"') {
+            let end = quoted.find('"').ok_or("Unterminated quote")?;
+            (&quoted[..end], &quoted[end + 1..])
+        } else {
+            let end = after.find(',').unwrap_or(after.len());
+            (after[..end].trim(), &after[end..])
+        };
+        let value: String = value.chars().filter(|c| *c != '{' && *c != '}').collect();
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+        fields.insert(name.trim().to_lowercase(), value);
+        fields_str = remainder;
+    }
+
+    let entry = Entry {
+        kind,
+        key: key.trim().to_string(),
+        fields,
+    };
+    Ok((Some(entry), rest))
+}
+
+/// The index of the bracket closing the one at `open`.
+fn matching_close(s: &str, open: usize) -> Option<usize> {
+    let (open_char, close_char) = match s[open..].chars().next()? {
+        '(' => ('(', ')'),
+        _ => ('{', '}'),
+    };
+    let mut depth = 0;
+    for (i, c) in s[open..].char_indices() {
+        if c == open_char {
+            depth += 1;
+        } else if c == close_char {
+            depth -= 1;
+            if depth == 0 {
+                return Some(open + i);
+            }
+        }
+    }
+    None
+}
+
+fn latex_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIB: &str = r#"
+@comment{ignore me}
+@book{beard2015,
+  author = {Mary Beard},
+  title = {{SPQR}: A History of Ancient Rome},
+  year = 2015,
+  publisher = "Profile Books"
+}
+@article(clark2012, title={The Sleepwalkers}, journal={Reviews & Notes})
+"#;
+
+    #[test]
+    fn test_parse_bibliography() {
+        let bib = Bibliography::parse(BIB).unwrap();
+        assert_eq!(bib.len(), 2);
+        let beard = bib.get("beard2015").unwrap();
+        assert_eq!(beard.kind, "book");
+        assert_eq!(
+            beard.fields["title"],
+            "SPQR: A History of Ancient Rome".to_string()
+        );
+        assert_eq!(beard.fields["year"], "2015".to_string());
+        assert_eq!(
+            beard.format(Markup::Markdown),
+            "Mary Beard (2015). *SPQR: A History of Ancient Rome*. Profile Books."
+        );
+        assert_eq!(
+            bib.get("clark2012").unwrap().format(Markup::Html),
+            "<i>The Sleepwalkers</i>. Reviews &amp; Notes."
+        );
+
+        let latex = bib.render(&["clark2012", "missing"], Markup::Latex);
+        assert!(
+            latex.contains("\\bibitem{clark2012} \\textit{The Sleepwalkers}. Reviews \\& Notes.")
+        );
+        assert!(!latex.contains("missing"));
+
+        assert!(Bibliography::parse("@book{unterminated, title={x}").is_err());
+    }
+}
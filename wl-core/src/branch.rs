@@ -0,0 +1,288 @@
+//! Branches: alternate copies of a worldline for drafts and counterfactuals.
+//!
+//! Branches of `worldline.txt` live in `worldline.txt.branches/`. Each branch `NAME` is stored
+//! as `NAME.wl`, along with `NAME.base`, a copy of the worldline as it was when the branch was
+//! forked (or last merged), which is what merges are computed against. The checked-out branch
+//! is recorded in `HEAD`; without one, the original file ("main") is checked out.
+
+use crate::progress::{Reporter, Silent};
+use crate::similar::Resolution;
+use crate::{Event, WorldLine};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const MAIN: &str = "main";
+
+/// The branches of a worldline file.
+pub struct Branches {
+    main_file: PathBuf,
+    dir: PathBuf,
+}
+
+/// What merging a branch changed, as returned by [`Branches::merge`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct MergeSummary {
+    pub added: usize,
+    pub removed: usize,
+    /// Events replaced by conflicting ones from the branch.
+    pub replaced: usize,
+}
+
+impl Branches {
+    pub fn for_file(main_file: impl AsRef<Path>) -> Self {
+        let main_file = main_file.as_ref().to_path_buf();
+        let mut dir = main_file.clone().into_os_string();
+        dir.push(".branches");
+        Self {
+            main_file,
+            dir: PathBuf::from(dir),
+        }
+    }
+
+    fn head_file(&self) -> PathBuf {
+        self.dir.join("HEAD")
+    }
+
+    fn base_file(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.base", name))
+    }
+
+    /// The file storing the given branch.
+    pub fn path(&self, name: &str) -> PathBuf {
+        if name == MAIN {
+            self.main_file.clone()
+        } else {
+            self.dir.join(format!("{}.wl", name))
+        }
+    }
+
+    /// The name of the checked-out branch.
+    pub fn current(&self) -> String {
+        fs::read_to_string(self.head_file())
+            .map(|s| s.trim().to_string())
+            .ok()
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| MAIN.to_string())
+    }
+
+    /// The file of the checked-out branch, which is what commands should operate on.
+    pub fn current_file(&self) -> PathBuf {
+        self.path(&self.current())
+    }
+
+    /// The names of all branches, including main.
+    pub fn list(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_suffix(".wl").map(str::to_string)
+            })
+            .collect();
+        names.sort();
+        names.insert(0, MAIN.to_string());
+        names
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        self.path(name).exists()
+    }
+
+    fn check_exists(&self, name: &str) -> Result<(), String> {
+        if self.exists(name) {
+            Ok(())
+        } else {
+            Err(format!("No branch named '{}'", name))
+        }
+    }
+
+    /// Fork the checked-out branch into a new branch. Does not switch to it.
+    pub fn create(&self, name: &str) -> Result<(), String> {
+        let valid = !name.is_empty()
+            && name != "HEAD"
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(format!("Invalid branch name: '{}'", name));
+        }
+        if self.exists(name) {
+            return Err(format!("Branch '{}' already exists", name));
+        }
+
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let source = self.current_file();
+        for target in [self.path(name), self.base_file(name)] {
+            fs::copy(&source, target).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Check out a branch.
+    pub fn switch(&self, name: &str) -> Result<(), String> {
+        self.check_exists(name)?;
+        if name == MAIN {
+            match fs::remove_file(self.head_file()) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+                _ => Ok(()),
+            }
+        } else {
+            fs::write(self.head_file(), name).map_err(|e| e.to_string())
+        }
+    }
+
+    /// Load a branch.
+    pub fn load(&self, name: &str) -> Result<WorldLine, String> {
+        self.check_exists(name)?;
+        WorldLine::from_file(self.path(name))
+    }
+
+    /// Apply the changes made on a branch since it was forked (or last merged) to the
+    /// checked-out branch, and save it.
+    pub fn merge(
+        &self,
+        name: &str,
+        resolve: impl FnMut(&Event, &Event) -> Resolution,
+    ) -> Result<MergeSummary, String> {
+        self.merge_reporting(name, resolve, &mut Silent)
+    }
+
+    /// [`Branches::merge`], reporting progress through the branch's added events to
+    /// `reporter`.
+    pub fn merge_reporting(
+        &self,
+        name: &str,
+        resolve: impl FnMut(&Event, &Event) -> Resolution,
+        reporter: &mut dyn Reporter,
+    ) -> Result<MergeSummary, String> {
+        let (merged, summary) = self.merged_reporting(name, resolve, reporter)?;
+        merged
+            .to_file(self.current_file())
+            .map_err(|e| e.to_string())?;
+        // later merges should only apply changes made after this one
+        fs::copy(self.path(name), self.base_file(name)).map_err(|e| e.to_string())?;
+        Ok(summary)
+    }
+
+    /// The checked-out branch with another branch's changes applied, as [`Branches::merge`]
+    /// would save it. Events the branch added that conflict with ones in the checked-out
+    /// branch are resolved with `resolve` (see [`WorldLine::import_resolving`]).
+    pub fn merged(
+        &self,
+        name: &str,
+        resolve: impl FnMut(&Event, &Event) -> Resolution,
+    ) -> Result<(WorldLine, MergeSummary), String> {
+        self.merged_reporting(name, resolve, &mut Silent)
+    }
+
+    fn merged_reporting(
+        &self,
+        name: &str,
+        resolve: impl FnMut(&Event, &Event) -> Resolution,
+        reporter: &mut dyn Reporter,
+    ) -> Result<(WorldLine, MergeSummary), String> {
+        if name == MAIN || name == self.current() {
+            return Err(format!(
+                "Can't merge '{}' into the checked-out branch",
+                name
+            ));
+        }
+        let theirs = self.load(name)?;
+        let base = WorldLine::from_file(self.base_file(name))?;
+        let mut current = WorldLine::from_file(self.current_file())?;
+
+        let changes = base.diff(&theirs);
+        let before = current.len();
+        let removed: Vec<&Event> = changes.removed().collect();
+        current.events.retain(|e| !removed.contains(&e));
+        let removed = before - current.len();
+        let report =
+            current.import_reporting(changes.added().cloned().collect(), resolve, reporter);
+
+        Ok((
+            current,
+            MergeSummary {
+                added: report.added,
+                removed,
+                replaced: report.replaced,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Date, Event};
+
+    #[test]
+    fn test_branch_and_merge() {
+        let dir = std::env::temp_dir().join(format!("wl-branch-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let main_file = dir.join("worldline.txt");
+        fs::write(
+            &main_file,
+            " CE 1914-06-28 Archduke shot\n CE 1914-07-28 War declared\n",
+        )
+        .unwrap();
+
+        let branches = Branches::for_file(&main_file);
+        assert_eq!(branches.current(), MAIN);
+        branches.create("whatif-1914").unwrap();
+        assert!(branches.create("whatif-1914").is_err());
+        assert!(branches.create("../escape").is_err());
+        assert_eq!(branches.list(), vec!["main", "whatif-1914"]);
+
+        // on the branch, the archduke survives
+        branches.switch("whatif-1914").unwrap();
+        assert_eq!(branches.current_file(), branches.path("whatif-1914"));
+        let mut alt = WorldLine::from_file(branches.current_file()).unwrap();
+        alt.events.clear();
+        alt.add_event(Event::new(
+            Date::new(1914, 6, 28).unwrap(),
+            "Archduke survives".to_string(),
+        ));
+        alt.to_file(branches.current_file()).unwrap();
+
+        // meanwhile main gains an unrelated event, which the merge must keep
+        branches.switch(MAIN).unwrap();
+        let mut main = WorldLine::from_file(&main_file).unwrap();
+        main.add_event(Event::new(
+            Date::new(1912, 4, 15).unwrap(),
+            "Titanic sinks".to_string(),
+        ));
+        main.to_file(&main_file).unwrap();
+
+        let summary = branches
+            .merge("whatif-1914", |_, _| Resolution::KeepBoth)
+            .unwrap();
+        assert_eq!(
+            summary,
+            MergeSummary {
+                added: 1,
+                removed: 2,
+                replaced: 0
+            }
+        );
+        let merged = WorldLine::from_file(&main_file).unwrap();
+        let descriptions: Vec<_> = merged.events.iter().map(|e| &e.description).collect();
+        assert_eq!(descriptions, vec!["Titanic sinks", "Archduke survives"]);
+
+        // nothing left to merge
+        let summary = branches
+            .merge("whatif-1914", |_, _| Resolution::KeepBoth)
+            .unwrap();
+        assert_eq!(
+            summary,
+            MergeSummary {
+                added: 0,
+                removed: 0,
+                replaced: 0
+            }
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,272 @@
+//! Dates in the Hebrew and Islamic calendars, behind the `calendars` feature, for events
+//! recorded in them, e.g. from letters or chronicles. They're converted to the proleptic
+//! Gregorian dates every date is kept in, so they sort and compare with the rest.
+//!
+//! Both are the arithmetic calendars from Dershowitz and Reingold's *Calendrical
+//! Calculations*. The Islamic one is the tabular calendar, which can be a day or two off the
+//! months as they were begun by sighting the new moon. Days are taken to start at midnight,
+//! not at the sunset before.
+
+use crate::query::parse_whole_date;
+use crate::Date;
+
+/// The day number (see [`Date::day_number`]) of day 1 in Dershowitz and Reingold's fixed day
+/// numbering, January 1st, 1 CE.
+const FIXED_EPOCH: i64 = -719162;
+
+/// Tishri 1, 1 AM (October 7th, 3761 BCE in the Julian calendar), as a fixed day.
+const HEBREW_EPOCH: i64 = -1373427;
+
+/// Muharram 1, 1 AH (July 16th, 622 CE in the Julian calendar), as a fixed day.
+const ISLAMIC_EPOCH: i64 = 227015;
+
+/// Hebrew month names, from Nisan as in the Bible, with Adar II last for leap years.
+const HEBREW_MONTHS: [&[&str]; 13] = [
+    &["nisan"],
+    &["iyar", "iyyar"],
+    &["sivan"],
+    &["tammuz", "tamuz"],
+    &["av", "ab"],
+    &["elul"],
+    &["tishrei", "tishri"],
+    &["cheshvan", "heshvan", "marcheshvan", "marheshvan"],
+    &["kislev"],
+    &["tevet", "tebeth"],
+    &["shevat", "shvat"],
+    &["adar", "adari", "adar1"],
+    &["adarii", "adar2", "adarsheni", "veadar"],
+];
+
+const ISLAMIC_MONTHS: [&[&str]; 12] = [
+    &["muharram"],
+    &["safar"],
+    &["rabialawwal", "rabiulawwal", "rabii", "rabi1"],
+    &[
+        "rabialthani",
+        "rabiulthani",
+        "rabialakhir",
+        "rabiii",
+        "rabi2",
+    ],
+    &["jumadaalawwal", "jumadaalula", "jumadai", "jumada1"],
+    &["jumadaalthani", "jumadaalakhirah", "jumadaii", "jumada2"],
+    &["rajab"],
+    &["shaban"],
+    &["ramadan", "ramazan"],
+    &["shawwal"],
+    &["dhualqadah", "dhulqadah", "dhualqidah", "dhulqidah"],
+    &["dhualhijjah", "dhulhijjah"],
+];
+
+fn is_hebrew_leap_year(year: i64) -> bool {
+    (7 * year + 1).rem_euclid(14) < 7
+}
+
+/// The days from the Hebrew epoch to the molad of Tishri of a year, put off a day when that
+/// would make Rosh Hashanah fall on a Sunday, Wednesday or Friday.
+fn hebrew_calendar_elapsed_days(year: i64) -> i64 {
+    let months_elapsed = (235 * year - 234).div_euclid(19);
+    let parts_elapsed = 12084 + 13753 * months_elapsed;
+    let days = 29 * months_elapsed + parts_elapsed.div_euclid(25920);
+    if (3 * (days + 1)).rem_euclid(7) < 3 {
+        days + 1
+    } else {
+        days
+    }
+}
+
+/// The fixed day of Rosh Hashanah, with the postponements keeping years to their allowed
+/// lengths.
+fn hebrew_new_year(year: i64) -> i64 {
+    let elapsed = hebrew_calendar_elapsed_days;
+    let correction = if elapsed(year + 1) - elapsed(year) == 356 {
+        2
+    } else if elapsed(year) - elapsed(year - 1) == 382 {
+        1
+    } else {
+        0
+    };
+    HEBREW_EPOCH + elapsed(year) + correction
+}
+
+fn days_in_hebrew_month(year: i64, month: u8) -> u8 {
+    let days_in_year = hebrew_new_year(year + 1) - hebrew_new_year(year);
+    let short = match month {
+        2 | 4 | 6 | 10 | 13 => true,
+        // Cheshvan is long and Kislev short in some years, to keep Rosh Hashanah's weekday
+        8 => days_in_year % 10 != 5,
+        9 => days_in_year % 10 == 3,
+        12 => !is_hebrew_leap_year(year),
+        _ => false,
+    };
+    if short {
+        29
+    } else {
+        30
+    }
+}
+
+fn is_islamic_leap_year(year: i64) -> bool {
+    (14 + 11 * year).rem_euclid(30) < 11
+}
+
+fn days_in_islamic_month(year: i64, month: u8) -> u8 {
+    if month % 2 == 1 || (month == 12 && is_islamic_leap_year(year)) {
+        30
+    } else {
+        29
+    }
+}
+
+/// The date on a fixed day, if it's in the supported years.
+fn from_fixed(fixed: i64) -> Result<Date, String> {
+    let days = fixed + FIXED_EPOCH - 1;
+    let first = Date::new(Date::MIN_YEAR, 1, 1).unwrap().day_number();
+    let last = Date::new(Date::MAX_YEAR, 12, 31).unwrap().day_number();
+    if !(first..=last).contains(&days) {
+        return Err("The date is out of range; supported years are 9999 BCE to 9999 CE".into());
+    }
+    Ok(Date::from_day_number(days))
+}
+
+impl Date {
+    /// The date of a day in the Hebrew calendar, in years Anno Mundi, with months counted from
+    /// Nisan (1) as in the Bible: Tishri, where the year starts, is 7, Adar 12, and Adar II 13
+    /// in leap years. E.g. 15 Nisan 5784, the first day of Passover, is `from_hebrew(5784, 1,
+    /// 15)`, April 23rd, 2024.
+    pub fn from_hebrew(year: i32, month: u8, day: u8) -> Result<Date, String> {
+        let year = year as i64;
+        if year < 1 {
+            return Err(format!("Invalid Hebrew year: {}", year));
+        }
+        let months = if is_hebrew_leap_year(year) { 13 } else { 12 };
+        if !(1..=months).contains(&month) {
+            return Err(format!("Invalid Hebrew month: {}", month));
+        }
+        if !(1..=days_in_hebrew_month(year, month)).contains(&day) {
+            return Err(format!("Invalid day: {}", day));
+        }
+        // the months from Tishri, then from Nisan if the month is after it
+        let mut months_before: Vec<u8> = (7..month.max(7)).collect();
+        if month < 7 {
+            months_before.extend((7..=months).chain(1..month));
+        }
+        let days_before: i64 = months_before
+            .into_iter()
+            .map(|m| days_in_hebrew_month(year, m) as i64)
+            .sum();
+        from_fixed(hebrew_new_year(year) + days_before + day as i64 - 1)
+    }
+
+    /// The date of a day in the (tabular) Islamic calendar, in years after the Hijra, e.g. 1
+    /// Ramadan 1445 is `from_islamic(1445, 9, 1)`, March 11th, 2024.
+    pub fn from_islamic(year: i32, month: u8, day: u8) -> Result<Date, String> {
+        let year = year as i64;
+        if year < 1 {
+            return Err(format!("Invalid Islamic year: {}", year));
+        }
+        if !(1..=12).contains(&month) {
+            return Err(format!("Invalid Islamic month: {}", month));
+        }
+        if !(1..=days_in_islamic_month(year, month)).contains(&day) {
+            return Err(format!("Invalid day: {}", day));
+        }
+        let month = month as i64;
+        from_fixed(
+            ISLAMIC_EPOCH - 1
+                + (year - 1) * 354
+                + (3 + 11 * year).div_euclid(30)
+                + 29 * (month - 1)
+                + (6 * month - 1).div_euclid(11)
+                + day as i64,
+        )
+    }
+}
+
+/// A month's number from its name, ignoring case, spaces and punctuation, e.g. "Adar II" or
+/// "Rabi' al-Awwal".
+fn month_number(months: &[&[&str]], name: &str) -> Option<u8> {
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let i = months
+        .iter()
+        .position(|names| names.contains(&name.as_str()))?;
+    Some(i as u8 + 1)
+}
+
+/// Parse a day in the Hebrew or Islamic calendar, written as the day, the month's name, the
+/// year and AM (Anno Mundi) or AH (after the Hijra), e.g. "15 Nisan 5784 AM" or "1 Ramadan
+/// 1445 AH". None if it doesn't end with AM or AH, so it's not meant as one.
+pub fn parse(s: &str) -> Option<Result<Date, String>> {
+    let words: Vec<&str> = s.split_whitespace().collect();
+    let (era, words) = words.split_last()?;
+    let hebrew = match era.to_lowercase().as_str() {
+        "am" => true,
+        "ah" => false,
+        _ => return None,
+    };
+    let invalid = || {
+        format!(
+            "Invalid date: {}, expected e.g. 15 Nisan 5784 AM or 1 Ramadan 1445 AH",
+            s.trim()
+        )
+    };
+    let parse = || {
+        let [day, month @ .., year] = words else {
+            return Err(invalid());
+        };
+        let day: u8 = day.parse().map_err(|_| invalid())?;
+        let year = parse_whole_date(year)
+            .ok()
+            .filter(|year| year.month == 0 && year.year > 0)
+            .ok_or_else(invalid)?
+            .year;
+        let month = month.join(" ");
+        if hebrew {
+            let month = month_number(&HEBREW_MONTHS, &month)
+                .ok_or_else(|| format!("Unknown Hebrew month: {}", month))?;
+            Date::from_hebrew(year, month, day)
+        } else {
+            let month = month_number(&ISLAMIC_MONTHS, &month)
+                .ok_or_else(|| format!("Unknown Islamic month: {}", month))?;
+            Date::from_islamic(year, month, day)
+        }
+    };
+    Some(parse())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calendars() {
+        let date = |s| parse_whole_date(s).unwrap();
+        // Rosh Hashanah, Hanukkah, Purim in a leap year, and Passover
+        assert_eq!(Date::from_hebrew(5784, 7, 1), Ok(date("2023-09-16")));
+        assert_eq!(Date::from_hebrew(5784, 9, 25), Ok(date("2023-12-08")));
+        assert_eq!(Date::from_hebrew(5784, 13, 14), Ok(date("2024-03-24")));
+        assert_eq!(Date::from_hebrew(5784, 1, 15), Ok(date("2024-04-23")));
+        assert!(Date::from_hebrew(5785, 13, 1).is_err());
+        assert!(Date::from_hebrew(5784, 2, 30).is_err());
+
+        assert_eq!(Date::from_islamic(1, 1, 1), Ok(date("622-07-19")));
+        assert_eq!(Date::from_islamic(1445, 9, 1), Ok(date("2024-03-11")));
+        assert!(Date::from_islamic(1445, 13, 1).is_err());
+
+        assert_eq!(parse("15 Nisan 5784 AM"), Some(Ok(date("2024-04-23"))));
+        assert_eq!(parse("14 Adar II 5784 am"), Some(Ok(date("2024-03-24"))));
+        assert_eq!(parse("1 Ramadan 1445 AH"), Some(Ok(date("2024-03-11"))));
+        // Eid al-Adha, a day after it was observed
+        assert_eq!(
+            parse("10 Dhu al-Hijjah 1445 AH"),
+            Some(Ok(date("2024-06-17")))
+        );
+        assert_eq!(parse("15 Nisan 5784"), None);
+        assert!(parse("1 Brumaire 1445 AH").unwrap().is_err());
+        assert!(parse("30 Iyar 5784 AM").unwrap().is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Checking a worldline file for problems: lines that can't be read, impossible dates, events
+//! out of order, duplicate ids, events without descriptions and links to missing files.
+//!
+//! [`check`] reads the contents of a file line by line, so it can report lines that would stop
+//! the worldline from loading at all. [`WorldLine::validate`] checks a loaded worldline.
+
+use crate::format::{self, FormatVersion};
+use crate::order::PartialDateOrder;
+use crate::{Date, Event, WorldLine, DATE_REGEX};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// A line that isn't an event or front matter.
+    Unparsable { line: usize, error: String },
+    /// A date that doesn't exist, e.g. 1900-02-29.
+    ImpossibleDate { line: usize, error: String },
+    /// An event dated before the event before it.
+    OutOfOrder {
+        line: usize,
+        date: Date,
+        previous: Date,
+    },
+    /// Events sharing an id, which should be unique.
+    DuplicateId { id: String, lines: Vec<usize> },
+    /// An event whose description is blank or only whitespace.
+    EmptyDescription { line: usize, date: Date },
+    /// Front matter naming a file that doesn't exist, e.g. the bibliography.
+    BrokenLink { key: String, path: String },
+}
+
+impl Issue {
+    /// The line the issue is on, or first on.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Issue::Unparsable { line, .. }
+            | Issue::ImpossibleDate { line, .. }
+            | Issue::OutOfOrder { line, .. }
+            | Issue::EmptyDescription { line, .. } => Some(*line),
+            Issue::DuplicateId { lines, .. } => lines.first().copied(),
+            Issue::BrokenLink { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::Unparsable { line, error } => write!(f, "line {}: {}", line, error),
+            Issue::ImpossibleDate { line, error } => {
+                write!(f, "line {}: Impossible date: {}", line, error)
+            }
+            Issue::OutOfOrder {
+                line,
+                date,
+                previous,
+            } => write!(
+                f,
+                "line {}: {} is out of order, after {}",
+                line, date, previous
+            ),
+            Issue::DuplicateId { id, lines } => {
+                let lines: Vec<String> = lines.iter().map(usize::to_string).collect();
+                write!(f, "lines {}: Duplicate id '{}'", lines.join(", "), id)
+            }
+            Issue::EmptyDescription { line, date } => {
+                write!(f, "line {}: The event on {} has no description", line, date)
+            }
+            Issue::BrokenLink { key, path } => {
+                write!(f, "#{}: {} doesn't exist", key, path)
+            }
+        }
+    }
+}
+
+/// Check the contents of a worldline file, including lines that can't be read.
+pub fn check(contents: &str) -> Vec<Issue> {
+    let (file, invalid) = match format::parse_lenient(contents) {
+        Ok(parsed) => parsed,
+        Err(error) => return vec![Issue::Unparsable { line: 1, error }],
+    };
+    let mut issues: Vec<Issue> = invalid
+        .into_iter()
+        .map(|line| {
+            // the date has the right shape, so it was rejected as a date that doesn't exist
+            if DATE_REGEX.is_match(line.text) && Date::parse(line.text).is_err() {
+                Issue::ImpossibleDate {
+                    line: line.number,
+                    error: line.error,
+                }
+            } else {
+                Issue::Unparsable {
+                    line: line.number,
+                    error: line.error,
+                }
+            }
+        })
+        .collect();
+    let events: Vec<(usize, &Event)> = file.event_lines.into_iter().zip(&file.events).collect();
+    issues.extend(validate(&events, &file.front_matter));
+    issues.sort_by_key(Issue::line);
+    issues
+}
+
+/// Check events (with their line numbers) and front matter that were read successfully.
+fn validate(events: &[(usize, &Event)], front_matter: &[(String, String)]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let order = PartialDateOrder::from_front_matter(front_matter);
+    for pair in events.windows(2) {
+        let ((_, previous), (line, event)) = (pair[0], pair[1]);
+        if order.cmp(&event.date, &previous.date) == Ordering::Less {
+            issues.push(Issue::OutOfOrder {
+                line,
+                date: event.date.clone(),
+                previous: previous.date.clone(),
+            });
+        }
+    }
+
+    let mut ids: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (line, event) in events {
+        if let Some(id) = &event.id {
+            ids.entry(id).or_default().push(*line);
+        }
+    }
+    issues.extend(
+        ids.into_iter()
+            .filter(|(_, lines)| lines.len() > 1)
+            .map(|(id, lines)| Issue::DuplicateId {
+                id: id.to_string(),
+                lines,
+            }),
+    );
+    issues.extend(
+        events
+            .iter()
+            .filter(|(_, event)| event.description.trim().is_empty())
+            .map(|(line, event)| Issue::EmptyDescription {
+                line: *line,
+                date: event.date.clone(),
+            }),
+    );
+    issues.extend(broken_links(front_matter));
+    issues
+}
+
+/// Front matter naming files that don't exist.
+#[cfg(feature = "std-fs")]
+fn broken_links(front_matter: &[(String, String)]) -> Vec<Issue> {
+    front_matter
+        .iter()
+        .filter(|(key, path)| {
+            key == crate::bib::FRONT_MATTER_KEY && !std::path::Path::new(path).exists()
+        })
+        .map(|(key, path)| Issue::BrokenLink {
+            key: key.clone(),
+            path: path.clone(),
+        })
+        .collect()
+}
+
+/// Files can't be checked without filesystem access.
+#[cfg(not(feature = "std-fs"))]
+fn broken_links(_front_matter: &[(String, String)]) -> Vec<Issue> {
+    Vec::new()
+}
+
+impl WorldLine {
+    /// Check the worldline for problems, with line numbers as it's written to a file.
+    pub fn validate(&self) -> Vec<Issue> {
+        let first_line = match self.file_version() {
+            FormatVersion::V1 => 1,
+            FormatVersion::V2 => self.front_matter.len() + 2,
+        };
+        let events: Vec<(usize, &Event)> = (first_line..).zip(&self.events).collect();
+        validate(&events, &self.front_matter)
+    }
+
+    /// Sort the events by date, fixing [`Issue::OutOfOrder`]. Events on the same date keep
+    /// their order.
+    pub fn sort_by_date(&mut self) {
+        let order = self.partial_date_order();
+        self.events.sort_by(|a, b| order.cmp(&a.date, &b.date));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let contents = "#worldline v2\n\
+                        1918-11-11 WWI ends\tid=ww1\n\
+                        1914-07-28 WWI begins\tid=ww1\n\
+                        1900-02-29 Not a leap day\n\
+                        Not an event\n\
+                        1919 [war]\n";
+        let issues: Vec<String> = check(contents).iter().map(Issue::to_string).collect();
+        assert_eq!(
+            issues,
+            vec![
+                "lines 2, 3: Duplicate id 'ww1'",
+                "line 3: 1914-07-28 is out of order, after 1918-11-11",
+                "line 4: Impossible date: Invalid day: 29",
+                "line 5: Invalid date format: Not an event",
+                "line 6: The event on 1919 has no description",
+            ]
+        );
+        #[cfg(feature = "std-fs")]
+        assert_eq!(
+            check("#worldline v2\n#bibliography: /nonexistent/refs.bib\n"),
+            vec![Issue::BrokenLink {
+                key: "bibliography".to_string(),
+                path: "/nonexistent/refs.bib".to_string(),
+            }]
+        );
+
+        let mut worldline: WorldLine = "1918 WWI ends\n1914 WWI begins\n".parse().unwrap();
+        assert_eq!(worldline.validate().len(), 1);
+        worldline.sort_by_date();
+        assert_eq!(worldline.validate(), Vec::new());
+    }
+}
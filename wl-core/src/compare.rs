@@ -0,0 +1,127 @@
+//! Showing two worldlines side by side, e.g. Roman and Chinese history over the same period.
+//!
+//! ```text
+//!          rome.txt                   │ china.txt
+//! BCE 0221                            │ Qin unifies China
+//! BCE 0218 Hannibal crosses the Alps  │ Zhang Liang attacks the emperor
+//! BCE 0206                            │ Han dynasty founded
+//! ```
+//!
+//! The events of both are merged into one list of dates, and events on the same date are on
+//! the same row.
+
+use crate::display::{truncate, wrap, DisplayOptions, DATE_STYLE, MIN_TEXT_WIDTH};
+use crate::Event;
+
+const SEPARATOR: &str = " │ ";
+
+/// Pair up the events of two sorted lists by date: each row has a left event, a right event,
+/// or both when they're on the same date.
+fn align<'a>(left: &'a [Event], right: &'a [Event]) -> Vec<(Option<&'a Event>, Option<&'a Event>)> {
+    let (mut left, mut right) = (left.iter().peekable(), right.iter().peekable());
+    let mut rows = Vec::new();
+    loop {
+        let row = match (left.peek(), right.peek()) {
+            (None, None) => break,
+            (Some(l), Some(r)) if l.date == r.date => (left.next(), right.next()),
+            (Some(l), Some(r)) if r.date < l.date => (None, right.next()),
+            (Some(_), _) => (left.next(), None),
+            (None, Some(_)) => (None, right.next()),
+        };
+        rows.push(row);
+    }
+    rows
+}
+
+/// Render two lists of events in columns under their titles, with the dates of both in a
+/// column on the left. The columns share the width; without one, the left column is as wide
+/// as its longest description.
+pub fn render(
+    left: &[Event],
+    right: &[Event],
+    titles: (&str, &str),
+    options: &DisplayOptions,
+) -> Vec<String> {
+    let rows = align(left, right);
+    let show_era = options.show_era(left.iter().chain(right));
+    let dates: Vec<String> = rows
+        .iter()
+        .map(|(l, r)| options.format_date(&l.or(*r).unwrap().date, show_era))
+        .collect();
+    let date_width = dates.iter().map(|d| d.chars().count()).max().unwrap_or(0);
+    let column_width = match options.width {
+        Some(width) => (width.saturating_sub(date_width + 1 + SEPARATOR.chars().count()) / 2)
+            .max(MIN_TEXT_WIDTH),
+        None => left
+            .iter()
+            .map(|e| e.full_description().chars().count())
+            .chain([titles.0.chars().count()])
+            .max()
+            .unwrap_or(0),
+    };
+    let cell = |event: Option<&Event>| -> Vec<String> {
+        let Some(event) = event else {
+            return Vec::new();
+        };
+        let text = event.full_description();
+        match options.width {
+            Some(_) if options.wrap => wrap(&text, column_width),
+            Some(_) => vec![truncate(&text, column_width)],
+            None => vec![text],
+        }
+    };
+    let line = |date: &str, left: &str, right: &str| {
+        let padding = column_width.saturating_sub(left.chars().count());
+        let date = if date.is_empty() {
+            format!("{:date_width$}", "")
+        } else {
+            options.paint(DATE_STYLE, &format!("{:date_width$}", date))
+        };
+        let line = format!("{} {}{:padding$}{}{}", date, left, "", SEPARATOR, right);
+        line.trim_end().to_string()
+    };
+
+    let mut lines = vec![line("", &truncate(titles.0, column_width), titles.1)];
+    for ((l, r), date) in rows.into_iter().zip(dates) {
+        let (l, r) = (cell(l), cell(r));
+        for i in 0..l.len().max(r.len()) {
+            let date = if i == 0 { date.as_str() } else { "" };
+            let get = |cell: &[String]| cell.get(i).cloned().unwrap_or_default();
+            lines.push(line(date, &get(&l), &get(&r)));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldLine;
+
+    #[test]
+    fn test_render() {
+        let rome: WorldLine = "-218 Hannibal crosses the Alps\n-44-03-15 Caesar assassinated\n"
+            .parse()
+            .unwrap();
+        let china: WorldLine = "-221 Qin unifies China\n-218 Zhang Liang attacks the emperor\n"
+            .parse()
+            .unwrap();
+        let options = DisplayOptions {
+            width: Some(50),
+            ..Default::default()
+        };
+        let lines: Vec<String> = render(rome.events(), china.events(), ("rome", "china"), &options)
+            .iter()
+            .map(|line| line.replace("\u{001B}[34m", "").replace("\u{001B}[0m", ""))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "               rome             │ china",
+                "BCE 0221                        │ Qin unifies Chi…",
+                "BCE 0218       Hannibal crosse… │ Zhang Liang att…",
+                "BCE 0044-03-15 Caesar assassin… │",
+            ]
+        );
+    }
+}
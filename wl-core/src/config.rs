@@ -0,0 +1,337 @@
+//! User configuration.
+//!
+//! The library doesn't decide where the config file is: the wl command reads it
+//! from `$WORLDLINE_CONFIG`, or `$XDG_CONFIG_HOME/worldline/config.toml`, where a
+//! `file` key before any section sets the worldline file. It uses a small subset
+//! of TOML: `[section]` headers, `key = "value"` pairs (keys and values may be
+//! bare, or strings in double quotes with `\"` and `\\` escapes, or in single
+//! quotes without escapes), and `#` comments. For example:
+//!
+//! ```toml
+//! [views]
+//! cold-war = "tag:coldwar AND 1945..1991"
+//! ides = '"Caesar dictator" OR brutus'
+//!
+//! [categories]
+//! war = "red"
+//!
+//! [places]
+//! rome = "41.9,12.5"
+//!
+//! [eras]
+//! AUC = "753 BCE"
+//! "Elizabeth II" = "1952"
+//!
+//! [templates]
+//! birth = "[life] Birth of {name}"
+//! ```
+
+use crate::display::Color;
+use crate::epoch::Epoch;
+use crate::geo::Coordinates;
+use crate::query::QueryExpr;
+use std::collections::BTreeMap;
+#[cfg(feature = "std-fs")]
+use std::fs;
+use std::ops::Range;
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct Config {
+    /// section name -> key -> value. Keys before any section header are in section "".
+    sections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Config {
+    #[cfg(feature = "std-fs")]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut config = Self::default();
+        let mut section = String::new();
+        for (i, line) in contents.lines().enumerate() {
+            let invalid = |e: String| format!("line {}: {}", i + 1, e);
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = unquote(name.trim()).map_err(invalid)?;
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("Expected key = value".to_string()))?;
+            config.sections.entry(section.clone()).or_default().insert(
+                unquote(key.trim()).map_err(invalid)?,
+                unquote(value.trim()).map_err(invalid)?,
+            );
+        }
+        Ok(config)
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections.get(section)?.get(key).map(String::as_str)
+    }
+
+    /// All key-value pairs in a section, sorted by key.
+    pub fn section(&self, name: &str) -> impl Iterator<Item = (&str, &str)> {
+        self.sections
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The filter defined by a named view in the `[views]` section.
+    pub fn view(&self, name: &str) -> Result<QueryExpr, String> {
+        let expr = self
+            .get("views", name)
+            .ok_or_else(|| format!("No view named '{}' in the config", name))?;
+        QueryExpr::parse(expr).map_err(|e| format!("View '{}': {}", name, e))
+    }
+
+    /// The colors to show categories in, from the `[categories]` section.
+    pub fn category_colors(&self) -> Result<Vec<(&str, Color)>, String> {
+        self.section("categories")
+            .map(|(category, color)| {
+                Color::parse(color)
+                    .map(|color| (category, color))
+                    .map_err(|e| format!("Category '{}': {}", category, e))
+            })
+            .collect()
+    }
+
+    /// The coordinates of named places (see [`crate::geo`]), from the `[places]` section.
+    pub fn places(&self) -> Result<BTreeMap<String, Coordinates>, String> {
+        self.section("places")
+            .map(|(place, coordinates)| {
+                Coordinates::parse(coordinates)
+                    .map(|coordinates| (place.to_string(), coordinates))
+                    .map_err(|e| format!("Place '{}': {}", place, e))
+            })
+            .collect()
+    }
+
+    /// Custom eras (see [`crate::epoch`]) from the `[eras]` section, whose keys are their
+    /// names and whose values are their first years, optionally followed by "backward".
+    pub fn eras(&self) -> Result<Vec<Epoch>, String> {
+        self.section("eras")
+            .map(|(name, year)| {
+                Epoch::parse(&format!("{}={}", name, year))
+                    .map_err(|e| format!("Era '{}': {}", name, e))
+            })
+            .collect()
+    }
+
+    /// The text of an event made from a template in the `[templates]` section, e.g. `birth =
+    /// "Birth of {name}"`, with its placeholders filled in with values in the order they first
+    /// appear.
+    pub fn fill_template(&self, name: &str, values: &[String]) -> Result<String, String> {
+        let template = self
+            .get("templates", name)
+            .ok_or_else(|| format!("No template named '{}' in the config", name))?;
+        let placeholders = placeholders(template);
+        let mut names: Vec<&str> = Vec::new();
+        for (_, placeholder) in &placeholders {
+            if !names.contains(placeholder) {
+                names.push(placeholder);
+            }
+        }
+        if values.len() != names.len() {
+            let expected: Vec<String> = names.iter().map(|n| format!("{{{}}}", n)).collect();
+            return Err(format!(
+                "Template '{}' needs {} value{} ({}), got {}",
+                name,
+                names.len(),
+                if names.len() == 1 { "" } else { "s" },
+                expected.join(", "),
+                values.len()
+            ));
+        }
+        let mut text = String::new();
+        let mut end = 0;
+        for (range, placeholder) in placeholders {
+            text += &template[end..range.start];
+            text += &values[names.iter().position(|n| *n == placeholder).unwrap()];
+            end = range.end;
+        }
+        text += &template[end..];
+        Ok(text)
+    }
+}
+
+/// Where the `{name}` placeholders in a template are, with their names. Braces around anything
+/// but letters, digits, `-` and `_` are left alone.
+fn placeholders(template: &str) -> Vec<(Range<usize>, &str)> {
+    let mut placeholders = Vec::new();
+    let mut start = 0;
+    while let Some(open) = template[start..].find('{').map(|i| start + i) {
+        start = open + 1;
+        let Some(close) = template[start..].find('}').map(|i| start + i) else {
+            break;
+        };
+        let name = &template[start..close];
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if valid {
+            placeholders.push((open..close + 1, name));
+            start = close + 1;
+        }
+    }
+    placeholders
+}
+
+/// Remove a trailing `#` comment, ignoring `#`s inside strings.
+fn strip_comment(line: &str) -> &str {
+    // the quote of the string we're in, if any
+    let mut quote = None;
+    let mut chars = line.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            // skip escaped characters in basic strings, e.g. \"
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (Some(open), c) if c == open => quote = None,
+            _ => {}
+        }
+    }
+    line
+}
+
+/// The text of a key or value: a basic string in double quotes, in which `\"`, `\\`, `\n`
+/// and `\t` are escapes, a literal string in single quotes, which has none, or else the bare
+/// text.
+fn unquote(s: &str) -> Result<String, String> {
+    if let Some(literal) = s.strip_prefix('\'') {
+        return match literal.split_once('\'') {
+            Some((text, "")) => Ok(text.to_string()),
+            Some(_) => Err(format!("Unexpected text after the string in {}", s)),
+            None => Err(format!("Unterminated string {}", s)),
+        };
+    }
+    let Some(basic) = s.strip_prefix('"') else {
+        return Ok(s.to_string());
+    };
+    let mut text = String::new();
+    let mut chars = basic.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' if basic[i + 1..].is_empty() => return Ok(text),
+            '"' => return Err(format!("Unexpected text after the string in {}", s)),
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some(c @ ('"' | '\\')) => text.push(c),
+                Some('n') => text.push('\n'),
+                Some('t') => text.push('\t'),
+                _ => return Err(format!("Invalid escape in {}", s)),
+            },
+            c => text.push(c),
+        }
+    }
+    Err(format!("Unterminated string {}", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            "# my config\n\
+             file = ~/history.txt\n\
+             \n\
+             [views]\n\
+             \"cold-war\" = \"tag:coldwar AND 1945..1991\" # the long peace\n\
+             rome = \"#rome\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.get("", "file"), Some("~/history.txt"));
+        assert_eq!(
+            config.get("views", "cold-war"),
+            Some("tag:coldwar AND 1945..1991")
+        );
+        assert_eq!(config.get("views", "rome"), Some("#rome"));
+        assert_eq!(config.section("views").count(), 2);
+
+        assert!(config.view("cold-war").is_ok());
+        assert!(config.view("hot-war").is_err());
+        assert!(config.category_colors().unwrap().is_empty());
+
+        let config = Config::parse(
+            "[views]\n\
+             ides = \"\\\"Caesar dictator\\\" OR brutus\" # quoted phrase\n\
+             senate = '\"Caesar\" AND \"dictator\"' # \"literal\"\n\
+             path = \"C:\\\\history\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.get("views", "ides"),
+            Some("\"Caesar dictator\" OR brutus")
+        );
+        assert_eq!(
+            config.get("views", "senate"),
+            Some("\"Caesar\" AND \"dictator\"")
+        );
+        assert_eq!(config.get("views", "path"), Some("C:\\history"));
+        assert!(config.view("ides").is_ok());
+        assert!(Config::parse("[views]\nb = \"Caesar\" AND \"dictator\"\n").is_err());
+        assert!(Config::parse("[views]\nb = \"Caesar\n").is_err());
+
+        let config = Config::parse("[categories]\nwar = red\nscience = \"green\"\n").unwrap();
+        assert_eq!(
+            config.category_colors(),
+            Ok(vec![("science", Color::Green), ("war", Color::Red)])
+        );
+        assert!(Config::parse("[categories]\nwar = mauve\n")
+            .unwrap()
+            .category_colors()
+            .is_err());
+
+        let config = Config::parse("[places]\n\"New York\" = \"40.7, -74.0\"\n").unwrap();
+        let places = config.places().unwrap();
+        assert_eq!(places["New York"], Coordinates::new(40.7, -74.0).unwrap());
+        assert!(Config::parse("[places]\nrome = 141.9,12.5\n")
+            .unwrap()
+            .places()
+            .is_err());
+
+        let config = Config::parse("[eras]\n\"Elizabeth II\" = 1952\nBP = \"1950 backward\"\n");
+        let eras = config.unwrap().eras().unwrap();
+        assert_eq!(eras[1].label(), "Elizabeth II");
+        assert_eq!(eras[0].year(1900), (51, "BP"));
+        assert!(Config::parse("[eras]\nAUC = soon\n")
+            .unwrap()
+            .eras()
+            .is_err());
+
+        let config = Config::parse(
+            "[templates]\n\
+             birth = \"[life] Birth of {name} {x}\"\n\
+             marriage = \"{a} marries {b}; {a} {is happy}\"\n",
+        )
+        .unwrap();
+        let values = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            config.fill_template("marriage", &values(&["Ada", "{b}"])),
+            Ok("Ada marries {b}; Ada {is happy}".to_string())
+        );
+        assert_eq!(
+            config.fill_template("birth", &values(&["Ada Lovelace"])),
+            Err("Template 'birth' needs 2 values ({name}, {x}), got 1".to_string())
+        );
+        assert!(config.fill_template("death", &[]).is_err());
+
+        assert!(Config::parse("[views]\nnot a pair\n").is_err());
+    }
+}
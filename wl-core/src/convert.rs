@@ -0,0 +1,838 @@
+//! Converting worldlines to and from other file formats.
+//!
+//! The format is chosen by file extension:
+//! - `wl`: the native format (see [`crate::format`])
+//! - `json`: `{"front_matter": {"key": "value"}, "events": [EVENT, ...]}`
+//! - `jsonl`: one EVENT object per line
+//! - `csv`: a `date,end,description,id,tags,sources,category,place` header, then one event per
+//!   row, with tags and sources separated by `;`
+//! - `ics`: an iCalendar file with one all-day VEVENT per event
+//!
+//! An EVENT object has the fields `date` and `description`, and optionally `category`, `place` (as
+//! written after the `@`, e.g. `Rome` or `(41.9,12.5)`), `id`, `end`, `tags` (array of strings),
+//! `people` (array of names), `series`, `sources` (array of strings), `notes` (array of
+//! `{"timestamp": ..., "text": ...}` objects), `archived` (`true` for archived events), `pinned`
+//! (`true` for pinned events), `seq` (a number ordering events on the same date), and `created` and
+//! `modified` (timestamps). Dates are written like `1945-09-02`, `1945-09`, `1945` or `-0044-03-15`
+//! (44 BCE), and timestamps like `2025-03-01T14:30:00Z`.
+//! [`crate::schema`] describes the `json` and `jsonl` formats as a JSON Schema.
+//!
+//! JSON is lossless. The other formats drop what they can't store; see [`Format::losses`].
+
+use crate::geo::{Coordinates, Place};
+use crate::json::Value;
+use crate::query::parse_whole_date;
+#[cfg(feature = "std-fs")]
+use crate::storage::Backend;
+use crate::timestamp::Timestamp;
+use crate::{Date, Event, Note, WorldLine};
+#[cfg(feature = "std-fs")]
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Wl,
+    Json,
+    Jsonl,
+    Csv,
+    Ics,
+}
+
+const CSV_COLUMNS: [&str; 8] = [
+    "date",
+    "end",
+    "description",
+    "id",
+    "tags",
+    "sources",
+    "category",
+    "place",
+];
+
+impl Format {
+    /// Detect the format from a file's extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match extension.as_str() {
+            "wl" | "txt" => Ok(Format::Wl),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::Jsonl),
+            "csv" => Ok(Format::Csv),
+            "ics" => Ok(Format::Ics),
+            "sqlite" | "db" => Err(format!(
+                "{} is a SQLite database, which is storage rather than a file format",
+                path.display()
+            )),
+            _ => Err(format!(
+                "Can't tell the format of {} from its extension (expected wl, json, jsonl, csv or ics)",
+                path.display()
+            )),
+        }
+    }
+
+    pub fn read(&self, contents: &str) -> Result<WorldLine, String> {
+        match self {
+            Format::Wl => WorldLine::from_reader(contents.as_bytes()),
+            Format::Json => read_json(contents),
+            Format::Jsonl => {
+                let events = contents
+                    .lines()
+                    .enumerate()
+                    .filter(|(_, line)| !line.trim().is_empty())
+                    .map(|(i, line)| {
+                        Value::parse(line)
+                            .and_then(|value| event_from_json(&value))
+                            .map_err(|e| format!("line {}: {}", i + 1, e))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(worldline_from(events, Vec::new()))
+            }
+            Format::Csv => read_csv(contents),
+            Format::Ics => read_ics(contents),
+        }
+    }
+
+    pub fn write(&self, worldline: &WorldLine) -> String {
+        match self {
+            Format::Wl => {
+                let mut out = Vec::new();
+                worldline.write_to(&mut out).unwrap();
+                String::from_utf8(out).unwrap()
+            }
+            Format::Json => {
+                let front_matter = worldline
+                    .front_matter
+                    .iter()
+                    .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+                    .collect();
+                let events = worldline.events.iter().map(event_to_json).collect();
+                let value = Value::Object(vec![
+                    ("front_matter".to_string(), Value::Object(front_matter)),
+                    ("events".to_string(), Value::Array(events)),
+                ]);
+                format!("{}\n", value)
+            }
+            Format::Jsonl => worldline
+                .events
+                .iter()
+                .map(|e| format!("{}\n", event_to_json(e)))
+                .collect(),
+            Format::Csv => write_csv(worldline),
+            Format::Ics => write_ics(worldline),
+        }
+    }
+
+    /// Descriptions of the worldline's data that this format can't store, e.g. "2 BCE events can't be stored".
+    pub fn losses(&self, worldline: &WorldLine) -> Vec<String> {
+        let count = |f: fn(&Event) -> bool| worldline.events.iter().filter(|e| f(e)).count();
+        let mut losses = Vec::new();
+        let mut lose = |n: usize, what: &str| {
+            if n > 0 {
+                losses.push(format!("{} {} can't be stored", n, what));
+            }
+        };
+        let front_matter = worldline.front_matter.len();
+        match self {
+            Format::Wl | Format::Json => {}
+            Format::Jsonl => lose(front_matter, "front matter entries"),
+            Format::Csv => {
+                lose(front_matter, "front matter entries");
+                lose(count(|e| !e.notes.is_empty()), "events' notes");
+                lose(count(|e| !e.people.is_empty()), "events' people");
+                lose(count(|e| e.series.is_some()), "events' series");
+                lose(count(|e| e.archived), "archived flags");
+                lose(count(|e| e.pinned), "pinned flags");
+                lose(count(|e| e.seq.is_some()), "sequence numbers");
+                lose(
+                    count(|e| e.created.is_some() || e.modified.is_some()),
+                    "events' timestamps",
+                );
+            }
+            Format::Ics => {
+                lose(front_matter, "front matter entries");
+                lose(count(|e| !e.notes.is_empty()), "events' notes");
+                lose(count(|e| !e.people.is_empty()), "events' people");
+                lose(count(|e| e.series.is_some()), "events' series");
+                lose(count(|e| e.archived), "archived flags");
+                lose(count(|e| e.pinned), "pinned flags");
+                lose(count(|e| e.seq.is_some()), "sequence numbers");
+                lose(
+                    count(|e| e.created.is_some() || e.modified.is_some()),
+                    "events' timestamps",
+                );
+                lose(count(|e| !e.sources.is_empty()), "events' sources");
+                lose(count(|e| e.date.year < 1), "BCE events");
+            }
+        }
+        losses
+    }
+}
+
+/// Convert a file from one format to another, detected by extension. Returns the losses (see
+/// [`Format::losses`]). With the `sqlite` feature, either file can be a SQLite database (see
+/// [`crate::storage`]), which is lossless.
+#[cfg(feature = "std-fs")]
+pub fn convert(input: impl AsRef<Path>, output: impl AsRef<Path>) -> Result<Vec<String>, String> {
+    let (input, output) = (input.as_ref(), output.as_ref());
+    let (from, to) = (Backend::from_path(input)?, Backend::from_path(output)?);
+    let worldline = match from {
+        Backend::Text => {
+            let format = Format::from_path(input)?;
+            let contents = fs::read_to_string(input)
+                .map_err(|e| format!("Could not read {}: {}", input.display(), e))?;
+            format
+                .read(&contents)
+                .map_err(|e| format!("{}: {}", input.display(), e))?
+        }
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => from.open(input)?.load()?,
+        // needs a passphrase, which open() explains
+        #[cfg(feature = "crypto")]
+        Backend::Encrypted => from.open(input)?.load()?,
+    };
+    match to {
+        Backend::Text => {
+            let format = Format::from_path(output)?;
+            fs::write(output, format.write(&worldline))
+                .map_err(|e| format!("Could not write {}: {}", output.display(), e))?;
+            Ok(format.losses(&worldline))
+        }
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => {
+            to.open(output)?.save(&worldline)?;
+            Ok(Vec::new())
+        }
+        #[cfg(feature = "crypto")]
+        Backend::Encrypted => {
+            to.open(output)?.save(&worldline)?;
+            Ok(Vec::new())
+        }
+    }
+}
+
+fn worldline_from(events: Vec<Event>, front_matter: Vec<(String, String)>) -> WorldLine {
+    let mut worldline: WorldLine = events.into_iter().collect();
+    for (key, value) in front_matter {
+        worldline.set_front_matter(&key, value);
+    }
+    worldline
+}
+
+fn check_end(event: &Event) -> Result<(), String> {
+    match &event.end {
+        Some(end) if *end < event.date => Err(format!(
+            "Event '{}' ends before it starts",
+            event.description
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn string_array(items: &[String]) -> Value {
+    Value::Array(items.iter().cloned().map(Value::String).collect())
+}
+
+impl Event {
+    /// The event as a JSON object on one line, as in `jsonl` files.
+    pub fn to_json(&self) -> String {
+        event_to_json(self).to_string()
+    }
+}
+
+fn event_to_json(event: &Event) -> Value {
+    let mut fields = vec![
+        ("date".to_string(), Value::String(event.date.to_string())),
+        (
+            "description".to_string(),
+            Value::String(event.description.clone()),
+        ),
+    ];
+    if let Some(category) = &event.category {
+        fields.push(("category".to_string(), Value::String(category.clone())));
+    }
+    if let Some(place) = &event.place {
+        fields.push(("place".to_string(), Value::String(place.to_string())));
+    }
+    if let Some(id) = &event.id {
+        fields.push(("id".to_string(), Value::String(id.clone())));
+    }
+    if let Some(end) = &event.end {
+        fields.push(("end".to_string(), Value::String(end.to_string())));
+    }
+    if !event.tags.is_empty() {
+        fields.push(("tags".to_string(), string_array(&event.tags)));
+    }
+    if !event.people.is_empty() {
+        fields.push(("people".to_string(), string_array(&event.people)));
+    }
+    if let Some(series) = &event.series {
+        fields.push(("series".to_string(), Value::String(series.clone())));
+    }
+    if !event.sources.is_empty() {
+        fields.push(("sources".to_string(), string_array(&event.sources)));
+    }
+    if !event.notes.is_empty() {
+        let notes = event
+            .notes
+            .iter()
+            .map(|note| {
+                Value::Object(vec![
+                    (
+                        "timestamp".to_string(),
+                        Value::String(note.timestamp.to_string()),
+                    ),
+                    ("text".to_string(), Value::String(note.text.clone())),
+                ])
+            })
+            .collect();
+        fields.push(("notes".to_string(), Value::Array(notes)));
+    }
+    if event.archived {
+        fields.push(("archived".to_string(), Value::Bool(true)));
+    }
+    if event.pinned {
+        fields.push(("pinned".to_string(), Value::Bool(true)));
+    }
+    if let Some(seq) = event.seq {
+        fields.push(("seq".to_string(), Value::Number(seq as f64)));
+    }
+    for (key, timestamp) in [("created", event.created), ("modified", event.modified)] {
+        if let Some(timestamp) = timestamp {
+            fields.push((key.to_string(), Value::String(timestamp.to_string())));
+        }
+    }
+    Value::Object(fields)
+}
+
+fn json_string<'a>(value: &'a Value, field: &str) -> Result<&'a str, String> {
+    value
+        .as_str()
+        .ok_or_else(|| format!("'{}' must be a string", field))
+}
+
+fn json_strings(value: &Value, field: &str) -> Result<Vec<String>, String> {
+    value
+        .as_array()
+        .ok_or_else(|| format!("'{}' must be an array", field))?
+        .iter()
+        .map(|item| json_string(item, field).map(str::to_string))
+        .collect()
+}
+
+fn event_from_json(value: &Value) -> Result<Event, String> {
+    let Value::Object(fields) = value else {
+        return Err("Events must be objects".to_string());
+    };
+    let date = value.get("date").ok_or("Event has no 'date'")?;
+    let description = value
+        .get("description")
+        .ok_or("Event has no 'description'")?;
+    let mut event = Event::new(
+        parse_whole_date(json_string(date, "date")?)?,
+        json_string(description, "description")?.to_string(),
+    );
+    for (key, value) in fields {
+        match key.as_str() {
+            "date" | "description" => {}
+            "category" => event.category = Some(json_string(value, key)?.to_string()),
+            "place" => event.place = Some(Place::parse(json_string(value, key)?)?),
+            "id" => event.id = Some(json_string(value, key)?.to_string()),
+            "end" => event.end = Some(parse_whole_date(json_string(value, key)?)?),
+            "tags" => event.tags = json_strings(value, key)?,
+            "people" => event.people = json_strings(value, key)?,
+            "series" => event.series = Some(json_string(value, key)?.to_string()),
+            "sources" => event.sources = json_strings(value, key)?,
+            "notes" => {
+                for note in value.as_array().ok_or("'notes' must be an array")? {
+                    let timestamp = note.get("timestamp").ok_or("Note has no 'timestamp'")?;
+                    let text = note.get("text").ok_or("Note has no 'text'")?;
+                    event.notes.push(Note {
+                        timestamp: Timestamp::parse(json_string(timestamp, "timestamp")?)?,
+                        text: json_string(text, "text")?.to_string(),
+                    });
+                }
+            }
+            "archived" => {
+                let Value::Bool(archived) = value else {
+                    return Err("'archived' must be true or false".to_string());
+                };
+                event.archived = *archived;
+            }
+            "pinned" => {
+                let Value::Bool(pinned) = value else {
+                    return Err("'pinned' must be true or false".to_string());
+                };
+                event.pinned = *pinned;
+            }
+            "seq" => {
+                let seq = match value {
+                    Value::Number(n) if n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(n) => {
+                        *n as u32
+                    }
+                    _ => return Err("'seq' must be a whole number".to_string()),
+                };
+                event.seq = Some(seq);
+            }
+            "created" => event.created = Some(Timestamp::parse(json_string(value, key)?)?),
+            "modified" => event.modified = Some(Timestamp::parse(json_string(value, key)?)?),
+            _ => return Err(format!("Unknown event field: {}", key)),
+        }
+    }
+    check_end(&event)?;
+    Ok(event)
+}
+
+fn read_json(contents: &str) -> Result<WorldLine, String> {
+    let value = Value::parse(contents)?;
+    let events = value
+        .get("events")
+        .and_then(Value::as_array)
+        .ok_or("Expected an object with an 'events' array")?
+        .iter()
+        .enumerate()
+        .map(|(i, event)| event_from_json(event).map_err(|e| format!("event {}: {}", i + 1, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let front_matter = match value.get("front_matter") {
+        Some(Value::Object(fields)) => fields
+            .iter()
+            .map(|(k, v)| Ok((k.clone(), json_string(v, k)?.to_string())))
+            .collect::<Result<Vec<_>, String>>()?,
+        Some(_) => return Err("'front_matter' must be an object".to_string()),
+        None => Vec::new(),
+    };
+    Ok(worldline_from(events, front_matter))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn write_csv(worldline: &WorldLine) -> String {
+    let mut out = CSV_COLUMNS.join(",") + "\n";
+    for event in &worldline.events {
+        let row = [
+            event.date.to_string(),
+            event.end.as_ref().map(Date::to_string).unwrap_or_default(),
+            event.description.clone(),
+            event.id.clone().unwrap_or_default(),
+            event.tags.join(";"),
+            event.sources.join(";"),
+            event.category.clone().unwrap_or_default(),
+            event
+                .place
+                .as_ref()
+                .map(Place::to_string)
+                .unwrap_or_default(),
+        ];
+        let row: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        out += &row.join(",");
+        out += "\n";
+    }
+    out
+}
+
+/// Split CSV into records of fields, handling quoted fields.
+fn csv_records(contents: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = contents.chars().peekable();
+    let mut quoted = false;
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if field.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+fn read_csv(contents: &str) -> Result<WorldLine, String> {
+    let mut records = csv_records(contents)?.into_iter();
+    let header = records.next().ok_or("Missing header row")?;
+    for column in &header {
+        if !CSV_COLUMNS.contains(&column.as_str()) {
+            return Err(format!("Unknown column: {}", column));
+        }
+    }
+    let column = |name: &str| header.iter().position(|c| c == name);
+    let (date_col, description_col) = column("date")
+        .zip(column("description"))
+        .ok_or("The header must have 'date' and 'description' columns")?;
+    let list = |s: &str| -> Vec<String> {
+        s.split(';')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+
+    let mut events = Vec::new();
+    for (i, record) in records.enumerate() {
+        let row = i + 2;
+        let get = |name: &str| {
+            column(name)
+                .and_then(|c| record.get(c))
+                .map(String::as_str)
+                .unwrap_or_default()
+        };
+        let date = record.get(date_col).map(String::as_str).unwrap_or_default();
+        let mut event = Event::new(
+            parse_whole_date(date).map_err(|e| format!("row {}: {}", row, e))?,
+            record.get(description_col).cloned().unwrap_or_default(),
+        );
+        if !get("end").is_empty() {
+            event.end =
+                Some(parse_whole_date(get("end")).map_err(|e| format!("row {}: {}", row, e))?);
+        }
+        if !get("id").is_empty() {
+            event.id = Some(get("id").to_string());
+        }
+        if !get("category").is_empty() {
+            event.category = Some(get("category").to_string());
+        }
+        if !get("place").is_empty() {
+            event.place =
+                Some(Place::parse(get("place")).map_err(|e| format!("row {}: {}", row, e))?);
+        }
+        event.tags = list(get("tags"));
+        event.sources = list(get("sources"));
+        check_end(&event).map_err(|e| format!("row {}: {}", row, e))?;
+        events.push(event);
+    }
+    Ok(worldline_from(events, Vec::new()))
+}
+
+/// A date as an iCalendar DATE value, e.g. 19450902. Partial dates use their first day.
+fn ics_date(date: &Date) -> String {
+    format!(
+        "{:04}{:02}{:02}",
+        date.year,
+        date.month.max(1),
+        date.day.max(1)
+    )
+}
+
+fn ics_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn ics_unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => out.push('\n'),
+                Some(c) => out.push(c),
+                None => {}
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Fold a content line to at most 75 bytes per line, as iCalendar requires.
+fn ics_fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out += "\r\n ";
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out + "\r\n"
+}
+
+fn write_ics(worldline: &WorldLine) -> String {
+    let dtstamp = Timestamp::now().to_string().replace(['-', ':'], "");
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//worldline//wl//EN".to_string(),
+    ];
+    for (i, event) in worldline.events.iter().enumerate() {
+        if event.date.year < 1 {
+            continue;
+        }
+        let last = event.end.as_ref().unwrap_or(&event.date);
+        lines.push("BEGIN:VEVENT".to_string());
+        match &event.id {
+            Some(id) => lines.push(format!("UID:{}", id)),
+            None => lines.push(format!("UID:event-{}@worldline", i + 1)),
+        }
+        lines.push(format!("DTSTAMP:{}", dtstamp));
+        match event.date.time() {
+            // a floating time, as times of day have no time zone
+            Some(time) => {
+                lines.push(format!(
+                    "DTSTART:{}T{:02}{:02}00",
+                    ics_date(&event.date),
+                    time.hour(),
+                    time.minute()
+                ));
+                if event.end.is_some() {
+                    let after = Date::from_day_number(last.day_number() + 1);
+                    lines.push(format!("DTEND:{}T000000", ics_date(&after)));
+                }
+            }
+            None => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", ics_date(&event.date)));
+                lines.push(format!("DTEND;VALUE=DATE:{}", ics_date(&last.next())));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", ics_escape(&event.description)));
+        if !event.tags.is_empty() {
+            let tags: Vec<String> = event.tags.iter().map(|t| ics_escape(t)).collect();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+        // keep the precision of partial dates and times of day, which aren't read from DTSTART
+        if !event.date.is_day_precision() || event.date.time().is_some() {
+            lines.push(format!("X-WORLDLINE-DATE:{}", event.date));
+        }
+        if let Some(end) = &event.end {
+            lines.push(format!("X-WORLDLINE-END:{}", end));
+        }
+        if let Some(category) = &event.category {
+            lines.push(format!("X-WORLDLINE-CATEGORY:{}", ics_escape(category)));
+        }
+        match &event.place {
+            Some(Place::Named(name)) => lines.push(format!("LOCATION:{}", ics_escape(name))),
+            Some(Place::Coordinates(coordinates)) => lines.push(format!(
+                "GEO:{};{}",
+                coordinates.latitude(),
+                coordinates.longitude()
+            )),
+            None => {}
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|l| ics_fold(l)).collect()
+}
+
+/// Parse an iCalendar DATE or DATE-TIME value, ignoring the time.
+fn parse_ics_date(value: &str) -> Result<Date, String> {
+    let invalid = || format!("Invalid date: {}", value);
+    let digits = value.get(..8).ok_or_else(invalid)?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    Date::new(
+        digits[..4].parse().unwrap(),
+        digits[4..6].parse().unwrap(),
+        digits[6..].parse().unwrap(),
+    )
+}
+
+fn read_ics(contents: &str) -> Result<WorldLine, String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut properties: Option<Vec<(String, String)>> = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = name.split(';').next().unwrap().to_uppercase();
+        match (name.as_str(), value, &mut properties) {
+            ("BEGIN", "VEVENT", None) => properties = Some(Vec::new()),
+            ("END", "VEVENT", Some(props)) => {
+                events.push(event_from_ics(props)?);
+                properties = None;
+            }
+            (_, _, Some(props)) => props.push((name, value.to_string())),
+            _ => {}
+        }
+    }
+    Ok(worldline_from(events, Vec::new()))
+}
+
+fn event_from_ics(properties: &[(String, String)]) -> Result<Event, String> {
+    let get = |name: &str| {
+        properties
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let date = match (get("X-WORLDLINE-DATE"), get("DTSTART")) {
+        (Some(date), _) => parse_whole_date(date)?,
+        (None, Some(start)) => parse_ics_date(start)?,
+        (None, None) => return Err("VEVENT has no DTSTART".to_string()),
+    };
+    let mut event = Event::new(date, ics_unescape(get("SUMMARY").unwrap_or_default()));
+    event.end = match (get("X-WORLDLINE-END"), get("DTEND")) {
+        (Some(end), _) => Some(parse_whole_date(end)?),
+        (None, Some(end)) => {
+            // DTEND is exclusive, and only an end if it's after the start's last day
+            let last = Date::from_day_number(parse_ics_date(end)?.day_number() - 1);
+            (last.day_number() >= event.date.next().day_number()).then_some(last)
+        }
+        (None, None) => None,
+    };
+    event.category = get("X-WORLDLINE-CATEGORY").map(ics_unescape);
+    event.place = match (get("GEO"), get("LOCATION")) {
+        (Some(geo), _) => Some(Place::Coordinates(Coordinates::parse(
+            &geo.replace(';', ","),
+        )?)),
+        (None, Some(location)) => Some(Place::Named(ics_unescape(location))),
+        (None, None) => None,
+    };
+    if let Some(uid) = get("UID").filter(|uid| !uid.ends_with("@worldline")) {
+        event.id = Some(uid.to_string());
+    }
+    if let Some(categories) = get("CATEGORIES") {
+        event.tags = categories
+            .split(',')
+            .map(|t| ics_unescape(t.trim()))
+            .filter(|t| !t.is_empty())
+            .collect();
+    }
+    check_end(&event)?;
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> WorldLine {
+        let mut first = Event::new(
+            Date::new(1914, 7, 28).unwrap(),
+            "WWI begins, \"the war\"".to_string(),
+        );
+        first.id = Some("ww1".to_string());
+        first.category = Some("war".to_string());
+        first.place = Some(Place::Named("Sarajevo".to_string()));
+        first.tags = vec!["war".to_string(), "europe".to_string()];
+        first.end = Some(Date::new(1918, 11, 0).unwrap());
+        first.sources = vec!["clark2012".to_string()];
+        first.notes.push(Note {
+            timestamp: Timestamp::parse("2025-03-01T14:30:00Z").unwrap(),
+            text: "Check the date".to_string(),
+        });
+        let mut second = Event::new(Date::new(1945, 0, 0).unwrap(), "WWII ends".to_string());
+        second.place = Some(Place::Coordinates(Coordinates::new(35.35, 139.76).unwrap()));
+        let third = Event::new(Date::new(-44, 3, 15).unwrap(), "Ides of March".to_string());
+        let mut worldline = worldline_from(vec![first, second, third], Vec::new());
+        worldline.set_front_matter("title", "Wars".to_string());
+        worldline
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let worldline = sample();
+        for format in [
+            Format::Wl,
+            Format::Json,
+            Format::Jsonl,
+            Format::Csv,
+            Format::Ics,
+        ] {
+            let written = format.write(&worldline);
+            let read = format.read(&written).unwrap();
+            let mut expected = sample();
+            match format {
+                Format::Wl | Format::Json => {}
+                Format::Jsonl => expected.front_matter.clear(),
+                Format::Csv => {
+                    expected.front_matter.clear();
+                    expected.events[1].notes.clear();
+                }
+                Format::Ics => {
+                    expected.front_matter.clear();
+                    expected.events.remove(0);
+                    expected.events[0].notes.clear();
+                    expected.events[0].sources.clear();
+                }
+            }
+            assert_eq!(read.events, expected.events, "{:?}", format);
+            assert_eq!(read.front_matter, expected.front_matter, "{:?}", format);
+            assert_eq!(
+                format.losses(&worldline).is_empty(),
+                matches!(format, Format::Wl | Format::Json)
+            );
+        }
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = Event::parse("1969-07-20 [space] Moon landing @(0.67,23.47)").unwrap();
+        assert_eq!(
+            event.to_json(),
+            r#"{"date":"1969-07-20","description":"Moon landing","category":"space","place":"(0.67,23.47)"}"#
+        );
+        assert_eq!(
+            event_from_json(&Value::parse(&event.to_json()).unwrap()),
+            Ok(event)
+        );
+
+        let json = r#"{"date":"1957-10-04","description":"Rocket \ud83d\ude80"}"#;
+        let event = event_from_json(&Value::parse(json).unwrap()).unwrap();
+        assert_eq!(event.description, "Rocket 🚀");
+        assert_eq!(
+            event_from_json(&Value::parse(&event.to_json()).unwrap()),
+            Ok(event)
+        );
+    }
+
+    #[test]
+    fn test_foreign_files() {
+        let csv = "description,date\n\"Moon landing, Apollo 11\",1969-07-20\n";
+        let worldline = Format::Csv.read(csv).unwrap();
+        assert_eq!(worldline.events[0].description, "Moon landing, Apollo 11");
+        assert!(Format::Csv.read("date,venue\n1969,Moon\n").is_err());
+
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:abc@example.com\r\n\
+                   DTSTART:19690720T201700Z\r\nSUMMARY:Moon la\r\n nding\r\nEND:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+        let worldline = Format::Ics.read(ics).unwrap();
+        assert_eq!(worldline.events[0].date, Date::new(1969, 7, 20).unwrap());
+        assert_eq!(worldline.events[0].description, "Moon landing");
+        assert_eq!(worldline.events[0].id.as_deref(), Some("abc@example.com"));
+
+        assert!(Format::from_path("events.sqlite").is_err());
+        assert_eq!(Format::from_path("a/b.JSONL"), Ok(Format::Jsonl));
+    }
+}
@@ -0,0 +1,69 @@
+//! Finding events in two worldlines that happened around the same time.
+
+use crate::span::Span;
+use crate::{Event, WorldLine};
+
+impl WorldLine {
+    /// Pairs of events (one from each worldline) that are within `window` of each other.
+    /// A partial date covers its whole year or month, so 1914 and 1915-03 are within 1y.
+    pub fn correlate<'a>(
+        &'a self,
+        other: &'a WorldLine,
+        window: &Span,
+    ) -> Vec<(&'a Event, &'a Event)> {
+        let mut pairs = Vec::new();
+        for event in &self.events {
+            let start = other.first_geq(&event.date.add_span(&window.negate()));
+            let end = event.date.add_span(window).after();
+            pairs.extend(
+                other.events[start..]
+                    .iter()
+                    .take_while(|e| e.date < end)
+                    .map(|e| (event, e)),
+            );
+        }
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Date;
+
+    fn worldline(events: &[((i32, u8, u8), &str)]) -> WorldLine {
+        let mut worldline = WorldLine::default();
+        for ((year, month, day), description) in events {
+            let date = Date::new(*year, *month, *day).unwrap();
+            worldline.add_event(Event::new(date, description.to_string()));
+        }
+        worldline
+    }
+
+    #[test]
+    fn test_correlate() {
+        let journal = worldline(&[((1969, 7, 1), "Born"), ((1989, 0, 0), "Moved to Berlin")]);
+        let world = worldline(&[
+            ((1969, 7, 20), "Moon landing"),
+            ((1970, 6, 1), "Eleven months later"),
+            ((1989, 11, 9), "Berlin Wall falls"),
+            ((1991, 12, 26), "USSR dissolved"),
+        ]);
+
+        let pairs = journal.correlate(&world, &Span::parse("1m").unwrap());
+        let descriptions: Vec<_> = pairs
+            .iter()
+            .map(|(a, b)| (a.description.as_str(), b.description.as_str()))
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                ("Born", "Moon landing"),
+                ("Moved to Berlin", "Berlin Wall falls")
+            ]
+        );
+
+        let pairs = journal.correlate(&world, &Span::years(1));
+        assert_eq!(pairs.len(), 3);
+    }
+}
@@ -0,0 +1,90 @@
+//! Encrypted worldline files, behind the `crypto` feature, so personal timelines aren't stored
+//! in plain text.
+//!
+//! Files are encrypted with a passphrase in the [age](https://age-encryption.org) format, so
+//! they can also be decrypted with `age -d`. They're recognized by age's header, see
+//! [`crate::storage::is_encrypted`].
+
+use crate::storage::{check_file_writable, Storage};
+use crate::WorldLine;
+use age::secrecy::SecretString;
+use std::path::{Path, PathBuf};
+
+/// Encrypt data with a passphrase.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let recipient = age::scrypt::Recipient::new(SecretString::from(passphrase));
+    age::encrypt(&recipient, plaintext).map_err(|e| format!("Could not encrypt: {}", e))
+}
+
+/// Decrypt data encrypted by [`encrypt`] (or by `age --passphrase`).
+pub fn decrypt(ciphertext: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let identity = age::scrypt::Identity::new(SecretString::from(passphrase));
+    age::decrypt(&identity, ciphertext).map_err(|e| format!("Could not decrypt: {}", e))
+}
+
+/// Read a passphrase from the first line of a key file.
+pub fn read_keyfile(path: impl AsRef<Path>) -> Result<String, String> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read key file {}: {}", path.display(), e))?;
+    match contents.lines().next().map(str::trim) {
+        Some(passphrase) if !passphrase.is_empty() => Ok(passphrase.to_string()),
+        _ => Err(format!("Key file {} is empty", path.display())),
+    }
+}
+
+/// A worldline file in the text format, encrypted with a passphrase.
+pub struct EncryptedFile {
+    pub path: PathBuf,
+    passphrase: String,
+}
+
+impl EncryptedFile {
+    pub fn new(path: PathBuf, passphrase: String) -> Self {
+        Self { path, passphrase }
+    }
+}
+
+impl Storage for EncryptedFile {
+    fn load(&self) -> Result<WorldLine, String> {
+        let ciphertext = std::fs::read(&self.path).map_err(|e| e.to_string())?;
+        let plaintext = decrypt(&ciphertext, &self.passphrase)?;
+        WorldLine::from_reader(plaintext.as_slice())
+    }
+
+    fn save(&self, worldline: &WorldLine) -> Result<(), String> {
+        if worldline.read_only {
+            return Err("the worldline is read-only".to_string());
+        }
+        let ciphertext = encrypt(worldline.to_string().as_bytes(), &self.passphrase)?;
+        std::fs::write(&self.path, ciphertext).map_err(|e| e.to_string())
+    }
+
+    fn check_writable(&self) -> Result<(), String> {
+        check_file_writable(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::is_encrypted;
+
+    #[test]
+    fn test_encrypted_file() {
+        let path = std::env::temp_dir().join(format!("wl-crypto-{}.wl", std::process::id()));
+        let worldline: WorldLine = "1914 WWI\n1939 WWII\n".parse().unwrap();
+        let storage = EncryptedFile::new(path.clone(), "correct horse".to_string());
+        storage.save(&worldline).unwrap();
+        assert!(is_encrypted(&path));
+        assert!(!std::fs::read(&path)
+            .unwrap()
+            .windows(4)
+            .any(|w| w == b"WWII"));
+        assert_eq!(storage.load().unwrap().to_string(), worldline.to_string());
+
+        let wrong = EncryptedFile::new(path.clone(), "battery staple".to_string());
+        assert!(wrong.load().is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,179 @@
+//! Pinning events, and the pieces of `wl dashboard`, a one-screen overview of a worldline: its
+//! pinned events, the events added most recently, the anniversaries coming up, and how many
+//! events of each kind it has.
+//!
+//! Pinned events are written with a `pinned=true` field, so only v2 files can have them.
+
+use crate::{Date, Event, WorldLine};
+use std::collections::BTreeSet;
+
+/// An anniversary of an event, on a day coming up.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Anniversary<'a> {
+    pub event: &'a Event,
+    /// The day of the anniversary.
+    pub date: Date,
+    /// How many years it'll be since the event.
+    pub years: i32,
+}
+
+/// How many events a worldline has, and how many different categories, tags, people and
+/// series they have.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub events: usize,
+    pub pinned: usize,
+    pub archived: usize,
+    pub categories: usize,
+    pub tags: usize,
+    pub people: usize,
+    pub series: usize,
+}
+
+/// The number of different values.
+fn distinct<'a>(values: impl Iterator<Item = &'a str>) -> usize {
+    values.collect::<BTreeSet<_>>().len()
+}
+
+impl WorldLine {
+    /// Pin or unpin the event at an index. Returns false if it already was.
+    pub fn set_pinned(&mut self, idx: usize, pinned: bool) -> bool {
+        let event = &mut self.events[idx];
+        let changed = event.pinned != pinned;
+        event.pinned = pinned;
+        changed
+    }
+
+    /// The pinned events, in order.
+    pub fn pinned(&self) -> Vec<&Event> {
+        self.events.iter().filter(|e| e.pinned).collect()
+    }
+
+    /// The `n` events added most recently, newest first. Events without a `created` timestamp
+    /// (see [`WorldLine::stamp_changes`]), e.g. those in v1 files, are left out.
+    pub fn recently_added(&self, n: usize) -> Vec<&Event> {
+        let mut events: Vec<&Event> = self.events.iter().filter(|e| e.created.is_some()).collect();
+        events.sort_by_key(|e| std::cmp::Reverse(e.created));
+        events.truncate(n);
+        events
+    }
+
+    /// The anniversaries of events with a full date in the `days` days from `today` on,
+    /// soonest first. Events on February 29th only have them in leap years.
+    pub fn upcoming_anniversaries(&self, today: &Date, days: u32) -> Vec<Anniversary<'_>> {
+        let today_number = today.day_number();
+        let mut anniversaries: Vec<Anniversary> = self
+            .events
+            .iter()
+            .filter(|e| e.date.is_day_precision())
+            .filter_map(|event| {
+                // this year's, or next year's if it's passed
+                let date = [today.year, today.year + 1]
+                    .into_iter()
+                    .filter_map(|year| Date::new(year, event.date.month, event.date.day).ok())
+                    .find(|date| date.day_number() >= today_number)?;
+                if date.day_number() - today_number >= days as i64 {
+                    return None;
+                }
+                // there's no year 0
+                let years = date.year - event.date.year - i32::from(event.date.year < 0);
+                (years > 0).then_some(Anniversary { event, date, years })
+            })
+            .collect();
+        anniversaries.sort_by_key(|a| a.date.day_number());
+        anniversaries
+    }
+
+    /// How many events the worldline has, including archived ones, and of what kinds.
+    pub fn counts(&self) -> Counts {
+        let events = &self.events;
+        Counts {
+            events: events.len(),
+            pinned: events.iter().filter(|e| e.pinned).count(),
+            archived: events.iter().filter(|e| e.archived).count(),
+            categories: distinct(events.iter().filter_map(|e| e.category.as_deref())),
+            tags: distinct(events.iter().flat_map(|e| &e.tags).map(String::as_str)),
+            people: distinct(events.iter().flat_map(|e| &e.people).map(String::as_str)),
+            series: self.series_names().len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convert::Format;
+    use crate::query::parse_whole_date;
+
+    #[test]
+    fn test_dashboard() {
+        let mut worldline: WorldLine = "#worldline v2\n\
+                                        -44-03-15 [politics] Caesar assassinated\tpeople=Julius Caesar,Brutus\n\
+                                        1914-07-28 [war] WWI begins\ttags=ww1\tcreated=2025-03-01T10:00:00Z\n\
+                                        1918-11-11 [war] Armistice\ttags=ww1\tcreated=2025-03-02T10:00:00Z\n\
+                                        1940-02-29 Leap day\n\
+                                        1969-07 Apollo 11 launches\n\
+                                        1969-07-20 Moon landing\tarchived=true\n"
+            .parse()
+            .unwrap();
+
+        assert!(worldline.set_pinned(2, true));
+        assert!(!worldline.set_pinned(2, true));
+        assert_eq!(worldline.pinned().len(), 1);
+        let written = worldline.to_string();
+        assert!(written.contains("Armistice\ttags=ww1\tpinned=true\tcreated="));
+        let read: WorldLine = written.parse().unwrap();
+        assert_eq!(read.pinned()[0].description, "Armistice");
+        let json = Format::Json.write(&worldline);
+        assert!(json.contains(r#""pinned":true"#));
+        assert_eq!(
+            Format::Json.read(&json).unwrap().events(),
+            worldline.events()
+        );
+
+        let recent: Vec<&str> = worldline
+            .recently_added(5)
+            .iter()
+            .map(|e| e.description.as_str())
+            .collect();
+        assert_eq!(recent, ["Armistice", "WWI begins"]);
+
+        let date = |s| parse_whole_date(s).unwrap();
+        let upcoming = |today, days| -> Vec<(String, i32)> {
+            worldline
+                .upcoming_anniversaries(&date(today), days)
+                .iter()
+                .map(|a| (a.event.description.clone(), a.years))
+                .collect()
+        };
+        assert_eq!(
+            upcoming("2025-07-10", 30),
+            [
+                ("Moon landing".to_string(), 56),
+                ("WWI begins".to_string(), 111)
+            ]
+        );
+        // next year's, across BCE and CE with no year 0
+        assert_eq!(
+            upcoming("2025-12-01", 120),
+            [("Caesar assassinated".to_string(), 2069)]
+        );
+        assert!(upcoming("2025-02-20", 30)
+            .iter()
+            .all(|(d, _)| d != "Leap day"));
+        assert_eq!(upcoming("2028-02-29", 1), [("Leap day".to_string(), 88)]);
+
+        assert_eq!(
+            worldline.counts(),
+            Counts {
+                events: 6,
+                pinned: 1,
+                archived: 1,
+                categories: 2,
+                tags: 1,
+                people: 2,
+                series: 0,
+            }
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Comparing worldlines.
+
+use crate::format::{self, FormatVersion};
+use crate::timestamp::Timestamp;
+use crate::{Event, WorldLine};
+use std::collections::HashMap;
+
+/// The differences between two worldlines, as returned by [`WorldLine::diff`].
+#[derive(Debug, Default)]
+pub struct DiffReport<'a> {
+    /// Events only in the worldline `diff` was called on.
+    pub only_left: Vec<&'a Event>,
+    /// Events only in the other worldline.
+    pub only_right: Vec<&'a Event>,
+    /// Events on the same date in both worldlines that differ, e.g. in their descriptions, as
+    /// (left, right) pairs.
+    pub changed: Vec<(&'a Event, &'a Event)>,
+}
+
+/// Which side of a [`DiffReport`] an event is from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl<'a> DiffReport<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.changed.is_empty()
+    }
+
+    /// Events in the left worldline but not the right, including the left side of changes.
+    pub fn removed(&self) -> impl Iterator<Item = &'a Event> + '_ {
+        let changed = self.changed.iter().map(|(left, _)| *left);
+        self.only_left.iter().copied().chain(changed)
+    }
+
+    /// Events in the right worldline but not the left, including the right side of changes.
+    pub fn added(&self) -> impl Iterator<Item = &'a Event> + '_ {
+        let changed = self.changed.iter().map(|(_, right)| *right);
+        self.only_right.iter().copied().chain(changed)
+    }
+
+    /// Every differing event in date order, as in a unified diff: the left and right versions
+    /// of a changed event are next to each other.
+    pub fn unified(&self) -> Vec<(Side, &'a Event)> {
+        let mut lines: Vec<(Side, &Event)> = self
+            .only_left
+            .iter()
+            .map(|e| (Side::Left, *e))
+            .chain(self.only_right.iter().map(|e| (Side::Right, *e)))
+            .chain(
+                self.changed
+                    .iter()
+                    .flat_map(|(left, right)| [(Side::Left, *left), (Side::Right, *right)]),
+            )
+            .collect();
+        // stable, so changed events stay in pairs
+        lines.sort_by(|a, b| a.1.date.cmp(&b.1.date));
+        lines
+    }
+}
+
+/// Everything a change to a worldline would write, for previewing it, e.g. with `--dry-run`.
+#[derive(Debug)]
+pub struct ChangeSet<'a> {
+    pub events: DiffReport<'a>,
+    /// Front matter entries removed (left) or added (right). A changed value is both.
+    pub front_matter: Vec<(Side, &'a str, &'a str)>,
+    /// The new format version, if it changed.
+    pub version: Option<FormatVersion>,
+}
+
+impl<'a> ChangeSet<'a> {
+    /// The changes from one version of a worldline to another.
+    pub fn between(before: &'a WorldLine, after: &'a WorldLine) -> Self {
+        let mut front_matter = Vec::new();
+        for (side, a, b) in [(Side::Left, before, after), (Side::Right, after, before)] {
+            for (key, value) in &a.front_matter {
+                if !b.front_matter.contains(&(key.clone(), value.clone())) {
+                    front_matter.push((side, key.as_str(), value.as_str()));
+                }
+            }
+        }
+        Self {
+            events: before.diff(after),
+            front_matter,
+            version: (before.version != after.version).then_some(after.version),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.front_matter.is_empty() && self.version.is_none()
+    }
+
+    /// The changed lines of the worldline file, as in a unified diff: the header and front
+    /// matter, then events in date order, written with all their fields.
+    pub fn lines(&self) -> Vec<(Side, String)> {
+        let mut lines = Vec::new();
+        if self.version == Some(FormatVersion::V2) {
+            lines.push((Side::Right, format::V2_HEADER.to_string()));
+        }
+        for (side, key, value) in &self.front_matter {
+            lines.push((*side, format!("#{}: {}", key, value)));
+        }
+        for (side, event) in self.events.unified() {
+            lines.push((side, format::format_event_v2(event)));
+        }
+        lines
+    }
+}
+
+/// A [`DiffReport`] as indices into the events of the two worldlines.
+#[derive(Debug, Default)]
+struct DiffIndices {
+    only_left: Vec<usize>,
+    only_right: Vec<usize>,
+    changed: Vec<(usize, usize)>,
+}
+
+impl WorldLine {
+    /// Compare this worldline to another, event by event.
+    pub fn diff<'a>(&'a self, other: &'a WorldLine) -> DiffReport<'a> {
+        let indices = self.diff_indices(other);
+        DiffReport {
+            only_left: indices.only_left.iter().map(|&i| &self.events[i]).collect(),
+            only_right: indices
+                .only_right
+                .iter()
+                .map(|&j| &other.events[j])
+                .collect(),
+            changed: indices
+                .changed
+                .iter()
+                .map(|&(i, j)| (&self.events[i], &other.events[j]))
+                .collect(),
+        }
+    }
+
+    fn diff_indices(&self, other: &WorldLine) -> DiffIndices {
+        // files can be edited by hand, so don't assume they are sorted
+        let (left, right) = (&self.events, &other.events);
+        let mut left_order: Vec<usize> = (0..left.len()).collect();
+        let mut right_order: Vec<usize> = (0..right.len()).collect();
+        left_order.sort_by(|&a, &b| left[a].cmp(&left[b]));
+        right_order.sort_by(|&a, &b| right[a].cmp(&right[b]));
+
+        let mut indices = DiffIndices::default();
+        let (mut i, mut j) = (0, 0);
+        while i < left_order.len() && j < right_order.len() {
+            match left[left_order[i]].cmp(&right[right_order[j]]) {
+                std::cmp::Ordering::Less => {
+                    indices.only_left.push(left_order[i]);
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    indices.only_right.push(right_order[j]);
+                    j += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        indices.only_left.extend(&left_order[i..]);
+        indices.only_right.extend(&right_order[j..]);
+        indices.pair_changed(left, right);
+        indices
+    }
+}
+
+impl WorldLine {
+    /// Record when events were added and changed since an earlier version of this worldline,
+    /// in their `created` and `modified` fields. An event changed from one in the earlier
+    /// version (on the same date, with the same id, or moved to another date with the same
+    /// description) keeps its `created` time. Only v2 worldlines are stamped, since the fields
+    /// would upgrade a v1 file.
+    pub fn stamp_changes(&mut self, before: &WorldLine, now: Timestamp) {
+        if self.version != FormatVersion::V2 {
+            return;
+        }
+        let diff = before.diff_indices(self);
+        // events in neither pair may have moved to another date, e.g. with `wl shift`
+        let mut by_id: HashMap<&str, usize> = HashMap::new();
+        let mut by_description: HashMap<String, Vec<usize>> = HashMap::new();
+        for &i in diff.only_left.iter().rev() {
+            let event = &before.events[i];
+            match &event.id {
+                Some(id) => {
+                    by_id.insert(id, i);
+                }
+                None => by_description
+                    .entry(event.full_description())
+                    .or_default()
+                    .push(i),
+            }
+        }
+        let moved = diff.only_right.iter().map(|&j| {
+            let event = &self.events[j];
+            let previous = match &event.id {
+                Some(id) => by_id.remove(id.as_str()),
+                None => by_description
+                    .get_mut(&event.full_description())
+                    .and_then(Vec::pop),
+            };
+            (previous, j)
+        });
+        let changed = diff.changed.iter().map(|&(i, j)| (Some(i), j));
+        let stamps: Vec<(usize, Option<Timestamp>, Option<Timestamp>)> = changed
+            .chain(moved)
+            .map(|(previous, j)| {
+                let added = &self.events[j];
+                match previous.map(|i| &before.events[i]) {
+                    Some(previous) => (j, previous.created.or(added.created), Some(now)),
+                    None => (j, added.created.or(Some(now)), added.modified),
+                }
+            })
+            .collect();
+        for (j, created, modified) in stamps {
+            self.events[j].created = created;
+            self.events[j].modified = modified;
+        }
+    }
+}
+
+impl DiffIndices {
+    /// Move events on the same date on both sides to `changed`, pairing them up in order.
+    fn pair_changed(&mut self, left: &[Event], right: &[Event]) {
+        let (only_left, only_right) = (
+            std::mem::take(&mut self.only_left),
+            std::mem::take(&mut self.only_right),
+        );
+        let (mut i, mut j) = (0, 0);
+        while i < only_left.len() && j < only_right.len() {
+            match left[only_left[i]].date.cmp(&right[only_right[j]].date) {
+                std::cmp::Ordering::Less => {
+                    self.only_left.push(only_left[i]);
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    self.only_right.push(only_right[j]);
+                    j += 1;
+                }
+                std::cmp::Ordering::Equal => {
+                    self.changed.push((only_left[i], only_right[j]));
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        self.only_left.extend(&only_left[i..]);
+        self.only_right.extend(&only_right[j..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Date;
+
+    fn worldline(events: &[(i32, &str)]) -> WorldLine {
+        let mut worldline = WorldLine::default();
+        for (year, description) in events {
+            let date = Date::new(*year, 0, 0).unwrap();
+            worldline.add_event(Event::new(date, description.to_string()));
+        }
+        worldline
+    }
+
+    #[test]
+    fn test_change_set() {
+        let before: WorldLine = "1066 Hastings\n1215 Magna Carta\n".parse().unwrap();
+        let mut after: WorldLine = "1066 Hastings\n1215 Magna Carta\n".parse().unwrap();
+        assert!(ChangeSet::between(&before, &after).is_empty());
+
+        after.migrate();
+        after.set_front_matter("title", "England".to_string());
+        after.annotate(1, "Sealed at Runnymede".to_string());
+        after.add_event(Event::parse("1415 Agincourt").unwrap());
+        let changes = ChangeSet::between(&before, &after);
+        let lines: Vec<(Side, String)> = changes.lines();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], (Side::Right, "#worldline v2".to_string()));
+        assert_eq!(lines[1], (Side::Right, "#title: England".to_string()));
+        assert_eq!(
+            lines[2],
+            (Side::Left, " CE 1215       Magna Carta".to_string())
+        );
+        assert!(lines[3].1.ends_with("Sealed at Runnymede"));
+        assert_eq!(
+            lines[4],
+            (Side::Right, " CE 1415       Agincourt".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stamp_changes() {
+        let before: WorldLine = "#worldline v2\n\
+                                 1066 Hastings\tcreated=2025-03-01T14:30:00Z\n\
+                                 1215 Magna Carta\n"
+            .parse()
+            .unwrap();
+        let mut after = before.clone();
+        after.events[0].description = "Battle of Hastings".to_string();
+        after.add_event(Event::parse("1415 Agincourt").unwrap());
+        let now = Timestamp::parse("2025-03-02T09:00:00Z").unwrap();
+        after.stamp_changes(&before, now);
+        let created = Timestamp::parse("2025-03-01T14:30:00Z").unwrap();
+        assert_eq!(after.events[0].created, Some(created));
+        assert_eq!(after.events[0].modified, Some(now));
+        assert_eq!(after.events[1].created, None);
+        assert_eq!(after.events[2].created, Some(now));
+        assert_eq!(after.events[2].modified, None);
+
+        // moved to another date without an id
+        let mut shifted = before.clone();
+        shifted.events[0].date = Date::new(1067, 0, 0).unwrap();
+        shifted.stamp_changes(&before, now);
+        assert_eq!(shifted.events[0].created, Some(created));
+        assert_eq!(shifted.events[0].modified, Some(now));
+        assert_eq!(shifted.events[1].modified, None);
+
+        let v1: WorldLine = "1066 Hastings\n".parse().unwrap();
+        let mut changed = v1.clone();
+        changed.add_event(Event::parse("1415 Agincourt").unwrap());
+        changed.stamp_changes(&v1, now);
+        assert!(!changed.events[1].has_extended_fields());
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = worldline(&[
+            (1066, "Hastings"),
+            (1215, "Magna Carta"),
+            (1415, "Agincourt"),
+        ]);
+        let b = worldline(&[(1066, "Hastings"), (1346, "Crecy"), (1415, "Agincourt")]);
+
+        let report = a.diff(&b);
+        assert_eq!(report.only_left.len(), 1);
+        assert_eq!(report.only_left[0].description, "Magna Carta");
+        assert_eq!(report.only_right.len(), 1);
+        assert_eq!(report.only_right[0].description, "Crecy");
+
+        assert!(a.diff(&a).is_empty());
+        assert!(report.changed.is_empty());
+
+        let c = worldline(&[(1066, "Battle of Hastings"), (1346, "Crecy")]);
+        let report = a.diff(&c);
+        assert_eq!(report.changed.len(), 1);
+        assert_eq!(report.changed[0].0.description, "Hastings");
+        assert_eq!(report.changed[0].1.description, "Battle of Hastings");
+        let unified: Vec<(Side, &str)> = report
+            .unified()
+            .into_iter()
+            .map(|(side, e)| (side, e.description.as_str()))
+            .collect();
+        assert_eq!(
+            unified,
+            vec![
+                (Side::Left, "Hastings"),
+                (Side::Right, "Battle of Hastings"),
+                (Side::Left, "Magna Carta"),
+                (Side::Right, "Crecy"),
+                (Side::Left, "Agincourt"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,770 @@
+//! Rendering dates for display.
+//!
+//! How dates and events are displayed is chosen per call with [`DisplayOptions`]. Dates are
+//! rendered by its [`DateFormatter`]: the built-in ones are the [`DateFormat`]s, and library
+//! users can plug in their own. The file format is not affected.
+//!
+//! The padded and long formats count years in the options' [`Epoch`], and the long format
+//! names months in their locale. Both can describe dates less precise than a day in words
+//! instead, e.g. "sometime in 1845", with [`PrecisionDisplay::Descriptive`].
+//!
+//! Output is plain text unless the options turn on ANSI escape sequences, e.g. for a terminal:
+//! dates are then blue, and event categories are shown in the [`Color`] given for them.
+//!
+//! Query matches can be picked out in event descriptions in a [`Highlight`] style.
+//!
+//! Events with longer notes (see `crate::notes`) are marked with [`NOTE_MARKER`] when their
+//! ids are among the options' noted ids.
+
+use crate::epoch::Epoch;
+use crate::locale::Locale;
+use crate::{Date, Event};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+pub trait DateFormatter: fmt::Debug + Send + Sync {
+    /// Render a date. `show_era` is set when the dates being shown include BCE ones, so CE
+    /// dates may need marking too. The options give the epoch, precision display and locale
+    /// to render it in.
+    fn format(&self, date: &Date, show_era: bool, options: &DisplayOptions) -> String;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateFormat {
+    /// Aligned columns, e.g. " CE 1945-09-02" or "BCE 0044      "
+    #[default]
+    Padded,
+    /// ISO 8601 with astronomical years, e.g. "1945-09-02" or "-0043-03-15" (44 BCE)
+    Iso,
+    /// Written out, e.g. "September 2, 1945" or "March 15, 44 BCE", with month names in the
+    /// [`Locale`]
+    Long,
+    /// The form used in files, e.g. "1945-09-02" or "-0044-03-15"
+    Compact,
+}
+
+pub const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+impl DateFormat {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "padded" => Ok(DateFormat::Padded),
+            "iso" => Ok(DateFormat::Iso),
+            "long" => Ok(DateFormat::Long),
+            "compact" => Ok(DateFormat::Compact),
+            _ => Err(format!(
+                "Invalid date format '{}', expected padded, iso, long, or compact",
+                s
+            )),
+        }
+    }
+}
+
+impl DateFormat {
+    /// Format a date counting years from an epoch, which only the padded and long formats
+    /// use, and naming months in a locale, which only the long format uses.
+    pub fn format_in(&self, date: &Date, show_era: bool, epoch: &Epoch, locale: Locale) -> String {
+        match self {
+            DateFormat::Padded => date.format_in(epoch, show_era),
+            DateFormat::Iso => {
+                let period = date.period_suffix();
+                let year = period.as_ref().map_or(date.year, |(year, _)| *year);
+                let year = Date::astronomical_year(year);
+                let sign = if year < 0 { "-" } else { "" };
+                let mut out = format!("{}{:04}", sign, year.abs());
+                if let Some((_, suffix)) = period {
+                    out += &suffix;
+                } else if date.month != 0 {
+                    out += &format!("-{:02}", date.month);
+                }
+                if date.day != 0 {
+                    out += &format!("-{:02}", date.day);
+                }
+                if let Some(time) = date.time() {
+                    out += &format!("T{}", time);
+                }
+                out
+            }
+            DateFormat::Long => {
+                let week = date.week();
+                let year = week.map_or(date.year, |(year, _)| year);
+                let year = match (epoch.year(year), show_era) {
+                    ((year, "CE"), false) if *epoch == Epoch::CommonEra => year.to_string(),
+                    ((year, label), _) => format!("{} {}", year, label),
+                };
+                if let Some((_, week)) = week {
+                    return format!("week {} of {}", week, year);
+                }
+                if let Some(quarter) = date.quarter() {
+                    return format!("Q{} {}", quarter, year);
+                }
+                match (date.month, date.day) {
+                    (0, _) => year,
+                    (month, 0) => locale.format_month(month, &year),
+                    (month, day) => {
+                        let day = locale.format_day(month, day, &year);
+                        match date.time() {
+                            Some(time) => format!("{}, {}", day, time),
+                            None => day,
+                        }
+                    }
+                }
+            }
+            DateFormat::Compact => date.to_string(),
+        }
+    }
+}
+
+impl DateFormatter for DateFormat {
+    fn format(&self, date: &Date, show_era: bool, options: &DisplayOptions) -> String {
+        let (epoch, locale) = (&options.epoch, options.locale);
+        match options.precision {
+            PrecisionDisplay::Plain => self.format_in(date, show_era, epoch, locale),
+            PrecisionDisplay::Descriptive => self.describe_in(date, show_era, epoch, locale),
+        }
+    }
+}
+
+/// How dates less precise than a day (years, months, quarters and weeks) are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrecisionDisplay {
+    /// As the date format writes them, e.g. " CE 1845      " padded to line up with days
+    #[default]
+    Plain,
+    /// In words in the padded and long formats, e.g. "sometime in 1845" or "March 1845", so
+    /// they don't look like dates with missing parts
+    Descriptive,
+}
+
+impl PrecisionDisplay {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "plain" => Ok(PrecisionDisplay::Plain),
+            "descriptive" => Ok(PrecisionDisplay::Descriptive),
+            _ => Err(format!(
+                "Invalid precision display '{}', expected plain or descriptive",
+                s
+            )),
+        }
+    }
+}
+
+impl DateFormat {
+    /// Format a date like [`DateFormat::format_in`], but with dates less precise than a day
+    /// described in words in the padded and long formats (see
+    /// [`PrecisionDisplay::Descriptive`]).
+    pub fn describe_in(
+        &self,
+        date: &Date,
+        show_era: bool,
+        epoch: &Epoch,
+        locale: Locale,
+    ) -> String {
+        if date.is_day_precision() || matches!(self, DateFormat::Iso | DateFormat::Compact) {
+            return self.format_in(date, show_era, epoch, locale);
+        }
+        let long = DateFormat::Long.format_in(date, show_era, epoch, locale);
+        if date.month == 0 && date.period_suffix().is_none() {
+            format!("sometime in {}", long)
+        } else {
+            long
+        }
+    }
+}
+
+/// When to show eras (BCE/CE) on displayed dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EraDisplay {
+    Always,
+    Never,
+    /// When any of the dates shown together is BCE, so CE dates are marked too.
+    #[default]
+    Auto,
+}
+
+impl EraDisplay {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "always" => Ok(EraDisplay::Always),
+            "never" => Ok(EraDisplay::Never),
+            "auto" => Ok(EraDisplay::Auto),
+            _ => Err(format!(
+                "Invalid era display '{}', expected always, never, or auto",
+                s
+            )),
+        }
+    }
+}
+
+/// The ANSI escape sequence resetting the style.
+const RESET: &str = "\u{001B}[0m";
+
+/// The style of dates.
+pub(crate) const DATE_STYLE: &str = "\u{001B}[34m";
+
+/// Descriptions are laid out in at least this many columns, however narrow the output.
+pub(crate) const MIN_TEXT_WIDTH: usize = 10;
+
+/// Options for displaying dates and lists of events, e.g. [`crate::WorldLine::format_range`].
+#[derive(Debug, Clone)]
+pub struct DisplayOptions {
+    pub era: EraDisplay,
+    /// The number of columns to fit each event in, e.g. the terminal's width, or None for no
+    /// limit.
+    pub width: Option<usize>,
+    /// Wrap descriptions that don't fit in the width onto more lines, instead of truncating
+    /// them with an ellipsis.
+    pub wrap: bool,
+    /// The style of highlighted text, e.g. query matches.
+    pub highlight: Highlight,
+    /// How dates are rendered, [`DateFormat::Padded`] by default.
+    pub formatter: Arc<dyn DateFormatter>,
+    /// The epoch the padded and long date formats count years from.
+    pub epoch: Epoch,
+    /// How the built-in date formats show dates less precise than a day.
+    pub precision: PrecisionDisplay,
+    /// The language the long date format names months in.
+    pub locale: Locale,
+    /// Style text with ANSI escape sequences (colors, bold), e.g. for a terminal.
+    pub ansi: bool,
+    /// The colors categories (lowercase) are shown in with ANSI escape sequences.
+    pub category_colors: BTreeMap<String, Color>,
+    /// The ids of the events with notes, which are marked with [`NOTE_MARKER`].
+    pub noted_ids: BTreeSet<String>,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            era: EraDisplay::default(),
+            width: None,
+            wrap: false,
+            highlight: Highlight::default(),
+            formatter: Arc::new(DateFormat::default()),
+            epoch: Epoch::default(),
+            precision: PrecisionDisplay::default(),
+            locale: Locale::default(),
+            ansi: false,
+            category_colors: BTreeMap::new(),
+            noted_ids: BTreeSet::new(),
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Format a date with the options' formatter.
+    pub fn format_date(&self, date: &Date, show_era: bool) -> String {
+        self.formatter.format(date, show_era, self)
+    }
+
+    /// Text in an ANSI style, if ANSI escape sequences are turned on.
+    pub(crate) fn paint(&self, style: &str, text: &str) -> String {
+        if self.ansi {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Format a category as it's displayed, e.g. "[war]", colored if a color is given for it.
+    pub fn format_category(&self, category: &str) -> String {
+        match self.category_colors.get(&category.to_lowercase()) {
+            Some(color) => self.paint(&color.ansi(), &format!("[{}]", category)),
+            None => format!("[{}]", category),
+        }
+    }
+
+    /// Whether the event with an id is marked as having a note.
+    pub fn has_note(&self, id: &str) -> bool {
+        self.noted_ids.contains(id)
+    }
+
+    /// Whether to show eras on the dates of events displayed together. This is decided once
+    /// for all of them, so the formatting doesn't change partway through a list.
+    pub fn show_era<'a>(&self, events: impl IntoIterator<Item = &'a Event>) -> bool {
+        match self.era {
+            EraDisplay::Always => true,
+            EraDisplay::Never => false,
+            EraDisplay::Auto => events.into_iter().any(|e| e.date.year < 0),
+        }
+    }
+
+    /// The options for output `columns` narrower, e.g. to leave room for a gutter.
+    pub fn narrowed(&self, columns: usize) -> Self {
+        Self {
+            width: self.width.map(|width| width.saturating_sub(columns)),
+            ..self.clone()
+        }
+    }
+
+    /// Lay out events displayed together, one string per event. Dates are padded to line up
+    /// in a column, and the category, description and place after them are fitted to the
+    /// width. Wrapped lines are joined with newlines and indented past the dates.
+    pub fn format_events(&self, events: &[&Event]) -> Vec<String> {
+        self.format_highlighted(events, &[])
+    }
+
+    /// Lay out events like [`DisplayOptions::format_events`], highlighting parts of their
+    /// descriptions: `highlights` has byte ranges of each event's description, e.g. from
+    /// [`crate::query::QueryExpr::match_spans`].
+    pub fn format_highlighted(
+        &self,
+        events: &[&Event],
+        highlights: &[Vec<Range<usize>>],
+    ) -> Vec<String> {
+        let show_era = self.show_era(events.iter().copied());
+        let dates: Vec<String> = events
+            .iter()
+            .map(|event| self.format_date(&event.date, show_era))
+            .collect();
+        let date_width = dates.iter().map(|d| d.chars().count()).max().unwrap_or(0);
+        let text_width = self
+            .width
+            .map(|width| width.saturating_sub(date_width + 1).max(MIN_TEXT_WIDTH));
+        let indent = format!("\n{:date_width$} ", "");
+        events
+            .iter()
+            .zip(dates)
+            .enumerate()
+            .map(|(i, (event, date))| {
+                let text = event.full_description();
+                let noted = event.id.as_ref().is_some_and(|id| self.has_note(id));
+                let marker_width = if noted {
+                    NOTE_MARKER.chars().count() + 1
+                } else {
+                    0
+                };
+                let text_width =
+                    text_width.map(|width| width.saturating_sub(marker_width).max(MIN_TEXT_WIDTH));
+                let mut lines = match text_width {
+                    Some(width) if self.wrap => wrap(&text, width),
+                    Some(width) => vec![truncate(&text, width)],
+                    None => vec![text.clone()],
+                };
+                let spans = highlights.get(i).map_or(&[][..], Vec::as_slice);
+                let style = if self.ansi {
+                    self.highlight.ansi()
+                } else {
+                    String::new()
+                };
+                if !spans.is_empty() && !style.is_empty() {
+                    // the description follows the category
+                    let offset = event.category.as_ref().map_or(0, |c| c.len() + 3);
+                    let marked: Vec<(char, bool)> = text
+                        .char_indices()
+                        .map(|(i, c)| {
+                            let highlighted = i
+                                .checked_sub(offset)
+                                .is_some_and(|i| spans.iter().any(|span| span.contains(&i)));
+                            (c, highlighted)
+                        })
+                        .collect();
+                    let mut position = 0;
+                    for line in &mut lines {
+                        *line = highlight(line, &marked, &mut position, &style);
+                    }
+                }
+                if noted {
+                    if let Some(last) = lines.last_mut() {
+                        *last += &format!(" {}", NOTE_MARKER);
+                    }
+                }
+                let mut text = lines.join(&indent);
+                if let Some(category) = &event.category {
+                    let plain = format!("[{}]", category);
+                    if text.starts_with(&plain) {
+                        text = self.format_category(category) + &text[plain.len()..];
+                    }
+                }
+                let date = format!("{:date_width$}", date);
+                format!("{} {}", self.paint(DATE_STYLE, &date), text)
+            })
+            .collect()
+    }
+}
+
+/// Highlight the characters of a line laid out from marked text, where they're marked.
+/// The line's characters are found in order in the text from `position`, skipping any that
+/// aren't there, like a truncating ellipsis.
+fn highlight(line: &str, marked: &[(char, bool)], position: &mut usize, style: &str) -> String {
+    let mut out = String::new();
+    let mut on = false;
+    for c in line.chars() {
+        let highlighted = match marked[*position..].iter().position(|(m, _)| *m == c) {
+            Some(skipped) => {
+                *position += skipped + 1;
+                marked[*position - 1].1
+            }
+            None => false,
+        };
+        if highlighted != on {
+            out += if highlighted { style } else { RESET };
+            on = highlighted;
+        }
+        out.push(c);
+    }
+    if on {
+        out += RESET;
+    }
+    out
+}
+
+/// Shorten text to at most `width` characters, ending in an ellipsis if it's cut.
+pub(crate) fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(width - 1).collect();
+    truncated.trim_end().to_string() + "…"
+}
+
+/// Break text into lines of at most `width` characters between words, splitting words that
+/// are longer than a line.
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    if text.chars().count() <= width {
+        return vec![text.to_string()];
+    }
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        if line_width > 0 && line_width + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        while word.len() > width {
+            lines.push(word.drain(..width).collect());
+        }
+        if word.is_empty() {
+            continue;
+        }
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+        line_width += word.len();
+        line.extend(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Terminal colors for categories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    const ALL: [Color; 8] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+    ];
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|color| format!("{:?}", color).to_lowercase() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid color '{}', expected black, red, green, yellow, blue, magenta, cyan, or white",
+                    s
+                )
+            })
+    }
+
+    /// The ANSI escape sequence setting the foreground color.
+    fn ansi(&self) -> String {
+        format!("\u{001B}[{}m", 30 + *self as u8)
+    }
+}
+
+/// How highlighted text is shown: bold, underlined, reversed and/or in a color. The default
+/// is bold red, like grep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highlight {
+    pub bold: bool,
+    pub underline: bool,
+    pub reverse: bool,
+    pub color: Option<Color>,
+}
+
+impl Default for Highlight {
+    fn default() -> Self {
+        Self {
+            bold: true,
+            underline: false,
+            reverse: false,
+            color: Some(Color::Red),
+        }
+    }
+}
+
+impl Highlight {
+    /// Parse a style from words, e.g. "bold yellow", "underline" or "none" for no highlighting.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut highlight = Self {
+            bold: false,
+            underline: false,
+            reverse: false,
+            color: None,
+        };
+        for word in s.split_whitespace() {
+            match word.to_lowercase().as_str() {
+                "bold" => highlight.bold = true,
+                "underline" => highlight.underline = true,
+                "reverse" => highlight.reverse = true,
+                "none" => {}
+                color => {
+                    highlight.color = Some(Color::parse(color).map_err(|_| {
+                        format!(
+                            "Invalid highlight '{}', expected bold, underline, reverse, a color, or none",
+                            word
+                        )
+                    })?)
+                }
+            }
+        }
+        Ok(highlight)
+    }
+
+    /// The ANSI escape sequences turning the style on, empty for no highlighting.
+    fn ansi(&self) -> String {
+        let mut ansi = String::new();
+        for (on, code) in [(self.bold, 1), (self.underline, 4), (self.reverse, 7)] {
+            if on {
+                ansi += &format!("\u{001B}[{}m", code);
+            }
+        }
+        ansi + &self.color.map(|color| color.ansi()).unwrap_or_default()
+    }
+}
+
+/// Shown after the description of events with notes.
+pub const NOTE_MARKER: &str = "✎";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_formats() {
+        let cases = [
+            (
+                (1945, 9, 2),
+                ["1945-09-02", "September 2, 1945", "1945-09-02"],
+            ),
+            ((1945, 9, 0), ["1945-09", "September 1945", "1945-09"]),
+            (
+                (-44, 3, 15),
+                ["-0043-03-15", "March 15, 44 BCE", "-0044-03-15"],
+            ),
+            ((-1, 0, 0), ["0000", "1 BCE", "-0001"]),
+        ];
+        let options = DisplayOptions::default();
+        for ((year, month, day), [iso, long, compact]) in cases {
+            let date = Date::new(year, month, day).unwrap();
+            assert_eq!(DateFormat::Iso.format(&date, false, &options), iso);
+            assert_eq!(DateFormat::Long.format(&date, false, &options), long);
+            assert_eq!(DateFormat::Compact.format(&date, false, &options), compact);
+        }
+        let date = Date::new(1945, 0, 0).unwrap();
+        assert_eq!(DateFormat::Long.format(&date, true, &options), "1945 CE");
+        assert_eq!(
+            DateFormat::Padded.format(&date, true, &options),
+            " CE 1945      "
+        );
+
+        let holocene = DisplayOptions {
+            epoch: Epoch::Holocene,
+            ..Default::default()
+        };
+        let holocene = |format: DateFormat| format.format(&date, false, &holocene);
+        assert_eq!(holocene(DateFormat::Long), "11945 HE");
+        assert_eq!(holocene(DateFormat::Padded), "HE 11945      ");
+        assert_eq!(holocene(DateFormat::Iso), "1945");
+
+        assert_eq!(DateFormat::parse("ISO"), Ok(DateFormat::Iso));
+        assert!(DateFormat::parse("fancy").is_err());
+    }
+
+    #[test]
+    fn test_descriptive_precision() {
+        let describe = |format: DateFormat, (year, month, day), show_era| {
+            let date = Date::new(year, month, day).unwrap();
+            format.describe_in(&date, show_era, &Epoch::CommonEra, Locale::English)
+        };
+        assert_eq!(
+            describe(DateFormat::Padded, (1845, 0, 0), false),
+            "sometime in 1845"
+        );
+        assert_eq!(
+            describe(DateFormat::Long, (-44, 0, 0), false),
+            "sometime in 44 BCE"
+        );
+        assert_eq!(
+            describe(DateFormat::Padded, (1845, 3, 0), true),
+            "March 1845 CE"
+        );
+        assert_eq!(
+            describe(DateFormat::Padded, (1845, 3, 2), false),
+            "1845-03-02"
+        );
+        assert_eq!(describe(DateFormat::Iso, (1845, 0, 0), false), "1845");
+        let quarter = Date::parse("1845-Q2").unwrap().0;
+        assert_eq!(
+            DateFormat::Padded.describe_in(&quarter, false, &Epoch::CommonEra, Locale::English),
+            "Q2 1845"
+        );
+
+        assert_eq!(
+            PrecisionDisplay::parse("Descriptive"),
+            Ok(PrecisionDisplay::Descriptive)
+        );
+        assert!(PrecisionDisplay::parse("vague").is_err());
+    }
+
+    #[test]
+    fn test_era_display() {
+        let events: Vec<Event> = ["1945 CE", "BCE 44 BCE"]
+            .iter()
+            .map(|line| Event::parse(line).unwrap())
+            .collect();
+        let auto = DisplayOptions::default();
+        assert!(!auto.show_era(&events[..1]));
+        assert!(auto.show_era(&events));
+        let never = DisplayOptions {
+            era: EraDisplay::Never,
+            ..Default::default()
+        };
+        assert!(!never.show_era(&events));
+        assert_eq!(EraDisplay::parse("Always"), Ok(EraDisplay::Always));
+        assert!(EraDisplay::parse("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_format_events() {
+        let events: Vec<Event> = [
+            "1914-07-28 [war] Austria-Hungary declares war on Serbia",
+            "1918-11-11 Armistice",
+        ]
+        .iter()
+        .map(|line| Event::parse(line).unwrap())
+        .collect();
+        let events: Vec<&Event> = events.iter().collect();
+        let ansi = DisplayOptions {
+            ansi: true,
+            ..Default::default()
+        };
+        let date = |d| format!("\u{001B}[34m CE {}\u{001B}[0m", d);
+
+        let unlimited = ansi.format_events(&events);
+        assert_eq!(
+            unlimited[0],
+            "\u{001B}[34m1914-07-28\u{001B}[0m [war] Austria-Hungary declares war on Serbia"
+        );
+        let truncated = DisplayOptions {
+            era: EraDisplay::Always,
+            width: Some(40),
+            ..ansi.clone()
+        };
+        assert_eq!(
+            truncated.format_events(&events),
+            vec![
+                date("1914-07-28") + " [war] Austria-Hungary de…",
+                date("1918-11-11") + " Armistice",
+            ]
+        );
+        let wrapped = DisplayOptions {
+            wrap: true,
+            ..truncated.clone()
+        };
+        assert_eq!(
+            wrapped.format_events(&events)[0],
+            date("1914-07-28") + " [war] Austria-Hungary\n               declares war on Serbia"
+        );
+        assert_eq!(wrap("Czechoslovakia", 10), vec!["Czechoslov", "akia"]);
+
+        // highlights follow the description through wrapping
+        let bold = |s| format!("\u{001B}[1m{}\u{001B}[0m", s);
+        let highlight = Highlight::parse("bold").unwrap();
+        let spans = crate::query::QueryExpr::Text("declares war".to_string())
+            .match_spans(&events[0].description);
+        let highlighted = DisplayOptions {
+            highlight,
+            ..wrapped
+        }
+        .format_highlighted(&events, &[spans]);
+        assert_eq!(
+            highlighted[0],
+            date("1914-07-28")
+                + " [war] Austria-Hungary\n               "
+                + &bold("declares war")
+                + " on Serbia"
+        );
+        assert_eq!(highlighted[1], date("1918-11-11") + " Armistice");
+        assert!(Highlight::parse("bold mauve").is_err());
+
+        let mut armistice = events[1].clone();
+        armistice.id = Some("armistice".to_string());
+        let noted = DisplayOptions {
+            noted_ids: BTreeSet::from(["armistice".to_string()]),
+            ..truncated
+        };
+        assert_eq!(
+            noted.format_events(&[&armistice]),
+            vec![date("1918-11-11") + " Armistice ✎"]
+        );
+    }
+
+    #[test]
+    fn test_category_colors() {
+        assert_eq!(Color::parse("Red"), Ok(Color::Red));
+        assert!(Color::parse("mauve").is_err());
+
+        let mut options = DisplayOptions {
+            ansi: true,
+            ..Default::default()
+        };
+        assert_eq!(options.format_category("science"), "[science]");
+        options
+            .category_colors
+            .insert("science".to_string(), Color::Green);
+        assert_eq!(
+            options.format_category("science"),
+            "\u{001B}[32m[science]\u{001B}[0m"
+        );
+    }
+}
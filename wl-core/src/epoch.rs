@@ -0,0 +1,244 @@
+//! Counting years from an epoch other than the start of the Common Era, for display.
+//!
+//! In the Holocene calendar, 10,000 BCE is year 1 HE, so 1 BCE is 10000 HE and 1 CE is 10001 HE.
+//! A custom epoch counts from a chosen year, e.g. years since the founding of Rome with
+//! `AUC=753 BCE`, or counts down from it with `BP=1950 backward`. Either way there's no sign
+//! flip and no missing year 0: years before a custom epoch's year 1 are 0, -1, and so on.
+//!
+//! Custom epochs given as eras, e.g. from the config, can also be named when reading dates, e.g.
+//! "AUC 709" or "5 Elizabeth II" with [`Date::parse_natural_in`], and chosen by name with
+//! [`Epoch::parse_in`]. Years are whole calendar years, so a regnal year is taken to start on
+//! January 1st of the year of accession rather than on the day.
+//!
+//! Negative years typed as numbers are historical by default, so -44 is 44 BCE, but data from
+//! other tools often numbers them astronomically like ISO 8601, where -43 is 44 BCE and year
+//! 0 is 1 BCE. [`YearNumbering`] says which to read them as, with [`Date::parse_in`].
+
+use crate::query::parse_whole_date;
+use crate::span::from_astronomical_year;
+use crate::Date;
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Epoch {
+    /// BCE and CE
+    #[default]
+    CommonEra,
+    Holocene,
+    /// Years counted from `year` (a CE or BCE year), which is year 1 `label`, forward or
+    /// `backward` in time.
+    Custom {
+        label: String,
+        year: i32,
+        backward: bool,
+    },
+}
+
+impl Epoch {
+    /// Parse "ce", "he" (or "holocene"), or a custom epoch like "AUC=753 BCE" or "BP=1950
+    /// backward".
+    pub fn parse(s: &str) -> Result<Self, String> {
+        Self::parse_in(s, &[])
+    }
+
+    /// Parse an epoch like [`Epoch::parse`], or the name of one of `eras`, ignoring case.
+    pub fn parse_in(s: &str, eras: &[Epoch]) -> Result<Self, String> {
+        let invalid = || {
+            format!(
+                "Invalid epoch '{}', expected ce, he, an era from the config, or e.g. AUC=753 BCE",
+                s
+            )
+        };
+        match s.trim().to_lowercase().as_str() {
+            "ce" => return Ok(Epoch::CommonEra),
+            "he" | "holocene" => return Ok(Epoch::Holocene),
+            _ => {}
+        }
+        let Some((label, year)) = s.split_once('=') else {
+            return eras
+                .iter()
+                .find(|era| era.label().eq_ignore_ascii_case(s.trim()))
+                .cloned()
+                .ok_or_else(invalid);
+        };
+        let year = year.trim();
+        let (year, backward) = match year.rsplit_once(' ') {
+            Some((year, direction)) if direction.eq_ignore_ascii_case("backward") => (year, true),
+            Some((year, direction)) if direction.eq_ignore_ascii_case("forward") => (year, false),
+            _ => (year, false),
+        };
+        // allow the era after the year, e.g. "753 BCE"
+        let year = match year.trim().rsplit_once(' ') {
+            Some((number, era))
+                if ["BCE", "BC", "CE", "AD"].contains(&era.to_uppercase().as_str()) =>
+            {
+                format!("{} {}", era, number)
+            }
+            _ => year.to_string(),
+        };
+        let year = parse_whole_date(&year).map_err(|_| invalid())?;
+        if label.trim().is_empty() || year.month != 0 {
+            return Err(invalid());
+        }
+        Ok(Epoch::Custom {
+            label: label.trim().to_string(),
+            year: year.year,
+            backward,
+        })
+    }
+
+    /// The label years in this epoch are shown with, e.g. "HE".
+    pub fn label(&self) -> &str {
+        self.year(1).1
+    }
+
+    /// The number and label of a CE or BCE year in this epoch, e.g. (10001, "HE") for 1 CE.
+    pub fn year(&self, year: i32) -> (i64, &str) {
+        let astronomical = Date::astronomical_year(year) as i64;
+        match self {
+            Epoch::CommonEra if year < 0 => (-year as i64, "BCE"),
+            Epoch::CommonEra => (year as i64, "CE"),
+            Epoch::Holocene => (astronomical + 10_000, "HE"),
+            Epoch::Custom {
+                label,
+                year: first,
+                backward,
+            } => {
+                let first = Date::astronomical_year(*first) as i64;
+                let year = if *backward {
+                    first - astronomical
+                } else {
+                    astronomical - first
+                };
+                (year + 1, label)
+            }
+        }
+    }
+
+    /// The CE or BCE year of a year counted in this epoch, the inverse of [`Epoch::year`].
+    /// Years in the Common Era are taken to be CE.
+    pub fn common_era_year(&self, year: i64) -> Result<i32, String> {
+        let astronomical = match self {
+            Epoch::CommonEra => year,
+            Epoch::Holocene => year - 10_000,
+            Epoch::Custom {
+                year: first,
+                backward,
+                ..
+            } => {
+                let first = Date::astronomical_year(*first) as i64;
+                if *backward {
+                    first - (year - 1)
+                } else {
+                    first + (year - 1)
+                }
+            }
+        };
+        let supported = Date::astronomical_year(Date::MIN_YEAR) as i64..=Date::MAX_YEAR as i64;
+        if !supported.contains(&astronomical) {
+            return Err(format!(
+                "Year {} {} is out of range; supported years are 9999 BCE to 9999 CE",
+                year,
+                self.label()
+            ));
+        }
+        Ok(from_astronomical_year(astronomical as i32))
+    }
+}
+
+/// How negative years without an era are numbered when reading dates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum YearNumbering {
+    /// -44 is 44 BCE, and there is no year 0.
+    #[default]
+    Historical,
+    /// -43 is 44 BCE, and 0 is 1 BCE, like ISO 8601 and astronomy.
+    Astronomical,
+}
+
+impl YearNumbering {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "historical" => Ok(YearNumbering::Historical),
+            "astronomical" | "iso" => Ok(YearNumbering::Astronomical),
+            _ => Err(format!(
+                "Invalid year numbering '{}', expected historical or astronomical",
+                s
+            )),
+        }
+    }
+}
+
+impl Date {
+    /// Like [`Date::format`], counting years from an epoch. Other epochs than the Common Era
+    /// always show their label, e.g. "HE 11945-09-02" or "AUC 2698      ".
+    pub fn format_in(&self, epoch: &Epoch, show_era: bool) -> String {
+        if *epoch == Epoch::CommonEra {
+            return self.format(show_era);
+        }
+        let period = self.period_suffix();
+        let (year, label) = epoch.year(period.as_ref().map_or(self.year, |(year, _)| *year));
+        let sign = if year < 0 { "-" } else { "" };
+        let mut out = format!("{} {}{:04}", label, sign, year.abs());
+        if let Some((_, suffix)) = period {
+            out += &format!("{:<6}", suffix);
+        } else if self.month == 0 {
+            out += "      ";
+        } else if self.day == 0 {
+            out += &format!("-{:02}   ", self.month);
+        } else {
+            out += &format!("-{:02}-{:02}", self.month, self.day);
+        }
+        if let Some(time) = self.time {
+            out += &format!(" {}", time);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epochs() {
+        assert_eq!(Epoch::Holocene.year(1), (10001, "HE"));
+        assert_eq!(Epoch::Holocene.year(-1), (10000, "HE"));
+        assert_eq!(Epoch::Holocene.year(-9999), (2, "HE"));
+        assert_eq!(Epoch::CommonEra.year(-44), (44, "BCE"));
+
+        let rome = Epoch::parse("AUC=753 BCE").unwrap();
+        assert_eq!(
+            rome,
+            Epoch::Custom {
+                label: "AUC".to_string(),
+                year: -753,
+                backward: false,
+            }
+        );
+        assert_eq!(rome.year(-753), (1, "AUC"));
+        assert_eq!(rome.year(-44), (710, "AUC"));
+        assert_eq!(rome.year(-754), (0, "AUC"));
+        assert_eq!(Epoch::parse("HE"), Ok(Epoch::Holocene));
+        assert!(Epoch::parse("AUC=753-04-21").is_err());
+        assert!(Epoch::parse("mayan").is_err());
+        assert_eq!(rome.common_era_year(709), Ok(-45));
+        assert_eq!(rome.common_era_year(754), Ok(1));
+        assert_eq!(Epoch::Holocene.common_era_year(10001), Ok(1));
+        assert!(rome.common_era_year(20_000).is_err());
+
+        let present = Epoch::parse("BP=1950 backward").unwrap();
+        assert_eq!(present.year(1950), (1, "BP"));
+        assert_eq!(present.year(1900), (51, "BP"));
+        assert_eq!(present.common_era_year(51), Ok(1900));
+        assert_eq!(present.common_era_year(1951), Ok(-1));
+        let eras = [present.clone()];
+        assert_eq!(Epoch::parse_in("bp", &eras), Ok(present));
+        assert!(Epoch::parse("BP").is_err());
+
+        let date = Date::new(1945, 9, 2).unwrap();
+        assert_eq!(date.format_in(&Epoch::Holocene, false), "HE 11945-09-02");
+        assert_eq!(date.format_in(&Epoch::CommonEra, true), " CE 1945-09-02");
+        let date = Date::new(-5, 0, 0).unwrap();
+        assert_eq!(date.format_in(&rome, false), "AUC 0749      ");
+    }
+}
@@ -0,0 +1,139 @@
+//! Exporting worldlines by name, through [`Exporter`]s.
+//!
+//! The built-in exporters are `anki`, `text` (the worldline file format) and the
+//! [`Format`]s `json`, `jsonl`, `csv` and `ics`. Library users can add their own, or replace
+//! a built-in one, in a set of [`Exporters`]; `wl export --format NAME` looks the built-in
+//! exporters up here, as do `wl show --export NAME` and `wl query --export NAME` to export
+//! only the events they find (see [`WorldLine::with_events`]).
+
+use crate::convert::Format;
+use crate::WorldLine;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Arc;
+
+pub trait Exporter: Send + Sync {
+    /// Write the worldline in the exporter's format.
+    fn export(&self, worldline: &WorldLine, writer: &mut dyn Write) -> Result<(), String>;
+}
+
+impl Exporter for Format {
+    fn export(&self, worldline: &WorldLine, writer: &mut dyn Write) -> Result<(), String> {
+        writer
+            .write_all(self.write(worldline).as_bytes())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Tab-separated cards for Anki, see [`WorldLine::write_anki`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Anki;
+
+impl Exporter for Anki {
+    fn export(&self, worldline: &WorldLine, writer: &mut dyn Write) -> Result<(), String> {
+        worldline.write_anki(writer).map_err(|e| e.to_string())
+    }
+}
+
+const BUILT_IN: [&str; 6] = ["anki", "csv", "ics", "json", "jsonl", "text"];
+
+fn built_in(name: &str) -> Option<Arc<dyn Exporter>> {
+    Some(match name {
+        "anki" => Arc::new(Anki),
+        "csv" => Arc::new(Format::Csv),
+        "ics" => Arc::new(Format::Ics),
+        "json" => Arc::new(Format::Json),
+        "jsonl" => Arc::new(Format::Jsonl),
+        "text" => Arc::new(Format::Wl),
+        _ => return None,
+    })
+}
+
+/// The exporters available by name: the built-in ones, and any registered with
+/// [`Exporters::register`].
+#[derive(Clone, Default)]
+pub struct Exporters {
+    registered: BTreeMap<String, Arc<dyn Exporter>>,
+}
+
+impl Exporters {
+    /// Make an exporter available by name, replacing any exporter with that name.
+    pub fn register(&mut self, name: &str, exporter: impl Exporter + 'static) {
+        self.registered
+            .insert(name.to_lowercase(), Arc::new(exporter));
+    }
+
+    /// The exporter with a name (ignoring case): a registered one, or a built-in one.
+    pub fn get(&self, name: &str) -> Result<Arc<dyn Exporter>, String> {
+        let key = name.trim().to_lowercase();
+        let registered = self.registered.get(&key).cloned();
+        registered.or_else(|| built_in(&key)).ok_or_else(|| {
+            format!(
+                "Unknown export format '{}', expected one of: {}",
+                name,
+                self.names().join(", ")
+            )
+        })
+    }
+
+    /// The names of all exporters, sorted.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = BUILT_IN.iter().map(|name| name.to_string()).collect();
+        names.extend(self.registered.keys().cloned());
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+/// The built-in exporter with a name, ignoring case.
+pub fn exporter(name: &str) -> Result<Arc<dyn Exporter>, String> {
+    Exporters::default().get(name)
+}
+
+impl WorldLine {
+    /// Write the worldline with the built-in exporter named `format`.
+    pub fn export(&self, format: &str, writer: &mut dyn Write) -> Result<(), String> {
+        exporter(format)?.export(self, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Count;
+
+    impl Exporter for Count {
+        fn export(&self, worldline: &WorldLine, writer: &mut dyn Write) -> Result<(), String> {
+            writeln!(writer, "{} events", worldline.len()).map_err(|e| e.to_string())
+        }
+    }
+
+    #[test]
+    fn test_exporters() {
+        let worldline: WorldLine = "1914 WWI begins\n1918 WWI ends\n".parse().unwrap();
+        let export = |format: &str| {
+            let mut out = Vec::new();
+            worldline
+                .export(format, &mut out)
+                .map(|()| String::from_utf8(out).unwrap())
+        };
+        assert_eq!(export("text"), Ok(worldline.to_string()));
+        assert!(export("CSV").unwrap().starts_with("date,end,description"));
+        assert!(export("anki").unwrap().starts_with("#separator:Tab\n"));
+        assert!(export("count").unwrap_err().contains("anki, csv, ics"));
+
+        let mut exporters = Exporters::default();
+        exporters.register("count", Count);
+        let mut out = Vec::new();
+        exporters
+            .get("Count")
+            .unwrap()
+            .export(&worldline.with_events(worldline.query("ends")), &mut out)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1 events\n");
+        assert!(exporters.names().contains(&"count".to_string()));
+        assert!(export("count").is_err());
+    }
+}
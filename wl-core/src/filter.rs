@@ -0,0 +1,185 @@
+//! Composable filters for selecting events, e.g. "BCE events mentioning Rome".
+//!
+//! Anything that decides whether an event is wanted can be an [`EventFilter`]: the
+//! [`QueryExpr`]s of `wl query`, the [`Era`] and [`Century`] of a date, or a closure. Filters
+//! are combined with [`EventFilter::and`], [`EventFilter::or`] and [`EventFilter::not`], and
+//! applied with [`WorldLine::select`].
+
+use crate::query::QueryExpr;
+use crate::{Event, WorldLine};
+
+pub trait EventFilter {
+    fn matches(&self, event: &Event) -> bool;
+
+    /// Events matching both filters.
+    fn and<F: EventFilter>(self, other: F) -> And<Self, F>
+    where
+        Self: Sized,
+    {
+        And(self, other)
+    }
+
+    /// Events matching either filter.
+    fn or<F: EventFilter>(self, other: F) -> Or<Self, F>
+    where
+        Self: Sized,
+    {
+        Or(self, other)
+    }
+
+    /// Events not matching the filter.
+    fn not(self) -> Not<Self>
+    where
+        Self: Sized,
+    {
+        Not(self)
+    }
+}
+
+pub struct And<A, B>(A, B);
+
+impl<A: EventFilter, B: EventFilter> EventFilter for And<A, B> {
+    fn matches(&self, event: &Event) -> bool {
+        self.0.matches(event) && self.1.matches(event)
+    }
+}
+
+pub struct Or<A, B>(A, B);
+
+impl<A: EventFilter, B: EventFilter> EventFilter for Or<A, B> {
+    fn matches(&self, event: &Event) -> bool {
+        self.0.matches(event) || self.1.matches(event)
+    }
+}
+
+pub struct Not<F>(F);
+
+impl<F: EventFilter> EventFilter for Not<F> {
+    fn matches(&self, event: &Event) -> bool {
+        !self.0.matches(event)
+    }
+}
+
+impl<F: Fn(&Event) -> bool> EventFilter for F {
+    fn matches(&self, event: &Event) -> bool {
+        self(event)
+    }
+}
+
+impl EventFilter for QueryExpr {
+    fn matches(&self, event: &Event) -> bool {
+        QueryExpr::matches(self, event)
+    }
+}
+
+/// Events matching all the filters, e.g. ones chosen at runtime. No filters match everything.
+impl EventFilter for Vec<Box<dyn EventFilter>> {
+    fn matches(&self, event: &Event) -> bool {
+        self.iter().all(|filter| filter.matches(event))
+    }
+}
+
+/// Events starting before or after the start of the Common Era.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Era {
+    Bce,
+    Ce,
+}
+
+impl Era {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "bce" | "bc" => Ok(Era::Bce),
+            "ce" | "ad" => Ok(Era::Ce),
+            _ => Err(format!("Invalid era '{}', expected bce or ce", s)),
+        }
+    }
+}
+
+impl EventFilter for Era {
+    fn matches(&self, event: &Event) -> bool {
+        (event.date.year < 0) == (*self == Era::Bce)
+    }
+}
+
+/// Events starting in a century, numbered like "the 20th century" (1901-2000), with negative
+/// numbers for BCE centuries: -1 is 100 to 1 BCE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Century(i32);
+
+impl Century {
+    pub fn new(number: i32) -> Result<Self, String> {
+        if number == 0 {
+            return Err("There is no century 0: the 1st century BCE is -1".to_string());
+        }
+        Ok(Self(number))
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let number = s
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid century '{}', expected e.g. 20 or -1 (BCE)", s))?;
+        Self::new(number)
+    }
+
+    /// The first and last year of the century, e.g. (1901, 2000), or (-100, -1) for -1.
+    pub fn years(&self) -> (i32, i32) {
+        let last = self.0 * 100;
+        if self.0 > 0 {
+            (last - 99, last)
+        } else {
+            (last, last + 99)
+        }
+    }
+}
+
+impl EventFilter for Century {
+    fn matches(&self, event: &Event) -> bool {
+        let (first, last) = self.years();
+        (first..=last).contains(&event.date.year)
+    }
+}
+
+impl WorldLine {
+    /// All events matching a filter.
+    pub fn select(&self, filter: &impl EventFilter) -> Vec<&Event> {
+        self.events.iter().filter(|e| filter.matches(e)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters() {
+        let worldline: WorldLine = "-753 Rome founded\n-44 Caesar assassinated\n\
+                                    476 Fall of Rome in the west\n1914 WWI begins\n\
+                                    2000 Y2K\n"
+            .parse()
+            .unwrap();
+        let descriptions = |events: Vec<&Event>| -> Vec<String> {
+            events.iter().map(|e| e.description.clone()).collect()
+        };
+        let rome = QueryExpr::parse("rome").unwrap();
+        assert_eq!(
+            descriptions(worldline.select(&Era::Bce.and(rome))),
+            ["Rome founded"]
+        );
+        assert_eq!(
+            descriptions(worldline.select(&Century::parse("20").unwrap())),
+            ["WWI begins", "Y2K"]
+        );
+        assert_eq!(
+            descriptions(worldline.select(&Century::new(-1).unwrap().or(Era::Ce.not()))),
+            ["Rome founded", "Caesar assassinated"]
+        );
+        assert_eq!(Century::new(-8).unwrap().years(), (-800, -701));
+        assert!(Century::parse("0").is_err());
+
+        let filters: Vec<Box<dyn EventFilter>> =
+            vec![Box::new(Era::Ce), Box::new(|e: &Event| e.date.year < 1000)];
+        assert_eq!(worldline.select(&filters).len(), 1);
+    }
+}
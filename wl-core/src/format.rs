@@ -0,0 +1,405 @@
+//! Reading and writing the worldline file format.
+//!
+//! v1 files have one event per line: a date followed by a description.
+//!
+//! v2 files start with a `#worldline v2` header, optionally followed by `#key: value` front
+//! matter lines. After the date and description, event lines may carry tab-separated
+//! `key=value` fields:
+//! - `id`: a unique identifier for the event
+//! - `tags`: comma-separated tags
+//! - `people`: comma-separated names of the people the event is about, e.g.
+//!   `people=Julius Caesar,Brutus`
+//! - `series`: the named series of events the event is part of, e.g. `series=Punic Wars`
+//! - `end`: the date the event ended, for events spanning time
+//! - `sources`: comma-separated citation keys
+//! - `note`: a timestamped note, e.g. `note=2025-03-01T14:30:00Z Source disputed`. Repeatable.
+//! - `archived`: `true` for events hidden from `wl show`
+//! - `pinned`: `true` for events shown at the top of `wl dashboard`
+//! - `seq`: the event's place among the events on the same date, e.g. `seq=2`
+//! - `created` and `modified`: when wl added the event and last changed it, e.g.
+//!   `created=2025-03-01T14:30:00Z`
+//!
+//! Tabs, newlines and backslashes in v2 descriptions and values are backslash-escaped.
+
+use crate::timestamp::Timestamp;
+use crate::{Date, Event, Note};
+
+pub const V2_HEADER: &str = "#worldline v2";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FormatVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl FormatVersion {
+    /// The version files are upgraded to by [`crate::WorldLine::migrate`].
+    pub const LATEST: FormatVersion = FormatVersion::V2;
+
+    /// The version's number, as in the `#worldline v2` header.
+    pub fn number(&self) -> u32 {
+        match self {
+            FormatVersion::V1 => 1,
+            FormatVersion::V2 => 2,
+        }
+    }
+}
+
+/// How strictly event lines are read, e.g. by [`crate::WorldLine::parse_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Reject lines that aren't in the file format.
+    #[default]
+    Strict,
+    /// Also read lines starting with a date written in English, e.g. "July 14, 1789: Storming
+    /// of the Bastille" (see [`Date::parse_natural`]), and skip lines that can't be read at
+    /// all, with a warning about each.
+    Lenient,
+}
+
+impl ParseMode {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "strict" => Ok(ParseMode::Strict),
+            "lenient" => Ok(ParseMode::Lenient),
+            _ => Err(format!(
+                "Invalid parse mode '{}', expected strict or lenient",
+                s
+            )),
+        }
+    }
+
+    /// Read a line leniently that couldn't be read strictly with `error`: the event, if a
+    /// date could be found in it, and a warning either way.
+    pub(crate) fn recover(line: &str, error: String) -> (Option<Event>, String) {
+        match crate::parse::natural_event(line) {
+            Some(event) => {
+                let warning = format!("Read the date as {}", event.date);
+                (Some(event), warning)
+            }
+            None => (None, format!("Skipped: {}", error)),
+        }
+    }
+}
+
+/// The contents of a worldline file.
+pub(crate) struct ParsedFile {
+    pub version: FormatVersion,
+    pub front_matter: Vec<(String, String)>,
+    pub events: Vec<Event>,
+    /// The (1-based) line number of each event.
+    pub event_lines: Vec<usize>,
+}
+
+/// A line that couldn't be read by [`parse_lenient`].
+pub(crate) struct InvalidLine<'a> {
+    /// 1-based
+    pub number: usize,
+    pub text: &'a str,
+    pub error: String,
+}
+
+/// Parse the contents of a worldline file, detecting its version from the header.
+pub(crate) fn parse(contents: &str) -> Result<ParsedFile, String> {
+    let (file, invalid) = parse_lenient(contents)?;
+    match invalid.first() {
+        Some(line) => Err(format!("line {}: {}", line.number, line.error)),
+        None => Ok(file),
+    }
+}
+
+/// Like [`parse`], but skipping invalid front matter and event lines instead of failing. Only
+/// an unsupported version is an error.
+pub(crate) fn parse_lenient(contents: &str) -> Result<(ParsedFile, Vec<InvalidLine<'_>>), String> {
+    let mut invalid = Vec::new();
+    let mut lines = contents.lines().enumerate().peekable();
+
+    let version = match lines.peek() {
+        Some((_, line)) if line.trim_end() == V2_HEADER => {
+            lines.next();
+            FormatVersion::V2
+        }
+        Some((_, line)) if line.starts_with("#worldline") => {
+            return Err(format!("Unsupported file version: {}", line));
+        }
+        _ => FormatVersion::V1,
+    };
+
+    let mut front_matter = Vec::new();
+    if version == FormatVersion::V2 {
+        while let Some((i, line)) = lines.next_if(|(_, line)| line.starts_with('#')) {
+            match line[1..].split_once(':') {
+                Some((key, value)) => {
+                    front_matter.push((key.trim().to_string(), value.trim().to_string()))
+                }
+                None => invalid.push(InvalidLine {
+                    number: i + 1,
+                    text: line,
+                    error: format!("Invalid front matter: {}", line),
+                }),
+            }
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut event_lines = Vec::new();
+    for (i, line) in lines {
+        let event = match version {
+            FormatVersion::V1 => Event::parse(line),
+            FormatVersion::V2 => parse_event_v2(line),
+        };
+        match event {
+            Ok(event) => {
+                events.push(event);
+                event_lines.push(i + 1);
+            }
+            Err(error) => invalid.push(InvalidLine {
+                number: i + 1,
+                text: line,
+                error,
+            }),
+        }
+    }
+
+    let file = ParsedFile {
+        version,
+        front_matter,
+        events,
+        event_lines,
+    };
+    Ok((file, invalid))
+}
+
+/// Serialize events (and front matter, for v2) into the contents of a worldline file.
+pub(crate) fn serialize(
+    version: FormatVersion,
+    front_matter: &[(String, String)],
+    events: &[Event],
+) -> String {
+    let mut contents = String::new();
+    match version {
+        FormatVersion::V1 => {
+            for event in events {
+                contents += &event.format_for_file();
+                contents += "\n";
+            }
+        }
+        FormatVersion::V2 => {
+            contents += V2_HEADER;
+            contents += "\n";
+            for (key, value) in front_matter {
+                contents += &format!("#{}: {}\n", key, value);
+            }
+            for event in events {
+                contents += &format_event_v2(event);
+                contents += "\n";
+            }
+        }
+    }
+    contents
+}
+
+impl Event {
+    /// Whether the event has data that can only be stored in a v2 file.
+    pub fn has_extended_fields(&self) -> bool {
+        self.id.is_some()
+            || !self.tags.is_empty()
+            || !self.people.is_empty()
+            || self.series.is_some()
+            || self.end.is_some()
+            || !self.sources.is_empty()
+            || !self.notes.is_empty()
+            || self.archived
+            || self.pinned
+            || self.seq.is_some()
+            || self.created.is_some()
+            || self.modified.is_some()
+    }
+}
+
+pub(crate) fn parse_event_v2(line: &str) -> Result<Event, String> {
+    let mut fields = line.split('\t');
+    // split always yields at least one item
+    let mut event = Event::parse(&unescape(fields.next().unwrap()))?;
+
+    for field in fields {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid field: {}", field))?;
+        let value = unescape(value);
+        match key {
+            "id" => event.id = Some(value),
+            "tags" => event.tags = split_list(&value),
+            "people" => event.people = split_list(&value),
+            "series" => event.series = Some(value),
+            "sources" => event.sources = split_list(&value),
+            "end" => {
+                let (end, idx) = Date::parse(&value)?;
+                if !value[idx..].is_empty() {
+                    return Err(format!("Invalid end date: {}", value));
+                }
+                if end < event.date {
+                    return Err(format!("End date {} is before the start date", value));
+                }
+                event.end = Some(end);
+            }
+            "note" => {
+                let (timestamp, text) = value.split_once(' ').unwrap_or((&value, ""));
+                event.notes.push(Note {
+                    timestamp: Timestamp::parse(timestamp)?,
+                    text: text.to_string(),
+                });
+            }
+            "archived" => {
+                event.archived = match value.as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(format!("Invalid archived flag: {}", value)),
+                }
+            }
+            "pinned" => {
+                event.pinned = match value.as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(format!("Invalid pinned flag: {}", value)),
+                }
+            }
+            "seq" => {
+                event.seq = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid sequence number: {}", value))?,
+                )
+            }
+            "created" => event.created = Some(Timestamp::parse(&value)?),
+            "modified" => event.modified = Some(Timestamp::parse(&value)?),
+            _ => return Err(format!("Unknown field: {}", key)),
+        }
+    }
+    Ok(event)
+}
+
+pub(crate) fn format_event_v2(event: &Event) -> String {
+    let mut line = escape(&event.format_for_file());
+    if let Some(id) = &event.id {
+        line += &format!("\tid={}", escape(id));
+    }
+    if !event.tags.is_empty() {
+        line += &format!("\ttags={}", escape(&event.tags.join(",")));
+    }
+    if !event.people.is_empty() {
+        line += &format!("\tpeople={}", escape(&event.people.join(",")));
+    }
+    if let Some(series) = &event.series {
+        line += &format!("\tseries={}", escape(series));
+    }
+    if let Some(end) = &event.end {
+        line += &format!("\tend={}", end);
+    }
+    if !event.sources.is_empty() {
+        line += &format!("\tsources={}", escape(&event.sources.join(",")));
+    }
+    for note in &event.notes {
+        line += &format!("\tnote={} {}", note.timestamp, escape(&note.text));
+    }
+    if event.archived {
+        line += "\tarchived=true";
+    }
+    if event.pinned {
+        line += "\tpinned=true";
+    }
+    if let Some(seq) = event.seq {
+        line += &format!("\tseq={}", seq);
+    }
+    if let Some(created) = event.created {
+        line += &format!("\tcreated={}", created);
+    }
+    if let Some(modified) = event.modified {
+        line += &format!("\tmodified={}", modified);
+    }
+    line
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', r"\\")
+        .replace('\t', r"\t")
+        .replace('\n', r"\n")
+}
+
+pub(crate) fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('t') => out.push('\t'),
+                Some('n') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_v1() {
+        let file = parse("BCE 0044-03-15 Ides of March\n CE 1969-07-20 Moon landing\n").unwrap();
+        assert_eq!(file.version, FormatVersion::V1);
+        assert!(file.front_matter.is_empty());
+        assert_eq!(file.events.len(), 2);
+        assert_eq!(file.events[1].description, "Moon landing");
+    }
+
+    #[test]
+    fn test_parse_v2() {
+        let contents = "#worldline v2\n\
+                        #title: Wars\n\
+                        \u{20}CE 1914-07-28 WWI\tid=ww1\ttags=war, europe\tend=1918-11-11\n\
+                        \u{20}CE 1939-09-01 Tab\\there\tpeople=Hitler,Stalin\tsources=keegan1989\n\
+                        \u{20}CE 1945       End\tnote=2025-01-01T00:00:00Z a\\tb\tnote=2025-01-02T00:00:00Z c\n";
+        let file = parse(contents).unwrap();
+        assert_eq!(file.version, FormatVersion::V2);
+        assert_eq!(
+            file.front_matter,
+            vec![("title".to_string(), "Wars".to_string())]
+        );
+
+        let ww1 = &file.events[0];
+        assert_eq!(ww1.id.as_deref(), Some("ww1"));
+        assert_eq!(ww1.tags, vec!["war", "europe"]);
+        assert_eq!(ww1.end, Some(Date::new(1918, 11, 11).unwrap()));
+        assert_eq!(file.events[1].description, "Tab\there");
+        assert_eq!(file.events[1].sources, vec!["keegan1989"]);
+        assert_eq!(file.events[1].people, vec!["Hitler", "Stalin"]);
+        assert_eq!(file.events[2].notes.len(), 2);
+        assert_eq!(file.events[2].notes[0].text, "a\tb");
+
+        let written = serialize(file.version, &file.front_matter, &file.events);
+        assert_eq!(written, contents.replace("war, europe", "war,europe"));
+    }
+
+    #[test]
+    fn test_invalid_v2() {
+        assert!(parse("#worldline v3\n").is_err());
+        assert!(parse("#worldline v2\n CE 1914 WWI\tcolor=red\n").is_err());
+        assert!(parse("#worldline v2\n CE 1914 WWI\tend=1900\n").is_err());
+        assert!(parse("#worldline v2\n CE 1914 WWI\tid\n").is_err());
+    }
+}
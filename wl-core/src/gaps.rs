@@ -0,0 +1,66 @@
+//! Finding periods of the worldline that have no events.
+
+use crate::span::Span;
+use crate::{Date, WorldLine};
+
+impl WorldLine {
+    /// Spans of at least `min_gap` with no events, as (start, end) pairs, longest first. A gap
+    /// starts when the events before it have ended, so it doesn't overlap an event's `end`.
+    pub fn gaps(&self, min_gap: &Span) -> Vec<(Date, Date)> {
+        let mut gaps = Vec::new();
+        let mut covered: Option<&Date> = None;
+        for event in &self.events {
+            if let Some(covered) = covered {
+                if event.date >= covered.add_span(min_gap) {
+                    gaps.push((covered.clone(), event.date.clone()));
+                }
+            }
+            let last = event.end.as_ref().unwrap_or(&event.date);
+            covered = covered.max(Some(last));
+        }
+        gaps.sort_by_key(|(start, end)| (start.day_number() - end.day_number(), start.clone()));
+        gaps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Event;
+
+    #[test]
+    fn test_gaps() {
+        let mut worldline = WorldLine::default();
+        let mut ww1 = Event::new(Date::new(1914, 7, 28).unwrap(), "WWI".to_string());
+        ww1.end = Some(Date::new(1918, 11, 11).unwrap());
+        worldline.add_event(ww1);
+        for (year, description) in [
+            (1800, "Start"),
+            (1815, "Waterloo"),
+            (1916, "Somme"),
+            (1939, "WWII"),
+        ] {
+            worldline.add_event(Event::new(
+                Date::new(year, 0, 0).unwrap(),
+                description.to_string(),
+            ));
+        }
+
+        let gaps = worldline.gaps(&Span::years(20));
+        assert_eq!(
+            gaps,
+            vec![
+                (
+                    Date::new(1815, 0, 0).unwrap(),
+                    Date::new(1914, 7, 28).unwrap()
+                ),
+                (
+                    Date::new(1918, 11, 11).unwrap(),
+                    Date::new(1939, 0, 0).unwrap()
+                ),
+            ]
+        );
+        assert_eq!(worldline.gaps(&Span::years(10)).len(), 3);
+        assert!(worldline.gaps(&Span::years(100)).is_empty());
+    }
+}
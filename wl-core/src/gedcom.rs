@@ -0,0 +1,386 @@
+//! Importing births, deaths and marriages from GEDCOM files, the format genealogy programs
+//! exchange family trees in:
+//!
+//! ```text
+//! 0 @I1@ INDI
+//! 1 NAME Ada /Lovelace/
+//! 1 BIRT
+//! 2 DATE 10 DEC 1815
+//! 2 PLAC London, England
+//! 0 @F1@ FAM
+//! 1 HUSB @I2@
+//! 1 WIFE @I1@
+//! 1 MARR
+//! 2 DATE 8 JUL 1835
+//! ```
+//!
+//! Each birth, death or marriage with a date becomes an event, e.g. "[birth] Ada Lovelace
+//! born @(London, England)". Approximate dates and ranges, like `ABT 1850` or
+//! `BET 1850 AND 1860`, are dated by their first date, with the date as written added to the
+//! description. The people in each event are stored in its `people` field, so that
+//! `wl show --person "Ada Lovelace"` shows a person's life, and events can also be tagged
+//! with them, e.g. `ada-lovelace`.
+
+use crate::geo::Place;
+use crate::{Date, Event};
+use std::collections::HashMap;
+
+/// The events read from a GEDCOM file by [`parse`].
+#[derive(Debug, Default)]
+pub struct GedcomImport {
+    pub events: Vec<Event>,
+    /// Events that couldn't be dated, with the (1-based) line numbers where they start and
+    /// why.
+    pub skipped: Vec<(usize, String)>,
+}
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+/// The words that can start a date that isn't exact, and how they read in a description.
+const QUALIFIERS: [(&str, &str); 9] = [
+    ("ABT", "about"),
+    ("CAL", "calculated"),
+    ("EST", "estimated"),
+    ("BEF", "before"),
+    ("AFT", "after"),
+    ("BET", "between"),
+    ("FROM", "from"),
+    ("TO", "to"),
+    ("INT", "interpreted"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Birth,
+    Death,
+    Marriage,
+}
+
+impl Kind {
+    fn category(self) -> &'static str {
+        match self {
+            Kind::Birth => "birth",
+            Kind::Death => "death",
+            Kind::Marriage => "marriage",
+        }
+    }
+}
+
+/// An event as found in a record, before the names of the people in it are known.
+struct Found {
+    kind: Kind,
+    /// The cross-reference of the individual or family record, e.g. `@I1@`.
+    record: String,
+    date: Option<String>,
+    place: Option<String>,
+    line: usize,
+}
+
+/// A line of a GEDCOM file: `LEVEL [@XREF@] TAG [VALUE]`.
+struct Line<'a> {
+    level: u32,
+    xref: Option<&'a str>,
+    tag: &'a str,
+    value: &'a str,
+}
+
+fn parse_line(line: &str) -> Option<Line<'_>> {
+    let line = line.trim_start_matches('\u{feff}').trim();
+    let (level, rest) = line.split_once(' ')?;
+    let (xref, rest) = if rest.starts_with('@') {
+        let (xref, rest) = rest.split_once(' ')?;
+        (Some(xref), rest)
+    } else {
+        (None, rest)
+    };
+    let (tag, value) = rest.split_once(' ').unwrap_or((rest, ""));
+    Some(Line {
+        level: level.parse().ok()?,
+        xref,
+        tag,
+        value: value.trim(),
+    })
+}
+
+/// A name as written in GEDCOM, with the surname between slashes, e.g. "Ada /Lovelace/".
+fn person_name(name: &str) -> String {
+    name.replace('/', " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The tag for a person, e.g. "ada-lovelace".
+fn person_tag(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Read a GEDCOM date, e.g. "10 DEC 1815", "MAR 1850", "ABT 1850", "BET 1850 AND 1860" or
+/// "44 B.C.", as its first date, and whether it's exact. Dual years like "1750/51" are read
+/// as the first year.
+fn parse_date(s: &str) -> Result<(Date, bool), String> {
+    let invalid = || format!("invalid date '{}'", s);
+    let mut words: Vec<&str> = s.split_whitespace().collect();
+    if let Some(calendar) = words.first().filter(|word| word.starts_with("@#")) {
+        if !["@#DGREGORIAN@", "@#DJULIAN@"].contains(calendar) {
+            return Err(format!("unsupported calendar {}", calendar));
+        }
+        words.remove(0);
+    }
+    let exact = !words
+        .first()
+        .is_some_and(|word| QUALIFIERS.iter().any(|(q, _)| q == word));
+    if !exact {
+        words.remove(0);
+    }
+    // the end of a range, or the phrase after an interpreted date
+    if let Some(end) = words
+        .iter()
+        .position(|word| ["AND", "TO"].contains(word) || word.starts_with('('))
+    {
+        words.truncate(end);
+    }
+    let bce = words
+        .last()
+        .is_some_and(|word| ["B.C.", "BC", "BCE"].contains(word));
+    if bce {
+        words.pop();
+    }
+    let month = |month: &str| {
+        MONTHS
+            .iter()
+            .position(|m| *m == month)
+            .map(|i| i as u8 + 1)
+            .ok_or_else(invalid)
+    };
+    let (day, month, year) = match words.as_slice() {
+        [year] => (0, 0, *year),
+        [m, year] => (0, month(m)?, *year),
+        [day, m, year] => (day.parse().map_err(|_| invalid())?, month(m)?, *year),
+        _ => return Err(invalid()),
+    };
+    let year: i32 = year
+        .split('/')
+        .next()
+        .and_then(|year| year.parse().ok())
+        .ok_or_else(invalid)?;
+    let date = Date::new(if bce { -year } else { year }, month, day)?;
+    Ok((date, exact))
+}
+
+/// A date as written in GEDCOM, in words, e.g. "about Mar 1850".
+fn written_date(s: &str) -> String {
+    s.split_whitespace()
+        .filter(|word| !word.starts_with("@#"))
+        .map(|word| {
+            if let Some((_, words)) = QUALIFIERS.iter().find(|(q, _)| *q == word) {
+                words.to_string()
+            } else if word == "AND" {
+                "and".to_string()
+            } else if MONTHS.contains(&word) {
+                word[..1].to_string() + &word[1..].to_lowercase()
+            } else {
+                word.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Read the births, deaths and marriages in a GEDCOM file, tagging each event with the
+/// people in it if `tag_people` is set.
+pub fn parse(contents: &str, tag_people: bool) -> GedcomImport {
+    let mut names: HashMap<String, String> = HashMap::new();
+    let mut spouses: HashMap<String, Vec<String>> = HashMap::new();
+    let mut found: Vec<Found> = Vec::new();
+    // the cross-reference and type of the record being read, and whether its last level 1
+    // line started an event
+    let mut record: Option<(String, String)> = None;
+    let mut in_event = false;
+    for (i, line) in contents.lines().enumerate() {
+        let Some(line) = parse_line(line) else {
+            continue;
+        };
+        match line.level {
+            0 => {
+                record = line
+                    .xref
+                    .map(|xref| (xref.to_string(), line.tag.to_string()));
+                in_event = false;
+            }
+            1 => {
+                let Some((xref, record_type)) = &record else {
+                    continue;
+                };
+                let kind = match (record_type.as_str(), line.tag) {
+                    ("INDI", "NAME") => {
+                        let name = person_name(line.value);
+                        if !name.is_empty() {
+                            names.entry(xref.clone()).or_insert(name);
+                        }
+                        None
+                    }
+                    ("FAM", "HUSB" | "WIFE") => {
+                        let spouse = line.value.to_string();
+                        spouses.entry(xref.clone()).or_default().push(spouse);
+                        None
+                    }
+                    ("INDI", "BIRT") => Some(Kind::Birth),
+                    ("INDI", "DEAT") => Some(Kind::Death),
+                    ("FAM", "MARR") => Some(Kind::Marriage),
+                    _ => None,
+                };
+                in_event = kind.is_some();
+                found.extend(kind.map(|kind| Found {
+                    kind,
+                    record: xref.clone(),
+                    date: None,
+                    place: None,
+                    line: i + 1,
+                }));
+            }
+            2 if in_event => {
+                let event = found.last_mut().unwrap();
+                match line.tag {
+                    "DATE" => event.date = Some(line.value.to_string()),
+                    "PLAC" => event.place = Some(line.value.to_string()),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut import = GedcomImport::default();
+    let name = |xref: &str| {
+        names
+            .get(xref)
+            .cloned()
+            .unwrap_or_else(|| xref.trim_matches('@').to_string())
+    };
+    for found in found {
+        let people: Vec<String> = match found.kind {
+            Kind::Marriage => spouses
+                .get(&found.record)
+                .map_or(Vec::new(), |xrefs| xrefs.iter().map(|x| name(x)).collect()),
+            _ => vec![name(&found.record)],
+        };
+        let mut description = match (found.kind, people.as_slice()) {
+            (Kind::Birth, [person]) => format!("{} born", person),
+            (Kind::Death, [person]) => format!("{} dies", person),
+            (Kind::Marriage, [a, b, ..]) => format!("{} and {} marry", a, b),
+            (Kind::Marriage, [person]) => format!("{} marries", person),
+            _ => "Marriage".to_string(),
+        };
+        let Some(written) = found.date else {
+            import
+                .skipped
+                .push((found.line, format!("{}: no date", description)));
+            continue;
+        };
+        let date = match parse_date(&written) {
+            Ok((date, exact)) => {
+                if !exact {
+                    description += &format!(" ({})", written_date(&written));
+                }
+                date
+            }
+            Err(e) => {
+                import
+                    .skipped
+                    .push((found.line, format!("{}: {}", description, e)));
+                continue;
+            }
+        };
+        let mut event = Event::new(date, description);
+        event.category = Some(found.kind.category().to_string());
+        event.place = found
+            .place
+            .filter(|place| !place.is_empty())
+            .map(Place::Named);
+        if tag_people {
+            event.tags = people.iter().map(|person| person_tag(person)).collect();
+        }
+        event.people = people;
+        import.events.push(event);
+    }
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gedcom_import() {
+        let import = parse(
+            "\u{feff}0 HEAD\n\
+             1 GEDC\n\
+             2 VERS 5.5.1\n\
+             0 @I1@ INDI\n\
+             1 NAME Ada /Lovelace/\n\
+             1 BIRT\n\
+             2 DATE 10 DEC 1815\n\
+             2 PLAC London, England\n\
+             1 DEAT\n\
+             2 DATE 27 NOV 1852\n\
+             0 @I2@ INDI\n\
+             1 NAME William /King/\n\
+             1 BIRT\n\
+             2 DATE ABT 1805\n\
+             1 DEAT\n\
+             2 PLAC London\n\
+             0 @I3@ INDI\n\
+             1 BIRT\n\
+             2 DATE @#DHEBREW@ 1 TSH 5600\n\
+             0 @F1@ FAM\n\
+             1 MARR\n\
+             2 DATE BET 8 JUL 1835 AND 9 JUL 1835\n\
+             1 HUSB @I2@\n\
+             1 WIFE @I1@\n\
+             0 TRLR\n",
+            true,
+        );
+        let lines: Vec<String> = import
+            .events
+            .iter()
+            .map(crate::format::format_event_v2)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                " CE 1815-12-10 [birth] Ada Lovelace born @(London, England)\ttags=ada-lovelace\tpeople=Ada Lovelace",
+                " CE 1852-11-27 [death] Ada Lovelace dies\ttags=ada-lovelace\tpeople=Ada Lovelace",
+                " CE 1805       [birth] William King born (about 1805)\ttags=william-king\tpeople=William King",
+                " CE 1835-07-08 [marriage] William King and Ada Lovelace marry \
+                 (between 8 Jul 1835 and 9 Jul 1835)\ttags=william-king,ada-lovelace\t\
+                 people=William King,Ada Lovelace",
+            ]
+        );
+        assert_eq!(
+            import.skipped,
+            [
+                (15, "William King dies: no date".to_string()),
+                (18, "I3 born: unsupported calendar @#DHEBREW@".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            parse_date("44 B.C."),
+            Ok((Date::new(-44, 0, 0).unwrap(), true))
+        );
+        assert_eq!(
+            parse_date("INT 1750/51 (Candlemas)"),
+            Ok((Date::new(1750, 0, 0).unwrap(), false))
+        );
+        assert!(parse_date("(Around the war)").is_err());
+        let untagged = parse("0 @I1@ INDI\n1 BIRT\n2 DATE 1900\n", false);
+        assert!(untagged.events[0].tags.is_empty());
+    }
+}
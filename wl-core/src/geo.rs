@@ -0,0 +1,275 @@
+//! Where events happened, and exporting them to GeoJSON to plot on maps.
+//!
+//! A place is written at the end of a description, after an `@`: a name like `@Rome` (or
+//! `@(New York)` if it has spaces), or coordinates in degrees like `@(41.9,12.5)` (latitude,
+//! longitude). Named places can be given coordinates in the config; see
+//! [`crate::config::Config::places`].
+
+use crate::json::Value;
+use crate::{Event, WorldLine};
+use std::collections::BTreeMap;
+
+/// A latitude and longitude in degrees. Never NaN, so it can be compared and ordered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coordinates {
+    latitude: f64,
+    longitude: f64,
+}
+
+impl Coordinates {
+    pub fn new(latitude: f64, longitude: f64) -> Result<Self, String> {
+        if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+            return Err(format!(
+                "Invalid coordinates {},{}: latitude must be within ±90 and longitude within ±180",
+                latitude, longitude
+            ));
+        }
+        Ok(Self {
+            latitude,
+            longitude,
+        })
+    }
+
+    /// Parse a latitude and longitude separated by a comma, e.g. "41.9,12.5".
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid coordinates '{}', expected e.g. 41.9,12.5", s);
+        let (latitude, longitude) = s.split_once(',').ok_or_else(invalid)?;
+        let latitude = latitude.trim().parse().map_err(|_| invalid())?;
+        let longitude = longitude.trim().parse().map_err(|_| invalid())?;
+        Self::new(latitude, longitude)
+    }
+
+    pub fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    pub fn longitude(&self) -> f64 {
+        self.longitude
+    }
+}
+
+impl Eq for Coordinates {}
+
+impl PartialOrd for Coordinates {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Coordinates {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.latitude
+            .total_cmp(&other.latitude)
+            .then(self.longitude.total_cmp(&other.longitude))
+    }
+}
+
+impl std::fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.latitude, self.longitude)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(untagged)
+)]
+pub enum Place {
+    Named(String),
+    Coordinates(Coordinates),
+}
+
+impl Place {
+    /// Parse a place as written after the `@`: "Rome", "(New York)" or "(41.9,12.5)".
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid place '{}'", s);
+        if let Some(inner) = s.strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            if inner
+                .chars()
+                .all(|c| c.is_ascii_digit() || "+-., ".contains(c))
+            {
+                return Coordinates::parse(inner).map(Place::Coordinates);
+            }
+            if inner.trim().is_empty() || inner.contains(['(', ')']) {
+                return Err(invalid());
+            }
+            return Ok(Place::Named(inner.trim().to_string()));
+        }
+        if s.is_empty() || s.contains(char::is_whitespace) || s.contains(['(', ')']) {
+            return Err(invalid());
+        }
+        Ok(Place::Named(s.to_string()))
+    }
+
+    /// The coordinates of the place, looking names up (case-insensitively) in `places`.
+    pub fn coordinates(&self, places: &BTreeMap<String, Coordinates>) -> Option<Coordinates> {
+        match self {
+            Place::Coordinates(coordinates) => Some(*coordinates),
+            Place::Named(name) => places
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, c)| *c),
+        }
+    }
+}
+
+impl std::fmt::Display for Place {
+    /// The place as written after the `@`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Place::Named(name) if name.contains(char::is_whitespace) => write!(f, "({})", name),
+            Place::Named(name) => write!(f, "{}", name),
+            Place::Coordinates(coordinates) => write!(f, "({})", coordinates),
+        }
+    }
+}
+
+/// Split a place from the end of a description, e.g. "Battle of Hastings @Hastings". Text
+/// after an `@` that isn't a valid place, e.g. out of range coordinates, stays in the
+/// description.
+pub(crate) fn split_place(description: &str) -> (&str, Option<Place>) {
+    let start = if description.ends_with(')') {
+        description.rfind("@(")
+    } else {
+        description.rfind('@')
+    };
+    let Some(start) = start else {
+        return (description, None);
+    };
+    let (rest, place) = description.split_at(start);
+    if !(rest.is_empty() || rest.ends_with(' ')) {
+        return (description, None);
+    }
+    match Place::parse(&place[1..]) {
+        Ok(place) => (rest.trim_end(), Some(place)),
+        Err(_) => (description, None),
+    }
+}
+
+fn event_feature(event: &Event, places: &BTreeMap<String, Coordinates>) -> Option<Value> {
+    let place = event.place.as_ref()?;
+    let geometry = match place.coordinates(places) {
+        Some(coordinates) => Value::Object(vec![
+            ("type".to_string(), Value::String("Point".to_string())),
+            (
+                "coordinates".to_string(),
+                Value::Array(vec![
+                    Value::Number(coordinates.longitude),
+                    Value::Number(coordinates.latitude),
+                ]),
+            ),
+        ]),
+        None => Value::Null,
+    };
+    let mut properties = vec![
+        ("date".to_string(), Value::String(event.date.to_string())),
+        (
+            "description".to_string(),
+            Value::String(event.description.clone()),
+        ),
+    ];
+    if let Place::Named(name) = place {
+        properties.push(("place".to_string(), Value::String(name.clone())));
+    }
+    if let Some(end) = &event.end {
+        properties.push(("end".to_string(), Value::String(end.to_string())));
+    }
+    if let Some(category) = &event.category {
+        properties.push(("category".to_string(), Value::String(category.clone())));
+    }
+    if let Some(id) = &event.id {
+        properties.push(("id".to_string(), Value::String(id.clone())));
+    }
+    Some(Value::Object(vec![
+        ("type".to_string(), Value::String("Feature".to_string())),
+        ("geometry".to_string(), geometry),
+        ("properties".to_string(), Value::Object(properties)),
+    ]))
+}
+
+impl WorldLine {
+    /// A GeoJSON FeatureCollection of the events with places, each a Point feature with the
+    /// event's date and description as properties. Named places are located with `places`;
+    /// the features of names that aren't in it have no geometry.
+    pub fn to_geojson(&self, places: &BTreeMap<String, Coordinates>) -> String {
+        let features = self
+            .events
+            .iter()
+            .filter_map(|e| event_feature(e, places))
+            .collect();
+        let collection = Value::Object(vec![
+            (
+                "type".to_string(),
+                Value::String("FeatureCollection".to_string()),
+            ),
+            ("features".to_string(), Value::Array(features)),
+        ]);
+        format!("{}\n", collection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_places() {
+        let event = Event::parse("1066-10-14 Battle of Hastings @Hastings").unwrap();
+        assert_eq!(event.description, "Battle of Hastings");
+        assert_eq!(event.place, Some(Place::Named("Hastings".to_string())));
+        assert_eq!(
+            event.format_for_file(),
+            " CE 1066-10-14 Battle of Hastings @Hastings"
+        );
+        let event = Event::parse("BCE 0044-03-15 [politics] Ides of March @(41.9,12.5)").unwrap();
+        assert_eq!(event.category.as_deref(), Some("politics"));
+        assert_eq!(
+            event.place,
+            Some(Place::Coordinates(Coordinates::new(41.9, 12.5).unwrap()))
+        );
+        let event = Event::parse("1776-07-04 Declaration @(Philadelphia, PA)").unwrap();
+        assert_eq!(
+            event.format_for_file(),
+            " CE 1776-07-04 Declaration @(Philadelphia, PA)"
+        );
+
+        // not places
+        for text in [
+            "mail me@home",
+            "Out of range @(91,0)",
+            "A place with spaces @New York",
+        ] {
+            let event = Event::from_text(crate::Date::new(2000, 0, 0).unwrap(), text);
+            assert_eq!((event.description.as_str(), event.place), (text, None));
+        }
+    }
+
+    #[test]
+    fn test_geojson() {
+        let worldline: WorldLine = "BCE 0044-03-15 Ides of March @(41.9,12.5)\n\
+                                    1066-10-14 Battle of Hastings @Hastings\n\
+                                    1215 Magna Carta @Runnymede\n\
+                                    1492 No place\n"
+            .parse()
+            .unwrap();
+        let places = BTreeMap::from([(
+            "hastings".to_string(),
+            Coordinates::new(50.91, 0.49).unwrap(),
+        )]);
+        let geojson = Value::parse(&worldline.to_geojson(&places)).unwrap();
+        let features = geojson.get("features").unwrap().as_array().unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(
+            features[0].get("geometry").unwrap().to_string(),
+            r#"{"type":"Point","coordinates":[12.5,41.9]}"#
+        );
+        assert_eq!(
+            features[1].get("properties").unwrap().to_string(),
+            r#"{"date":"1066-10-14","description":"Battle of Hastings","place":"Hastings"}"#
+        );
+        assert_eq!(features[2].get("geometry"), Some(&Value::Null));
+    }
+}
@@ -0,0 +1,174 @@
+//! Grouping listed events under headers by century, decade or year, e.g.
+//! "── 1st century BCE ──", instead of showing a flat list, or summarizing each group in a line.
+
+use crate::display::{truncate, DisplayOptions, DATE_STYLE};
+use crate::Event;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Century,
+    Decade,
+    Year,
+}
+
+impl GroupBy {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "century" => Ok(GroupBy::Century),
+            "decade" => Ok(GroupBy::Decade),
+            "year" => Ok(GroupBy::Year),
+            _ => Err(format!(
+                "Invalid grouping '{}', expected century, decade, or year",
+                s
+            )),
+        }
+    }
+
+    /// The name of the group a year (CE if positive, BCE if negative) is in, e.g. "20th
+    /// century", "1910s", "40s BCE" or "44 BCE".
+    pub fn label(&self, year: i32) -> String {
+        let era = if year < 0 { " BCE" } else { "" };
+        let abs = year.unsigned_abs();
+        match self {
+            // the 1st century is years 1 to 100
+            GroupBy::Century => format!("{} century{}", ordinal(abs.div_ceil(100)), era),
+            GroupBy::Decade => format!("{}s{}", abs / 10 * 10, era),
+            GroupBy::Year => format!("{}{}", abs, era),
+        }
+    }
+
+    /// A separator line for a group.
+    pub fn header(&self, year: i32) -> String {
+        format!("── {} ──", self.label(year))
+    }
+}
+
+/// A number with its English ordinal suffix, e.g. 1st, 12th, 21st.
+fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+/// Insert a header before the first line of each group, given the lines events are shown as
+/// (one per event).
+pub fn with_headers(events: &[Event], lines: Vec<String>, group_by: GroupBy) -> Vec<String> {
+    let mut grouped = Vec::with_capacity(lines.len());
+    let mut current = None;
+    for (event, line) in events.iter().zip(lines) {
+        let label = group_by.label(event.date.year);
+        if current.as_ref() != Some(&label) {
+            if current.is_some() {
+                grouped.push(String::new());
+            }
+            grouped.push(group_by.header(event.date.year));
+            current = Some(label);
+        }
+        grouped.push(line);
+    }
+    grouped
+}
+
+/// One line per group, with its number of events and the first `top` of them, e.g.
+/// "20th century  12 events  WWI begins; WWII begins; …", as an overview of a long range.
+pub fn summarize(
+    events: &[Event],
+    group_by: GroupBy,
+    top: usize,
+    options: &DisplayOptions,
+) -> Vec<String> {
+    let groups: Vec<(String, &[Event])> = events
+        .chunk_by(|a, b| group_by.label(a.date.year) == group_by.label(b.date.year))
+        .map(|group| (group_by.label(group[0].date.year), group))
+        .collect();
+    let label_width = groups
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let count = |n: usize| format!("{} event{}", n, if n == 1 { "" } else { "s" });
+    let count_width = groups
+        .iter()
+        .map(|(_, group)| count(group.len()).len())
+        .max()
+        .unwrap_or(0);
+    groups
+        .iter()
+        .map(|(label, group)| {
+            let mut descriptions: Vec<&str> = group
+                .iter()
+                .take(top)
+                .map(|e| e.description.as_str())
+                .collect();
+            if group.len() > top {
+                descriptions.push("…");
+            }
+            let mut text = descriptions.join("; ");
+            if let Some(width) = options.width {
+                text = truncate(&text, width.saturating_sub(label_width + count_width + 4));
+            }
+            format!(
+                "{}  {:>count_width$}  {}",
+                options.paint(DATE_STYLE, &format!("{:label_width$}", label)),
+                count(group.len()),
+                text
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_labels() {
+        assert_eq!(GroupBy::Century.label(1914), "20th century");
+        assert_eq!(GroupBy::Century.label(1900), "19th century");
+        assert_eq!(GroupBy::Century.label(2001), "21st century");
+        assert_eq!(GroupBy::Century.label(-44), "1st century BCE");
+        assert_eq!(GroupBy::Century.label(-1112), "12th century BCE");
+        assert_eq!(GroupBy::Decade.label(1914), "1910s");
+        assert_eq!(GroupBy::Decade.label(-44), "40s BCE");
+        assert_eq!(GroupBy::Year.label(-44), "44 BCE");
+        assert_eq!(GroupBy::Decade.header(1969), "── 1960s ──");
+        assert!(GroupBy::parse("millennium").is_err());
+
+        let events: Vec<Event> = ["BCE 44 Ides of March", "1914 WWI", "1939 WWII"]
+            .iter()
+            .map(|line| Event::parse(line).unwrap())
+            .collect();
+        let lines = events.iter().map(|e| e.description.clone()).collect();
+        assert_eq!(
+            with_headers(&events, lines, GroupBy::Century),
+            vec![
+                "── 1st century BCE ──",
+                "Ides of March",
+                "",
+                "── 20th century ──",
+                "WWI",
+                "WWII",
+            ]
+        );
+
+        let lines: Vec<String> =
+            summarize(&events, GroupBy::Century, 1, &DisplayOptions::default())
+                .iter()
+                .map(|line| line.replace("\u{001B}[34m", "").replace("\u{001B}[0m", ""))
+                .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "1st century BCE   1 event  Ides of March",
+                "20th century     2 events  WWI; …",
+            ]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+//! Major holidays, fixed and computed, behind the `holidays` feature.
+//!
+//! Holidays are computed in the proleptic Gregorian calendar like every other date, so Easter
+//! before 1583 won't match the Julian date it was celebrated on at the time.
+
+use crate::query::parse_whole_date;
+use crate::weekday::Weekday;
+use crate::Date;
+
+enum Rule {
+    /// Month and day
+    Fixed(u8, u8),
+    /// Days after Easter Sunday
+    Easter(i64),
+    /// The nth weekday of a month, e.g. the 4th Thursday of November
+    NthWeekday(u8, Weekday, u8),
+}
+
+pub struct Holiday {
+    pub name: &'static str,
+    /// Other names it can be referred to by, in lowercase
+    aliases: &'static [&'static str],
+    rule: Rule,
+}
+
+pub const HOLIDAYS: &[Holiday] = &[
+    Holiday {
+        name: "New Year's Day",
+        aliases: &["new year", "new years"],
+        rule: Rule::Fixed(1, 1),
+    },
+    Holiday {
+        name: "Epiphany",
+        aliases: &[],
+        rule: Rule::Fixed(1, 6),
+    },
+    Holiday {
+        name: "Valentine's Day",
+        aliases: &["valentines"],
+        rule: Rule::Fixed(2, 14),
+    },
+    Holiday {
+        name: "Ash Wednesday",
+        aliases: &[],
+        rule: Rule::Easter(-46),
+    },
+    Holiday {
+        name: "Good Friday",
+        aliases: &[],
+        rule: Rule::Easter(-2),
+    },
+    Holiday {
+        name: "Easter Sunday",
+        aliases: &["easter"],
+        rule: Rule::Easter(0),
+    },
+    Holiday {
+        name: "Ascension Day",
+        aliases: &["ascension"],
+        rule: Rule::Easter(39),
+    },
+    Holiday {
+        name: "Pentecost",
+        aliases: &["whitsun"],
+        rule: Rule::Easter(49),
+    },
+    Holiday {
+        name: "May Day",
+        aliases: &[],
+        rule: Rule::Fixed(5, 1),
+    },
+    Holiday {
+        name: "Halloween",
+        aliases: &[],
+        rule: Rule::Fixed(10, 31),
+    },
+    Holiday {
+        name: "All Saints' Day",
+        aliases: &["all saints"],
+        rule: Rule::Fixed(11, 1),
+    },
+    Holiday {
+        name: "Thanksgiving (US)",
+        aliases: &["thanksgiving"],
+        rule: Rule::NthWeekday(11, Weekday::Thursday, 4),
+    },
+    Holiday {
+        name: "Christmas Eve",
+        aliases: &[],
+        rule: Rule::Fixed(12, 24),
+    },
+    Holiday {
+        name: "Christmas Day",
+        aliases: &["christmas", "xmas"],
+        rule: Rule::Fixed(12, 25),
+    },
+    Holiday {
+        name: "Boxing Day",
+        aliases: &[],
+        rule: Rule::Fixed(12, 26),
+    },
+    Holiday {
+        name: "New Year's Eve",
+        aliases: &[],
+        rule: Rule::Fixed(12, 31),
+    },
+];
+
+impl Holiday {
+    /// The date of the holiday in a year.
+    pub fn date(&self, year: i32) -> Date {
+        match self.rule {
+            Rule::Fixed(month, day) => Date::new(year, month, day).unwrap(),
+            Rule::Easter(offset) => Date::from_day_number(easter(year).day_number() + offset),
+            Rule::NthWeekday(month, weekday, n) => {
+                let first = Date::new(year, month, 1).unwrap();
+                let first_weekday = first.weekday().unwrap() as i64;
+                let offset = (weekday as i64 - first_weekday).rem_euclid(7) + 7 * (n as i64 - 1);
+                Date::from_day_number(first.day_number() + offset)
+            }
+        }
+    }
+
+    fn is_called(&self, name: &str) -> bool {
+        let name = name.trim().to_lowercase();
+        self.name.to_lowercase() == name || self.aliases.contains(&name.as_str())
+    }
+}
+
+/// Easter Sunday in a year, by the anonymous Gregorian computus.
+pub fn easter(year: i32) -> Date {
+    let y = Date::astronomical_year(year) as i64;
+    let a = y.rem_euclid(19);
+    let (b, c) = (y.div_euclid(100), y.rem_euclid(100));
+    let (d, e) = (b.div_euclid(4), b.rem_euclid(4));
+    let f = (b + 8).div_euclid(25);
+    let g = (b - f + 1).div_euclid(3);
+    let h = (19 * a + b - d - g + 15).rem_euclid(30);
+    let (i, k) = (c / 4, c % 4);
+    let l = (32 + 2 * e + 2 * i - h - k).rem_euclid(7);
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    Date::new(year, month as u8, day as u8).unwrap()
+}
+
+/// The holidays falling on a day precision date.
+pub fn on(date: &Date) -> Vec<&'static str> {
+    if !date.is_day_precision() {
+        return Vec::new();
+    }
+    HOLIDAYS
+        .iter()
+        .filter(|h| h.date(date.year) == *date)
+        .map(|h| h.name)
+        .collect()
+}
+
+/// Parse a symbolic date like "easter 1525" or "Christmas Day 1914".
+pub fn parse_symbolic(s: &str) -> Result<Date, String> {
+    let invalid = || {
+        format!(
+            "Invalid date: {}, expected e.g. 1525-04-16 or easter 1525",
+            s
+        )
+    };
+    let (name, year) = s.trim().rsplit_once(' ').ok_or_else(invalid)?;
+    // allow an era, e.g. "christmas 800 CE"
+    let (name, year) = match year.to_uppercase().as_str() {
+        "BCE" | "BC" | "CE" | "AD" => {
+            let (name, number) = name.rsplit_once(' ').ok_or_else(invalid)?;
+            (name, format!("{} {}", year, number))
+        }
+        _ => (name, year.to_string()),
+    };
+    let year = parse_whole_date(&year).map_err(|_| invalid())?;
+    if year.month != 0 {
+        return Err(invalid());
+    }
+    let holiday = HOLIDAYS
+        .iter()
+        .find(|h| h.is_called(name))
+        .ok_or_else(|| format!("Unknown holiday: {}", name))?;
+    Ok(holiday.date(year.year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_holidays() {
+        for (year, month, day) in [(1525, 4, 19), (2000, 4, 23), (2024, 3, 31), (2038, 4, 25)] {
+            assert_eq!(easter(year), Date::new(year, month, day).unwrap());
+        }
+        assert_eq!(
+            parse_symbolic("easter 1525"),
+            Ok(Date::new(1525, 4, 19).unwrap())
+        );
+        assert_eq!(
+            parse_symbolic("Good Friday 2024"),
+            Ok(Date::new(2024, 3, 29).unwrap())
+        );
+        assert_eq!(
+            parse_symbolic("thanksgiving 2023"),
+            Ok(Date::new(2023, 11, 23).unwrap())
+        );
+        assert_eq!(
+            parse_symbolic("christmas 800 CE"),
+            Ok(Date::new(800, 12, 25).unwrap())
+        );
+        assert!(parse_symbolic("festivus 1997").is_err());
+        assert!(parse_symbolic("easter").is_err());
+
+        assert_eq!(on(&Date::new(1914, 12, 25).unwrap()), vec!["Christmas Day"]);
+        assert!(on(&Date::new(1914, 12, 0).unwrap()).is_empty());
+    }
+}
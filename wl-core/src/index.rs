@@ -0,0 +1,276 @@
+//! An inverted index of the words in event descriptions, so text queries on large worldlines
+//! don't have to scan every description, and misspelled queries can be corrected.
+//!
+//! With the `std-fs` feature, the index of `history.txt` is cached in `history.txt.idx`, and
+//! rebuilt whenever the worldline file's modification time or size changes.
+
+use crate::similar::levenshtein;
+use crate::{Event, WorldLine};
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "std-fs")]
+use std::fs;
+#[cfg(feature = "std-fs")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "std-fs")]
+const CACHE_HEADER: &str = "#worldline-index v1";
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TextIndex {
+    /// lowercase word -> indices of the events whose descriptions contain it
+    words: BTreeMap<String, Vec<usize>>,
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+}
+
+impl TextIndex {
+    pub fn build(worldline: &WorldLine) -> Self {
+        let mut index = Self::default();
+        for (i, event) in worldline.events.iter().enumerate() {
+            let description = event.description.to_lowercase();
+            let unique: BTreeSet<&str> = words(&description).collect();
+            for word in unique {
+                index.words.entry(word.to_string()).or_default().push(i);
+            }
+        }
+        index
+    }
+
+    /// The indices of events that might contain the query, a superset of the ones that do, or
+    /// None if the query has no words to look up. A query word can be part of a longer word,
+    /// so each is looked for in every indexed word, which is still far fewer than the events.
+    fn candidates(&self, query: &str) -> Option<BTreeSet<usize>> {
+        let mut candidates: Option<BTreeSet<usize>> = None;
+        for query_word in words(query) {
+            let matches: BTreeSet<usize> = self
+                .words
+                .iter()
+                .filter(|(word, _)| word.contains(query_word))
+                .flat_map(|(_, indices)| indices.iter().copied())
+                .collect();
+            candidates = Some(match candidates {
+                Some(candidates) => &candidates & &matches,
+                None => matches,
+            });
+        }
+        candidates
+    }
+
+    /// The query with each word that isn't in any description replaced by the closest word
+    /// that is, e.g. "caesar crosses" for "ceasar crosses", to suggest when it matches nothing.
+    /// Words are close if they're one edit apart, or two for words of more than four letters;
+    /// of those the fewest edits away win, then the most common. None if no word is replaced.
+    pub fn did_you_mean(&self, query: &str) -> Option<String> {
+        let query = query.to_lowercase();
+        let mut suggestion = String::new();
+        let mut replaced = false;
+        let mut rest = query.as_str();
+        while let Some(start) = rest.find(char::is_alphanumeric) {
+            suggestion += &rest[..start];
+            rest = &rest[start..];
+            let end = rest
+                .find(|c: char| !c.is_alphanumeric())
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            match self.correction(word) {
+                Some(correction) => {
+                    suggestion += correction;
+                    replaced = true;
+                }
+                None => suggestion += word,
+            }
+            rest = &rest[end..];
+        }
+        suggestion += rest;
+        replaced.then_some(suggestion)
+    }
+
+    /// The closest indexed word to a (lowercase) word that isn't part of any, if one is close.
+    fn correction(&self, query_word: &str) -> Option<&str> {
+        let chars: Vec<char> = query_word.chars().collect();
+        if chars.len() < 3 || self.words.keys().any(|word| word.contains(query_word)) {
+            return None;
+        }
+        let max_edits = if chars.len() > 4 { 2 } else { 1 };
+        self.words
+            .iter()
+            .map(|(word, events)| {
+                let edits = levenshtein(&chars, &word.chars().collect::<Vec<_>>());
+                (edits, usize::MAX - events.len(), word.as_str())
+            })
+            .filter(|(edits, _, _)| *edits <= max_edits)
+            .min()
+            .map(|(_, _, word)| word)
+    }
+
+    /// Where the index of a worldline file is cached.
+    #[cfg(feature = "std-fs")]
+    pub fn cache_path(worldline_path: impl AsRef<Path>) -> PathBuf {
+        let mut path = worldline_path.as_ref().as_os_str().to_owned();
+        path.push(".idx");
+        PathBuf::from(path)
+    }
+
+    /// The index of a worldline read from a file, from the cache if it's up to date. Otherwise
+    /// it's built and cached; failing to write the cache isn't an error.
+    #[cfg(feature = "std-fs")]
+    pub fn for_file(worldline_path: impl AsRef<Path>, worldline: &WorldLine) -> Self {
+        Self::load(worldline_path.as_ref(), worldline, true)
+    }
+
+    /// Like [`TextIndex::for_file`], but without writing the cache, for when nothing next to
+    /// the worldline should change, e.g. with `--read-only`.
+    #[cfg(feature = "std-fs")]
+    pub fn for_file_uncached(worldline_path: impl AsRef<Path>, worldline: &WorldLine) -> Self {
+        Self::load(worldline_path.as_ref(), worldline, false)
+    }
+
+    #[cfg(feature = "std-fs")]
+    fn load(worldline_path: &Path, worldline: &WorldLine, write_cache: bool) -> Self {
+        let cache_path = Self::cache_path(worldline_path);
+        let Some(stamp) = file_stamp(worldline_path) else {
+            return Self::build(worldline);
+        };
+        let cached = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|contents| Self::parse_cache(&contents, &stamp, worldline.len()));
+        if let Some(index) = cached {
+            return index;
+        }
+        let index = Self::build(worldline);
+        if write_cache {
+            fs::write(&cache_path, index.to_cache(&stamp, worldline.len())).ok();
+        }
+        index
+    }
+
+    #[cfg(feature = "std-fs")]
+    fn to_cache(&self, stamp: &str, events: usize) -> String {
+        let mut out = format!("{} {} {}\n", CACHE_HEADER, stamp, events);
+        for (word, indices) in &self.words {
+            let indices: Vec<String> = indices.iter().map(usize::to_string).collect();
+            out += &format!("{}\t{}\n", word, indices.join(","));
+        }
+        out
+    }
+
+    /// Read a cached index, if it was made for the same file contents.
+    #[cfg(feature = "std-fs")]
+    fn parse_cache(contents: &str, stamp: &str, events: usize) -> Option<Self> {
+        let mut lines = contents.lines();
+        let expected = format!("{} {} {}", CACHE_HEADER, stamp, events);
+        if lines.next()? != expected {
+            return None;
+        }
+        let mut index = Self::default();
+        for line in lines {
+            let (word, indices) = line.split_once('\t')?;
+            let indices = indices
+                .split(',')
+                .map(|i| i.parse().ok().filter(|i| *i < events))
+                .collect::<Option<Vec<usize>>>()?;
+            index.words.insert(word.to_string(), indices);
+        }
+        Some(index)
+    }
+}
+
+/// The modification time and size of a file, which change whenever it's written.
+#[cfg(feature = "std-fs")]
+fn file_stamp(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    Some(format!(
+        "{}.{:09} {}",
+        modified.as_secs(),
+        modified.subsec_nanos(),
+        metadata.len()
+    ))
+}
+
+impl WorldLine {
+    /// The same as [`WorldLine::query`], looking words up in an index of this worldline.
+    pub fn query_indexed(&self, index: &TextIndex, query: &str) -> Vec<&Event> {
+        let query = query.to_lowercase();
+        let Some(candidates) = index.candidates(&query) else {
+            return self.query(&query);
+        };
+        candidates
+            .into_iter()
+            .filter_map(|i| self.events.get(i))
+            .filter(|e| e.description.to_lowercase().contains(&query))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_index() {
+        let worldline: WorldLine = "1789-07-14 Storming of the Bastille\n\
+                                    1917-11-07 October Revolution\n\
+                                    1989-11-09 Berlin Wall falls, revolutions of 1989\n"
+            .parse()
+            .unwrap();
+        let index = TextIndex::build(&worldline);
+        for query in [
+            "revolution",
+            "REVOL",
+            "wall falls",
+            "of the",
+            "ls, rev",
+            ",",
+            "",
+            "xyz",
+        ] {
+            assert_eq!(
+                worldline.query_indexed(&index, query),
+                worldline.query(query),
+                "{}",
+                query
+            );
+        }
+        assert_eq!(worldline.query_indexed(&index, "revolution").len(), 2);
+        assert_eq!(
+            index.did_you_mean("Berlni wall"),
+            Some("berlin wall".to_string())
+        );
+        assert_eq!(
+            index.did_you_mean("revolutoin of 1989"),
+            Some("revolution of 1989".to_string())
+        );
+        assert_eq!(index.did_you_mean("storming"), None);
+        assert_eq!(index.did_you_mean("xyzzy"), None);
+
+        #[cfg(feature = "std-fs")]
+        {
+            let dir = std::env::temp_dir();
+            let path = dir.join(format!("wl-index-{}.txt", std::process::id()));
+            fs::write(&path, worldline.to_string()).unwrap();
+            assert_eq!(TextIndex::for_file_uncached(&path, &worldline), index);
+            assert!(!TextIndex::cache_path(&path).exists());
+            let cached = TextIndex::for_file(&path, &worldline);
+            assert_eq!(cached, index);
+            assert!(TextIndex::cache_path(&path).exists());
+            assert_eq!(TextIndex::for_file(&path, &worldline), index);
+
+            // a changed file is reindexed
+            let mut changed = worldline;
+            changed.add_event(Event::parse("1848 Revolutions across Europe").unwrap());
+            fs::write(&path, changed.to_string()).unwrap();
+            let index = TextIndex::for_file(&path, &changed);
+            assert_eq!(changed.query_indexed(&index, "revolution").len(), 3);
+            fs::remove_file(TextIndex::cache_path(&path)).unwrap();
+            fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! The journal: a record of the changes wl makes to a v2 worldline file, for `wl history`.
+//!
+//! The journal of `history.txt` is `history.txt.journal`. Each line is an event added or
+//! removed by a change (a changed event is both): when, the number of the change, `+` or `-`,
+//! and the event as a v2 line, separated by tabs, e.g.
+//! `2025-03-01T14:30:00Z\t1\t+\t CE 1066       Hastings`.
+
+use crate::diff::{DiffReport, Side};
+use crate::format;
+use crate::timestamp::Timestamp;
+use crate::Event;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The journal of a worldline file. It's only created when a change is recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Journal {
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JournalEntry {
+    pub timestamp: Timestamp,
+    /// The change the entry is part of, counting from 1. Changes made within a second of each
+    /// other share a timestamp, but not a number.
+    pub change: u64,
+    /// [`Side::Left`] for an event removed, [`Side::Right`] for one added.
+    pub side: Side,
+    pub event: Event,
+}
+
+impl Journal {
+    /// The journal of a worldline file.
+    pub fn for_file(worldline_path: impl AsRef<Path>) -> Self {
+        let mut path = worldline_path.as_ref().as_os_str().to_owned();
+        path.push(".journal");
+        Self {
+            path: PathBuf::from(path),
+        }
+    }
+
+    /// Append the events removed and added by a change.
+    pub fn record(&self, changes: &DiffReport, timestamp: Timestamp) -> Result<(), String> {
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let change = self.last_change()? + 1;
+        let mut lines = String::new();
+        let removed = changes.removed().map(|e| (Side::Left, e));
+        for (side, event) in removed.chain(changes.added().map(|e| (Side::Right, e))) {
+            let sign = if side == Side::Left { "-" } else { "+" };
+            lines += &format!(
+                "{}\t{}\t{}\t{}\n",
+                timestamp,
+                change,
+                sign,
+                format::format_event_v2(event)
+            );
+        }
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| file.write_all(lines.as_bytes()))
+            .map_err(|e| format!("Could not write {}: {}", self.path.display(), e))
+    }
+
+    /// The contents of the journal, empty if it's missing.
+    fn contents(&self) -> Result<String, String> {
+        match fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(format!("Could not read {}: {}", self.path.display(), e)),
+        }
+    }
+
+    /// The number of the last change recorded, or 0 for none.
+    fn last_change(&self) -> Result<u64, String> {
+        let contents = self.contents()?;
+        let Some(line) = contents.lines().next_back() else {
+            return Ok(0);
+        };
+        line.split('\t')
+            .nth(1)
+            .and_then(|change| change.parse().ok())
+            .ok_or_else(|| format!("{}: Invalid journal entry", self.path.display()))
+    }
+
+    /// Every entry, oldest first. A missing journal has none.
+    pub fn entries(&self) -> Result<Vec<JournalEntry>, String> {
+        self.contents()?
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let invalid = |e: String| format!("{}:{}: {}", self.path.display(), i + 1, e);
+                let mut parts = line.splitn(4, '\t');
+                let (Some(timestamp), Some(change), Some(sign), Some(event)) =
+                    (parts.next(), parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(invalid("Invalid journal entry".to_string()));
+                };
+                let change = change
+                    .parse()
+                    .map_err(|_| invalid(format!("Invalid change number '{}'", change)))?;
+                let side = match sign {
+                    "-" => Side::Left,
+                    "+" => Side::Right,
+                    _ => return Err(invalid(format!("Invalid change '{}'", sign))),
+                };
+                Ok(JournalEntry {
+                    timestamp: Timestamp::parse(timestamp).map_err(invalid)?,
+                    change,
+                    side,
+                    event: format::parse_event_v2(event).map_err(invalid)?,
+                })
+            })
+            .collect()
+    }
+
+    /// The versions of an event, oldest first, as the entries adding them: when it was added
+    /// and each time it changed. They're found by following the event back through the
+    /// journal, from each version to the one it replaced, which has the same id or else the
+    /// same `created` time (and if several do, the same date). Changes made to the file by
+    /// hand break the trail.
+    pub fn history(&self, event: &Event) -> Result<Vec<JournalEntry>, String> {
+        let entries = self.entries()?;
+        let mut current = event.clone();
+        let mut history = Vec::new();
+        for change in entries.chunk_by(|a, b| a.change == b.change).rev() {
+            let Some(added) = change
+                .iter()
+                .find(|e| e.side == Side::Right && e.event == current)
+            else {
+                continue;
+            };
+            history.push(added.clone());
+            let same_event = |e: &Event| match (&current.id, current.created) {
+                (Some(id), _) => e.id.as_ref() == Some(id),
+                (None, Some(created)) => e.created == Some(created),
+                (None, None) => false,
+            };
+            // events added together share a created time, so prefer one on the same date
+            match change
+                .iter()
+                .filter(|e| e.side == Side::Left && same_event(&e.event))
+                .min_by_key(|e| e.event.date != current.date)
+            {
+                Some(previous) => current = previous.event.clone(),
+                None => break,
+            }
+        }
+        history.reverse();
+        Ok(history)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldLine;
+
+    #[test]
+    fn test_journal() {
+        let path = std::env::temp_dir().join(format!("wl-journal-{}.wl", std::process::id()));
+        let journal = Journal::for_file(&path);
+        let _ = fs::remove_file(&journal.path);
+        assert!(journal.entries().unwrap().is_empty());
+
+        let mut worldline: WorldLine = "#worldline v2\n".parse().unwrap();
+        let save = |worldline: &mut WorldLine, before: &WorldLine, time: &str| {
+            let now = Timestamp::parse(time).unwrap();
+            worldline.stamp_changes(before, now);
+            journal.record(&before.diff(worldline), now).unwrap();
+        };
+        let before = worldline.clone();
+        worldline.add_events_sorted(vec![
+            Event::parse("1066 Hastings").unwrap(),
+            Event::parse("1215 Magna Carta").unwrap(),
+        ]);
+        save(&mut worldline, &before, "2025-03-01T14:30:00Z");
+        let before = worldline.clone();
+        worldline.events[0].description = "Battle of Hastings".to_string();
+        save(&mut worldline, &before, "2025-03-02T09:00:00Z");
+        let before = worldline.clone();
+        worldline.events[0].description = "Battle of Hastings (Senlac Hill)".to_string();
+        worldline.events[1].description = "Magna Carta sealed".to_string();
+        save(&mut worldline, &before, "2025-03-03T10:00:00Z");
+
+        assert_eq!(journal.entries().unwrap().len(), 8);
+        let history = journal.history(&worldline.events()[0]).unwrap();
+        let descriptions: Vec<&str> = history
+            .iter()
+            .map(|entry| entry.event.description.as_str())
+            .collect();
+        assert_eq!(
+            descriptions,
+            [
+                "Hastings",
+                "Battle of Hastings",
+                "Battle of Hastings (Senlac Hill)"
+            ]
+        );
+        assert_eq!(history[0].timestamp.to_string(), "2025-03-01T14:30:00Z");
+        assert_eq!(journal.history(&worldline.events()[1]).unwrap().len(), 2);
+
+        // an event added and then changed within the same second
+        let before = worldline.clone();
+        worldline.add_event(Event::parse("1415 Agincourt").unwrap());
+        save(&mut worldline, &before, "2025-03-04T08:00:00Z");
+        let before = worldline.clone();
+        worldline.events[2].description = "Battle of Agincourt".to_string();
+        save(&mut worldline, &before, "2025-03-04T08:00:00Z");
+        let history = journal.history(&worldline.events()[2]).unwrap();
+        let changes: Vec<(u64, &str)> = history
+            .iter()
+            .map(|entry| (entry.change, entry.event.description.as_str()))
+            .collect();
+        assert_eq!(changes, [(4, "Agincourt"), (5, "Battle of Agincourt")]);
+
+        fs::remove_file(&journal.path).unwrap();
+    }
+}
@@ -0,0 +1,345 @@
+//! A minimal JSON reader and writer, enough for the exchange formats.
+
+use std::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Keys in the order they appeared.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn parse(s: &str) -> Result<Self, String> {
+        let mut parser = Parser { s, pos: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != s.len() {
+            return Err(parser.error("Trailing characters"));
+        }
+        Ok(value)
+    }
+
+    /// JSON indented by two spaces, one array item or object field per line.
+    pub(crate) fn pretty(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        out
+    }
+
+    fn write_pretty(&self, out: &mut String, depth: usize) {
+        let indent = |out: &mut String, depth: usize| out.push_str(&"  ".repeat(depth));
+        match self {
+            Value::Array(items) if !items.is_empty() => {
+                out.push_str("[\n");
+                for (i, item) in items.iter().enumerate() {
+                    indent(out, depth + 1);
+                    item.write_pretty(out, depth + 1);
+                    out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+                }
+                indent(out, depth);
+                out.push(']');
+            }
+            Value::Object(fields) if !fields.is_empty() => {
+                out.push_str("{\n");
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    indent(out, depth + 1);
+                    out.push_str(&quote(key));
+                    out.push_str(": ");
+                    value.write_pretty(out, depth + 1);
+                    out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                indent(out, depth);
+                out.push('}');
+            }
+            value => out.push_str(&value.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    /// Compact JSON.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write!(f, "{}", quote(s)),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", quote(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// A JSON string literal.
+pub fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                write!(out, "\\u{:04x}", c as u32).unwrap();
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at byte {}", message, self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.s[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), String> {
+        if self.s[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", literal)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Value::String),
+            Some('t') => self.expect("true").map(|_| Value::Bool(true)),
+            Some('f') => self.expect("false").map(|_| Value::Bool(false)),
+            Some('n') => self.expect("null").map(|_| Value::Null),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(":")?;
+            fields.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect("[")?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Value::Array(items));
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let rest = &self.s[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_ascii_digit() || "+-.eE".contains(c)))
+            .unwrap_or(rest.len());
+        let n = rest[..len]
+            .parse()
+            .map_err(|_| self.error("Invalid number"))?;
+        self.pos += len;
+        Ok(Value::Number(n))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect("\"")?;
+        let mut out = String::new();
+        let mut chars = self.s[self.pos..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(out);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let unpaired = || self.error("Unpaired surrogate in \\u escape");
+                            let unit =
+                                hex4(&mut chars).ok_or_else(|| self.error("Invalid \\u escape"))?;
+                            let code = match unit {
+                                // a character outside the BMP, as a UTF-16 surrogate pair
+                                0xD800..=0xDBFF => {
+                                    let low = match (chars.next(), chars.next()) {
+                                        (Some((_, '\\')), Some((_, 'u'))) => hex4(&mut chars),
+                                        _ => None,
+                                    };
+                                    let low = low
+                                        .filter(|low| (0xDC00..=0xDFFF).contains(low))
+                                        .ok_or_else(unpaired)?;
+                                    0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                                }
+                                0xDC00..=0xDFFF => return Err(unpaired()),
+                                unit => unit,
+                            };
+                            char::from_u32(code).ok_or_else(unpaired)?
+                        }
+                        Some(c @ ('"' | '\\' | '/')) => c,
+                        _ => return Err(self.error("Invalid escape")),
+                    };
+                    out.push(escaped);
+                }
+                c => out.push(c),
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+}
+
+/// The code unit of the four hex digits of a `\u` escape.
+fn hex4(chars: &mut std::str::CharIndices) -> Option<u32> {
+    let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+    if hex.len() != 4 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(&hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let json = r#" {"a": [1, -2.5, true, null], "b\n": "x\"é", "c": {}} "#;
+        let value = Value::parse(json).unwrap();
+        assert_eq!(value.get("b\n").and_then(Value::as_str), Some("x\"é"));
+        assert_eq!(value.get("a").and_then(Value::as_array).unwrap().len(), 4);
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":[1,-2.5,true,null],"b\n":"x\"é","c":{}}"#
+        );
+        assert_eq!(Value::parse(&value.to_string()).unwrap(), value);
+
+        let rocket = Value::parse(r#"{"description":"Rocket \ud83d\ude80 \u00e9"}"#).unwrap();
+        let description = rocket.get("description").and_then(Value::as_str);
+        assert_eq!(description, Some("Rocket 🚀 é"));
+        assert_eq!(Value::parse(&rocket.to_string()).unwrap(), rocket);
+        for invalid in [
+            r#""\ud83d""#,
+            r#""\ude80x""#,
+            r#""\ud83d\u0041""#,
+            r#""\u12g4""#,
+        ] {
+            assert!(Value::parse(invalid).is_err(), "{}", invalid);
+        }
+        assert_eq!(
+            value.pretty(),
+            r#"{
+  "a": [
+    1,
+    -2.5,
+    true,
+    null
+  ],
+  "b\n": "x\"é",
+  "c": {}
+}"#
+        );
+        assert_eq!(Value::parse(&value.pretty()).unwrap(), value);
+
+        assert!(Value::parse(r#"{"a": }"#).is_err());
+        assert!(Value::parse("[1] 2").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+//! Exporting a worldline as a TikZ timeline, for print-quality figures in papers and theses.
+//!
+//! The timeline is a `tikzpicture` with an axis of labelled year ticks, a dot and a rotated
+//! label for each event, and a bar under events with an end. It only needs
+//! `\usepackage{tikz}`, so it can be pasted or `\input` into any document.
+
+use crate::query::QueryExpr;
+use crate::{Date, Event, WorldLine};
+
+/// The width of the axis when no scale is given, in centimetres.
+const DEFAULT_WIDTH: f64 = 15.0;
+
+const DAYS_PER_YEAR: f64 = 365.2425;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatexOptions {
+    /// Centimetres per year, or None to fit the timeline to 15cm.
+    pub scale: Option<f64>,
+    /// How far the labels are rotated anticlockwise from the axis, in degrees.
+    pub rotate: f64,
+}
+
+impl Default for LatexOptions {
+    fn default() -> Self {
+        Self {
+            scale: None,
+            rotate: 45.0,
+        }
+    }
+}
+
+/// Escape the characters LaTeX treats specially.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            '\\' => out.push_str("\\textbackslash{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn year_label(year: i32) -> String {
+    if year < 0 {
+        format!("{} BCE", -year)
+    } else {
+        year.to_string()
+    }
+}
+
+/// The years between ticks: 1, 2 or 5 times a power of ten, for at most ten intervals.
+fn tick_step(years: f64) -> i32 {
+    let mut power = 1;
+    loop {
+        for step in [power, 2 * power, 5 * power] {
+            if years / step as f64 <= 10.0 {
+                return step;
+            }
+        }
+        power *= 10;
+    }
+}
+
+/// The first day of a tick's year, which is never 0 as there is no year 0.
+fn tick_day(year: i32) -> i64 {
+    Date::new(year, 0, 0).map_or(0, |date| date.day_number())
+}
+
+impl WorldLine {
+    /// The events matching `filter` (or all events) as a TikZ timeline. With a range filter,
+    /// the axis covers the whole range, not just the events in it.
+    pub fn to_latex(
+        &self,
+        filter: Option<&QueryExpr>,
+        options: &LatexOptions,
+    ) -> Result<String, String> {
+        let events: Vec<&Event> = self
+            .events
+            .iter()
+            .filter(|e| filter.is_none_or(|f| f.matches(e)))
+            .collect();
+        let (start, end) = match filter {
+            Some(QueryExpr::Range(range)) => {
+                (range.start().day_number(), range.after().day_number())
+            }
+            _ => {
+                let (Some(first), Some(last_end)) = (
+                    events.first(),
+                    events
+                        .iter()
+                        .map(|e| e.end.as_ref().unwrap_or(&e.date).next().day_number())
+                        .max(),
+                ) else {
+                    return Err("No events to export".to_string());
+                };
+                (first.date.day_number(), last_end)
+            }
+        };
+
+        let step = tick_step((end - start) as f64 / DAYS_PER_YEAR);
+        let year = |day: i64| Date::from_day_number(day).year;
+        let mut first_tick = year(start).div_euclid(step) * step;
+        if first_tick == 0 {
+            first_tick = -step;
+        }
+        let mut ticks = Vec::new();
+        let mut tick = first_tick;
+        while ticks.is_empty() || tick_day(*ticks.last().unwrap()) < end {
+            if tick != 0 {
+                ticks.push(tick);
+            }
+            tick += step;
+        }
+        let origin = tick_day(first_tick);
+        let length = tick_day(*ticks.last().unwrap()) - origin;
+        let scale = options
+            .scale
+            .unwrap_or(DEFAULT_WIDTH * DAYS_PER_YEAR / length as f64);
+        let x = |date: &Date| (date.day_number() - origin) as f64 / DAYS_PER_YEAR * scale;
+
+        let mut tex = String::from("% Needs \\usepackage{tikz}\n\\begin{tikzpicture}\n");
+        tex += &format!(
+            "  \\draw[thick] (0,0) -- ({:.2},0);\n",
+            length as f64 / DAYS_PER_YEAR * scale
+        );
+        for tick in ticks {
+            let position = (tick_day(tick) - origin) as f64 / DAYS_PER_YEAR * scale;
+            tex += &format!(
+                "  \\draw ({:.2},0.1) -- ({:.2},-0.1) node[below] {{{}}};\n",
+                position,
+                position,
+                year_label(tick)
+            );
+        }
+        for event in events {
+            let position = x(&event.date);
+            if let Some(until) = &event.end {
+                tex += &format!(
+                    "  \\draw[line width=3pt, opacity=0.4] ({:.2},0) -- ({:.2},0);\n",
+                    position,
+                    x(&until.next())
+                );
+            }
+            tex += &format!("  \\fill ({:.2},0) circle (1.5pt);\n", position);
+            tex += &format!(
+                "  \\node[anchor=west, rotate={}, font=\\small] at ({:.2},0.2) {{{}}};\n",
+                options.rotate,
+                position,
+                escape(&event.description)
+            );
+        }
+        tex += "\\end{tikzpicture}\n";
+        Ok(tex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_latex() {
+        let worldline: WorldLine = "#worldline v2\n\
+                                    1914-07-28 WWI begins\tend=1918-11-11\n\
+                                    1917 Revolutions & mutinies\n\
+                                    1939-09-01 WWII begins\n"
+            .parse()
+            .unwrap();
+        let tex = worldline.to_latex(None, &LatexOptions::default()).unwrap();
+        assert!(tex.starts_with("% Needs \\usepackage{tikz}\n\\begin{tikzpicture}\n"));
+        assert!(tex.contains("  \\draw[thick] (0,0) -- (15.00,0);\n"));
+        assert!(tex.contains("  \\draw (5.00,0.1) -- (5.00,-0.1) node[below] {1920};\n"));
+        assert!(tex.contains(
+            "node[anchor=west, rotate=45, font=\\small] at (3.50,0.2) {Revolutions \\& mutinies};"
+        ));
+        assert!(tex.contains("  \\draw[line width=3pt, opacity=0.4] (2.28,0) -- (4.43,0);\n"));
+        assert!(tex.ends_with("\\end{tikzpicture}\n"));
+
+        // a range sets the axis, even without events at its ends
+        let range = QueryExpr::range("1910..1919").unwrap();
+        let options = LatexOptions {
+            scale: Some(1.0),
+            rotate: 90.0,
+        };
+        let tex = worldline.to_latex(Some(&range), &options).unwrap();
+        assert!(tex.contains("  \\draw[thick] (0,0) -- (10.00,0);\n"));
+        assert!(tex.contains("rotate=90"));
+        assert!(!tex.contains("WWII"));
+        assert!(WorldLine::default().to_latex(None, &options).is_err());
+    }
+}
@@ -0,0 +1,1635 @@
+//! The core library has no filesystem access unless the `std-fs` feature (on by default) is
+//! enabled, so it can be built for targets like wasm32-unknown-unknown. Without it, worldlines
+//! are read and written as strings with [`str::parse`] and [`ToString::to_string`], or through
+//! [`WorldLine::from_reader`] and [`WorldLine::write_to`].
+
+use regex::Regex;
+#[cfg(feature = "std-fs")]
+use std::fs;
+use std::io::{Read, Write};
+#[cfg(feature = "std-fs")]
+use std::path::Path;
+use std::sync::LazyLock;
+
+pub mod anki;
+pub mod archive;
+#[cfg(feature = "astro")]
+pub mod astro;
+pub mod bib;
+#[cfg(feature = "std-fs")]
+pub mod branch;
+#[cfg(feature = "calendars")]
+pub mod calendars;
+pub mod check;
+pub mod compare;
+pub mod config;
+pub mod convert;
+pub mod correlate;
+#[cfg(feature = "crypto")]
+pub mod crypto;
+pub mod dashboard;
+pub mod diff;
+pub mod display;
+pub mod epoch;
+pub mod export;
+pub mod filter;
+pub mod format;
+pub mod gaps;
+#[cfg(feature = "gedcom")]
+pub mod gedcom;
+pub mod geo;
+pub mod group;
+#[cfg(feature = "holidays")]
+pub mod holidays;
+pub mod index;
+#[cfg(feature = "std-fs")]
+pub mod journal;
+pub mod json;
+pub mod latex;
+pub mod locale;
+#[cfg(feature = "mmap")]
+pub mod mapped;
+pub mod markdown;
+#[cfg(feature = "std-fs")]
+pub mod notes;
+pub mod obsidian;
+pub mod order;
+pub mod outline;
+pub mod parse;
+pub mod pattern;
+mod period;
+pub mod progress;
+pub mod query;
+pub mod range;
+pub mod ruler;
+pub mod schema;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod series;
+pub mod similar;
+pub mod span;
+pub mod sparkline;
+pub mod split;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "std-fs")]
+pub mod storage;
+pub mod table;
+pub mod templates;
+pub mod testing;
+pub mod time_of_day;
+pub mod timestamp;
+pub mod weekday;
+pub mod wikidata;
+pub mod worksheet;
+
+use display::DisplayOptions;
+use epoch::YearNumbering;
+pub use format::{FormatVersion, ParseMode};
+use period::Period;
+use query::QueryExpr;
+use range::DateRange;
+use time_of_day::TimeOfDay;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+    /// Weeks and quarters, see [`period`].
+    period: Period,
+    /// A time of day, for days. See [`time_of_day`].
+    time: Option<TimeOfDay>,
+}
+
+impl Date {
+    const MONTH_LENGTHS: [u8; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+    /// The earliest supported year, 9999 BCE. Dates are written with at most 4 year digits.
+    pub const MIN_YEAR: i32 = -9999;
+    /// The latest supported year, 9999 CE.
+    pub const MAX_YEAR: i32 = 9999;
+
+    /// Construct a new Date from year, month, and day.
+    /// months and days can be 0 to indicate that they are not known.
+    /// Negative years are BCE; there is no year 0.
+    pub fn new(year: i32, month: u8, day: u8) -> Result<Self, String> {
+        if year == 0 {
+            Err("There is no year 0; 1 BCE is followed by 1 CE".to_string())
+        } else if !(Self::MIN_YEAR..=Self::MAX_YEAR).contains(&year) {
+            Err(format!(
+                "Year {} is out of range; supported years are 9999 BCE to 9999 CE",
+                year
+            ))
+        } else if month > 12 {
+            Err(format!("Invalid month: {}", month))
+        } else if month != 0 && day > Self::days_in_month(year, month) {
+            Err(format!("Invalid day: {}", day))
+        } else {
+            Ok(Self {
+                year,
+                month,
+                day,
+                period: Period::Calendar,
+                time: None,
+            })
+        }
+    }
+
+    /// Return the date one unit of its precision (minute, day, week, month, quarter or year)
+    /// later, as a month or year when it's the first day of one, e.g. 2023-11-30 is followed
+    /// by 2023-12 and 2023-Q4 by 2024. The date after the last of [`Date::MAX_YEAR`] is in the
+    /// year after it, which is only meaningful as an exclusive bound.
+    pub fn next(&self) -> Self {
+        if self.time.is_some() {
+            return self.next_minute().unwrap_or_else(|| {
+                Self {
+                    time: None,
+                    ..self.clone()
+                }
+                .next()
+            });
+        }
+        let (year, month, day) = self.next_start();
+        match (self.period, Self::new(year, month, day)) {
+            (Period::Week, Ok(monday)) => monday.with_period(Period::Week),
+            (Period::Quarter, Ok(first)) if month != 1 => first.with_period(Period::Quarter),
+            _ => Self::calendar_starting_on(year, month, day),
+        }
+    }
+
+    /// The first date after this one, as the coarsest date starting then: a year, quarter,
+    /// month, week or day, e.g. 2023-03-31 is followed by 2023-Q2 and 2023-03-19 by 2023-W12.
+    /// This sorts before every later date, so it works as an exclusive bound for ranges ending
+    /// with this date.
+    pub(crate) fn after(&self) -> Self {
+        if self.time.is_some() {
+            return self.next_minute().unwrap_or_else(|| {
+                Self {
+                    time: None,
+                    ..self.clone()
+                }
+                .after()
+            });
+        }
+        let (year, month, day) = self.next_start();
+        Self::starting_on(year, month, day)
+    }
+
+    /// The year, month and day of the first day after this date, ignoring its time.
+    fn next_start(&self) -> (i32, u8, u8) {
+        let year = if self.year == -1 { 1 } else { self.year + 1 };
+        match self.period {
+            Period::Week => {
+                let monday = self.day_number() + 7;
+                let next = Self::from_day_number(monday);
+                if next.day_number() != monday {
+                    (year, 1, 1)
+                } else {
+                    (next.year, next.month, next.day)
+                }
+            }
+            Period::Quarter if self.month < 10 => (self.year, self.month + 3, 1),
+            Period::Calendar
+                if self.day != 0 && self.day < Self::days_in_month(self.year, self.month) =>
+            {
+                (self.year, self.month, self.day + 1)
+            }
+            Period::Calendar if self.month != 0 && self.month < 12 => {
+                (self.year, self.month + 1, 1)
+            }
+            _ => (year, 1, 1),
+        }
+    }
+
+    /// The year or month starting on a day if it's the first day of one, or else the day.
+    /// Years aren't checked for range.
+    fn calendar_starting_on(year: i32, month: u8, day: u8) -> Self {
+        let (month, day) = match (month, day) {
+            (1, 1) => (0, 0),
+            (month, 1) => (month, 0),
+            (month, day) => (month, day),
+        };
+        Self {
+            year,
+            month,
+            day,
+            period: Period::Calendar,
+            time: None,
+        }
+    }
+
+    /// The coarsest date starting on a day: its year, quarter, month or week if it's the
+    /// first day of one, or else the day. Years aren't checked for range.
+    fn starting_on(year: i32, month: u8, day: u8) -> Self {
+        let date = Self::calendar_starting_on(year, month, day);
+        if date.month != 0 && date.day == 0 && month % 3 == 1 {
+            date.with_period(Period::Quarter)
+        } else if date.day != 0 && date.weekday() == Some(weekday::Weekday::Monday) {
+            date.with_period(Period::Week)
+        } else {
+            date
+        }
+    }
+
+    /// The year on the astronomical scale, where 1 BCE is year 0, 2 BCE is year -1, etc.
+    /// There is no year 0 in BCE/CE, so this is what arithmetic should be done on.
+    fn astronomical_year(year: i32) -> i32 {
+        if year < 0 {
+            year + 1
+        } else {
+            year
+        }
+    }
+
+    /// Whether the given (BCE/CE) year is a leap year in the proleptic Gregorian calendar.
+    pub fn is_leap_year(year: i32) -> bool {
+        let year = Self::astronomical_year(year);
+        year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+    }
+
+    /// Number of days in the given month (1-12) of the given year.
+    pub fn days_in_month(year: i32, month: u8) -> u8 {
+        if month == 2 && Self::is_leap_year(year) {
+            29
+        } else {
+            Self::MONTH_LENGTHS[month as usize - 1]
+        }
+    }
+
+    /// The elapsed time from self to other, in years, months, and days.
+    ///
+    /// The result is computed at the coarsest precision of the two dates, e.g. the difference
+    /// between 1914-06-28 and 1918 is only given in years. The order of the dates doesn't matter.
+    /// Crossing the BCE/CE boundary accounts for the lack of a year 0, so 1 BCE to 1 CE is a year.
+    pub fn difference(&self, other: &Date) -> DateDifference {
+        let (start, end) = if self <= other {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let with_months = start.month != 0 && end.month != 0;
+        let with_days = with_months && start.day != 0 && end.day != 0;
+
+        let mut years = Self::astronomical_year(end.year) - Self::astronomical_year(start.year);
+        let mut months = 0;
+        let mut days = 0;
+        if with_months {
+            months = end.month as i32 - start.month as i32;
+        }
+        if with_days {
+            days = end.day as i32 - start.day as i32;
+            if days < 0 {
+                // borrow the length of the month before end's month
+                let (year, month) = if end.month == 1 {
+                    (end.year - 1, 12)
+                } else {
+                    (end.year, end.month - 1)
+                };
+                // clamp, e.g. Jan 31 -> Mar 1 is 1 month (to Feb 28) and 1 day
+                days = end.day as i32
+                    + (Self::days_in_month(year, month) as i32 - start.day as i32).max(0);
+                months -= 1;
+            }
+        }
+        if months < 0 {
+            months += 12;
+            years -= 1;
+        }
+
+        DateDifference {
+            years: years as u32,
+            months: with_months.then_some(months as u8),
+            days: with_days.then_some(days as u8),
+        }
+    }
+}
+
+/// Compact form of a date that [`Date::parse`] can read back, e.g. "1945-09-02", "-0044-03" or
+/// "2023-W12".
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((year, suffix)) = self.period_suffix() {
+            let sign = if year < 0 { "-" } else { "" };
+            return write!(f, "{}{:04}{}", sign, year.abs(), suffix);
+        }
+        let sign = if self.year < 0 { "-" } else { "" };
+        write!(f, "{}{:04}", sign, self.year.abs())?;
+        if self.month != 0 {
+            write!(f, "-{:02}", self.month)?;
+        }
+        if self.day != 0 {
+            write!(f, "-{:02}", self.day)?;
+        }
+        if let Some(time) = self.time {
+            write!(f, " {}", time)?;
+        }
+        Ok(())
+    }
+}
+
+/// Elapsed time between two dates, as returned by [`Date::difference`].
+/// months and days are None when either date was too imprecise to compute them.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DateDifference {
+    pub years: u32,
+    pub months: Option<u8>,
+    pub days: Option<u8>,
+}
+
+impl std::fmt::Display for DateDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |n: u32, unit: &str| {
+            if n == 1 {
+                format!("1 {}", unit)
+            } else {
+                format!("{} {}s", n, unit)
+            }
+        };
+        let mut parts = vec![plural(self.years, "year")];
+        if let Some(months) = self.months {
+            parts.push(plural(months as u32, "month"));
+        }
+        if let Some(days) = self.days {
+            parts.push(plural(days as u32, "day"));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+static DATE_REGEX: LazyLock<Regex> = LazyLock::new(Date::construct_date_regex);
+/// Matches dates whose year has too many digits, to explain why they don't parse.
+static LONG_YEAR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?i:BCE|BC|CE|AD)?\s*[+-]?\d{5,}").unwrap());
+
+impl Date {
+    /// Construct the regex for parsing dates. Only evaluated once, lazily, for DATE_REGEX.
+    fn construct_date_regex() -> Regex {
+        let era = r"(?<era>(?i:BCE|BC|CE|AD))?"; // Optional era prefix, case-insensitive
+        let sign = r"(?<sign>[+-])?"; // Optional sign of the year
+        let year = r"(?<year>\d{1,4})";
+        let basic = r"(?<basic_year>\d{4})(?<basic_month>\d{2})(?<basic_day>\d{2})"; // YYYYMMDD
+        let month = r"(?:-(?<month>\d{1,2}))?"; // Optional month part. Outer group is non-capturing.
+        let time = r"(?:(?:\s+|T)(?<hour>\d{1,2}):(?<minute>\d{2}))?"; // Optional time of day
+        let day = format!(r"(?:-(?<day>\d{{1,2}}){time})?"); // Optional day part, with the time.
+        let period = r"-W(?<week>\d{2})|-Q(?<quarter>\d)"; // ISO week or quarter instead
+        let pattern =
+            format!(r"^\s*{era}\s*{sign}(?:{basic}|{year}(?:{period}|{month}{day}))(?:\s+|$)");
+        Regex::new(&pattern).unwrap()
+    }
+
+    /// Parse a string starting with a date into a [year, month, day] array.
+    ///
+    /// Accepts dates in the following formats:
+    /// - BCE/BC dates: "BCE 44" or "-44"
+    /// - CE/AD dates: "CE 2023", "2023-12", "2023-12-25"
+    /// - ISO 8601 signed years, which are astronomical: "+0044-03-15", or "+0000" for 1 BCE
+    /// - ISO 8601 basic days: "20231225"
+    /// - ISO weeks and quarters: "2023-W12", "2023-Q2"
+    /// - Days with a time of day: "2023-12-25 14:30"
+    ///
+    /// Returns Ok(([year, month, day], index)) on success, with month/day set to 0 if not
+    ///     specified. index is the index of the first character in the string that was not parsed.
+    /// Returns Err with error message on invalid input.
+    ///
+    /// Note: BCE years are stored as negative numbers, e.g. "BCE 44" -> [-44, 0, 0]
+    ///
+    /// Negative years are read with the historical [`YearNumbering`], where "-44" is 44 BCE,
+    /// as in worldline files; see [`Date::parse_in`] to read them astronomically instead.
+    pub fn parse(date_string: &str) -> Result<(Date, usize), String> {
+        Self::parse_in(date_string, YearNumbering::Historical)
+    }
+
+    /// Parse a string starting with a date like [`Date::parse`], reading negative years with
+    /// the given numbering, e.g. "-0043-03-15" as 44 BCE when astronomical like ISO 8601.
+    pub fn parse_in(date_string: &str, numbering: YearNumbering) -> Result<(Date, usize), String> {
+        let caps = DATE_REGEX.captures(date_string).ok_or_else(|| {
+            if LONG_YEAR_REGEX.is_match(date_string) {
+                format!(
+                    "Year out of range: {}; supported years are 9999 BCE to 9999 CE",
+                    date_string.trim()
+                )
+            } else {
+                format!("Invalid date format: {}", date_string)
+            }
+        })?;
+
+        // safe to unwrap parse because year, month and day groups are all digits by construction
+        // can't use direct indexing into caps because each is in one of the alternatives
+        let number = |names: [&str; 2]| {
+            names
+                .iter()
+                .find_map(|name| caps.name(name))
+                .map_or(0, |n| n.as_str().parse::<i32>().unwrap())
+        };
+        let mut year = number(["year", "basic_year"]);
+        let sign = caps.name("sign").map(|s| s.as_str());
+        if sign == Some("-") {
+            year = -year;
+        }
+        // a + sign is ISO 8601's, whose years are astronomical; eras are always historical
+        let astronomical = sign == Some("+") || numbering == YearNumbering::Astronomical;
+        match caps.name("era") {
+            Some(era) if era.as_str().starts_with(['B', 'b']) => year = -year,
+            Some(_) => {}
+            None if astronomical => year = span::from_astronomical_year(year),
+            None => {}
+        }
+        let month = number(["month", "basic_month"]) as u8;
+        let day = number(["day", "basic_day"]) as u8;
+
+        // Get the length of the matched substring by finding the end position of the match
+        let match_len = caps.get(0).unwrap().end();
+        let date = if let Some(week) = caps.name("week") {
+            Date::from_week(year, week.as_str().parse().unwrap())?
+        } else if let Some(quarter) = caps.name("quarter") {
+            Date::from_quarter(year, quarter.as_str().parse().unwrap())?
+        } else {
+            Date::new(year, month, day)?
+        };
+        let date = match (caps.name("hour"), caps.name("minute")) {
+            (Some(hour), Some(minute)) => date.with_time(Some(TimeOfDay::new(
+                hour.as_str().parse().unwrap(),
+                minute.as_str().parse().unwrap(),
+            )?))?,
+            _ => date,
+        };
+        Ok((date, match_len))
+    }
+
+    /// Format a date into a string for writing to a file.
+    pub fn format(&self, display_era: bool) -> String {
+        let period = self.period_suffix();
+        // weeks are written with their week-numbering year
+        let year = period.as_ref().map_or(self.year, |(year, _)| *year);
+        let prefix = if display_era {
+            if year < 0 {
+                "BCE "
+            } else {
+                " CE "
+            }
+        } else {
+            ""
+        };
+        let year = year.abs().to_string();
+
+        if let Some((_, suffix)) = period {
+            format!("{}{:0>4}{:<6}", prefix, year, suffix)
+        } else if self.month == 0 {
+            format!("{}{:0>4}      ", prefix, year)
+        } else if self.day == 0 {
+            format!("{}{:0>4}-{:02}   ", prefix, year, self.month)
+        } else if let Some(time) = self.time {
+            let date = format!("{:0>4}-{:02}-{:02}", year, self.month, self.day);
+            format!("{}{} {}", prefix, date, time)
+        } else {
+            format!("{}{:0>4}-{:02}-{:02}", prefix, year, self.month, self.day)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
+    pub date: Date,
+    pub description: String,
+    /// A single category, written as a bracketed prefix to the description, e.g. "[war] WWI
+    /// begins".
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub category: Option<String>,
+    /// Where the event happened, written at the end of the description, e.g. "Battle of
+    /// Hastings @Hastings". See [`geo`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub place: Option<geo::Place>,
+    /// The fields below are only stored in v2 files. See [`format`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub id: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub tags: Vec<String>,
+    /// The people the event is about, e.g. "Julius Caesar". See [`WorldLine::person`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub people: Vec<String>,
+    /// The series of events the event is part of, e.g. "Punic Wars". See [`series`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub series: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub end: Option<Date>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub sources: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub notes: Vec<Note>,
+    /// Kept in the file but hidden from `wl show`, e.g. superseded or doubtful events. See
+    /// [`archive`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub archived: bool,
+    /// Shown at the top of `wl dashboard`, for the events that matter most. See [`dashboard`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    pub pinned: bool,
+    /// The event's place among the events on the same date: lower numbers come first, and
+    /// events with a number come before events without one. See [`order`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub seq: Option<u32>,
+    /// When the event was added to the worldline, if it was added by wl to a v2 file. See
+    /// [`WorldLine::stamp_changes`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub created: Option<timestamp::Timestamp>,
+    /// When the event was last changed, like `created`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub modified: Option<timestamp::Timestamp>,
+}
+
+/// Events are ordered by date, then by sequence number, then alphabetically (see
+/// [`order::SameDayOrder`] for keeping the order they were added in instead).
+impl Ord for Event {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        fn rest(e: &Event) -> impl Ord + '_ {
+            (
+                &e.description,
+                &e.category,
+                &e.place,
+                &e.id,
+                &e.tags,
+                (&e.people, &e.series),
+                &e.end,
+                &e.sources,
+                &e.notes,
+                (e.archived, e.pinned),
+                e.created,
+                e.modified,
+            )
+        }
+        self.same_day_key()
+            .cmp(&other.same_day_key())
+            .then_with(|| rest(self).cmp(&rest(other)))
+    }
+}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Commentary attached to an event after the fact.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Note {
+    pub timestamp: timestamp::Timestamp,
+    pub text: String,
+}
+
+impl Event {
+    pub fn new(date: Date, description: String) -> Self {
+        Self {
+            date,
+            description,
+            category: None,
+            place: None,
+            id: None,
+            tags: Vec::new(),
+            people: Vec::new(),
+            series: None,
+            end: None,
+            sources: Vec::new(),
+            notes: Vec::new(),
+            archived: false,
+            pinned: false,
+            seq: None,
+            created: None,
+            modified: None,
+        }
+    }
+
+    pub fn parse(event_string: &str) -> Result<Self, String> {
+        let (date, index) = Date::parse(event_string)?;
+        Ok(Self::from_text(date, &event_string[index..]))
+    }
+
+    /// An event from a description that may start with a category and end with a place, e.g.
+    /// "[war] WWI begins @Sarajevo".
+    pub fn from_text(date: Date, text: &str) -> Self {
+        let (category, description) = Self::split_category(text);
+        let (description, place) = geo::split_place(description);
+        let mut event = Self::new(date, description.to_string());
+        event.category = category.map(str::to_string);
+        event.place = place;
+        event
+    }
+
+    /// Split a bracketed category prefix, e.g. "[war]", from a description. Categories are
+    /// made of letters, digits, `-` and `_`.
+    fn split_category(description: &str) -> (Option<&str>, &str) {
+        let Some((category, rest)) = description
+            .strip_prefix('[')
+            .and_then(|d| d.split_once(']'))
+        else {
+            return (None, description);
+        };
+        let valid = !category.is_empty()
+            && category
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !valid || !(rest.is_empty() || rest.starts_with(' ')) {
+            return (None, description);
+        }
+        (Some(category), rest.strip_prefix(' ').unwrap_or(rest))
+    }
+
+    /// The description with the category prefix and place, as written in files.
+    fn full_description(&self) -> String {
+        let mut description = match &self.category {
+            Some(category) => format!("[{}] {}", category, self.description),
+            None => self.description.clone(),
+        };
+        if let Some(place) = &self.place {
+            description += &format!(" @{}", place);
+        }
+        description
+    }
+
+    pub fn format_for_file(&self) -> String {
+        format!("{} {}", self.date.format(true), self.full_description())
+    }
+
+    /// The event as it's displayed on its own, with its date's era if `display_era` is set.
+    pub fn format_for_display(&self, display_era: bool, options: &DisplayOptions) -> String {
+        let category = match &self.category {
+            Some(category) => options.format_category(category) + " ",
+            None => String::new(),
+        };
+        let place = match &self.place {
+            Some(place) => format!(" @{}", place),
+            None => String::new(),
+        };
+        // don't pad year
+        format!(
+            "{} {}{}{}",
+            options.paint(
+                display::DATE_STYLE,
+                &options.format_date(&self.date, display_era)
+            ),
+            category,
+            self.description,
+            place
+        )
+    }
+}
+
+/// The outcome of a successful [`WorldLine::add_events`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct BatchReport {
+    pub added: usize,
+    /// blank and comment lines
+    pub skipped: usize,
+    /// Lines read leniently or skipped in [`ParseMode::Lenient`], with their (1-based) line
+    /// numbers.
+    pub warnings: Vec<(usize, String)>,
+}
+
+#[derive(Default, Clone)]
+pub struct WorldLine {
+    events: Vec<Event>,
+    version: FormatVersion,
+    front_matter: Vec<(String, String)>,
+    /// Set for worldlines that don't correspond to a whole file, e.g. filtered views.
+    read_only: bool,
+}
+
+impl std::str::FromStr for WorldLine {
+    type Err = String;
+
+    /// Parse the contents of a worldline file.
+    fn from_str(contents: &str) -> Result<Self, String> {
+        let file = format::parse(contents)?;
+        Ok(Self {
+            events: file.events,
+            version: file.version,
+            front_matter: file.front_matter,
+            read_only: false,
+        })
+    }
+}
+
+impl std::fmt::Display for WorldLine {
+    /// The contents of the worldline file. See [`WorldLine::write_to`].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&format::serialize(
+            self.file_version(),
+            &self.front_matter,
+            &self.events,
+        ))
+    }
+}
+
+/// A worldline of events in any order. They're sorted, like events added with
+/// [`WorldLine::add_event`].
+impl FromIterator<Event> for WorldLine {
+    fn from_iter<I: IntoIterator<Item = Event>>(iter: I) -> Self {
+        let mut events: Vec<Event> = iter.into_iter().collect();
+        events.sort();
+        Self {
+            events,
+            ..Self::default()
+        }
+    }
+}
+
+impl IntoIterator for WorldLine {
+    type Item = Event;
+    type IntoIter = std::vec::IntoIter<Event>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a WorldLine {
+    type Item = &'a Event;
+    type IntoIter = std::slice::Iter<'a, Event>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.events.iter()
+    }
+}
+
+impl WorldLine {
+    #[cfg(feature = "std-fs")]
+    pub fn from_file(file_path: impl AsRef<Path>) -> Result<Self, String> {
+        let file = fs::File::open(file_path).map_err(|e| e.to_string())?;
+        Self::from_reader(file)
+    }
+
+    /// Read a worldline from anything readable, e.g. stdin.
+    pub fn from_reader(mut reader: impl Read) -> Result<Self, String> {
+        let mut contents = String::new();
+        reader
+            .read_to_string(&mut contents)
+            .map_err(|e| e.to_string())?;
+        contents.parse()
+    }
+
+    /// Parse the contents of a worldline file like [`str::parse`] in a [`ParseMode`]. In
+    /// [`ParseMode::Lenient`], lines that aren't in the file format are read as best they can
+    /// be, or skipped, and returned as warnings with their (1-based) line numbers; only an
+    /// unsupported version is an error.
+    pub fn parse_with(
+        contents: &str,
+        mode: ParseMode,
+    ) -> Result<(Self, Vec<(usize, String)>), String> {
+        if mode == ParseMode::Strict {
+            return Ok((contents.parse()?, Vec::new()));
+        }
+        let (file, invalid) = format::parse_lenient(contents)?;
+        let mut worldline = Self {
+            events: file.events,
+            version: file.version,
+            front_matter: file.front_matter,
+            read_only: false,
+        };
+        let mut warnings = Vec::new();
+        for line in invalid {
+            let (event, warning) = ParseMode::recover(line.text, line.error);
+            if let Some(event) = event {
+                worldline.add_event(event);
+            }
+            warnings.push((line.number, warning));
+        }
+        Ok((worldline, warnings))
+    }
+
+    /// Write the worldline to a file. See [`WorldLine::write_to`].
+    #[cfg(feature = "std-fs")]
+    pub fn to_file(&self, file_path: impl AsRef<Path>) -> Result<(), std::io::Error> {
+        if self.read_only {
+            return Err(std::io::Error::other("the worldline is read-only"));
+        }
+        self.write_to(fs::File::create(file_path)?)
+    }
+
+    /// Write the worldline in the format version it was read with. v1 worldlines are written as
+    /// v2 if they contain data v1 can't store.
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), std::io::Error> {
+        writer.write_all(self.to_string().as_bytes())
+    }
+
+    /// The version the worldline is written in: the one it was read with, or v2 if it has
+    /// data v1 can't store.
+    fn file_version(&self) -> FormatVersion {
+        let needs_v2 =
+            !self.front_matter.is_empty() || self.events.iter().any(Event::has_extended_fields);
+        if needs_v2 {
+            FormatVersion::V2
+        } else {
+            self.version
+        }
+    }
+
+    /// Whether the worldline can be written back to a file.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// The file format version the worldline will be written in.
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// Upgrade the worldline to the latest file format. Returns false if it was already there.
+    pub fn migrate(&mut self) -> bool {
+        let migrated = self.version != FormatVersion::LATEST;
+        self.version = FormatVersion::LATEST;
+        migrated
+    }
+
+    /// Key-value metadata from the header of a v2 file.
+    pub fn front_matter(&self) -> &[(String, String)] {
+        &self.front_matter
+    }
+
+    /// Set a front matter key, replacing any existing value.
+    pub fn set_front_matter(&mut self, key: &str, value: String) {
+        match self.front_matter.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => self.front_matter.push((key.to_string(), value)),
+        }
+    }
+
+    #[cfg(feature = "std-fs")]
+    pub fn to_anki_file(&self, file_path: String) -> Result<(), std::io::Error> {
+        self.write_anki(fs::File::create(file_path)?)
+    }
+
+    /// Write the worldline as a tab-separated file which is easily importable with Anki.
+    pub fn write_anki(&self, mut writer: impl Write) -> Result<(), std::io::Error> {
+        let header = "#separator:Tab\n";
+        let mut contents = self.build_file("\t");
+        contents.insert_str(0, header);
+        writer.write_all(contents.as_bytes())
+    }
+
+    /// One row per event: the date, including any time of day, then the separator and the
+    /// description.
+    fn build_file(&self, separator: &str) -> String {
+        self.events
+            .iter()
+            .map(|e| e.date.format(true) + separator + &e.full_description())
+            .fold(String::new(), |a, b| a + &b + "\n")
+    }
+
+    /// number of events in the worldline
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// whether the worldline has no events
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Find the index of the event with the given id.
+    pub fn find_id(&self, id: &str) -> Option<usize> {
+        self.events.iter().position(|e| e.id.as_deref() == Some(id))
+    }
+
+    /// The event at the given index.
+    pub fn get(&self, idx: usize) -> Option<&Event> {
+        self.events.get(idx)
+    }
+
+    /// All events, in order.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Iterate over the events in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Event> {
+        self.events.iter()
+    }
+
+    /// The first (oldest) `n` events, or all of them if there are fewer.
+    pub fn head(&self, n: usize) -> &[Event] {
+        &self.events[..n.min(self.events.len())]
+    }
+
+    /// The last (most recent) `n` events, or all of them if there are fewer.
+    pub fn tail(&self, n: usize) -> &[Event] {
+        &self.events[self.events.len().saturating_sub(n)..]
+    }
+
+    /// Find events by reference: the event with the given id if there is one, the event with
+    /// the given number (`#12` or `12`, counting from 1 in file order, as numbered by `wl show
+    /// --numbered`), or otherwise all events whose descriptions contain the reference
+    /// (case-insensitive).
+    pub fn find(&self, reference: &str) -> Vec<usize> {
+        if let Some(idx) = self.find_id(reference) {
+            return vec![idx];
+        }
+        let number = reference.strip_prefix('#').unwrap_or(reference);
+        if let Ok(number) = number.parse::<usize>() {
+            if (1..=self.events.len()).contains(&number) {
+                return vec![number - 1];
+            }
+        }
+        let reference = reference.to_lowercase();
+        (0..self.events.len())
+            .filter(|&i| {
+                self.events[i]
+                    .description
+                    .to_lowercase()
+                    .contains(&reference)
+            })
+            .collect()
+    }
+
+    /// Remove the event at the given index.
+    pub fn remove(&mut self, idx: usize) -> Event {
+        self.events.remove(idx)
+    }
+
+    /// Replace the event at the given index, keeping events sorted. Returns the new event's
+    /// index.
+    pub fn replace(&mut self, idx: usize, event: Event) -> usize {
+        self.events.remove(idx);
+        self.add_event(event)
+    }
+
+    /// Append a note, timestamped now, to the event at the given index.
+    pub fn annotate(&mut self, idx: usize, text: String) {
+        self.events[idx].notes.push(Note {
+            timestamp: timestamp::Timestamp::now(),
+            text,
+        });
+    }
+
+    /// Add an event to the worldline.
+    /// Returns the index of the new event.
+    pub fn add_event(&mut self, event: Event) -> usize {
+        let idx = self.events.partition_point(|e| self.goes_before(e, &event));
+        self.events.insert(idx, event);
+        idx
+    }
+
+    /// Add many events to the worldline, in any order. This sorts them and merges them in
+    /// one pass, which is much faster than [`WorldLine::add_event`] for each of them on large
+    /// worldlines, where every insert moves the events after it.
+    pub fn add_events_sorted(&mut self, mut events: Vec<Event>) {
+        self.sort_new(&mut events);
+        let mut existing = std::mem::take(&mut self.events).into_iter().peekable();
+        let mut merged = Vec::with_capacity(existing.len() + events.len());
+        for event in events {
+            merged.extend(std::iter::from_fn(|| {
+                existing.next_if(|e| self.goes_before(e, &event))
+            }));
+            merged.push(event);
+        }
+        merged.extend(existing);
+        self.events = merged;
+    }
+
+    /// Add all events from another worldline, skipping exact duplicates.
+    /// Returns the number of events added.
+    pub fn import(&mut self, other: WorldLine) -> usize {
+        let mut events: Vec<Event> = other
+            .events
+            .into_iter()
+            .filter(|e| !self.events[self.date_range(&DateRange::of(e.date.clone()))].contains(e))
+            .collect();
+        events.sort();
+        events.dedup();
+        let added = events.len();
+        self.add_events_sorted(events);
+        added
+    }
+
+    /// Parse and add many event lines at once, e.g. from a file. Lines are in the file format
+    /// (v2 fields are allowed); blank lines and `#` comments are skipped.
+    ///
+    /// Either every line is added, or none are: if any line is invalid, nothing is added and
+    /// the errors are returned with their (1-based) line numbers.
+    pub fn add_events<'a>(
+        &mut self,
+        lines: impl Iterator<Item = &'a str>,
+    ) -> Result<BatchReport, Vec<(usize, String)>> {
+        self.add_events_with(lines, ParseMode::Strict)
+    }
+
+    /// [`WorldLine::add_events`] in a [`ParseMode`]. In [`ParseMode::Lenient`], invalid lines
+    /// are read as best they can be, or skipped, with warnings instead of errors, so the valid
+    /// ones are always added.
+    pub fn add_events_with<'a>(
+        &mut self,
+        lines: impl Iterator<Item = &'a str>,
+        mode: ParseMode,
+    ) -> Result<BatchReport, Vec<(usize, String)>> {
+        let mut events = Vec::new();
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let mut skipped = 0;
+        for (i, line) in lines.enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                skipped += 1;
+                continue;
+            }
+            let event = match (format::parse_event_v2(line), mode) {
+                (Ok(event), _) => Ok(event),
+                (Err(e), ParseMode::Strict) => Err(e),
+                (Err(e), ParseMode::Lenient) => {
+                    let (event, warning) = ParseMode::recover(line, e);
+                    warnings.push((i + 1, warning));
+                    match event {
+                        Some(event) => Ok(event),
+                        None => continue,
+                    }
+                }
+            };
+            match event {
+                Ok(event) => {
+                    let duplicate_id = event.id.as_deref().is_some_and(|id| {
+                        self.find_id(id).is_some()
+                            || events.iter().any(|e: &Event| e.id.as_deref() == Some(id))
+                    });
+                    if duplicate_id {
+                        errors.push((i + 1, "An event with this id already exists".to_string()));
+                    } else {
+                        events.push(event);
+                    }
+                }
+                Err(e) => errors.push((i + 1, e)),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let added = events.len();
+        self.add_events_sorted(events);
+        Ok(BatchReport {
+            added,
+            skipped,
+            warnings,
+        })
+    }
+
+    /// The events in a range of indices as lines of a v2 file, with all their fields, e.g. to
+    /// edit them as text.
+    pub fn range_lines(&self, range: std::ops::Range<usize>) -> Vec<String> {
+        self.events[range]
+            .iter()
+            .map(format::format_event_v2)
+            .collect()
+    }
+
+    /// Replace the events in a range of indices with event lines, e.g. the range's
+    /// [`WorldLine::range_lines`] after editing them: events can be changed, removed or
+    /// added. Lines are read like in [`WorldLine::add_events`], and if any is invalid,
+    /// nothing changes.
+    pub fn replace_range<'a>(
+        &mut self,
+        range: std::ops::Range<usize>,
+        lines: impl Iterator<Item = &'a str>,
+    ) -> Result<BatchReport, Vec<(usize, String)>> {
+        let start = range.start;
+        let removed: Vec<Event> = self.events.drain(range).collect();
+        self.add_events(lines).inspect_err(|_| {
+            let after = self.events.split_off(start);
+            self.events.extend(removed);
+            self.events.extend(after);
+        })
+    }
+
+    /// Find the index of the first event on or after the given date, or within it (see
+    /// [`order::PartialDateOrder`]).
+    fn first_geq(&self, date: &Date) -> usize {
+        let order = self.partial_date_order();
+        self.events
+            .partition_point(|e| order.is_before(&e.date, date))
+    }
+
+    /// The indices of the events in a date range.
+    pub fn date_range(&self, range: &DateRange) -> std::ops::Range<usize> {
+        self.first_geq(range.start())..self.first_geq(range.after())
+    }
+
+    /// The indices of up to `n` events before a date and up to `n` events after it (after the
+    /// whole day, month or year it covers), e.g. to show what's near a date without events.
+    pub fn neighbors(
+        &self,
+        date: &Date,
+        n: usize,
+    ) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let range = DateRange::of(date.clone());
+        let before = self.first_geq(range.start());
+        let after = self.first_geq(range.after());
+        (
+            before.saturating_sub(n)..before,
+            after..(after + n).min(self.events.len()),
+        )
+    }
+
+    /// The event at an index with up to `radius` events on each side, fewer at the start and
+    /// end of the worldline, e.g. to preview where a new event was added. Empty if there's no
+    /// event at the index.
+    pub fn context(&self, idx: usize, radius: usize) -> &[Event] {
+        if idx >= self.events.len() {
+            return &[];
+        }
+        let start = idx.saturating_sub(radius);
+        let end = idx.saturating_add(radius).saturating_add(1);
+        &self.events[start..end.min(self.events.len())]
+    }
+
+    /// The events in a range of indices laid out for display, one string per event (see
+    /// [`DisplayOptions::format_events`]).
+    pub fn format_range(
+        &self,
+        start_idx: usize,
+        end_idx: usize,
+        options: &DisplayOptions,
+    ) -> Vec<String> {
+        let events: Vec<&Event> = self.events[start_idx..end_idx].iter().collect();
+        options.format_events(&events)
+    }
+
+    /// Find all events whose descriptions contain the given query string (case-insensitive).
+    pub fn query(&self, query: &str) -> Vec<&Event> {
+        let query = query.to_lowercase();
+        query::scan(&self.events, |_, e| {
+            e.description.to_lowercase().contains(&query).then_some(e)
+        })
+    }
+
+    /// Lay out events found by a query for display, highlighting where it matches their
+    /// descriptions.
+    pub fn format_matches(
+        events: &[&Event],
+        expr: &QueryExpr,
+        options: &DisplayOptions,
+    ) -> Vec<String> {
+        let spans: Vec<_> = events
+            .iter()
+            .map(|event| expr.match_spans(&event.description))
+            .collect();
+        options.format_highlighted(events, &spans)
+    }
+
+    /// All tags used in the worldline, sorted.
+    pub fn tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self
+            .events
+            .iter()
+            .flat_map(|e| e.tags.iter().map(String::as_str))
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
+    /// All people named in events, sorted.
+    pub fn people(&self) -> Vec<&str> {
+        let mut people: Vec<&str> = self
+            .events
+            .iter()
+            .flat_map(|e| e.people.iter().map(String::as_str))
+            .collect();
+        people.sort();
+        people.dedup();
+        people
+    }
+
+    /// The events about a person (case-insensitive), as a read-only worldline: their own
+    /// timeline within this one.
+    pub fn person(&self, name: &str) -> WorldLine {
+        self.filtered(&QueryExpr::Person(name.to_lowercase()))
+    }
+
+    /// All years with events, sorted.
+    pub fn years(&self) -> Vec<i32> {
+        let mut years: Vec<i32> = self.events.iter().map(|e| e.date.year).collect();
+        years.sort();
+        years.dedup();
+        years
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dates() {
+        let test_cases = [
+            // CE dates
+            ("CE 2023", (2023, 0, 0)),
+            ("CE 2023-12", (2023, 12, 0)),
+            ("CE 2023-12-25", (2023, 12, 25)),
+            ("1-2-3", (1, 2, 3)),
+            ("AD 2023", (2023, 0, 0)),
+            // BCE dates
+            ("BCE 44", (-44, 0, 0)),
+            ("BC 44", (-44, 0, 0)),
+            ("-44", (-44, 0, 0)),
+            ("-44-12", (-44, 12, 0)),
+            ("-44-12-25", (-44, 12, 25)),
+            // ISO 8601 signed years and basic days
+            ("+0044-03-15", (44, 3, 15)),
+            ("+0000", (-1, 0, 0)),
+            ("20231225", (2023, 12, 25)),
+            ("-00440315", (-44, 3, 15)),
+        ];
+
+        for (input, (year, month, day)) in test_cases {
+            assert_eq!(
+                Date::parse(input).unwrap().0,
+                Date::new(year, month, day).unwrap()
+            );
+        }
+
+        let astronomical = |input| {
+            Date::parse_in(input, YearNumbering::Astronomical)
+                .unwrap()
+                .0
+        };
+        assert_eq!(astronomical("-0043-03-15"), Date::new(-44, 3, 15).unwrap());
+        assert_eq!(astronomical("0"), Date::new(-1, 0, 0).unwrap());
+        assert_eq!(astronomical("BCE 44"), Date::new(-44, 0, 0).unwrap());
+        assert_eq!(astronomical("1969"), Date::new(1969, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        assert!(Date::parse("CE").is_err());
+        assert!(Date::parse("CE 2023-13").is_err()); // Invalid month
+        assert!(Date::parse("CE 2023-12-32").is_err()); // Invalid day
+        assert!(Date::parse("CE 2023-01-01-01").is_err()); // hours???
+        assert!(Date::parse("invalid").is_err());
+        assert!(Date::parse("0000").is_err()); // no year 0
+        assert!(Date::parse("12345").is_err());
+        assert!(Date::parse("202312").is_err()); // no basic months
+        assert!(Date::parse("20231232").is_err());
+        assert!(Date::new(10000, 0, 0).is_err());
+        assert!(Date::new(-10000, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_format_dates() {
+        let test_cases = [
+            // CE dates
+            ((2023, 0, 0), " CE 2023      "),
+            ((2023, 12, 0), " CE 2023-12   "),
+            ((2023, 12, 25), " CE 2023-12-25"),
+            ((1, 2, 3), " CE 0001-02-03"),
+            // BCE dates
+            ((-44, 0, 0), "BCE 0044      "),
+            ((-44, 12, 0), "BCE 0044-12   "),
+            ((-44, 12, 25), "BCE 0044-12-25"),
+            ((-1, 0, 0), "BCE 0001      "),
+        ];
+
+        for ((year, month, day), expected) in test_cases {
+            assert_eq!(Date::new(year, month, day).unwrap().format(true), expected);
+        }
+    }
+
+    #[test]
+    fn test_display_dates() {
+        for (year, month, day) in [(2023, 0, 0), (2023, 12, 25), (-44, 3, 0), (-1, 0, 0)] {
+            let date = Date::new(year, month, day).unwrap();
+            assert_eq!(Date::parse(&date.to_string()).unwrap().0, date);
+        }
+        assert_eq!(Date::new(-44, 3, 0).unwrap().to_string(), "-0044-03");
+    }
+
+    #[test]
+    fn test_date_next() {
+        assert_eq!(
+            Date::new(2023, 11, 30).unwrap().next(),
+            Date::new(2023, 12, 0).unwrap()
+        );
+        assert_eq!(
+            Date::new(2023, 12, 31).unwrap().next(),
+            Date::new(2024, 0, 0).unwrap()
+        );
+        assert_eq!(
+            Date::new(2023, 12, 0).unwrap().next(),
+            Date::new(2024, 0, 0).unwrap()
+        );
+        assert_eq!(
+            Date::new(2023, 0, 0).unwrap().next(),
+            Date::new(2024, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_leap_years() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert!(Date::new(2000, 2, 29).is_ok());
+        assert!(Date::new(1900, 2, 29).is_err());
+        assert!(Date::new(2023, 2, 29).is_err());
+        // 1 BCE is astronomical year 0, which is a leap year
+        assert!(Date::new(-1, 2, 29).is_ok());
+        assert_eq!(
+            Date::new(2024, 2, 28).unwrap().next(),
+            Date::new(2024, 2, 29).unwrap()
+        );
+        assert_eq!(
+            Date::new(-1, 12, 31).unwrap().next(),
+            Date::new(1, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_date_difference() {
+        let diff = |a: (i32, u8, u8), b: (i32, u8, u8)| {
+            let a = Date::new(a.0, a.1, a.2).unwrap();
+            let b = Date::new(b.0, b.1, b.2).unwrap();
+            a.difference(&b)
+        };
+
+        let d = diff((1914, 6, 28), (1918, 11, 11));
+        assert_eq!((d.years, d.months, d.days), (4, Some(4), Some(14)));
+        assert_eq!(d.to_string(), "4 years, 4 months, 14 days");
+        // order doesn't matter
+        assert_eq!(diff((1918, 11, 11), (1914, 6, 28)), d);
+
+        let d = diff((2023, 1, 31), (2023, 3, 1));
+        assert_eq!((d.years, d.months, d.days), (0, Some(1), Some(1)));
+
+        // imprecise dates give imprecise differences
+        let d = diff((1914, 6, 28), (1945, 0, 0));
+        assert_eq!((d.years, d.months, d.days), (31, None, None));
+        assert_eq!(d.to_string(), "31 years");
+
+        // there is no year 0
+        assert_eq!(diff((-1, 0, 0), (1, 0, 0)).years, 1);
+        let d = diff((-44, 3, 15), (14, 8, 19));
+        assert_eq!((d.years, d.months, d.days), (57, Some(5), Some(4)));
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let test_cases = [
+            ("CE 2023 Some event", (2023, 0, 0), "Some event"),
+            (" CE 2023 Some event", (2023, 0, 0), "Some event"),
+            ("2023-12-25 Christmas Day", (2023, 12, 25), "Christmas Day"),
+            ("-44 et tu", (-44, 0, 0), "et tu"),
+        ];
+
+        for (input, (year, month, day), desc) in test_cases {
+            // TODO test these with an actual test?
+            //println!("{}", Event::parse(input).unwrap().format_for_display(true));
+            //println!("{}", Event::parse(input).unwrap().format_for_display(false));
+            //println!("{}", Event::parse(input).unwrap().format_for_file());
+
+            assert_eq!(
+                Event::parse(input).unwrap(),
+                Event::new(Date::new(year, month, day).unwrap(), desc.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_write() {
+        let contents = "BCE 0044-03-15 Ides of March\n CE 1969-07-20 Moon landing\n";
+        let mut worldline = WorldLine::from_reader(contents.as_bytes()).unwrap();
+        let mut written = Vec::new();
+        worldline.write_to(&mut written).unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), contents);
+        assert_eq!(contents.parse::<WorldLine>().unwrap().to_string(), contents);
+
+        let other =
+            WorldLine::from_reader(" CE 1066 Hastings\n CE 1969-07-20 Moon landing\n".as_bytes());
+        assert_eq!(worldline.import(other.unwrap()), 1);
+        assert_eq!(worldline.len(), 3);
+        assert_eq!(worldline.years(), vec![-44, 1066, 1969]);
+
+        assert_eq!(worldline.head(1)[0].description, "Ides of March");
+        assert_eq!(worldline.tail(2)[0].description, "Hastings");
+        assert_eq!(worldline.tail(10).len(), 3);
+        assert!(worldline.head(0).is_empty());
+    }
+
+    #[test]
+    fn test_add_events_sorted() {
+        let lines = [
+            "1914 B",
+            "1066 Hastings",
+            "1914 A",
+            "-44 Ides",
+            "2001 Odyssey",
+        ];
+        let events: Vec<Event> = lines.iter().map(|l| Event::parse(l).unwrap()).collect();
+        let mut one_by_one: WorldLine = "1914 A\n1969 Moon landing\n".parse().unwrap();
+        let mut batch = one_by_one.clone();
+        for event in events.clone() {
+            one_by_one.add_event(event);
+        }
+        batch.add_events_sorted(events);
+        assert_eq!(batch.events(), one_by_one.events());
+        assert_eq!(batch.years(), vec![-44, 1066, 1914, 1969, 2001]);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let worldline: WorldLine = "1346 A\n1347-01 B\n1348 C\n1349 D\n1350 E\n"
+            .parse()
+            .unwrap();
+        let year = |y: &str| query::parse_whole_date(y).unwrap();
+        assert_eq!(worldline.neighbors(&year("1347"), 1), (0..1, 2..3));
+        assert_eq!(worldline.neighbors(&year("1347-06"), 2), (0..2, 2..4));
+        assert_eq!(worldline.neighbors(&year("1400"), 2), (3..5, 5..5));
+
+        let descriptions = |events: &[Event]| -> String {
+            events.iter().map(|e| e.description.as_str()).collect()
+        };
+        assert_eq!(descriptions(worldline.context(0, 1)), "AB");
+        assert_eq!(descriptions(worldline.context(2, 1)), "BCD");
+        assert_eq!(descriptions(worldline.context(4, 2)), "CDE");
+        assert_eq!(descriptions(worldline.context(2, usize::MAX)), "ABCDE");
+        assert_eq!(descriptions(worldline.context(5, 1)), "");
+    }
+
+    #[test]
+    fn test_iteration() {
+        let worldline: WorldLine = ["1969 Moon landing", "BCE 44 Ides of March", "1066 Hastings"]
+            .into_iter()
+            .map(|line| Event::parse(line).unwrap())
+            .collect();
+        assert_eq!(worldline.years(), vec![-44, 1066, 1969]);
+        assert!(!worldline.is_read_only());
+        assert_eq!(worldline.events().len(), 3);
+        assert_eq!(worldline.get(1), worldline.iter().nth(1));
+
+        let mut descriptions = Vec::new();
+        for event in &worldline {
+            descriptions.push(event.description.as_str());
+        }
+        assert_eq!(descriptions, ["Ides of March", "Hastings", "Moon landing"]);
+        let owned: Vec<Event> = worldline.into_iter().collect();
+        assert_eq!(owned[2].description, "Moon landing");
+        assert!(WorldLine::from_iter(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_add_events() {
+        let mut worldline = WorldLine::default();
+        let lines = "# battles\n1066 Hastings\tid=hastings\n\n1415-10-25 Agincourt\n";
+        let report = worldline.add_events(lines.lines()).unwrap();
+        assert_eq!(
+            report,
+            BatchReport {
+                added: 2,
+                skipped: 2,
+                warnings: Vec::new()
+            }
+        );
+
+        // all or nothing
+        let lines = "1346 Crecy\n1356-13 Poitiers\n1066 Again\tid=hastings\n";
+        let errors = worldline.add_events(lines.lines()).unwrap_err();
+        assert_eq!(errors.iter().map(|e| e.0).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(worldline.len(), 2);
+
+        // lenient: dates written out are read, and what can't be read is skipped
+        let lines = "1346 Crecy
+September 19, 1356: Poitiers
+1356-13 Poitiers
+";
+        let report = worldline
+            .add_events_with(lines.lines(), ParseMode::Lenient)
+            .unwrap();
+        assert_eq!(report.added, 2);
+        assert_eq!(
+            report.warnings[0],
+            (2, "Read the date as 1356-09-19".to_string())
+        );
+        assert_eq!(report.warnings[1].0, 3);
+        assert!(report.warnings[1].1.starts_with("Skipped: "));
+        assert_eq!(worldline.get(2).unwrap().description, "Poitiers");
+        let lines = "1346 Crecy
+1356-13 Poitiers
+";
+        let (parsed, warnings) = WorldLine::parse_with(lines, ParseMode::Lenient).unwrap();
+        assert_eq!((parsed.len(), warnings.len()), (1, 1));
+        assert!(WorldLine::parse_with(lines, ParseMode::Strict).is_err());
+    }
+
+    #[test]
+    fn test_replace_range() {
+        let mut worldline: WorldLine = "1066 Hastings
+1215 Magna Carta
+1346 Crecy
+1415 Agincourt
+"
+        .parse()
+        .unwrap();
+        let mut lines = worldline.range_lines(1..3);
+        assert_eq!(
+            lines,
+            [" CE 1215       Magna Carta", " CE 1346       Crecy"]
+        );
+        lines[0] = " CE 1215-06-15 Magna Carta sealed\ttags=law".to_string();
+        lines.remove(1);
+        lines.push("1356 Poitiers".to_string());
+        lines.push("1000 Before the range".to_string());
+        let report = worldline
+            .replace_range(1..3, lines.iter().map(String::as_str))
+            .unwrap();
+        assert_eq!(report.added, 3);
+        assert_eq!(worldline.years(), vec![1000, 1066, 1215, 1356, 1415]);
+        assert_eq!(worldline.get(2).unwrap().tags, vec!["law"]);
+
+        let before = worldline.to_string();
+        assert!(worldline
+            .replace_range(1..3, ["1300 Fine", "1300-13 Bad month"].into_iter())
+            .is_err());
+        assert_eq!(worldline.to_string(), before);
+    }
+
+    #[test]
+    fn test_find_and_edit() {
+        let mut worldline: WorldLine =
+            "#worldline v2\n1066 Hastings\tid=hastings\n1215 Magna Carta\n1415 Agincourt\n"
+                .parse()
+                .unwrap();
+        assert_eq!(worldline.find("hastings"), vec![0]);
+        assert_eq!(worldline.find("#2"), vec![1]);
+        assert_eq!(worldline.find("3"), vec![2]);
+        // out of range numbers are searched for as text
+        assert!(worldline.find("#4").is_empty());
+
+        let mut event = worldline.get(0).unwrap().clone();
+        event.date = Date::new(1500, 0, 0).unwrap();
+        assert_eq!(worldline.replace(0, event), 2);
+        assert_eq!(worldline.find_id("hastings"), Some(2));
+        assert_eq!(worldline.remove(0).description, "Magna Carta");
+        assert_eq!(worldline.years(), vec![1415, 1500]);
+    }
+
+    #[test]
+    fn test_invalid_events() {
+        assert!(Event::parse("").is_err());
+        assert!(Event::parse("Invalid date Some event").is_err());
+        assert!(Event::parse("CE 2023-13-01 Invalid month").is_err());
+    }
+
+    #[test]
+    fn test_event_categories() {
+        let event = Event::parse("1914-07-28 [war] WWI begins").unwrap();
+        assert_eq!(event.category.as_deref(), Some("war"));
+        assert_eq!(event.description, "WWI begins");
+        assert_eq!(event.format_for_file(), " CE 1914-07-28 [war] WWI begins");
+
+        for description in [
+            "[citation needed] Fact",
+            "[war]Bad",
+            "[] Empty",
+            "No [war] here",
+        ] {
+            let event = Event::parse(&format!("1914 {}", description)).unwrap();
+            assert_eq!(event.category, None);
+            assert_eq!(event.description, description);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Month names in other languages, for reading dates written like "15 mars 1789" and for the
+//! long date format.
+//!
+//! The locale is chosen per call: with [`Date::parse_natural_in`](crate::Date::parse_natural_in)
+//! for reading dates, and in the [`DisplayOptions`](crate::display::DisplayOptions) for the
+//! long format. English month names are read whatever the locale, so dates written in English
+//! keep working.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    English,
+    French,
+    German,
+    Spanish,
+    Italian,
+    Portuguese,
+    Dutch,
+}
+
+impl Locale {
+    /// Parse a language code, e.g. "fr", or a locale name like "fr_FR.UTF-8".
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let language = s
+            .trim()
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match language.as_str() {
+            "en" => Ok(Locale::English),
+            "fr" => Ok(Locale::French),
+            "de" => Ok(Locale::German),
+            "es" => Ok(Locale::Spanish),
+            "it" => Ok(Locale::Italian),
+            "pt" => Ok(Locale::Portuguese),
+            "nl" => Ok(Locale::Dutch),
+            _ => Err(format!(
+                "Invalid locale '{}', expected en, fr, de, es, it, pt, or nl",
+                s
+            )),
+        }
+    }
+
+    /// The names of the months, as written in the middle of a sentence.
+    pub fn month_names(&self) -> [&'static str; 12] {
+        match self {
+            Locale::English => crate::display::MONTH_NAMES,
+            Locale::French => [
+                "janvier",
+                "février",
+                "mars",
+                "avril",
+                "mai",
+                "juin",
+                "juillet",
+                "août",
+                "septembre",
+                "octobre",
+                "novembre",
+                "décembre",
+            ],
+            Locale::German => [
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ],
+            Locale::Spanish => [
+                "enero",
+                "febrero",
+                "marzo",
+                "abril",
+                "mayo",
+                "junio",
+                "julio",
+                "agosto",
+                "septiembre",
+                "octubre",
+                "noviembre",
+                "diciembre",
+            ],
+            Locale::Italian => [
+                "gennaio",
+                "febbraio",
+                "marzo",
+                "aprile",
+                "maggio",
+                "giugno",
+                "luglio",
+                "agosto",
+                "settembre",
+                "ottobre",
+                "novembre",
+                "dicembre",
+            ],
+            Locale::Portuguese => [
+                "janeiro",
+                "fevereiro",
+                "março",
+                "abril",
+                "maio",
+                "junho",
+                "julho",
+                "agosto",
+                "setembro",
+                "outubro",
+                "novembro",
+                "dezembro",
+            ],
+            Locale::Dutch => [
+                "januari",
+                "februari",
+                "maart",
+                "april",
+                "mei",
+                "juni",
+                "juli",
+                "augustus",
+                "september",
+                "oktober",
+                "november",
+                "december",
+            ],
+        }
+    }
+
+    /// Words that can come between the parts of a date and mean nothing on their own, e.g.
+    /// "the" and "of" in "the 4th of July".
+    pub(crate) fn filler_words(&self) -> &'static [&'static str] {
+        match self {
+            Locale::English => &["the", "of"],
+            Locale::French => &["le"],
+            Locale::Spanish | Locale::Portuguese => &["de", "del"],
+            Locale::Italian => &["il"],
+            Locale::German | Locale::Dutch => &[],
+        }
+    }
+
+    /// The month (1-12) named by a lowercase word: a whole name, or the start of just one
+    /// name that's at least three letters long, e.g. "sept".
+    pub(crate) fn month(&self, word: &str) -> Option<u8> {
+        let names = self.month_names().map(str::to_lowercase);
+        if let Some(i) = names.iter().position(|name| name == word) {
+            return Some(i as u8 + 1);
+        }
+        if word.chars().count() < 3 {
+            return None;
+        }
+        let mut matches = names
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.starts_with(word));
+        match (matches.next(), matches.next()) {
+            (Some((i, _)), None) => Some(i as u8 + 1),
+            _ => None,
+        }
+    }
+
+    /// A month of a year (already formatted), e.g. "March 1789" or "marzo de 1789".
+    pub fn format_month(&self, month: u8, year: &str) -> String {
+        let name = self.month_names()[month as usize - 1];
+        match self {
+            Locale::Spanish | Locale::Portuguese => format!("{} de {}", name, year),
+            _ => format!("{} {}", name, year),
+        }
+    }
+
+    /// A day of a year (already formatted), e.g. "March 15, 1789" or "15. März 1789".
+    pub fn format_day(&self, month: u8, day: u8, year: &str) -> String {
+        let name = self.month_names()[month as usize - 1];
+        match self {
+            Locale::English => format!("{} {}, {}", name, day, year),
+            Locale::German => format!("{}. {} {}", day, name, year),
+            Locale::Spanish | Locale::Portuguese => format!("{} de {} de {}", day, name, year),
+            _ => format!("{} {} {}", day, name, year),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locales() {
+        assert_eq!(Locale::parse("fr_FR.UTF-8"), Ok(Locale::French));
+        assert_eq!(Locale::parse("DE"), Ok(Locale::German));
+        assert!(Locale::parse("klingon").is_err());
+        assert_eq!(Locale::French.month("mars"), Some(3));
+        assert_eq!(Locale::French.month("déc"), Some(12));
+        // juin or juillet
+        assert_eq!(Locale::French.month("jui"), None);
+        assert_eq!(Locale::English.month("sept"), Some(9));
+        assert_eq!(Locale::German.format_day(3, 14, "1879"), "14. März 1879");
+        assert_eq!(
+            Locale::Spanish.format_day(7, 14, "1789"),
+            "14 de julio de 1789"
+        );
+        assert_eq!(Locale::French.format_month(7, "1789"), "juillet 1789");
+    }
+}
@@ -0,0 +1,252 @@
+//! Reading large text worldlines without loading them, for read-only commands like `wl show`.
+//!
+//! A [`MappedFile`] memory-maps a worldline file and only parses the date of each event up
+//! front. Its [`EventRef`]s borrow the rest of their lines from the map, so finding a range of
+//! dates in a file of many megabytes allocates little more than a list of dates, and only the
+//! events that are shown are parsed into [`Event`]s.
+
+use crate::format::{self, FormatVersion, V2_HEADER};
+use crate::order::PartialDateOrder;
+use crate::range::DateRange;
+use crate::{Date, Event, WorldLine};
+use memmap2::Mmap;
+use std::borrow::Cow;
+use std::fs::File;
+use std::ops::Range;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// A worldline file mapped into memory.
+pub struct MappedFile {
+    map: Mmap,
+    file: File,
+    /// The file's length and modification time when it was mapped.
+    stamp: (u64, Option<SystemTime>),
+}
+
+/// An event in a [`MappedFile`] whose date has been parsed, but whose other data is still text
+/// in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventRef<'a> {
+    pub date: Date,
+    line: &'a str,
+    /// Where the description starts in the line.
+    offset: usize,
+    version: FormatVersion,
+}
+
+impl MappedFile {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        let stamp = stamp(&file)?;
+        // SAFETY: this isn't guaranteed. The map's contents change if another process writes
+        // the file while it's mapped, as saving a worldline does in place. Reading past the
+        // end of a file truncated meanwhile raises SIGBUS and kills the process, and other
+        // writes can change text that has already been checked. This is why the mmap feature
+        // is opt-in; `worldline` at least reports files that changed while they were read
+        // instead of returning a mix of their old and new contents.
+        let map = unsafe { Mmap::map(&file) }.map_err(|e| e.to_string())?;
+        Ok(Self { map, file, stamp })
+    }
+
+    /// An error if the file was written since it was mapped, when what was read from it may
+    /// mix its old and new contents.
+    fn check_unchanged(&self) -> Result<(), String> {
+        if stamp(&self.file)? == self.stamp {
+            Ok(())
+        } else {
+            Err("The file changed while it was being read; try again".to_string())
+        }
+    }
+
+    /// The front matter of the file, empty for a v1 file.
+    pub fn front_matter(&self) -> Vec<(String, String)> {
+        // only the start of the file is read
+        let mut lines = self
+            .map
+            .split(|&byte| byte == b'\n')
+            .map(String::from_utf8_lossy);
+        if lines.next().as_deref().map(str::trim_end) != Some(V2_HEADER) {
+            return Vec::new();
+        }
+        lines
+            .map_while(|line| {
+                let (key, value) = line.strip_prefix('#')?.split_once(':')?;
+                Some((key.trim().to_string(), value.trim().to_string()))
+            })
+            .collect()
+    }
+
+    /// How the file sorts dates less precise than a day, from its front matter.
+    pub fn partial_date_order(&self) -> PartialDateOrder {
+        PartialDateOrder::from_front_matter(&self.front_matter())
+    }
+
+    /// Some of the file's events parsed into a read-only worldline with the file's front
+    /// matter. They're kept in the order given rather than re-sorted, so events on the same
+    /// date stay in the file's order, e.g. with `#same_day_order: insertion`. Fails if the
+    /// file was written since it was mapped.
+    pub fn worldline(&self, events: &[EventRef]) -> Result<WorldLine, String> {
+        let front_matter = self.front_matter();
+        let version = match events.first() {
+            Some(event) => event.version,
+            None if front_matter.is_empty() => FormatVersion::V1,
+            None => FormatVersion::V2,
+        };
+        let events = events
+            .iter()
+            .map(EventRef::to_event)
+            .collect::<Result<_, _>>()?;
+        self.check_unchanged()?;
+        Ok(WorldLine {
+            events,
+            version,
+            front_matter,
+            read_only: true,
+        })
+    }
+
+    /// The events of the file, in the order they're written in it. Only their dates are
+    /// checked: other errors in a line are found by [`EventRef::to_event`].
+    pub fn events(&self) -> Result<Vec<EventRef<'_>>, String> {
+        let contents = std::str::from_utf8(&self.map).map_err(|e| e.to_string())?;
+        let mut lines = contents.lines().enumerate().peekable();
+        let version = match lines.peek() {
+            Some((_, line)) if line.trim_end() == V2_HEADER => {
+                lines.next();
+                // front matter
+                while lines.next_if(|(_, line)| line.starts_with('#')).is_some() {}
+                FormatVersion::V2
+            }
+            Some((_, line)) if line.starts_with("#worldline") => {
+                return Err(format!("Unsupported file version: {}", line));
+            }
+            _ => FormatVersion::V1,
+        };
+        lines
+            .map(|(i, line)| {
+                let (date, offset) =
+                    Date::parse(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+                Ok(EventRef {
+                    date,
+                    line,
+                    offset,
+                    version,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The length and modification time of an open file.
+fn stamp(file: &File) -> Result<(u64, Option<SystemTime>), String> {
+    let metadata = file.metadata().map_err(|e| e.to_string())?;
+    Ok((metadata.len(), metadata.modified().ok()))
+}
+
+impl<'a> EventRef<'a> {
+    /// The description as written in the file, with any category and place. It's only copied
+    /// if it has escaped characters.
+    pub fn text(&self) -> Cow<'a, str> {
+        let rest = &self.line[self.offset..];
+        match self.version {
+            FormatVersion::V1 => Cow::Borrowed(rest),
+            FormatVersion::V2 => {
+                let text = rest.split('\t').next().unwrap_or_default();
+                if text.contains('\\') {
+                    Cow::Owned(format::unescape(text))
+                } else {
+                    Cow::Borrowed(text)
+                }
+            }
+        }
+    }
+
+    /// Whether the event is archived, see [`crate::archive`].
+    pub fn is_archived(&self) -> bool {
+        self.version == FormatVersion::V2
+            && self
+                .line
+                .split('\t')
+                .skip(1)
+                .any(|field| field == "archived=true")
+    }
+
+    /// Parse the whole event.
+    pub fn to_event(&self) -> Result<Event, String> {
+        match self.version {
+            FormatVersion::V1 => Event::parse(self.line),
+            FormatVersion::V2 => format::parse_event_v2(self.line),
+        }
+    }
+}
+
+/// The indices of the events in a range, like [`crate::WorldLine::date_range`], in a file
+/// sorted in `order`.
+pub fn date_range(events: &[EventRef], range: &DateRange, order: PartialDateOrder) -> Range<usize> {
+    events.partition_point(|e| order.is_before(&e.date, range.start()))
+        ..events.partition_point(|e| order.is_before(&e.date, range.after()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapped_file() {
+        let contents = "#worldline v2\n\
+                        #title: Wars\n\
+                        1914-07-28 [war] WWI begins\tend=1918-11-11\n\
+                        1917 Revolutions\\tand mutinies\tarchived=true\n\
+                        1939-09-01 WWII begins\n";
+        let path = std::env::temp_dir().join(format!("wl-mapped-{}.txt", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let file = MappedFile::open(&path).unwrap();
+        let events = file.events().unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(
+            events[0].text(),
+            Cow::Borrowed("[war] WWI begins")
+        ));
+        assert_eq!(events[1].text(), "Revolutions\tand mutinies");
+        assert!(events[1].is_archived() && !events[0].is_archived());
+
+        let worldline: WorldLine = contents.parse().unwrap();
+        for (event_ref, event) in events.iter().zip(worldline.events()) {
+            assert_eq!(&event_ref.to_event().unwrap(), event);
+        }
+        let range = DateRange::parse("1915..1939").unwrap();
+        assert_eq!(
+            date_range(&events, &range, file.partial_date_order()),
+            worldline.date_range(&range)
+        );
+
+        let shown = file.worldline(&events[1..]).unwrap();
+        assert_eq!(shown.len(), 2);
+        assert_eq!(shown.front_matter(), worldline.front_matter());
+
+        std::fs::write(
+            &path,
+            "#worldline v2\n#same_day_order: insertion\n1950 Zeta\n1950 Alpha\n",
+        )
+        .unwrap();
+        let file = MappedFile::open(&path).unwrap();
+        let shown = file.worldline(&file.events().unwrap()).unwrap();
+        let descriptions: Vec<&str> = shown.iter().map(|e| e.description.as_str()).collect();
+        assert_eq!(descriptions, ["Zeta", "Alpha"]);
+
+        // growing the file, unlike truncating it, is safe while it's mapped
+        let file = MappedFile::open(&path).unwrap();
+        let mut appending = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        std::io::Write::write_all(&mut appending, b"1951 Beta\n").unwrap();
+        assert!(file.worldline(&[]).is_err());
+
+        std::fs::write(&path, "1914 WWI begins\nnonsense\n").unwrap();
+        let file = MappedFile::open(&path).unwrap();
+        assert!(file.events().unwrap_err().starts_with("line 2: "));
+        std::fs::remove_file(path).unwrap();
+    }
+}
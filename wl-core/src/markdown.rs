@@ -0,0 +1,117 @@
+//! Importing events from Markdown notes, where timelines are often kept as bullet lists:
+//!
+//! ```markdown
+//! ## Space race
+//! - 1957-10-04: Sputnik 1
+//! * **1969-07-20** — Moon landing
+//! - [x] July 20, 1969: Apollo 11 lands
+//! - 44 BCE – Caesar assassinated
+//! ```
+//!
+//! Each bullet (`-`, `*`, `+` or `1.`, optionally a task `[ ]`) should start with a date, in the
+//! file format or written in English (see [`Date::parse_natural`]), separated from the
+//! description by a colon or dash. Other lines are ignored, and bullets without a date are
+//! skipped.
+
+use crate::query::parse_whole_date;
+use crate::{Date, Event};
+
+/// The events read from Markdown by [`parse`].
+#[derive(Debug, Default)]
+pub struct MarkdownImport {
+    pub events: Vec<Event>,
+    /// Bullet lines that couldn't be read as events, with their (1-based) line numbers.
+    pub skipped: Vec<(usize, String)>,
+}
+
+/// The text of a bullet list item, without its marker and any task checkbox.
+fn bullet_item(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let item = line
+        .strip_prefix(['-', '*', '+'])
+        .or_else(|| {
+            let digits = line.find(|c: char| !c.is_ascii_digit())?;
+            (digits > 0).then(|| line[digits..].strip_prefix(['.', ')']))?
+        })?
+        .strip_prefix([' ', '\t'])?
+        .trim();
+    let item = ["[ ]", "[x]", "[X]"]
+        .iter()
+        .find_map(|checkbox| item.strip_prefix(checkbox))
+        .unwrap_or(item);
+    Some(item.trim())
+}
+
+/// A date written in a bullet, possibly emphasized, e.g. "**1969-07-20**" or "44 BCE".
+fn bullet_date(s: &str) -> Option<Date> {
+    let s = s.trim().trim_matches(['*', '_', '`']).trim();
+    parse_whole_date(s).or_else(|_| Date::parse_natural(s)).ok()
+}
+
+/// Read a bullet list item as an event, with the date before a separator, or at least at the
+/// start in the file format.
+fn bullet_event(item: &str) -> Option<Event> {
+    for separator in [": ", " — ", " – ", " - ", " | "] {
+        let Some((date, description)) = item.split_once(separator) else {
+            continue;
+        };
+        if let Some(date) = bullet_date(date) {
+            return Some(Event::from_text(date, description.trim()));
+        }
+    }
+    let (date, idx) = Date::parse(item).ok()?;
+    let description = item[idx..].trim_start_matches([':', '-', '—', '–', ' ']);
+    Some(Event::from_text(date, description.trim()))
+}
+
+/// Read the events in Markdown bullet lists.
+pub fn parse(contents: &str) -> MarkdownImport {
+    let mut import = MarkdownImport::default();
+    for (i, line) in contents.lines().enumerate() {
+        let Some(item) = bullet_item(line) else {
+            continue;
+        };
+        match bullet_event(item) {
+            Some(event) if !event.description.is_empty() => import.events.push(event),
+            _ => import.skipped.push((i + 1, line.to_string())),
+        }
+    }
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_import() {
+        let import = parse(
+            "# Timeline\n\
+             \n\
+             Some notes about the space race.\n\
+             - 1957-10-04: Sputnik 1\n\
+             * **1969-07-20** — Moon landing\n\
+             \u{20}   + [x] July 20, 1969: Apollo 11 lands\n\
+             1. 44 BCE – [politics] Caesar assassinated\n\
+             - 1989-11-09 Berlin Wall falls\n\
+             - buy milk\n\
+             - 1914:\n\
+             -no space\n",
+        );
+        let events: Vec<String> = import.events.iter().map(Event::format_for_file).collect();
+        assert_eq!(
+            events,
+            vec![
+                " CE 1957-10-04 Sputnik 1",
+                " CE 1969-07-20 Moon landing",
+                " CE 1969-07-20 Apollo 11 lands",
+                "BCE 0044       [politics] Caesar assassinated",
+                " CE 1989-11-09 Berlin Wall falls",
+            ]
+        );
+        assert_eq!(
+            import.skipped,
+            vec![(9, "- buy milk".to_string()), (10, "- 1914:".to_string())]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Longer Markdown notes on events, kept as files next to the worldline rather than in it.
+//!
+//! The notes of `history.txt` are in `history.txt.notes/`, one file per event named after the
+//! event's id, e.g. `history.txt.notes/ww1.md`. Events need an id to have a note, so
+//! [`WorldLine::ensure_id`] gives one to events that don't have one.
+
+use crate::WorldLine;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The directory of a worldline file's notes. It's only created when a note is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteDir {
+    pub path: PathBuf,
+}
+
+impl NoteDir {
+    /// The notes directory of a worldline file.
+    pub fn for_file(worldline_path: impl AsRef<Path>) -> Self {
+        let mut path = worldline_path.as_ref().as_os_str().to_owned();
+        path.push(".notes");
+        Self {
+            path: PathBuf::from(path),
+        }
+    }
+
+    /// The file an event's note is (or would be) in.
+    pub fn note_path(&self, id: &str) -> Result<PathBuf, String> {
+        let invalid = id.is_empty()
+            || id.starts_with('.')
+            || id.contains(|c: char| c == '/' || c == '\\' || c.is_control());
+        if invalid {
+            return Err(format!(
+                "The id '{}' can't be used as a note's file name",
+                id
+            ));
+        }
+        Ok(self.path.join(format!("{}.md", id)))
+    }
+
+    /// The ids of the events with notes. A missing directory has none.
+    pub fn ids(&self) -> BTreeSet<String> {
+        let Ok(entries) = fs::read_dir(&self.path) else {
+            return BTreeSet::new();
+        };
+        entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                name.strip_suffix(".md").map(str::to_string)
+            })
+            .collect()
+    }
+}
+
+/// An id made from a description, e.g. "battle-of-hastings" for "Battle of Hastings".
+fn id_from_description(description: &str) -> String {
+    let words: Vec<String> = description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .take(6)
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        "event".to_string()
+    } else {
+        words.join("-")
+    }
+}
+
+impl WorldLine {
+    /// The id of the event at an index, giving it one made from its description if it has
+    /// none. Returns the id and whether it's new.
+    pub fn ensure_id(&mut self, idx: usize) -> (String, bool) {
+        if let Some(id) = &self.events[idx].id {
+            return (id.clone(), false);
+        }
+        let base = id_from_description(&self.events[idx].description);
+        let mut id = base.clone();
+        let mut n = 1;
+        while self.find_id(&id).is_some() {
+            n += 1;
+            id = format!("{}-{}", base, n);
+        }
+        self.events[idx].id = Some(id.clone());
+        (id, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notes() {
+        let mut worldline: WorldLine = "#worldline v2\n\
+                                        1066-10-14 Battle of Hastings\tid=battle-of-hastings\n\
+                                        1066-10-14 Battle of Hastings\n\
+                                        1914 WWI begins\tid=ww1\n"
+            .parse()
+            .unwrap();
+        assert_eq!(worldline.ensure_id(2), ("ww1".to_string(), false));
+        assert_eq!(
+            worldline.ensure_id(1),
+            ("battle-of-hastings-2".to_string(), true)
+        );
+        assert_eq!(worldline.find_id("battle-of-hastings-2"), Some(1));
+
+        let dir = std::env::temp_dir().join(format!("wl-notes-{}", std::process::id()));
+        let notes = NoteDir::for_file(dir.join("history.txt"));
+        assert_eq!(notes.path, dir.join("history.txt.notes"));
+        assert!(notes.ids().is_empty());
+        let path = notes.note_path("ww1").unwrap();
+        fs::create_dir_all(&notes.path).unwrap();
+        fs::write(&path, "# WWI begins\n").unwrap();
+        assert_eq!(notes.ids(), BTreeSet::from(["ww1".to_string()]));
+        assert!(notes.note_path("../escape").is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
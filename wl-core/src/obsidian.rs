@@ -0,0 +1,210 @@
+//! Exporting a worldline as Markdown notes for Obsidian, or any Zettelkasten that links notes
+//! with `[[wiki-links]]`.
+//!
+//! Each note starts with YAML front matter giving its date and tags, so the notes can be
+//! queried alongside the rest of a knowledge base, and links to the notes before and after it.
+
+use crate::{Date, Event, WorldLine};
+use std::collections::BTreeMap;
+
+/// What each exported note covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoteUnit {
+    /// All the events of a year, as a list.
+    #[default]
+    Year,
+    /// A single event, with its notes and sources.
+    Event,
+}
+
+impl NoteUnit {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "year" => Ok(NoteUnit::Year),
+            "event" => Ok(NoteUnit::Event),
+            _ => Err(format!("Invalid note unit '{}', expected year or event", s)),
+        }
+    }
+}
+
+/// A Markdown note, to be written to `<name>.md`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObsidianNote {
+    pub name: String,
+    pub contents: String,
+}
+
+/// The name of a year's note, e.g. "1914" or "44 BCE".
+fn year_name(year: i32) -> String {
+    if year < 0 {
+        format!("{} BCE", -year)
+    } else {
+        year.to_string()
+    }
+}
+
+/// An event's note name: its date and description, without characters that aren't allowed
+/// in file names or break wiki-links.
+fn event_name(event: &Event) -> String {
+    let description: String = event
+        .description
+        .chars()
+        .filter(|c| !"\\/:*?\"<>|#^[]".contains(*c))
+        .collect();
+    format!("{} {}", event.date, description.trim())
+}
+
+/// YAML front matter with a date and the tags, if any.
+fn front_matter(date: &str, tags: &[&str]) -> String {
+    let mut yaml = format!("---\ndate: {}\n", date);
+    if !tags.is_empty() {
+        yaml += &format!("tags: [{}]\n", tags.join(", "));
+    }
+    yaml + "---\n"
+}
+
+/// Links to the notes before and after a note, where there are any.
+fn adjacent_links(previous: Option<&str>, next: Option<&str>) -> String {
+    let links: Vec<String> = [
+        previous.map(|name| format!("← [[{}]]", name)),
+        next.map(|name| format!("[[{}]] →", name)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if links.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n\n", links.join(" | "))
+    }
+}
+
+fn year_note(year: i32, events: &[&Event], previous: Option<&str>, next: Option<&str>) -> String {
+    let mut tags: Vec<&str> = events
+        .iter()
+        .flat_map(|e| e.tags.iter().map(String::as_str))
+        .collect();
+    tags.sort();
+    tags.dedup();
+    let mut contents = front_matter(&Date::new(year, 0, 0).unwrap().to_string(), &tags);
+    contents += &format!("\n# {}\n\n", year_name(year));
+    contents += &adjacent_links(previous, next);
+    for event in events {
+        contents += &format!("- {} {}\n", event.date, event.full_description());
+    }
+    contents
+}
+
+fn event_note(event: &Event, previous: Option<&str>, next: Option<&str>) -> String {
+    let tags: Vec<&str> = event.tags.iter().map(String::as_str).collect();
+    let mut contents = front_matter(&event.date.to_string(), &tags);
+    contents += &format!("\n# {}\n\n", event.full_description());
+    contents += &adjacent_links(previous, next);
+    if let Some(end) = &event.end {
+        contents += &format!("Until {}\n\n", end);
+    }
+    for note in &event.notes {
+        contents += &format!("- {}: {}\n", note.timestamp, note.text);
+    }
+    if !event.sources.is_empty() {
+        contents += "\n## Sources\n\n";
+        for source in &event.sources {
+            contents += &format!("- {}\n", source);
+        }
+    }
+    contents
+}
+
+impl WorldLine {
+    /// The events as Markdown notes, one per year with events or one per event, each linking
+    /// to the notes before and after it.
+    pub fn to_obsidian(&self, unit: NoteUnit) -> Vec<ObsidianNote> {
+        match unit {
+            NoteUnit::Year => {
+                let mut years: BTreeMap<i32, Vec<&Event>> = BTreeMap::new();
+                for event in &self.events {
+                    years.entry(event.date.year).or_default().push(event);
+                }
+                let names: Vec<String> = years.keys().copied().map(year_name).collect();
+                years
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (year, events))| ObsidianNote {
+                        name: names[i].clone(),
+                        contents: year_note(
+                            *year,
+                            events,
+                            i.checked_sub(1).map(|i| names[i].as_str()),
+                            names.get(i + 1).map(String::as_str),
+                        ),
+                    })
+                    .collect()
+            }
+            NoteUnit::Event => {
+                let mut names: Vec<String> = Vec::new();
+                for event in &self.events {
+                    // events with the same date and description need different files
+                    let name = event_name(event);
+                    let mut unique = name.clone();
+                    let mut n = 1;
+                    while names.contains(&unique) {
+                        n += 1;
+                        unique = format!("{} ({})", name, n);
+                    }
+                    names.push(unique);
+                }
+                self.events
+                    .iter()
+                    .enumerate()
+                    .map(|(i, event)| ObsidianNote {
+                        name: names[i].clone(),
+                        contents: event_note(
+                            event,
+                            i.checked_sub(1).map(|i| names[i].as_str()),
+                            names.get(i + 1).map(String::as_str),
+                        ),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_obsidian() {
+        let worldline: WorldLine = "#worldline v2\n\
+                                    BCE 0044-03-15 Caesar assassinated\n\
+                                    1914-06-28 Franz Ferdinand shot\ttags=ww1\n\
+                                    1914-07-28 [war] War declared\ttags=ww1,balkans\n"
+            .parse()
+            .unwrap();
+        let notes = worldline.to_obsidian(NoteUnit::Year);
+        let names: Vec<&str> = notes.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, vec!["44 BCE", "1914"]);
+        assert_eq!(
+            notes[1].contents,
+            "---\n\
+             date: 1914\n\
+             tags: [balkans, ww1]\n\
+             ---\n\
+             \n\
+             # 1914\n\
+             \n\
+             ← [[44 BCE]]\n\
+             \n\
+             - 1914-06-28 Franz Ferdinand shot\n\
+             - 1914-07-28 [war] War declared\n"
+        );
+
+        let notes = worldline.to_obsidian(NoteUnit::Event);
+        assert_eq!(notes[1].name, "1914-06-28 Franz Ferdinand shot");
+        assert!(notes[1]
+            .contents
+            .contains("← [[-0044-03-15 Caesar assassinated]] | [[1914-07-28 War declared]] →"));
+        assert_eq!(NoteUnit::parse("Event"), Ok(NoteUnit::Event));
+    }
+}
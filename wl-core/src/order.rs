@@ -0,0 +1,264 @@
+//! The order of events.
+//!
+//! Events are sorted by date. A date less precise than a day, like 44 BCE or March 44 BCE,
+//! comes before the more precise dates within it by default, as if it were its first day:
+//! `-44`, `-44-03`, `-44-03-15`, `-44-12-01`, `-43`. Months and days count forward in BCE
+//! years as in CE ones. A worldline can put such dates after the dates within them instead,
+//! as if they were their last day, which is stored in its front matter as
+//! `#partial_dates: last` (see [`PartialDateOrder`]).
+//!
+//! Events on the same date are sorted by their sequence numbers (the `seq` field, see
+//! [`Event::seq`]), with numbered events first. Events without numbers are sorted
+//! alphabetically by default. A worldline can keep them in the order they were added in
+//! instead, which is stored in its front matter as `#same_day_order: insertion`.
+
+use crate::{Date, Event, WorldLine};
+use std::cmp::Ordering;
+
+/// The front matter key storing a worldline's [`SameDayOrder`].
+pub const FRONT_MATTER_KEY: &str = "same_day_order";
+
+/// The front matter key storing a worldline's [`PartialDateOrder`].
+pub const PARTIAL_DATES_KEY: &str = "partial_dates";
+
+/// Where dates less precise than a day (years, months, quarters and weeks) sort among the
+/// dates within them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PartialDateOrder {
+    /// Before them, by when they start: 1066, 1066-10, 1066-10-14.
+    #[default]
+    First,
+    /// After them, by when they end: 1066-10-14, 1066-10, 1066.
+    Last,
+}
+
+impl PartialDateOrder {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "first" => Ok(PartialDateOrder::First),
+            "last" => Ok(PartialDateOrder::Last),
+            _ => Err(format!(
+                "Invalid order of partial dates '{}', expected first or last",
+                s
+            )),
+        }
+    }
+
+    /// The order stored in a worldline's front matter.
+    pub(crate) fn from_front_matter(front_matter: &[(String, String)]) -> Self {
+        front_matter
+            .iter()
+            .find(|(key, _)| key == PARTIAL_DATES_KEY)
+            .and_then(|(_, value)| Self::parse(value).ok())
+            .unwrap_or_default()
+    }
+
+    /// Compare two dates in this order.
+    pub fn cmp(&self, a: &Date, b: &Date) -> Ordering {
+        match self {
+            PartialDateOrder::First => a.cmp(b),
+            // of dates ending together, e.g. 1066-12-31 and 1066, the one starting later
+            PartialDateOrder::Last => a.after().cmp(&b.after()).then_with(|| b.cmp(a)),
+        }
+    }
+
+    /// Whether an event on `date` sorts before all the events on or within `bound`, e.g. a
+    /// year. Events on `date` are before `bound` for every date after them.
+    pub(crate) fn is_before(&self, date: &Date, bound: &Date) -> bool {
+        match self {
+            PartialDateOrder::First => date < bound,
+            PartialDateOrder::Last => date.after() <= *bound,
+        }
+    }
+}
+
+/// How events on the same date without sequence numbers are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SameDayOrder {
+    #[default]
+    Alphabetical,
+    /// In the order they were added, with new events after the others on their date.
+    Insertion,
+}
+
+impl SameDayOrder {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "alphabetical" => Ok(SameDayOrder::Alphabetical),
+            "insertion" => Ok(SameDayOrder::Insertion),
+            _ => Err(format!(
+                "Invalid same-day order '{}', expected alphabetical or insertion",
+                s
+            )),
+        }
+    }
+}
+
+impl Event {
+    /// What the position of an event among the events on its date depends on, besides the
+    /// [`SameDayOrder`]: numbered events come first, in order.
+    pub(crate) fn same_day_key(&self) -> (&Date, bool, Option<u32>) {
+        (&self.date, self.seq.is_none(), self.seq)
+    }
+}
+
+impl WorldLine {
+    pub fn same_day_order(&self) -> SameDayOrder {
+        self.front_matter
+            .iter()
+            .find(|(key, _)| key == FRONT_MATTER_KEY)
+            .and_then(|(_, value)| SameDayOrder::parse(value).ok())
+            .unwrap_or_default()
+    }
+
+    /// Order events on the same date this way from now on. Switching to alphabetical order
+    /// sorts the events that were kept in insertion order.
+    pub fn set_same_day_order(&mut self, order: SameDayOrder) {
+        match order {
+            SameDayOrder::Alphabetical => {
+                self.front_matter.retain(|(key, _)| key != FRONT_MATTER_KEY);
+                self.sort_events();
+            }
+            SameDayOrder::Insertion => {
+                self.set_front_matter(FRONT_MATTER_KEY, "insertion".to_string())
+            }
+        }
+    }
+
+    pub fn partial_date_order(&self) -> PartialDateOrder {
+        PartialDateOrder::from_front_matter(&self.front_matter)
+    }
+
+    /// Sort dates less precise than a day this way from now on, re-sorting the events.
+    pub fn set_partial_date_order(&mut self, order: PartialDateOrder) {
+        match order {
+            PartialDateOrder::First => self
+                .front_matter
+                .retain(|(key, _)| key != PARTIAL_DATES_KEY),
+            PartialDateOrder::Last => self.set_front_matter(PARTIAL_DATES_KEY, "last".to_string()),
+        }
+        self.sort_events();
+    }
+
+    /// Compare events in this worldline's order.
+    pub(crate) fn cmp_events(&self, a: &Event, b: &Event) -> Ordering {
+        let same_day_order = self.same_day_order();
+        self.partial_date_order()
+            .cmp(&a.date, &b.date)
+            .then_with(|| match same_day_order {
+                SameDayOrder::Alphabetical => a.cmp(b),
+                SameDayOrder::Insertion => a.same_day_key().cmp(&b.same_day_key()),
+            })
+    }
+
+    /// Whether `a` goes before (or at the same place as) `b`, in this worldline's order.
+    pub(crate) fn goes_before(&self, a: &Event, b: &Event) -> bool {
+        self.cmp_events(a, b) != Ordering::Greater
+    }
+
+    /// Sort new events into this worldline's order, keeping the order they're in where it
+    /// doesn't matter.
+    pub(crate) fn sort_new(&self, events: &mut [Event]) {
+        events.sort_by(|a, b| self.cmp_events(a, b));
+    }
+
+    fn sort_events(&mut self) {
+        let mut events = std::mem::take(&mut self.events);
+        self.sort_new(&mut events);
+        self.events = events;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::range::DateRange;
+
+    #[test]
+    fn test_same_day_order() {
+        let descriptions = |worldline: &WorldLine| -> Vec<String> {
+            worldline
+                .events()
+                .iter()
+                .map(|e| e.description.clone())
+                .collect()
+        };
+        let mut worldline: WorldLine = "#worldline v2\n\
+                                        #same_day_order: insertion\n\
+                                        1066-10-14 Battle begins\tseq=1\n\
+                                        1066-10-14 Harold killed\n"
+            .parse()
+            .unwrap();
+        assert_eq!(worldline.same_day_order(), SameDayOrder::Insertion);
+        worldline.add_event(Event::parse("1066-10-14 Normans win").unwrap());
+        worldline.add_events_sorted(vec![
+            Event::parse("1066-10-14 Survivors flee").unwrap(),
+            Event::parse("1066-10-14 Archers advance").unwrap(),
+        ]);
+        let mut first = Event::parse("1066-10-14 Armies meet").unwrap();
+        first.seq = Some(0);
+        worldline.add_event(first);
+        assert_eq!(
+            descriptions(&worldline),
+            [
+                "Armies meet",
+                "Battle begins",
+                "Harold killed",
+                "Normans win",
+                "Survivors flee",
+                "Archers advance"
+            ]
+        );
+
+        worldline.set_same_day_order(SameDayOrder::Alphabetical);
+        assert!(worldline.front_matter().is_empty());
+        assert_eq!(
+            descriptions(&worldline)[2..],
+            [
+                "Archers advance",
+                "Harold killed",
+                "Normans win",
+                "Survivors flee"
+            ]
+        );
+        assert!(worldline.to_string().contains("Battle begins\tseq=1\n"));
+    }
+
+    #[test]
+    fn test_partial_date_order() {
+        let descriptions = |worldline: &WorldLine| -> String {
+            worldline
+                .events()
+                .iter()
+                .map(|e| e.description.as_str())
+                .collect()
+        };
+        let lines = [
+            "-45 A",
+            "-44-03-15 B",
+            "-44 C",
+            "-44-12-01 D",
+            "-44-03 E",
+            "-43-01-01 F",
+        ];
+        let mut worldline: WorldLine = lines
+            .into_iter()
+            .map(|line| Event::parse(line).unwrap())
+            .collect();
+        assert_eq!(descriptions(&worldline), "ACEBDF");
+        worldline.set_partial_date_order(PartialDateOrder::Last);
+        assert_eq!(worldline.partial_date_order(), PartialDateOrder::Last);
+        assert_eq!(descriptions(&worldline), "ABEDCF");
+        worldline.add_event(Event::parse("-44-03-20 G").unwrap());
+        assert_eq!(descriptions(&worldline), "ABGEDCF");
+        let year = Date::new(-44, 0, 0).unwrap();
+        assert_eq!(worldline.date_range(&DateRange::of(year)), 1..6);
+        let march = Date::new(-44, 3, 0).unwrap();
+        assert_eq!(worldline.date_range(&DateRange::of(march)), 1..4);
+        assert!(worldline.validate().is_empty());
+
+        worldline.set_partial_date_order(PartialDateOrder::First);
+        assert!(worldline.front_matter().is_empty());
+        assert_eq!(descriptions(&worldline), "ACEBGDF");
+    }
+}
@@ -0,0 +1,136 @@
+//! Exporting a worldline as an outline of centuries, decades and years with the events in each,
+//! for course handouts and reading lists:
+//!
+//! ```text
+//! 20th century
+//!   1910s
+//!     1914
+//!       July 28, 1914: WWI begins
+//!     1918
+//!       November 11, 1918: Armistice
+//! ```
+//!
+//! In Markdown the centuries, decades and years are headings and the events a list under each
+//! year, written like the bullets [`crate::markdown`] imports. Dates are always written in
+//! English and in BCE/CE, so the outline can be read back.
+
+use crate::display::DateFormat;
+use crate::epoch::Epoch;
+use crate::group::GroupBy;
+use crate::locale::Locale;
+use crate::query::QueryExpr;
+use crate::WorldLine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutlineFormat {
+    /// Indented plain text
+    #[default]
+    Text,
+    /// Headings and lists
+    Markdown,
+}
+
+impl OutlineFormat {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "text" | "txt" => Ok(OutlineFormat::Text),
+            "markdown" | "md" => Ok(OutlineFormat::Markdown),
+            _ => Err(format!(
+                "Invalid outline format '{}', expected text or markdown",
+                s
+            )),
+        }
+    }
+
+    /// A line of the outline, at a depth from 0 (centuries) to 3 (events).
+    fn line(&self, depth: usize, text: &str) -> String {
+        match self {
+            OutlineFormat::Text => format!("{}{}\n", "  ".repeat(depth), text),
+            OutlineFormat::Markdown if depth < 3 => {
+                format!("{} {}\n\n", "#".repeat(depth + 1), text)
+            }
+            OutlineFormat::Markdown => format!("- {}\n", text),
+        }
+    }
+}
+
+impl WorldLine {
+    /// The events matching `filter` (or all events) as an outline, under the century, decade
+    /// and year they're in.
+    pub fn to_outline(&self, filter: Option<&QueryExpr>, format: OutlineFormat) -> String {
+        let levels = [GroupBy::Century, GroupBy::Decade, GroupBy::Year];
+        let mut current: [Option<String>; 3] = Default::default();
+        let mut out = String::new();
+        for event in self
+            .events
+            .iter()
+            .filter(|e| filter.is_none_or(|f| f.matches(e)))
+        {
+            for (depth, group_by) in levels.iter().enumerate() {
+                let label = group_by.label(event.date.year);
+                if current[depth].as_ref() == Some(&label) {
+                    continue;
+                }
+                // a new group starts new groups under it
+                current[depth + 1..].fill(None);
+                // a blank line between a list and the heading after it
+                if format == OutlineFormat::Markdown && !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push('\n');
+                }
+                out += &format.line(depth, &label);
+                current[depth] = Some(label);
+            }
+            let date =
+                DateFormat::Long.format_in(&event.date, false, &Epoch::CommonEra, Locale::English);
+            out += &format.line(3, &format!("{}: {}", date, event.description));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_outline() {
+        let worldline: WorldLine = "#worldline v2\n\
+                                    -44-03-15 Caesar assassinated\n\
+                                    1914-07-28 WWI begins\n\
+                                    1917 Russian Revolution\n\
+                                    1918-11-11 Armistice\n\
+                                    1939-09-01 WWII begins\n"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            worldline.to_outline(None, OutlineFormat::Text),
+            "1st century BCE\n\
+             \x20 40s BCE\n\
+             \x20   44 BCE\n\
+             \x20     March 15, 44 BCE: Caesar assassinated\n\
+             20th century\n\
+             \x20 1910s\n\
+             \x20   1914\n\
+             \x20     July 28, 1914: WWI begins\n\
+             \x20   1917\n\
+             \x20     1917: Russian Revolution\n\
+             \x20   1918\n\
+             \x20     November 11, 1918: Armistice\n\
+             \x20 1930s\n\
+             \x20   1939\n\
+             \x20     September 1, 1939: WWII begins\n"
+        );
+
+        let range = QueryExpr::range("1914..1917").unwrap();
+        assert_eq!(
+            worldline.to_outline(Some(&range), OutlineFormat::Markdown),
+            "# 20th century\n\n\
+             ## 1910s\n\n\
+             ### 1914\n\n\
+             - July 28, 1914: WWI begins\n\n\
+             ### 1917\n\n\
+             - 1917: Russian Revolution\n"
+        );
+        assert!(OutlineFormat::parse("html").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+//! Lenient parsing of dates written out in English, e.g. "March 15, 44 BC", "15th of July 1789"
+//! or "July 1789", for when the strict [`Date::parse`] format isn't used. Month names in a
+//! [`locale`](crate::locale) can be read too, e.g. "15 mars 1789" in French, and so can years in
+//! custom [`eras`](crate::epoch), e.g. "AUC 709" or "5 Elizabeth II".
+
+use crate::epoch::Epoch;
+use crate::locale::Locale;
+use crate::{Date, Event};
+
+/// The most words a date written at the start of a line is looked for in, e.g. "the 4th of
+/// July, 1776 AD".
+const MAX_DATE_WORDS: usize = 8;
+
+enum Token {
+    Month(u8),
+    /// A number with an ordinal suffix, e.g. "15th", which can only be a day
+    Ordinal(u32),
+    Number(u32),
+    Era {
+        bce: bool,
+    },
+}
+
+fn token(word: &str, locale: Locale) -> Option<Token> {
+    let word = word.trim_end_matches('.').replace('.', "");
+    match word.as_str() {
+        "bc" | "bce" => return Some(Token::Era { bce: true }),
+        "ad" | "ce" => return Some(Token::Era { bce: false }),
+        _ => {}
+    }
+    if let Some(month) = Locale::English.month(&word).or_else(|| locale.month(&word)) {
+        return Some(Token::Month(month));
+    }
+    if let Ok(n) = word.parse() {
+        return Some(Token::Number(n));
+    }
+    let digits = word
+        .strip_suffix("st")
+        .or_else(|| word.strip_suffix("nd"))
+        .or_else(|| word.strip_suffix("rd"))
+        .or_else(|| word.strip_suffix("th"))?;
+    digits.parse().ok().map(Token::Ordinal)
+}
+
+/// Find the name of one of the custom eras among the words of a lowercase date, returning the
+/// date without it. The longest name wins, e.g. "Elizabeth II" over "Elizabeth".
+fn take_era<'a>(lowercase: &str, eras: &'a [Epoch]) -> Option<(String, &'a Epoch)> {
+    let separator = |c: Option<char>| c.is_none_or(|c| c.is_whitespace() || c == ',');
+    eras.iter()
+        .filter_map(|era| {
+            let name = era.label().to_lowercase();
+            let (start, _) = lowercase.match_indices(&name).find(|(i, _)| {
+                separator(lowercase[..*i].chars().next_back())
+                    && separator(lowercase[i + name.len()..].chars().next())
+            })?;
+            let rest = format!(
+                "{} {}",
+                &lowercase[..start],
+                &lowercase[start + name.len()..]
+            );
+            Some((rest, era, name.len()))
+        })
+        .max_by_key(|(_, _, len)| *len)
+        .map(|(rest, era, _)| (rest, era))
+}
+
+impl Date {
+    /// Parse a date written in English: a year, optionally with a month name and a day in
+    /// either order, and an era, e.g. "March 15, 44 BC", "15th of July 1789", "the 4th of
+    /// July, 1776", "Sept 1939" or "AD 800". The whole string must be a date.
+    pub fn parse_natural(s: &str) -> Result<Date, String> {
+        Self::parse_natural_in(s, Locale::English, &[])
+    }
+
+    /// Parse a date like [`Date::parse_natural`], also reading month names in a given locale,
+    /// e.g. "15 de marzo de 44 BC" in Spanish, and years counted in one of the custom `eras`
+    /// named before or after them, e.g. "March 15, AUC 709".
+    pub fn parse_natural_in(s: &str, locale: Locale, eras: &[Epoch]) -> Result<Date, String> {
+        let invalid = |reason: &str| format!("Could not read '{}' as a date: {}", s.trim(), reason);
+        let (mut month, mut bce) = (None, None);
+        let (mut ordinals, mut numbers) = (Vec::new(), Vec::new());
+        let mut lowercase = s.to_lowercase();
+        let era = take_era(&lowercase, eras).map(|(rest, era)| {
+            lowercase = rest;
+            era
+        });
+        let words = lowercase
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|w| {
+                let filler = |fillers: &[&str]| fillers.contains(w);
+                !w.is_empty()
+                    && !filler(Locale::English.filler_words())
+                    && !filler(locale.filler_words())
+            });
+        for word in words {
+            match token(word, locale) {
+                Some(Token::Month(m)) if month.is_none() => month = Some(m),
+                Some(Token::Era { bce: b }) if bce.is_none() => bce = Some(b),
+                Some(Token::Ordinal(n)) => ordinals.push(n),
+                Some(Token::Number(n)) => numbers.push(n),
+                _ => return Err(invalid(&format!("unexpected '{}'", word))),
+            }
+        }
+
+        let (day, year) = match (ordinals.as_slice(), numbers.as_slice()) {
+            ([], [year]) => (None, *year),
+            ([day], [year]) => (Some(*day), *year),
+            // the first number is the day if it could be one, e.g. "15 March 44 BC"
+            ([], [day, year]) if *day <= 31 => (Some(*day), *year),
+            ([], [year, day]) if *day <= 31 => (Some(*day), *year),
+            ([], []) | ([_], []) => return Err(invalid("no year")),
+            _ => return Err(invalid("too many numbers")),
+        };
+        if day.is_some() && month.is_none() {
+            return Err(invalid("a day needs a month"));
+        }
+        let year = match era {
+            Some(_) if bce.is_some() => return Err(invalid("both an era and BC or AD")),
+            Some(era) => era.common_era_year(year as i64).map_err(|e| invalid(&e))?,
+            None => i32::try_from(year).map_err(|_| invalid("year out of range"))?,
+        };
+        let year = if bce == Some(true) { -year } else { year };
+        let day = u8::try_from(day.unwrap_or(0)).map_err(|_| invalid("invalid day"))?;
+        Date::new(year, month.unwrap_or(0), day)
+    }
+}
+
+/// An event from a line starting with a date written in English instead of the file format,
+/// e.g. "July 14, 1789: Storming of the Bastille", taking the longest run of words at the start
+/// that reads as a date. The description is the rest, after any colon or dash.
+pub(crate) fn natural_event(line: &str) -> Option<Event> {
+    let word_ends: Vec<usize> = line
+        .char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .filter(|&end| {
+            line[end..].starts_with(char::is_whitespace)
+                && !line[..end].ends_with(char::is_whitespace)
+        })
+        .take(MAX_DATE_WORDS)
+        .collect();
+    word_ends.into_iter().rev().find_map(|end| {
+        let date = line[..end].trim_end_matches([':', '-', '—', '–', '|']);
+        let date = Date::parse_natural(date).ok()?;
+        let description = line[end..]
+            .trim_start_matches(|c: char| c.is_whitespace() || ":-—–|".contains(c))
+            .trim_end();
+        (!description.is_empty()).then(|| Event::from_text(date, description))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_natural() {
+        let cases = [
+            ("March 15, 44 BC", (-44, 3, 15)),
+            ("15th of July 1789", (1789, 7, 15)),
+            ("the 4th of July, 1776", (1776, 7, 4)),
+            ("14 July 1789", (1789, 7, 14)),
+            ("Jul. 14 1789", (1789, 7, 14)),
+            ("Sept 1939", (1939, 9, 0)),
+            ("AD 800", (800, 0, 0)),
+            ("44 B.C.", (-44, 0, 0)),
+            ("1066 December 25th", (1066, 12, 25)),
+        ];
+        for (s, (year, month, day)) in cases {
+            assert_eq!(Date::parse_natural(s), Date::new(year, month, day), "{}", s);
+        }
+        let cases = [
+            (Locale::French, "15 mars 1789", (1789, 3, 15)),
+            (Locale::Spanish, "4 de julio de 1776", (1776, 7, 4)),
+            (Locale::German, "14. Juli 1789", (1789, 7, 14)),
+            (Locale::German, "July 14 1789", (1789, 7, 14)),
+        ];
+        for (locale, s, (year, month, day)) in cases {
+            assert_eq!(
+                Date::parse_natural_in(s, locale, &[]),
+                Date::new(year, month, day),
+                "{}",
+                s
+            );
+        }
+        assert!(Date::parse_natural_in("15 mars 1789", Locale::English, &[]).is_err());
+
+        let eras = [
+            Epoch::parse("AUC=753 BCE").unwrap(),
+            Epoch::parse("Elizabeth II=1952").unwrap(),
+            Epoch::parse("Elizabeth=1558").unwrap(),
+        ];
+        let in_eras = |s| Date::parse_natural_in(s, Locale::English, &eras);
+        let cases = [
+            ("AUC 709", (-45, 0, 0)),
+            ("March 15, 710 AUC", (-44, 3, 15)),
+            ("5 Elizabeth II", (1956, 0, 0)),
+            ("1 elizabeth", (1558, 0, 0)),
+        ];
+        for (s, (year, month, day)) in cases {
+            assert_eq!(in_eras(s), Date::new(year, month, day), "{}", s);
+        }
+        assert!(in_eras("AUC 709 BC").is_err());
+        assert!(in_eras("5 Elizabethan").is_err());
+        assert!(Date::parse_natural("AUC 709").is_err());
+        for s in [
+            "",
+            "July",
+            "15th of July",
+            "31 1789",
+            "Smarch 1 1789",
+            "June 31 1789",
+            "1 2 3",
+        ] {
+            assert!(Date::parse_natural(s).is_err(), "{}", s);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Importing events from plain-text timelines, e.g. scraped pages or OCR output, with a regular
+//! expression matching their lines. Named groups in it say what each part of a line is:
+//!
+//! - `date`: the whole date, in the file format or written in English (see
+//!   [`Date::parse_natural`]), or else
+//! - `y` (or `year`), with optionally `m` (or `month`) as a number or a name, `d` (or `day`),
+//!   and `era`, e.g. "BC"
+//! - `desc` (or `description`): the description, which may start with a category and end with
+//!   a place, as in [`Event::from_text`]
+//!
+//! Month names are read in English, and in the pattern's locale if it's given one with
+//! [`LinePattern::with_locale`]; whole dates may also be in the eras given with
+//! [`LinePattern::with_eras`].
+//!
+//! For example, `(?<y>\d+)\s*-\s*(?<desc>.+)` reads lines like "1066 - Battle of Hastings".
+//! Lines the expression doesn't match are left out, and matched lines whose date can't be read
+//! are skipped.
+
+use crate::epoch::Epoch;
+use crate::locale::Locale;
+use crate::query::parse_whole_date;
+use crate::{Date, Event};
+use regex::{Captures, Regex};
+
+/// The events read by [`LinePattern::parse`].
+#[derive(Debug, Default)]
+pub struct PatternImport {
+    /// The events, with the (1-based) line numbers they were read from.
+    pub events: Vec<(usize, Event)>,
+    /// Matched lines that couldn't be read as events, with their line numbers and why.
+    pub skipped: Vec<(usize, String)>,
+    /// How many lines that aren't blank the expression didn't match.
+    pub unmatched: usize,
+}
+
+/// A regular expression with named groups for reading events from lines of text.
+#[derive(Debug, Clone)]
+pub struct LinePattern {
+    regex: Regex,
+    locale: Locale,
+    eras: Vec<Epoch>,
+}
+
+impl LinePattern {
+    /// Compile a pattern, which needs a `desc` group and a `date` or `y` group.
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+        let has = |names: &[&str]| {
+            regex
+                .capture_names()
+                .flatten()
+                .any(|name| names.contains(&name))
+        };
+        if !has(&["desc", "description"]) {
+            return Err("The pattern needs a (?<desc>...) group for the description".to_string());
+        }
+        if !has(&["date", "y", "year"]) {
+            return Err(
+                "The pattern needs a (?<date>...) or (?<y>...) group for the date".to_string(),
+            );
+        }
+        Ok(Self {
+            regex,
+            locale: Locale::English,
+            eras: Vec::new(),
+        })
+    }
+
+    /// The pattern, reading month names in a locale as well as in English.
+    pub fn with_locale(self, locale: Locale) -> Self {
+        Self { locale, ..self }
+    }
+
+    /// The pattern, reading dates written in these eras as well as BCE/CE.
+    pub fn with_eras(self, eras: Vec<Epoch>) -> Self {
+        Self { eras, ..self }
+    }
+
+    /// Read events from the lines the pattern matches.
+    pub fn parse(&self, contents: &str) -> PatternImport {
+        let mut import = PatternImport::default();
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Some(captures) = self.regex.captures(line) else {
+                import.unmatched += 1;
+                continue;
+            };
+            match event(&captures, self.locale, &self.eras) {
+                Ok(event) => import.events.push((i + 1, event)),
+                Err(error) => import.skipped.push((i + 1, error)),
+            }
+        }
+        import
+    }
+}
+
+/// The trimmed text of the first group with one of the names that matched.
+fn group<'a>(captures: &Captures<'a>, names: &[&str]) -> Option<&'a str> {
+    names
+        .iter()
+        .find_map(|name| captures.name(name))
+        .map(|m| m.as_str().trim())
+        .filter(|text| !text.is_empty())
+}
+
+fn event(captures: &Captures, locale: Locale, eras: &[Epoch]) -> Result<Event, String> {
+    let description =
+        group(captures, &["desc", "description"]).ok_or("The description is empty")?;
+    let date = match group(captures, &["date"]) {
+        Some(date) => {
+            parse_whole_date(date).or_else(|_| Date::parse_natural_in(date, locale, eras))?
+        }
+        None => date_from_parts(captures, locale)?,
+    };
+    Ok(Event::from_text(date, description))
+}
+
+/// A date from the `y`, `m`, `d` and `era` groups.
+fn date_from_parts(captures: &Captures, locale: Locale) -> Result<Date, String> {
+    let year = group(captures, &["y", "year"]).ok_or("No date")?;
+    let mut year: i32 = year
+        .parse()
+        .map_err(|_| format!("Invalid year: {}", year))?;
+    if let Some(era) = group(captures, &["era"]) {
+        match era.to_lowercase().replace('.', "").as_str() {
+            "bc" | "bce" => year = -year,
+            "ad" | "ce" => {}
+            _ => return Err(format!("Invalid era: {}", era)),
+        }
+    }
+    let month = match group(captures, &["m", "month"]) {
+        None => 0,
+        Some(month) => month
+            .parse()
+            .ok()
+            .or_else(|| {
+                let name = month.trim_end_matches('.').to_lowercase();
+                Locale::English.month(&name).or_else(|| locale.month(&name))
+            })
+            .ok_or_else(|| format!("Invalid month: {}", month))?,
+    };
+    let day = match group(captures, &["d", "day"]) {
+        None => 0,
+        Some(day) => day.parse().map_err(|_| format!("Invalid day: {}", day))?,
+    };
+    Date::new(year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_pattern() {
+        let text = "Timeline of the Norman conquest\n\
+                    \n\
+                    1066 - Battle of Hastings\n\
+                    1086 -  Domesday Book @Winchester\n\
+                    10B6 - OCR noise\n\
+                    1087 - \n";
+        let pattern = LinePattern::new(r"(?<y>\w+)\s*-\s*(?<desc>.*)").unwrap();
+        let import = pattern.parse(text);
+        let events: Vec<(usize, String)> = import
+            .events
+            .iter()
+            .map(|(line, e)| (*line, format!("{} {}", e.date, e.description)))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (3, "1066 Battle of Hastings".to_string()),
+                (4, "1086 Domesday Book".to_string())
+            ]
+        );
+        assert!(import.events[1].1.place.is_some());
+        assert_eq!(
+            import.skipped,
+            [
+                (5, "Invalid year: 10B6".to_string()),
+                (6, "The description is empty".to_string())
+            ]
+        );
+        assert_eq!(import.unmatched, 1);
+
+        let pattern = LinePattern::new(
+            r"^(?<d>\d+)?\s*(?<m>[A-Za-z.]+)?\s*(?<y>\d+)\s*(?<era>BC|AD)?:\s*(?<desc>.+)$",
+        )
+        .unwrap();
+        let import = pattern.parse("15 Mar. 44 BC: Caesar assassinated\nJuly 1789: Revolution\n");
+        let dates: Vec<String> = import
+            .events
+            .iter()
+            .map(|(_, e)| e.date.to_string())
+            .collect();
+        assert_eq!(dates, ["-0044-03-15", "1789-07"]);
+
+        let pattern = LinePattern::new(r"^(?<date>[^|]+)\|(?<description>.+)$").unwrap();
+        let import = pattern.parse("July 14, 1789|Storming of the Bastille\n1066-10-14|Hastings\n");
+        assert_eq!(import.events.len(), 2);
+
+        let pattern = LinePattern::new(r"^(?<d>\d+) (?<m>\w+) (?<y>\d+): (?<desc>.+)$").unwrap();
+        assert!(pattern.parse("14 juillet 1789: Fête\n").events.is_empty());
+        let import = pattern
+            .with_locale(Locale::French)
+            .parse("14 juillet 1789: Fête\n");
+        assert_eq!(import.events[0].1.date.to_string(), "1789-07-14");
+
+        assert!(LinePattern::new(r"(?<y>\d+) (.+)").is_err());
+        assert!(LinePattern::new(r"(\d+) (?<desc>.+)").is_err());
+        assert!(LinePattern::new(r"(?<y>\d+").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! ISO week ("2023-W12") and quarter ("2023-Q2") precision dates, for modern project
+//! timelines.
+//!
+//! A week is stored as its Monday and a quarter as its first month, so they sort by when they
+//! start, before a month or day starting at the same time: 2023-Q2 sorts just before 2023-04,
+//! and 2023-W12 (which starts on 2023-03-20) between 2023-03-19 and 2023-03-20.
+
+use crate::Date;
+
+/// The precision of a date beyond which of its year, month and day are known. The order puts
+/// weeks and quarters before the day or month they start on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Period {
+    Quarter,
+    Week,
+    /// Year, month or day precision, depending on whether the month and day are 0.
+    Calendar,
+}
+
+impl Date {
+    /// The date for an ISO week of a year, from 1 to 52 (or 53 in long years). Weeks start on
+    /// Monday, and the first week of a year is the one with its first Thursday, so it can
+    /// start in December of the year before.
+    pub fn from_week(year: i32, week: u8) -> Result<Self, String> {
+        Self::new(year, 0, 0)?;
+        if week == 0 || week > Self::weeks_in_year(year) {
+            return Err(format!("Invalid week: {}", week));
+        }
+        let monday = Self::first_monday(year) + 7 * (week as i64 - 1);
+        let date = Self::from_day_number(monday);
+        if date.day_number() != monday {
+            return Err(format!(
+                "Week {} of {} is out of range; supported years are 9999 BCE to 9999 CE",
+                week, year
+            ));
+        }
+        Ok(date.with_period(Period::Week))
+    }
+
+    /// The date for a quarter (1-4) of a year, e.g. Q2 is April to June.
+    pub fn from_quarter(year: i32, quarter: u8) -> Result<Self, String> {
+        if !(1..=4).contains(&quarter) {
+            return Err(format!("Invalid quarter: {}", quarter));
+        }
+        Ok(Self::new(year, quarter * 3 - 2, 0)?.with_period(Period::Quarter))
+    }
+
+    /// The ISO week-numbering year and week, for week precision dates. The year can differ
+    /// from [`Date::year`] in the first and last days of a year, e.g. 2026-W01 starts on
+    /// 2025-12-29.
+    pub fn week(&self) -> Option<(i32, u8)> {
+        if self.period != Period::Week {
+            return None;
+        }
+        // the year with the week's Thursday
+        let year = Self::from_day_number(self.day_number() + 3).year;
+        let week = (self.day_number() - Self::first_monday(year)) / 7 + 1;
+        Some((year, week as u8))
+    }
+
+    /// The quarter (1-4), for quarter precision dates.
+    pub fn quarter(&self) -> Option<u8> {
+        (self.period == Period::Quarter).then(|| (self.month - 1) / 3 + 1)
+    }
+
+    /// Whether the date is a single day, rather than a week, month or year.
+    pub fn is_day_precision(&self) -> bool {
+        self.day != 0 && self.period == Period::Calendar
+    }
+
+    /// The year a week or quarter is written with and the rest of how it's written, e.g.
+    /// (2023, "-W12") or (-44, "-Q1"), or None for other dates.
+    pub(crate) fn period_suffix(&self) -> Option<(i32, String)> {
+        if let Some((year, week)) = self.week() {
+            Some((year, format!("-W{:02}", week)))
+        } else {
+            self.quarter().map(|q| (self.year, format!("-Q{}", q)))
+        }
+    }
+
+    /// The week or quarter containing this date, or the date itself for [`Period::Calendar`].
+    /// Dates without a day are in the week of the first day of their month or year.
+    pub(crate) fn with_period(&self, period: Period) -> Self {
+        match period {
+            Period::Calendar => self.clone(),
+            Period::Quarter => {
+                let month = (self.month.max(1) - 1) / 3 * 3 + 1;
+                Self {
+                    period,
+                    ..Self::new(self.year, month, 0).unwrap()
+                }
+            }
+            Period::Week => {
+                let day = self.day_number();
+                let mut monday = day - Self::weekday_index(day);
+                // the week of the first supported day starts before it
+                if Self::from_day_number(monday).day_number() != monday {
+                    monday += 7;
+                }
+                Self {
+                    period,
+                    ..Self::from_day_number(monday)
+                }
+            }
+        }
+    }
+
+    /// Days since Monday for a day number.
+    fn weekday_index(day: i64) -> i64 {
+        // day 0 (1970-01-01) was a Thursday
+        (day + 3).rem_euclid(7)
+    }
+
+    /// The day number of the Monday starting week 1 of a year, which has the 4th of January.
+    fn first_monday(year: i32) -> i64 {
+        let january_4th = Self::new(year, 1, 4).unwrap().day_number();
+        january_4th - Self::weekday_index(january_4th)
+    }
+
+    /// 53 if the year starts on a Thursday, or is a leap year starting on a Wednesday.
+    fn weeks_in_year(year: i32) -> u8 {
+        let january_1st = Self::weekday_index(Self::new(year, 1, 1).unwrap().day_number());
+        if january_1st == 3 || (january_1st == 2 && Self::is_leap_year(year)) {
+            53
+        } else {
+            52
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse_whole_date;
+    use crate::range::DateRange;
+
+    #[test]
+    fn test_weeks_and_quarters() {
+        let date = |s| parse_whole_date(s).unwrap();
+        let week = date("2023-W12");
+        assert_eq!(week.week(), Some((2023, 12)));
+        assert_eq!(week.to_string(), "2023-W12");
+        assert_eq!(week.format(true), " CE 2023-W12  ");
+        assert_eq!(week.day_number(), date("2023-03-20").day_number());
+        assert_eq!(week.next(), date("2023-W13"));
+        assert!(!week.is_day_precision());
+        assert_eq!(week.weekday(), None);
+
+        // week 1 can start in the year before, and the last week end in the year after
+        assert_eq!(
+            date("2026-W01").day_number(),
+            date("2025-12-29").day_number()
+        );
+        assert_eq!(date("2020-W53").next(), date("2021-W01"));
+        assert!(parse_whole_date("2021-W53").is_err());
+        assert!(parse_whole_date("2023-W00").is_err());
+
+        let quarter = date("2023-Q2");
+        assert_eq!(quarter.quarter(), Some(2));
+        assert_eq!(quarter.to_string(), "2023-Q2");
+        assert_eq!(date("BCE 44-Q1").to_string(), "-0044-Q1");
+        assert_eq!(quarter.next(), date("2023-Q3"));
+        assert_eq!(date("2023-Q4").next(), date("2024"));
+        assert_eq!(date("2023-03").next(), date("2023-04"));
+        assert_eq!(date("2023-03-19").next(), date("2023-03-20"));
+        // after() gives the coarsest date starting next, so ranges ending in March exclude Q2
+        assert_eq!(date("2023-03").after(), quarter);
+        assert_eq!(date("2023-03-19").after(), week);
+        assert_eq!(date("2023-03-19 23:59").after(), week);
+        assert!(!DateRange::new(date("2023"), date("2023-03")).contains(&quarter));
+        assert!(!DateRange::of(date("2023-03-19")).contains(&week));
+        assert!(parse_whole_date("2023-Q5").is_err());
+
+        let mut dates = [
+            date("2023-04"),
+            date("2023-03-20"),
+            date("2023-Q2"),
+            date("2023-W12"),
+            date("2023-03-19"),
+            date("2023-03"),
+            date("2023-Q1"),
+            date("2023"),
+        ];
+        dates.sort();
+        let dates: Vec<String> = dates.iter().map(Date::to_string).collect();
+        assert_eq!(
+            dates,
+            [
+                "2023",
+                "2023-Q1",
+                "2023-03",
+                "2023-03-19",
+                "2023-W12",
+                "2023-03-20",
+                "2023-Q2",
+                "2023-04",
+            ]
+        );
+    }
+}
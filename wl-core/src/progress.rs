@@ -0,0 +1,21 @@
+//! Reporting the progress of long work, e.g. importing tens of thousands of events, so a
+//! program can show it. The library doesn't print anything itself: functions taking a
+//! [`Reporter`] have a variant without one that reports to [`Silent`].
+
+/// Told how work on a number of items is getting on.
+pub trait Reporter {
+    /// Work on `total` items started, described as e.g. "Importing".
+    fn start(&mut self, _what: &str, _total: usize) {}
+
+    /// Another `n` items were done.
+    fn advance(&mut self, _n: usize) {}
+
+    /// The work finished, whether or not all items were done.
+    fn finish(&mut self) {}
+}
+
+/// A reporter ignoring all progress.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Silent;
+
+impl Reporter for Silent {}
@@ -0,0 +1,554 @@
+//! Filter expressions for selecting events.
+//!
+//! An expression is made of terms, where a term is one of:
+//! - `tag:NAME`: events with the tag
+//! - `category:NAME`: events in the category (case-insensitive)
+//! - `person:NAME`: events about the person (case-insensitive), e.g. `person:julius caesar`
+//! - `series:NAME`: events in the series (case-insensitive), e.g. `series:punic wars`
+//! - `START..END`: events dated within the range (inclusive), e.g. `1945..1991`
+//! - `weekday:DAY`: day precision events on that day of the week, e.g. `weekday:friday`
+//! - `length:N` or `length:MIN..MAX`: events whose description is that many characters long,
+//!   not counting surrounding whitespace, e.g. `length:0` for blank ones or `length:..10`
+//! - `"PHRASE"`: events whose description contains the phrase, even if it looks like another
+//!   term or contains `AND`, `OR` or `NOT`
+//! - anything else: events whose description contains the text (case-insensitive)
+//!
+//! Terms are combined with `NOT`, `AND` and `OR`, in that order of precedence, and grouped
+//! with parentheses, e.g. `caesar AND (senate OR "ides of march") AND NOT tag:fiction`.
+
+use crate::range::DateRange;
+use crate::weekday::Weekday;
+use crate::{Date, Event, WorldLine};
+use std::iter::Peekable;
+use std::ops::{Range, RangeInclusive};
+use std::vec::IntoIter;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryExpr {
+    /// Description contains the (lowercase) text.
+    Text(String),
+    Tag(String),
+    /// In the (lowercase) category.
+    Category(String),
+    /// About the person, by (lowercase) name.
+    Person(String),
+    /// In the series, by (lowercase) name.
+    Series(String),
+    /// Dated within the range. Partial end dates include their whole year/month.
+    Range(DateRange),
+    Weekday(Weekday),
+    /// The description's length in characters, without surrounding whitespace, is in the range.
+    Length(RangeInclusive<usize>),
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+    Not(Box<QueryExpr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    /// A quoted phrase, without its quotes.
+    Phrase(String),
+    Word(String),
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '"' => {
+                let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                tokens.push(Token::Phrase(phrase));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"()\"".contains(*c)) {
+                    word.push(c);
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    if expr.matches('"').count() % 2 == 1 {
+        return Err(format!("Unclosed quote in query: {}", expr));
+    }
+    Ok(tokens)
+}
+
+/// Join expressions with `AND` or `OR`, unless there's only one.
+fn combine(mut exprs: Vec<QueryExpr>, join: fn(Vec<QueryExpr>) -> QueryExpr) -> QueryExpr {
+    if exprs.len() == 1 {
+        exprs.pop().unwrap()
+    } else {
+        join(exprs)
+    }
+}
+
+/// A recursive descent parser over the tokens of an expression.
+struct Parser {
+    tokens: Peekable<IntoIter<Token>>,
+}
+
+impl Parser {
+    fn next_is_word(&mut self, word: &str) -> bool {
+        self.tokens
+            .next_if(|token| *token == Token::Word(word.to_string()))
+            .is_some()
+    }
+
+    fn or(&mut self) -> Result<QueryExpr, String> {
+        let mut terms = vec![self.and()?];
+        while self.next_is_word("OR") {
+            terms.push(self.and()?);
+        }
+        Ok(combine(terms, QueryExpr::Or))
+    }
+
+    fn and(&mut self) -> Result<QueryExpr, String> {
+        let mut terms = vec![self.not()?];
+        while self.next_is_word("AND") {
+            terms.push(self.not()?);
+        }
+        Ok(combine(terms, QueryExpr::And))
+    }
+
+    fn not(&mut self) -> Result<QueryExpr, String> {
+        if self.next_is_word("NOT") {
+            return Ok(QueryExpr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<QueryExpr, String> {
+        match self.tokens.next() {
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.tokens.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("Expected ) in query".to_string()),
+                }
+            }
+            Some(Token::Phrase(phrase)) if phrase.is_empty() => Err("Empty query term".to_string()),
+            Some(Token::Phrase(phrase)) => Ok(QueryExpr::Text(phrase.to_lowercase())),
+            Some(Token::Word(word)) if !matches!(word.as_str(), "AND" | "OR" | "NOT") => {
+                // consecutive words are one term, e.g. "world war"
+                let mut term = word;
+                while let Some(Token::Word(word)) = self.tokens.next_if(|token| {
+                    matches!(token, Token::Word(word) if !matches!(word.as_str(), "AND" | "OR" | "NOT"))
+                }) {
+                    term.push(' ');
+                    term.push_str(&word);
+                }
+                QueryExpr::parse_term(&term)
+            }
+            _ => Err("Empty query term".to_string()),
+        }
+    }
+}
+
+impl QueryExpr {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(expr)?.into_iter().peekable(),
+        };
+        let parsed = parser.or()?;
+        match parser.tokens.next() {
+            None => Ok(parsed),
+            Some(Token::Close) => Err("Unexpected ) in query".to_string()),
+            Some(_) => Err(format!(
+                "Expected AND or OR between the terms of query: {}",
+                expr
+            )),
+        }
+    }
+
+    fn parse_term(term: &str) -> Result<Self, String> {
+        if let Some(tag) = term.strip_prefix("tag:") {
+            return Ok(QueryExpr::Tag(tag.to_string()));
+        }
+        if let Some(category) = term.strip_prefix("category:") {
+            return Ok(QueryExpr::Category(category.to_lowercase()));
+        }
+        if let Some(name) = term.strip_prefix("person:") {
+            return Ok(QueryExpr::Person(name.to_lowercase()));
+        }
+        if let Some(name) = term.strip_prefix("series:") {
+            return Ok(QueryExpr::Series(name.to_lowercase()));
+        }
+        if let Some(weekday) = term.strip_prefix("weekday:") {
+            return Weekday::parse(weekday).map(QueryExpr::Weekday);
+        }
+        if let Some(length) = term.strip_prefix("length:") {
+            return Self::length(length);
+        }
+        if term.contains("..") {
+            return Self::range(term);
+        }
+        Ok(QueryExpr::Text(term.to_lowercase()))
+    }
+
+    /// Parse a `START..END` range term.
+    pub fn range(term: &str) -> Result<Self, String> {
+        DateRange::parse(term).map(QueryExpr::Range)
+    }
+
+    /// Parse the `N`, `MIN..MAX`, `..MAX` or `MIN..` of a `length:` term.
+    fn length(term: &str) -> Result<Self, String> {
+        let error = || {
+            format!(
+                "Invalid length '{}', expected a number of characters or a range like 0..10",
+                term
+            )
+        };
+        let bound = |s: &str, default: usize| match s.trim() {
+            "" => Ok(default),
+            s => s.parse().map_err(|_| error()),
+        };
+        let range = match term.split_once("..") {
+            Some((min, max)) => bound(min, 0)?..=bound(max, usize::MAX)?,
+            None if !term.trim().is_empty() => {
+                let n = bound(term, 0)?;
+                n..=n
+            }
+            None => return Err(error()),
+        };
+        Ok(QueryExpr::Length(range))
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        match self {
+            QueryExpr::Text(text) => event.description.to_lowercase().contains(text),
+            QueryExpr::Tag(tag) => event.tags.iter().any(|t| t == tag),
+            QueryExpr::Category(category) => event
+                .category
+                .as_ref()
+                .is_some_and(|c| c.to_lowercase() == *category),
+            QueryExpr::Person(name) => event.people.iter().any(|p| p.to_lowercase() == *name),
+            QueryExpr::Series(name) => event
+                .series
+                .as_ref()
+                .is_some_and(|s| s.to_lowercase() == *name),
+            QueryExpr::Range(range) => range.contains(&event.date),
+            QueryExpr::Weekday(weekday) => event.date.weekday() == Some(*weekday),
+            QueryExpr::Length(range) => range.contains(&event.description.trim().chars().count()),
+            QueryExpr::And(terms) => terms.iter().all(|t| t.matches(event)),
+            QueryExpr::Or(terms) => terms.iter().any(|t| t.matches(event)),
+            QueryExpr::Not(term) => !term.matches(event),
+        }
+    }
+
+    /// The text the expression looks for in descriptions, except under `NOT`.
+    pub fn texts(&self) -> Vec<&str> {
+        match self {
+            QueryExpr::Text(text) => vec![text.as_str()],
+            QueryExpr::And(terms) | QueryExpr::Or(terms) => {
+                terms.iter().flat_map(QueryExpr::texts).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Where the expression's text terms match in a description, as sorted byte ranges, e.g.
+    /// to highlight them.
+    pub fn match_spans(&self, description: &str) -> Vec<Range<usize>> {
+        let mut spans = match self {
+            QueryExpr::Text(text) => find_ignoring_case(description, text),
+            QueryExpr::And(terms) | QueryExpr::Or(terms) => terms
+                .iter()
+                .flat_map(|t| t.match_spans(description))
+                .collect(),
+            // text that doesn't match isn't there to highlight
+            _ => Vec::new(),
+        };
+        spans.sort_by_key(|span| (span.start, span.end));
+        spans
+    }
+}
+
+/// The non-overlapping byte ranges of a text where a lowercase needle occurs, ignoring case.
+fn find_ignoring_case(text: &str, needle: &str) -> Vec<Range<usize>> {
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    // lowercasing can change lengths, so map the lowercase text's bytes back to the text's
+    let mut lower = String::with_capacity(text.len());
+    let mut starts = Vec::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        for lower_c in c.to_lowercase() {
+            lower.push(lower_c);
+            starts.resize(lower.len(), i);
+        }
+    }
+    starts.push(text.len());
+    lower
+        .match_indices(needle)
+        .map(|(i, matched)| starts[i]..starts[i + matched.len()])
+        .collect()
+}
+
+/// Parse a string that must consist of nothing but a date.
+pub(crate) fn parse_whole_date(s: &str) -> Result<Date, String> {
+    let (date, idx) = Date::parse(s)?;
+    if s[idx..].trim().is_empty() {
+        Ok(date)
+    } else {
+        Err(format!("Invalid date: {}", s))
+    }
+}
+
+impl WorldLine {
+    /// All events matching the expression.
+    pub fn query_expr(&self, expr: &QueryExpr) -> Vec<&Event> {
+        scan(&self.events, |_, e| expr.matches(e).then_some(e))
+    }
+
+    /// The indices of events found in this worldline (e.g. by a query) with the `n` events
+    /// before and after each, to show them in context. Ranges that overlap or touch are
+    /// merged, so each event is in at most one range.
+    pub fn context_ranges(&self, found: &[&Event], n: usize) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for event in found {
+            let start = self.first_geq(&event.date);
+            let Some(i) = self.events[start..]
+                .iter()
+                .position(|e| std::ptr::eq(e, *event))
+            else {
+                continue;
+            };
+            let range = (start + i).saturating_sub(n)..(start + i + n + 1).min(self.events.len());
+            match ranges.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => ranges.push(range),
+            }
+        }
+        ranges
+    }
+
+    /// A read-only copy of the worldline containing only the events matching the expression.
+    pub fn filtered(&self, expr: &QueryExpr) -> WorldLine {
+        WorldLine {
+            events: self
+                .events
+                .iter()
+                .filter(|e| expr.matches(e))
+                .cloned()
+                .collect(),
+            version: self.version,
+            front_matter: self.front_matter.clone(),
+            read_only: true,
+        }
+    }
+
+    /// A read-only copy of the worldline containing only some of its events, e.g. the results
+    /// of a query, in the order given.
+    pub fn with_events<'a>(&self, events: impl IntoIterator<Item = &'a Event>) -> WorldLine {
+        WorldLine {
+            events: events.into_iter().cloned().collect(),
+            version: self.version,
+            front_matter: self.front_matter.clone(),
+            read_only: true,
+        }
+    }
+
+    /// The positions in `whole` of this worldline's events, if it was filtered from `whole`
+    /// (e.g. by [`WorldLine::filtered`]).
+    pub fn positions_in(&self, whole: &WorldLine) -> Vec<usize> {
+        // filtering keeps the order of events
+        let mut positions = Vec::with_capacity(self.events.len());
+        let mut candidates = whole.events.iter().enumerate();
+        for event in &self.events {
+            if let Some((i, _)) = candidates.find(|(_, e)| *e == event) {
+                positions.push(i);
+            }
+        }
+        positions
+    }
+
+    /// A copy of the worldline with only the events in a range (inclusive), and optionally
+    /// matching a filter. Unlike [`WorldLine::filtered`], the copy can be saved, e.g. to split
+    /// part of a worldline into a new file.
+    pub fn slice(&self, range: &DateRange, filter: Option<&QueryExpr>) -> WorldLine {
+        let mut terms = vec![QueryExpr::Range(range.clone())];
+        terms.extend(filter.cloned());
+        let mut slice = self.filtered(&QueryExpr::And(terms));
+        slice.read_only = false;
+        slice
+    }
+}
+
+/// Map events to values, keeping the `Some`s, in the events' order. With the `parallel` feature
+/// the events are scanned across threads.
+#[cfg(feature = "parallel")]
+pub(crate) fn scan<'a, T: Send>(
+    events: &'a [Event],
+    f: impl Fn(usize, &'a Event) -> Option<T> + Sync,
+) -> Vec<T> {
+    use rayon::prelude::*;
+    events
+        .par_iter()
+        .enumerate()
+        .filter_map(|(i, e)| f(i, e))
+        .collect()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn scan<'a, T>(
+    events: &'a [Event],
+    f: impl Fn(usize, &'a Event) -> Option<T>,
+) -> Vec<T> {
+    events
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| f(i, e))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_expr() {
+        let expr = QueryExpr::parse("tag:coldwar AND 1945..1991").unwrap();
+        let mut event = Event::new(
+            Date::new(1991, 12, 26).unwrap(),
+            "USSR dissolved".to_string(),
+        );
+        assert!(!expr.matches(&event));
+        event.tags.push("coldwar".to_string());
+        assert!(expr.matches(&event));
+        event.date = Date::new(1992, 1, 1).unwrap();
+        assert!(!expr.matches(&event));
+
+        let expr = QueryExpr::parse("ussr").unwrap();
+        assert_eq!(expr, QueryExpr::Text("ussr".to_string()));
+        assert!(expr.matches(&event));
+
+        let expr = QueryExpr::parse("weekday:wed AND ussr").unwrap();
+        assert!(expr.matches(&event)); // 1992-01-01
+        assert!(QueryExpr::parse("weekday:someday").is_err());
+
+        let expr = QueryExpr::parse("category:Politics").unwrap();
+        assert!(!expr.matches(&event));
+        event.category = Some("politics".to_string());
+        assert!(expr.matches(&event));
+
+        let expr = QueryExpr::parse("person:boris yeltsin AND ussr").unwrap();
+        assert!(!expr.matches(&event));
+        event.people.push("Boris Yeltsin".to_string());
+        assert!(expr.matches(&event));
+
+        assert_eq!(
+            QueryExpr::parse("length:..20"),
+            Ok(QueryExpr::Length(0..=20))
+        );
+        assert!(QueryExpr::parse("length:14").unwrap().matches(&event));
+        assert!(!QueryExpr::parse("length:0").unwrap().matches(&event));
+        event.description = " \t".to_string();
+        assert!(QueryExpr::parse("length:0").unwrap().matches(&event));
+        assert!(QueryExpr::parse("length:short").is_err());
+
+        assert!(QueryExpr::parse("1991..1945").is_err());
+        assert!(QueryExpr::parse("tag:a AND ").is_err());
+    }
+
+    #[test]
+    fn test_context_ranges() {
+        let worldline: WorldLine = "1914 WWI begins\n1917 Revolution\n1918 WWI ends\n\
+                                    1929 Crash\n1933 New Deal\n1939 WWII begins\n"
+            .parse()
+            .unwrap();
+        let found = worldline.query_expr(&QueryExpr::parse("WWI").unwrap());
+        assert_eq!(worldline.context_ranges(&found, 0), vec![0..1, 2..3, 5..6]);
+        assert_eq!(worldline.context_ranges(&found, 1), vec![0..6]);
+        let found = worldline.query("crash");
+        assert_eq!(worldline.context_ranges(&found, 2), vec![1..6]);
+        // events from another worldline aren't found
+        let other = worldline.clone();
+        assert!(worldline
+            .context_ranges(&other.query("crash"), 1)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_boolean_queries() {
+        let expr =
+            QueryExpr::parse(r#"caesar AND (senate OR "ides of march") AND NOT tag:fiction"#)
+                .unwrap();
+        let text = |t: &str| QueryExpr::Text(t.to_string());
+        assert_eq!(
+            expr,
+            QueryExpr::And(vec![
+                text("caesar"),
+                QueryExpr::Or(vec![text("senate"), text("ides of march")]),
+                QueryExpr::Not(Box::new(QueryExpr::Tag("fiction".to_string()))),
+            ])
+        );
+        let mut event = Event::parse("BCE 0044-03-15 Caesar stabbed on the Ides of March").unwrap();
+        assert!(expr.matches(&event));
+        event.tags.push("fiction".to_string());
+        assert!(!expr.matches(&event));
+
+        // AND binds tighter than OR, and phrases can contain operators
+        assert_eq!(
+            QueryExpr::parse(r#"world war OR "war AND peace" AND tag:x"#).unwrap(),
+            QueryExpr::Or(vec![
+                text("world war"),
+                QueryExpr::And(vec![text("war and peace"), QueryExpr::Tag("x".to_string())]),
+            ])
+        );
+        for invalid in ["(war", "war)", r#""war"#, r#"war "peace""#, "NOT", "war OR"] {
+            assert!(QueryExpr::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_match_spans() {
+        let expr = QueryExpr::parse("war AND tag:ww1 AND end").unwrap();
+        assert_eq!(
+            expr.match_spans("War ends, WAR is over"),
+            vec![0..3, 4..7, 10..13]
+        );
+        // İ lowercases to two characters
+        assert_eq!(
+            QueryExpr::Text("stanbul".to_string()).match_spans("İSTANBUL"),
+            vec![2..9]
+        );
+        assert!(QueryExpr::Text(String::new()).match_spans("War").is_empty());
+    }
+
+    #[test]
+    fn test_slice() {
+        let worldline: WorldLine = "#worldline v2\n\
+                                    #title: Medieval\n\
+                                    1066 Hastings\n\
+                                    1096 First Crusade\ttags=crusades\n\
+                                    1187 Hattin\n\
+                                    1189 Third Crusade\ttags=crusades\n\
+                                    1346 Crecy\n"
+            .parse()
+            .unwrap();
+        let range = DateRange::parse("1095..1291").unwrap();
+        let slice = worldline.slice(&range, None);
+        assert_eq!(slice.years(), vec![1096, 1187, 1189]);
+        assert_eq!(slice.front_matter(), worldline.front_matter());
+        assert!(!slice.is_read_only());
+        let tag = QueryExpr::parse("tag:crusades").unwrap();
+        assert_eq!(
+            worldline.slice(&range, Some(&tag)).years(),
+            vec![1096, 1189]
+        );
+
+        let positions = worldline.filtered(&tag).positions_in(&worldline);
+        assert_eq!(positions, vec![1, 3]);
+
+        // slices of read-only views can be saved too
+        let view = worldline.filtered(&tag);
+        assert!(!view.slice(&range, None).is_read_only());
+    }
+}
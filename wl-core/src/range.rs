@@ -0,0 +1,105 @@
+//! Ranges of dates, like `wl show 1990 1995` or the query term `1990..1995`.
+//!
+//! Both ends of a range are included whole: a partial end date covers its last day, so
+//! 1990 to 1995 runs from 1990-01-01 through 1995-12-31, the same as the implicit range of a
+//! single date (see [`DateRange::of`]).
+
+use crate::query::parse_whole_date;
+use crate::Date;
+use std::ops::{Bound, RangeBounds};
+
+/// The dates from the start of `start` to the end of `end`. Dates compare by when they start,
+/// so the range ends before the first date after `end` (see [`Date::next`]) rather than at
+/// `end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DateRange {
+    start: Date,
+    end: Date,
+    after: Date,
+}
+
+impl DateRange {
+    /// The range from `start` to `end` (inclusive). It's empty if `end` is before `start`.
+    pub fn new(start: Date, end: Date) -> Self {
+        let after = end.after();
+        Self { start, end, after }
+    }
+
+    /// The range a single date covers, e.g. all of 1994 for 1994.
+    pub fn of(date: Date) -> Self {
+        Self::new(date.clone(), date)
+    }
+
+    /// Parse a `START..END` range, e.g. `1945..1991`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let (start, end) = s
+            .split_once("..")
+            .ok_or_else(|| format!("Invalid range {}, expected e.g. 1945..1991", s))?;
+        let start = parse_whole_date(start)?;
+        let end = parse_whole_date(end)?;
+        if end < start {
+            return Err(format!("Range {} ends before it starts", s));
+        }
+        Ok(Self::new(start, end))
+    }
+
+    pub fn start(&self) -> &Date {
+        &self.start
+    }
+
+    /// The end date as given, e.g. 1995 rather than its last day.
+    pub fn end(&self) -> &Date {
+        &self.end
+    }
+
+    /// The first date after the range, where it ends (exclusive), e.g. 1996 for a range
+    /// ending in 1995.
+    pub fn after(&self) -> &Date {
+        &self.after
+    }
+
+    pub fn contains(&self, date: &Date) -> bool {
+        *date >= self.start && *date < self.after
+    }
+
+    /// The last day of the range, e.g. 1995-12-31 for a range ending in 1995.
+    pub fn last_day(&self) -> Date {
+        Date::from_day_number(self.after.day_number() - 1)
+    }
+}
+
+impl RangeBounds<Date> for DateRange {
+    fn start_bound(&self) -> Bound<&Date> {
+        Bound::Included(&self.start)
+    }
+
+    fn end_bound(&self) -> Bound<&Date> {
+        Bound::Excluded(&self.after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_range() {
+        let date = |s: &str| parse_whole_date(s).unwrap();
+        let range = DateRange::parse("1990..1995").unwrap();
+        assert_eq!(range.end(), &date("1995"));
+        assert_eq!(range.last_day(), date("1995-12-31"));
+        assert!(range.contains(&date("1990")));
+        assert!(range.contains(&date("1995-12-31")));
+        assert!(range.contains(&date("1995-12-31 23:59")));
+        assert!(!range.contains(&date("1996")));
+        assert!(!range.contains(&date("1989-12-31")));
+
+        let march = DateRange::of(date("-44-03"));
+        assert_eq!(march.last_day(), date("-44-03-31"));
+        assert!(march.contains(&date("-44-03-15")));
+        assert!(!march.contains(&date("-44")));
+
+        assert!(DateRange::parse("1995..1990").is_err());
+        assert!(!DateRange::new(date("1995"), date("1990")).contains(&date("1992")));
+    }
+}
@@ -0,0 +1,107 @@
+//! A "timeline ruler" gutter for displaying events, which conveys how much time passes between
+//! them rather than spacing every event equally.
+//!
+//! ```text
+//!      1900 ┼  CE 1914-06-28 Archduke Franz Ferdinand shot
+//!           │  CE 1918-11-11 Armistice
+//!           ┆ 21 years ┆
+//!           │  CE 1939-09-01 Germany invades Poland
+//! ```
+
+use crate::display::DisplayOptions;
+use crate::span::Span;
+use crate::{Event, WorldLine};
+
+const GUTTER_WIDTH: usize = 9;
+
+/// Render events with a ruler gutter. Ticks label each new century, decade, or year
+/// (depending on how much time the events cover), and gaps of at least `min_gap` between
+/// consecutive events get a separator line giving their length.
+pub fn render(events: &[Event], options: &DisplayOptions, min_gap: &Span) -> Vec<String> {
+    let (Some(first), Some(last)) = (events.first(), events.last()) else {
+        return Vec::new();
+    };
+    let tick_size = match last.date.year - first.date.year {
+        300.. => 100,
+        30.. => 10,
+        _ => 1,
+    };
+
+    let refs: Vec<&Event> = events.iter().collect();
+    let texts = options.narrowed(GUTTER_WIDTH + 3).format_events(&refs);
+    let continued = format!("\n{:>GUTTER_WIDTH$} │ ", "");
+
+    let mut lines = Vec::new();
+    let mut previous: Option<&Event> = None;
+    for (event, text) in events.iter().zip(texts) {
+        let tick = event.date.year.div_euclid(tick_size) * tick_size;
+        let mut new_tick = true;
+        if let Some(previous) = previous {
+            if event.date >= previous.date.add_span(min_gap) {
+                let years = previous.date.difference(&event.date).years;
+                let unit = if years == 1 { "year" } else { "years" };
+                lines.push(format!("{:>GUTTER_WIDTH$} ┆ {} {} ┆", "", years, unit));
+            }
+            new_tick = previous.date.year.div_euclid(tick_size) * tick_size != tick;
+        }
+
+        let gutter = if new_tick {
+            format!("{:>GUTTER_WIDTH$} ┼ ", label(tick))
+        } else {
+            format!("{:>GUTTER_WIDTH$} │ ", "")
+        };
+        lines.push(gutter + &text.replace('\n', &continued));
+        previous = Some(event);
+    }
+    lines
+}
+
+fn label(year: i32) -> String {
+    if year < 0 {
+        format!("{} BCE", -year)
+    } else {
+        year.to_string()
+    }
+}
+
+impl WorldLine {
+    /// The events in a range of indices laid out with a ruler gutter. See [`render`].
+    pub fn format_range_with_ruler(
+        &self,
+        start_idx: usize,
+        end_idx: usize,
+        min_gap: &Span,
+        options: &DisplayOptions,
+    ) -> Vec<String> {
+        let events = &self.events[start_idx..end_idx];
+        render(events, options, min_gap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::EraDisplay;
+    use crate::Date;
+
+    #[test]
+    fn test_render_ruler() {
+        let events: Vec<Event> = [(-44, "Caesar"), (14, "Augustus"), (30, "Crucifixion")]
+            .into_iter()
+            .map(|(year, d)| Event::new(Date::new(year, 0, 0).unwrap(), d.to_string()))
+            .collect();
+        let options = DisplayOptions {
+            era: EraDisplay::Always,
+            ..Default::default()
+        };
+        let lines = render(&events, &options, &Span::years(50));
+        assert_eq!(lines.len(), 4);
+        // BCE decades are labelled by their start, e.g. 50 BCE - 41 BCE
+        assert!(lines[0].starts_with("   50 BCE ┼ "));
+        assert_eq!(lines[1], format!("{:>9} ┆ 57 years ┆", ""));
+        assert!(lines[2].starts_with("       10 ┼ "));
+        assert!(lines[3].starts_with("       30 ┼ "));
+
+        assert!(render(&[], &options, &Span::years(50)).is_empty());
+    }
+}
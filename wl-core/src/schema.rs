@@ -0,0 +1,279 @@
+//! A [JSON Schema](https://json-schema.org) for the `json` and `jsonl` formats (see
+//! [`crate::convert`]), for checking worldlines generated by other programs before importing
+//! them. The whole schema describes a `json` file, and its `#/$defs/event` one line of a
+//! `jsonl` file.
+//!
+//! The schema names the file format version (see [`FormatVersion`]) its fields come from as
+//! `x-worldline-format-version`, so programs can tell when it changes.
+
+use crate::format::FormatVersion;
+use crate::json::Value;
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+fn object(fields: Vec<(&str, Value)>) -> Value {
+    Value::Object(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    )
+}
+
+/// A schema with a type and description, and any other keywords.
+fn typed(type_: &str, description: &str, rest: Vec<(&str, Value)>) -> Value {
+    let mut fields = vec![
+        ("type", string(type_)),
+        ("description", string(description)),
+    ];
+    fields.extend(rest);
+    object(fields)
+}
+
+fn reference(def: &str, description: &str) -> Value {
+    object(vec![
+        ("$ref", string(&format!("#/$defs/{}", def))),
+        ("description", string(description)),
+    ])
+}
+
+fn strings(description: &str) -> Value {
+    typed(
+        "array",
+        description,
+        vec![("items", object(vec![("type", string("string"))]))],
+    )
+}
+
+fn event() -> Value {
+    let properties = vec![
+        ("date", reference("date", "When the event happened")),
+        ("description", typed("string", "What happened", vec![])),
+        (
+            "category",
+            typed("string", "The category, e.g. \"war\"", vec![]),
+        ),
+        (
+            "place",
+            typed(
+                "string",
+                "Where it happened, a name or coordinates, e.g. \"Rome\" or \"(41.9,12.5)\"",
+                vec![],
+            ),
+        ),
+        ("id", typed("string", "A unique identifier", vec![])),
+        (
+            "end",
+            reference("date", "When the event ended, for events spanning time"),
+        ),
+        ("tags", strings("Tags")),
+        ("people", strings("The people the event is about")),
+        (
+            "series",
+            typed(
+                "string",
+                "The series the event is part of, e.g. \"Punic Wars\"",
+                vec![],
+            ),
+        ),
+        ("sources", strings("Citation keys of the event's sources")),
+        (
+            "notes",
+            typed(
+                "array",
+                "Notes added to the event after the fact",
+                vec![(
+                    "items",
+                    object(vec![
+                        ("type", string("object")),
+                        (
+                            "properties",
+                            object(vec![
+                                (
+                                    "timestamp",
+                                    reference("timestamp", "When the note was added"),
+                                ),
+                                ("text", object(vec![("type", string("string"))])),
+                            ]),
+                        ),
+                        (
+                            "required",
+                            Value::Array(vec![string("timestamp"), string("text")]),
+                        ),
+                        ("additionalProperties", Value::Bool(false)),
+                    ]),
+                )],
+            ),
+        ),
+        (
+            "archived",
+            typed("boolean", "Whether the event is archived", vec![]),
+        ),
+        (
+            "pinned",
+            typed(
+                "boolean",
+                "Whether the event is pinned to the dashboard",
+                vec![],
+            ),
+        ),
+        (
+            "seq",
+            typed(
+                "integer",
+                "The event's place among the events on the same date, lowest first",
+                vec![
+                    ("minimum", Value::Number(0.0)),
+                    ("maximum", Value::Number(u32::MAX as f64)),
+                ],
+            ),
+        ),
+        (
+            "created",
+            reference("timestamp", "When the event was added"),
+        ),
+        (
+            "modified",
+            reference("timestamp", "When the event was last changed"),
+        ),
+    ];
+    object(vec![
+        ("type", string("object")),
+        ("properties", object(properties)),
+        (
+            "required",
+            Value::Array(vec![string("date"), string("description")]),
+        ),
+        ("additionalProperties", Value::Bool(false)),
+    ])
+}
+
+/// The JSON Schema, indented for reading.
+pub fn json_schema() -> String {
+    let defs = object(vec![
+        ("event", event()),
+        (
+            "date",
+            typed(
+                "string",
+                "A year, month or day, negative for BCE, optionally with a time, or a week or \
+                 quarter, e.g. \"1945-09-02\", \"1945-09\", \"-0044-03-15\", \"2023-12-25 14:30\", \
+                 \"2024-W05\" or \"2024-Q2\"",
+                vec![(
+                    "pattern",
+                    string(concat!(
+                        r"^-?\d{1,4}(-((0[1-9]|1[0-2])(-(0[1-9]|[12]\d|3[01])",
+                        r"( ([01]\d|2[0-3]):[0-5]\d)?)?|W(0[1-9]|[1-4]\d|5[0-3])|Q[1-4]))?$"
+                    )),
+                )],
+            ),
+        ),
+        (
+            "timestamp",
+            typed(
+                "string",
+                "A UTC time, e.g. \"2025-03-01T14:30:00Z\"",
+                vec![(
+                    "pattern",
+                    string(r"^-?\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}Z$"),
+                )],
+            ),
+        ),
+    ]);
+    let schema = object(vec![
+        (
+            "$schema",
+            string("https://json-schema.org/draft/2020-12/schema"),
+        ),
+        ("title", string("worldline")),
+        (
+            "description",
+            string("A worldline in the json format, as written by wl export --format json and wl convert"),
+        ),
+        (
+            "x-worldline-format-version",
+            Value::Number(FormatVersion::LATEST.number() as f64),
+        ),
+        ("type", string("object")),
+        (
+            "properties",
+            object(vec![
+                (
+                    "front_matter",
+                    typed(
+                        "object",
+                        "Key-value metadata, as in the header of a v2 file",
+                        vec![(
+                            "additionalProperties",
+                            object(vec![("type", string("string"))]),
+                        )],
+                    ),
+                ),
+                (
+                    "events",
+                    typed(
+                        "array",
+                        "The events",
+                        vec![("items", object(vec![("$ref", string("#/$defs/event"))]))],
+                    ),
+                ),
+            ]),
+        ),
+        ("required", Value::Array(vec![string("events")])),
+        ("$defs", defs),
+    ]);
+    format!("{}\n", schema.pretty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestamp::Timestamp;
+    use crate::{Event, Note};
+
+    #[test]
+    fn test_json_schema() {
+        let schema = Value::parse(&json_schema()).unwrap();
+        assert_eq!(
+            schema.get("x-worldline-format-version"),
+            Some(&Value::Number(2.0))
+        );
+        let Some(Value::Object(properties)) = schema
+            .get("$defs")
+            .and_then(|defs| defs.get("event"))
+            .and_then(|event| event.get("properties"))
+        else {
+            panic!("no event properties");
+        };
+
+        // every field an event can be exported with is described
+        let timestamp = Timestamp::parse("2025-03-01T14:30:00Z").unwrap();
+        let mut event = Event::parse("1945-09-02 [war] End of WWII @Tokyo").unwrap();
+        event.id = Some("ww2-end".to_string());
+        event.end = Some(event.date.clone());
+        event.tags = vec!["ww2".to_string()];
+        event.people = vec!["Douglas MacArthur".to_string()];
+        event.series = Some("World Wars".to_string());
+        event.sources = vec!["keegan1989".to_string()];
+        event.notes = vec![Note {
+            timestamp,
+            text: "Signed aboard USS Missouri".to_string(),
+        }];
+        event.archived = true;
+        event.pinned = true;
+        event.seq = Some(1);
+        event.created = Some(timestamp);
+        event.modified = Some(timestamp);
+        let Value::Object(fields) = Value::parse(&event.to_json()).unwrap() else {
+            panic!("events are objects");
+        };
+        let keys = |fields: &[(String, Value)]| -> Vec<String> {
+            let mut keys: Vec<String> = fields.iter().map(|(k, _)| k.clone()).collect();
+            keys.sort();
+            keys
+        };
+        assert_eq!(keys(properties), keys(&fields));
+    }
+}
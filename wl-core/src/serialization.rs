@@ -0,0 +1,103 @@
+//! Serde support, behind the `serde` feature.
+//!
+//! Dates and timestamps are serialized as strings in the same form as the file format, e.g.
+//! `"-0044-03-15"` and `"2025-03-01T14:30:00Z"`. A [`WorldLine`] is serialized as
+//! `{"front_matter": [[key, value], ...], "events": [...]}`, which is the same shape the
+//! `json` exchange format uses apart from the front matter (see [`crate::convert`]).
+
+use crate::query::parse_whole_date;
+use crate::timestamp::Timestamp;
+use crate::{Date, Event, WorldLine};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_whole_date(&s).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Timestamp::parse(&s).map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize)]
+struct WorldLineRef<'a> {
+    front_matter: &'a [(String, String)],
+    events: &'a [Event],
+}
+
+#[derive(Deserialize)]
+struct WorldLineData {
+    #[serde(default)]
+    front_matter: Vec<(String, String)>,
+    events: Vec<Event>,
+}
+
+impl Serialize for WorldLine {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        WorldLineRef {
+            front_matter: &self.front_matter,
+            events: &self.events,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WorldLine {
+    /// Events don't need to be in order; they're sorted as they're added.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = WorldLineData::deserialize(deserializer)?;
+        let mut worldline = WorldLine {
+            front_matter: data.front_matter,
+            ..WorldLine::default()
+        };
+        worldline.add_events_sorted(data.events);
+        Ok(worldline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serde_round_trip() {
+        let json = r#"{"events":[
+            {"date":"1945-09-02","description":"WWII ends","tags":["war"]},
+            {"date":"-0044-03-15","description":"Ides of March","notes":[
+                {"timestamp":"2025-03-01T14:30:00Z","text":"Check"}]}
+        ]}"#;
+        let worldline: WorldLine = serde_json::from_str(json).unwrap();
+        assert_eq!(worldline.len(), 2);
+        assert_eq!(
+            worldline.get(0).unwrap().date,
+            Date::new(-44, 3, 15).unwrap()
+        );
+
+        let written = serde_json::to_string(&worldline).unwrap();
+        assert!(
+            written.contains(r#"{"date":"1945-09-02","description":"WWII ends","tags":["war"]}"#)
+        );
+        let reread: WorldLine = serde_json::from_str(&written).unwrap();
+        assert_eq!(reread.events, worldline.events);
+
+        assert!(serde_json::from_str::<Date>(r#""0000""#).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! Named series of events, e.g. the battles and treaties of the "Punic Wars", for following
+//! one story through a worldline. Events join a series with their `series` field (see
+//! [`crate::format`]), and each series can be shown on its own under a summary of it:
+//!
+//! ```text
+//! Punic Wars: 3 events, 264 BCE to 146 BCE
+//! ```
+
+use crate::group::GroupBy;
+use crate::query::QueryExpr;
+use crate::WorldLine;
+
+impl WorldLine {
+    /// The names of all series events are in, sorted.
+    pub fn series_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .events
+            .iter()
+            .filter_map(|e| e.series.as_deref())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// The events in a series (case-insensitive), as a read-only worldline.
+    pub fn series(&self, name: &str) -> WorldLine {
+        self.filtered(&QueryExpr::Series(name.to_lowercase()))
+    }
+
+    /// A line summing up the events in a series: its name as the events spell it, their
+    /// number, and the years from the first to the last (or the end of the last to end). None
+    /// if no events are in it.
+    pub fn series_summary(&self, name: &str) -> Option<String> {
+        let series = self.series(name);
+        let first = series.events.first()?;
+        let name = first.series.as_deref().unwrap_or(name);
+        let first = first.date.year;
+        let last = series
+            .events
+            .iter()
+            .map(|e| e.end.as_ref().unwrap_or(&e.date).year)
+            .max()
+            .unwrap_or(first);
+        let years = if first == last {
+            GroupBy::Year.label(first)
+        } else {
+            format!(
+                "{} to {}",
+                GroupBy::Year.label(first),
+                GroupBy::Year.label(last)
+            )
+        };
+        let plural = if series.len() == 1 { "" } else { "s" };
+        Some(format!(
+            "{}: {} event{}, {}",
+            name,
+            series.len(),
+            plural,
+            years
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_series() {
+        let worldline: WorldLine = "#worldline v2\n\
+                                    -264 First Punic War begins\tseries=Punic Wars\n\
+                                    -218 Hannibal crosses the Alps\tseries=Punic Wars\n\
+                                    -149 Siege of Carthage\tend=-146\tseries=Punic Wars\n\
+                                    -44-03-15 Caesar assassinated\n\
+                                    1914-07-28 WWI begins\tseries=World Wars\n"
+            .parse()
+            .unwrap();
+        assert_eq!(worldline.series_names(), ["Punic Wars", "World Wars"]);
+        assert_eq!(worldline.series("PUNIC WARS").len(), 3);
+        assert_eq!(
+            worldline.series_summary("punic wars").unwrap(),
+            "Punic Wars: 3 events, 264 BCE to 146 BCE"
+        );
+        assert_eq!(
+            worldline.series_summary("World Wars").unwrap(),
+            "World Wars: 1 event, 1914"
+        );
+        assert_eq!(worldline.series_summary("Cold War"), None);
+
+        let written = worldline.to_string();
+        assert!(written.contains("Hannibal crosses the Alps\tseries=Punic Wars\n"));
+        assert_eq!(
+            written.parse::<WorldLine>().unwrap().events(),
+            worldline.events()
+        );
+    }
+}
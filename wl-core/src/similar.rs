@@ -0,0 +1,317 @@
+//! Finding events that are probably already in the worldline, to warn before adding duplicates.
+//!
+//! Imports and merges call these conflicts when the events differ, and resolve them with a
+//! [`Resolution`] for each, e.g. chosen by the user or by a [`Strategy`].
+
+use crate::progress::{Reporter, Silent};
+use crate::range::DateRange;
+use crate::{Event, WorldLine};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A [`similarity`] above which descriptions are likely to be of the same event.
+pub const DEFAULT_THRESHOLD: f64 = 0.6;
+
+/// The lowercase words of a description, ignoring punctuation.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The number of single character insertions, deletions and substitutions between two strings.
+pub(crate) fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// How alike two descriptions are, from 0 to 1 (the same apart from case and punctuation).
+/// This is the larger of the overlap of their words and one minus their edit distance relative
+/// to the longer one, so both reordered ("Hastings, battle of") and misspelled ("Battle of
+/// Hastigns") descriptions are close to the original.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (words(a), words(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    let (a_set, b_set): (BTreeSet<&String>, BTreeSet<&String>) =
+        (a.iter().collect(), b.iter().collect());
+    let overlap = a_set.intersection(&b_set).count() as f64 / a_set.union(&b_set).count() as f64;
+
+    let a: Vec<char> = a.join(" ").chars().collect();
+    let b: Vec<char> = b.join(" ").chars().collect();
+    let longest = a.len().max(b.len()) as f64;
+    let edits = 1.0 - levenshtein(&a, &b) as f64 / longest;
+    overlap.max(edits)
+}
+
+/// What to do with an imported event that conflicts with a similar one in the worldline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution {
+    /// Keep the worldline's event, dropping the imported one.
+    KeepMine,
+    /// Replace the worldline's event with the imported one.
+    TakeTheirs,
+    /// Keep both events.
+    KeepBoth,
+    /// Replace the worldline's event with another, e.g. the imported one after editing it.
+    Replace(Box<Event>),
+}
+
+/// How to resolve every conflict without asking, e.g. in scripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Strategy {
+    Mine,
+    Theirs,
+    /// Keep both events, as imports do when conflicts aren't resolved.
+    #[default]
+    Both,
+}
+
+impl Strategy {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "mine" => Ok(Strategy::Mine),
+            "theirs" => Ok(Strategy::Theirs),
+            "both" => Ok(Strategy::Both),
+            _ => Err(format!(
+                "Invalid strategy '{}', expected mine, theirs, or both",
+                s
+            )),
+        }
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        match self {
+            Strategy::Mine => Resolution::KeepMine,
+            Strategy::Theirs => Resolution::TakeTheirs,
+            Strategy::Both => Resolution::KeepBoth,
+        }
+    }
+}
+
+/// What [`WorldLine::import_resolving`] changed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub added: usize,
+    /// Events of the worldline replaced by conflicting ones.
+    pub replaced: usize,
+    /// Imported events already in the worldline, or imported twice.
+    pub duplicates: usize,
+    /// Conflicting events that weren't imported, keeping the worldline's.
+    pub skipped: usize,
+}
+
+impl WorldLine {
+    /// Add all events from another worldline like [`WorldLine::import`], but resolve the ones
+    /// conflicting with an event in this worldline (the most [`similar`](similarity) one on
+    /// the same date, if any is similar enough) with `resolve`, which is given that event and
+    /// the imported one.
+    pub fn import_resolving(
+        &mut self,
+        other: WorldLine,
+        resolve: impl FnMut(&Event, &Event) -> Resolution,
+    ) -> ImportReport {
+        self.import_reporting(other, resolve, &mut Silent)
+    }
+
+    /// [`WorldLine::import_resolving`], reporting progress through the imported events to
+    /// `reporter`.
+    pub fn import_reporting(
+        &mut self,
+        other: WorldLine,
+        mut resolve: impl FnMut(&Event, &Event) -> Resolution,
+        reporter: &mut dyn Reporter,
+    ) -> ImportReport {
+        let imported = other.len();
+        let mut events: Vec<Event> = other
+            .events
+            .into_iter()
+            .filter(|e| !self.events[self.date_range(&DateRange::of(e.date.clone()))].contains(e))
+            .collect();
+        events.sort();
+        events.dedup();
+        let duplicates = imported - events.len();
+
+        reporter.start("Importing", events.len());
+        let mut added = Vec::new();
+        let mut replaced = BTreeMap::new();
+        let mut skipped = 0;
+        for event in events {
+            reporter.advance(1);
+            let Some(&(i, _)) = self.find_similar(&event, DEFAULT_THRESHOLD).first() else {
+                added.push(event);
+                continue;
+            };
+            let replacement = match resolve(&self.events[i], &event) {
+                Resolution::KeepMine => {
+                    skipped += 1;
+                    continue;
+                }
+                Resolution::KeepBoth => {
+                    added.push(event);
+                    continue;
+                }
+                Resolution::TakeTheirs => event,
+                Resolution::Replace(replacement) => *replacement,
+            };
+            // an event replaced twice keeps both replacements
+            if let Some(previous) = replaced.insert(i, replacement) {
+                added.push(previous);
+            }
+        }
+
+        reporter.finish();
+
+        let report = ImportReport {
+            added: added.len(),
+            replaced: replaced.len(),
+            duplicates,
+            skipped,
+        };
+        let mut i = 0;
+        self.events.retain(|_| {
+            i += 1;
+            !replaced.contains_key(&(i - 1))
+        });
+        added.extend(replaced.into_values());
+        self.add_events_sorted(added);
+        report
+    }
+
+    /// The events on the same date as `event` with descriptions at least `threshold` similar to
+    /// its description (see [`similarity`]), as indices with their similarity, most similar
+    /// first.
+    pub fn find_similar(&self, event: &Event, threshold: f64) -> Vec<(usize, f64)> {
+        let range = self.date_range(&DateRange::of(event.date.clone()));
+        let mut similar = crate::query::scan(&self.events[range.clone()], |i, e| {
+            if e.date != event.date {
+                return None;
+            }
+            let s = similarity(&e.description, &event.description);
+            (s >= threshold).then_some((range.start + i, s))
+        });
+        similar.sort_by(|a, b| b.1.total_cmp(&a.1));
+        similar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_similar() {
+        assert_eq!(similarity("Battle of Hastings", "battle of hastings!"), 1.0);
+        assert_eq!(similarity("Battle of Hastings", "Hastings, battle of"), 1.0);
+        assert!(similarity("Battle of Hastings", "Battle of Hastigns") > DEFAULT_THRESHOLD);
+        assert!(similarity("Battle of Hastings", "Harold crowned") < DEFAULT_THRESHOLD);
+
+        let worldline: WorldLine = "1066-10-14 Battle of Hastings\n\
+                                    1066-10-14 Harold killed\n\
+                                    1066-12-25 Battle of Hastings anniversary\n"
+            .parse()
+            .unwrap();
+        let event = Event::parse("1066-10-14 The battle of Hastings").unwrap();
+        let similar: Vec<usize> = worldline
+            .find_similar(&event, DEFAULT_THRESHOLD)
+            .iter()
+            .map(|(i, _)| *i)
+            .collect();
+        assert_eq!(similar, vec![0]);
+    }
+
+    #[test]
+    fn test_import_resolving() {
+        let worldline: WorldLine = "1066-10-14 Battle of Hastings\n\
+                                    1066-12-25 William crowned\n"
+            .parse()
+            .unwrap();
+        let theirs = || -> WorldLine {
+            "1066-10-14 The battle of Hastings\n\
+             1066-12-25 William crowned\n\
+             1087-09-09 William dies\n"
+                .parse()
+                .unwrap()
+        };
+        let descriptions = |worldline: &WorldLine| -> Vec<String> {
+            worldline
+                .events()
+                .iter()
+                .map(|e| e.description.clone())
+                .collect()
+        };
+
+        let mut mine = worldline.clone();
+        let mut conflicts = Vec::new();
+        let report = mine.import_resolving(theirs(), |mine, theirs| {
+            conflicts.push((mine.description.clone(), theirs.description.clone()));
+            Strategy::Theirs.resolution()
+        });
+        assert_eq!(
+            conflicts,
+            [(
+                "Battle of Hastings".to_string(),
+                "The battle of Hastings".to_string()
+            )]
+        );
+        assert_eq!(
+            report,
+            ImportReport {
+                added: 1,
+                replaced: 1,
+                duplicates: 1,
+                skipped: 0
+            }
+        );
+        assert_eq!(
+            descriptions(&mine),
+            ["The battle of Hastings", "William crowned", "William dies"]
+        );
+
+        // progress goes to the reporter, through the events that aren't duplicates
+        #[derive(Default)]
+        struct Counter(Vec<(String, usize)>, usize, bool);
+        impl Reporter for Counter {
+            fn start(&mut self, what: &str, total: usize) {
+                self.0.push((what.to_string(), total));
+            }
+            fn advance(&mut self, n: usize) {
+                self.1 += n;
+            }
+            fn finish(&mut self) {
+                self.2 = true;
+            }
+        }
+        let mut mine = worldline.clone();
+        let mut counter = Counter::default();
+        let report = mine.import_reporting(theirs(), |_, _| Resolution::KeepMine, &mut counter);
+        assert_eq!((report.added, report.skipped), (1, 1));
+        assert_eq!(counter.0, [("Importing".to_string(), 2)]);
+        assert_eq!((counter.1, counter.2), (2, true));
+        assert_eq!(
+            descriptions(&mine),
+            ["Battle of Hastings", "William crowned", "William dies"]
+        );
+        let mut mine = worldline.clone();
+        mine.import_resolving(theirs(), |_, _| Resolution::KeepBoth);
+        assert_eq!(mine.len(), 4);
+        let mut mine = worldline;
+        let edited = Event::parse("1066-10-14 Battle of Hastings (Senlac Hill)").unwrap();
+        mine.import_resolving(theirs(), |_, _| {
+            Resolution::Replace(Box::new(edited.clone()))
+        });
+        assert_eq!(descriptions(&mine)[0], "Battle of Hastings (Senlac Hill)");
+
+        assert_eq!(Strategy::parse("Theirs"), Ok(Strategy::Theirs));
+        assert!(Strategy::parse("newest").is_err());
+    }
+}
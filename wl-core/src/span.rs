@@ -0,0 +1,387 @@
+//! Calendar spans ("1y", "6m", "2w", "30d") and date arithmetic with them.
+
+use crate::display::DisplayOptions;
+use crate::filter::EventFilter;
+use crate::period::Period;
+use crate::{Date, Event, WorldLine};
+
+/// A signed length of calendar time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub years: i32,
+    pub months: i32,
+    pub days: i32,
+}
+
+impl Span {
+    /// The longest span that can be parsed, in years: enough to cross the supported range.
+    pub const MAX_YEARS: i32 = Date::MAX_YEAR - Date::MIN_YEAR;
+
+    pub fn years(years: i32) -> Self {
+        Self {
+            years,
+            ..Self::default()
+        }
+    }
+
+    /// Parse a span like "1y", "18m", "2w", "30d", "1y6m", or "-12y".
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid span '{}', expected e.g. 10y, 6m, 2w, or 30d", s);
+        let (sign, rest) = match s.trim().strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, s.trim().strip_prefix('+').unwrap_or(s.trim())),
+        };
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+
+        // accumulated in i64 so absurd input is rejected rather than overflowing
+        let (mut years, mut months, mut days) = (0i64, 0i64, 0i64);
+        let mut number = String::new();
+        for c in rest.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            let n: i64 = number.parse().map_err(|_| invalid())?;
+            let n = n.min(i64::MAX / 366);
+            number.clear();
+            match c {
+                'y' => years += sign * n,
+                'm' => months += sign * n,
+                'w' => days += sign * 7 * n,
+                'd' => days += sign * n,
+                _ => return Err(invalid()),
+            }
+        }
+        if !number.is_empty() {
+            return Err(invalid());
+        }
+
+        let max_years = Self::MAX_YEARS as i64;
+        if years.abs() > max_years || months.abs() > max_years * 12 || days.abs() > max_years * 366
+        {
+            return Err(format!(
+                "Span '{}' is too long; spans can be at most {} years",
+                s,
+                Self::MAX_YEARS
+            ));
+        }
+        Ok(Self {
+            years: years as i32,
+            months: months as i32,
+            days: days as i32,
+        })
+    }
+
+    pub fn negate(&self) -> Self {
+        Self {
+            years: -self.years,
+            months: -self.months,
+            days: -self.days,
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut wrote = false;
+        for (n, unit) in [(self.years, 'y'), (self.months, 'm'), (self.days, 'd')] {
+            if n != 0 {
+                write!(f, "{}{}", n, unit)?;
+                wrote = true;
+            }
+        }
+        if !wrote {
+            write!(f, "0d")?;
+        }
+        Ok(())
+    }
+}
+
+impl Date {
+    /// Days since 1970-01-01 in the proleptic Gregorian calendar. Partial dates count from the
+    /// first day of their year or month.
+    pub fn day_number(&self) -> i64 {
+        days_from_civil(
+            Self::astronomical_year(self.year) as i64,
+            self.month.max(1) as i64,
+            self.day.max(1) as i64,
+        )
+    }
+
+    /// The day-precision date for a day number, the inverse of [`Date::day_number`]. Day
+    /// numbers outside the supported years give the first or last supported day.
+    pub fn from_day_number(days: i64) -> Self {
+        let first = days_from_civil(Self::astronomical_year(Self::MIN_YEAR) as i64, 1, 1);
+        let last = days_from_civil(Self::MAX_YEAR as i64, 12, 31);
+        let (year, month, day) = civil_from_days(days.clamp(first, last));
+        Self::new(from_astronomical_year(year as i32), month as u8, day as u8).unwrap()
+    }
+
+    /// Move the date by a span, keeping its precision: days are ignored for year and month
+    /// precision dates, and months too for year precision dates (beyond whole years).
+    /// Days past the end of the resulting month are clamped, e.g. Jan 31 + 1m = Feb 28, and
+    /// results outside the supported years are clamped to the first or last supported date.
+    /// Weeks and quarters move by their first day or month, to the week or quarter it lands in.
+    pub fn add_span(&self, span: &Span) -> Self {
+        match self.try_add_span(span) {
+            Ok(date) | Err(date) => date,
+        }
+    }
+
+    /// Move the date by a span like [`Date::add_span`], or None if the result would be outside
+    /// the supported years rather than clamping it.
+    pub fn checked_add_span(&self, span: &Span) -> Option<Self> {
+        self.try_add_span(span).ok()
+    }
+
+    /// The date moved by a span, or Err with it clamped to the supported years.
+    fn try_add_span(&self, span: &Span) -> Result<Self, Self> {
+        if let Some(time) = self.time {
+            let day = Self {
+                time: None,
+                ..self.clone()
+            };
+            let with_time = |date: Self| date.with_time(Some(time)).unwrap();
+            return day.try_add_span(span).map(with_time).map_err(with_time);
+        }
+        if self.period != Period::Calendar {
+            let start = Self {
+                period: Period::Calendar,
+                ..self.clone()
+            };
+            let with_period = |date: Self| date.with_period(self.period);
+            return start
+                .try_add_span(span)
+                .map(with_period)
+                .map_err(with_period);
+        }
+        let year = Self::astronomical_year(self.year) as i64;
+        let (years, months) = (span.years as i64, span.months as i64);
+        if self.month == 0 {
+            let year = year + years + months / 12;
+            return match supported_year(year) {
+                Some(year) => Ok(Self::new(year, 0, 0).unwrap()),
+                None => Err(self.bound(year > 0)),
+            };
+        }
+
+        let months = year * 12 + (self.month as i64 - 1) + years * 12 + months;
+        let Some(year) = supported_year(months.div_euclid(12)) else {
+            return Err(self.bound(months > 0));
+        };
+        let month = (months.rem_euclid(12) + 1) as u8;
+        if self.day == 0 {
+            return Ok(Self::new(year, month, 0).unwrap());
+        }
+
+        let day = self.day.min(Self::days_in_month(year, month));
+        let days = Self::new(year, month, day).unwrap().day_number() + span.days as i64;
+        let date = Self::from_day_number(days);
+        if date.day_number() == days {
+            Ok(date)
+        } else {
+            Err(date)
+        }
+    }
+}
+
+impl WorldLine {
+    /// Move the events matching a filter by a span, e.g. to correct a systematic dating error
+    /// found later, keeping the worldline sorted. Dates and end dates move like with
+    /// [`Date::add_span`], so 1 BCE + 1y is 1 CE. If any event would move outside the supported
+    /// years, nothing moves. Returns the number of events moved.
+    pub fn shift_events(
+        &mut self,
+        filter: &impl EventFilter,
+        delta: &Span,
+    ) -> Result<usize, String> {
+        let shifted = self
+            .events
+            .iter()
+            .filter(|e| filter.matches(e))
+            .map(|e| -> Result<Event, String> {
+                let shift = |date: &Date| {
+                    date.checked_add_span(delta).ok_or_else(|| {
+                        format!(
+                            "Moving {} by {} would take it outside the supported years",
+                            e.format_for_display(true, &DisplayOptions::default()),
+                            delta
+                        )
+                    })
+                };
+                let date = shift(&e.date)?;
+                let end = e.end.as_ref().map(shift).transpose()?;
+                Ok(Event {
+                    date,
+                    end,
+                    ..e.clone()
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.events.retain(|e| !filter.matches(e));
+        let moved = shifted.len();
+        self.add_events_sorted(shifted);
+        Ok(moved)
+    }
+}
+
+impl Date {
+    /// The first or last supported date, with the same precision as this one.
+    fn bound(&self, last: bool) -> Self {
+        let (year, month, day) = if last {
+            (Self::MAX_YEAR, 12, 31)
+        } else {
+            (Self::MIN_YEAR, 1, 1)
+        };
+        Self {
+            year,
+            month: if self.month == 0 { 0 } else { month },
+            day: if self.day == 0 { 0 } else { day },
+            period: Period::Calendar,
+            time: None,
+        }
+    }
+}
+
+/// The BCE/CE year for an astronomical year, if it's within the supported range.
+fn supported_year(year: i64) -> Option<i32> {
+    let year = if year <= 0 { year - 1 } else { year };
+    let supported = Date::MIN_YEAR as i64..=Date::MAX_YEAR as i64;
+    supported.contains(&year).then_some(year as i32)
+}
+
+/// The inverse of [`Date::astronomical_year`].
+pub(crate) fn from_astronomical_year(year: i32) -> i32 {
+    if year <= 0 {
+        year - 1
+    } else {
+        year
+    }
+}
+
+// Conversions between civil dates and day numbers, from Howard Hinnant's date algorithms:
+// https://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_span() {
+        assert_eq!(Span::parse("1y").unwrap(), Span::years(1));
+        assert_eq!(
+            Span::parse("1y6m2w").unwrap(),
+            Span {
+                years: 1,
+                months: 6,
+                days: 14
+            }
+        );
+        assert_eq!(Span::parse("-12y").unwrap(), Span::years(-12));
+        assert_eq!(Span::parse("+12y").unwrap(), Span::years(12));
+        assert_eq!(Span::parse("1y6m").unwrap().to_string(), "1y6m");
+        assert!(Span::parse("19998y").is_ok());
+        for invalid in ["", "y", "12", "1x", "-", "19999y", "99999999999999999999d"] {
+            assert!(Span::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_day_numbers() {
+        assert_eq!(Date::new(1970, 1, 1).unwrap().day_number(), 0);
+        assert_eq!(Date::new(1970, 0, 0).unwrap().day_number(), 0);
+        for (year, month, day) in [(2024, 2, 29), (-44, 3, 15), (-1, 12, 31), (1, 1, 1)] {
+            let date = Date::new(year, month, day).unwrap();
+            assert_eq!(Date::from_day_number(date.day_number()), date);
+        }
+        // no year 0
+        assert_eq!(
+            Date::new(-1, 12, 31).unwrap().day_number() + 1,
+            Date::new(1, 1, 1).unwrap().day_number()
+        );
+    }
+
+    #[test]
+    fn test_add_span() {
+        let add = |date: (i32, u8, u8), span: &str| {
+            let date = Date::new(date.0, date.1, date.2).unwrap();
+            let d = date.add_span(&Span::parse(span).unwrap());
+            (d.year, d.month, d.day)
+        };
+        assert_eq!(add((2023, 1, 31), "1m"), (2023, 2, 28));
+        assert_eq!(add((2023, 12, 31), "1d"), (2024, 1, 1));
+        assert_eq!(add((2023, 3, 1), "-1d"), (2023, 2, 28));
+        assert_eq!(add((2023, 11, 0), "3m"), (2024, 2, 0));
+        assert_eq!(add((-1, 0, 0), "1y"), (1, 0, 0));
+        assert_eq!(add((5, 0, 0), "-10y"), (-6, 0, 0));
+        assert_eq!(add((1914, 0, 0), "30d"), (1914, 0, 0));
+        // clamped to the supported years
+        assert_eq!(add((9000, 6, 0), "2000y"), (9999, 12, 0));
+        assert_eq!(add((-9000, 0, 0), "-2000y"), (-9999, 0, 0));
+        assert_eq!(add((9999, 12, 30), "5d"), (9999, 12, 31));
+        let date = Date::new(9999, 12, 30).unwrap();
+        assert_eq!(date.checked_add_span(&Span::parse("5d").unwrap()), None);
+        assert!(date.checked_add_span(&Span::parse("1d").unwrap()).is_some());
+    }
+
+    #[test]
+    fn test_shift_events() {
+        let mut worldline: WorldLine = "-3 Dynasty X founded\n-2 Dynasty X falls\n1 Rome\n"
+            .parse()
+            .unwrap();
+        let dynasty = crate::query::QueryExpr::parse("dynasty").unwrap();
+        assert_eq!(worldline.shift_events(&dynasty, &Span::years(3)), Ok(2));
+        let dates: Vec<_> = worldline.iter().map(|e| e.date.year).collect();
+        assert_eq!(dates, [1, 1, 2]);
+        assert_eq!(worldline.get(2).unwrap().description, "Dynasty X falls");
+
+        assert!(worldline
+            .shift_events(&dynasty, &Span::years(9999))
+            .is_err());
+        assert_eq!(worldline.get(2).unwrap().date.year, 2);
+
+        let mut worldline: WorldLine = "#worldline v2\n CE 1939-09-01 WWII\tend=1945-09-02\n"
+            .parse()
+            .unwrap();
+        let war = crate::query::QueryExpr::parse("wwii").unwrap();
+        assert_eq!(worldline.shift_events(&war, &Span::years(10)), Ok(1));
+        let reloaded: WorldLine = worldline.to_string().parse().unwrap();
+        let event = reloaded.get(0).unwrap();
+        assert_eq!(event.date, Date::new(1949, 9, 1).unwrap());
+        assert_eq!(event.end, Some(Date::new(1955, 9, 2).unwrap()));
+
+        let mut worldline: WorldLine = "#worldline v2\n CE 9990 Ends late\tend=9999\n"
+            .parse()
+            .unwrap();
+        let late = crate::query::QueryExpr::parse("late").unwrap();
+        assert!(worldline.shift_events(&late, &Span::years(5)).is_err());
+        assert_eq!(
+            worldline.get(0).unwrap().end,
+            Some(Date::new(9999, 0, 0).unwrap())
+        );
+    }
+}
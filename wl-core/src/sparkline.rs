@@ -0,0 +1,121 @@
+//! A one-line chart of how events are spread over time, e.g. `▁▁▂▁▃▅█▆▂`.
+
+use crate::{Event, WorldLine};
+
+const BARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Chart sorted events' density in `width` equal periods from the first event to the last.
+/// Periods with no events are blank, and the busiest period gets a full bar.
+pub fn sparkline(events: &[Event], width: usize) -> String {
+    let (Some(first), Some(last)) = (events.first(), events.last()) else {
+        return String::new();
+    };
+    let start = first.date.day_number();
+    let days = last.date.day_number() - start + 1;
+    let mut counts = vec![0usize; width];
+    for event in events {
+        let bucket = (event.date.day_number() - start) * width as i64 / days;
+        counts[bucket as usize] += 1;
+    }
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    counts
+        .into_iter()
+        .map(|count| BARS[(count * 8).div_ceil(max)])
+        .collect()
+}
+
+/// The round numbers of years a bar of a [`YearChart`] can stand for.
+const BAR_YEARS: [i32; 17] = [
+    1, 2, 5, 10, 20, 25, 50, 100, 200, 250, 500, 1000, 2000, 2500, 5000, 10000, 20000,
+];
+
+/// A chart of how many events there are in each period of a round number of years, e.g. per
+/// decade from 1910 to 2019, chosen so the chart fits in a width.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YearChart {
+    pub bars: String,
+    /// The years each bar stands for.
+    pub years_per_bar: i32,
+    pub first_year: i32,
+    pub last_year: i32,
+    /// The number of events in the busiest period, shown as a full bar.
+    pub max: usize,
+}
+
+impl YearChart {
+    /// Chart sorted events in at most `width` bars (one year per bar at least), or None without
+    /// events. Periods start at multiples of their length, e.g. decades in 1910, 1920, ...
+    pub fn new(events: &[Event], width: usize) -> Option<Self> {
+        let first = events.first()?.date.year;
+        let last = events.last()?.date.year;
+        let bars = |years: i32| (last.div_euclid(years) - first.div_euclid(years) + 1) as usize;
+        let years_per_bar = BAR_YEARS
+            .into_iter()
+            .find(|&years| bars(years) <= width.max(1))
+            .unwrap_or(BAR_YEARS[BAR_YEARS.len() - 1]);
+        let start = first.div_euclid(years_per_bar);
+        let mut counts = vec![0usize; bars(years_per_bar)];
+        for event in events {
+            counts[(event.date.year.div_euclid(years_per_bar) - start) as usize] += 1;
+        }
+        let max = counts.iter().copied().max().unwrap_or(0);
+        // the period from 0 starts in 1 CE, as there's no year 0
+        let first_year = match start * years_per_bar {
+            0 => 1,
+            year => year,
+        };
+        Some(Self {
+            bars: counts
+                .iter()
+                .map(|&count| BARS[(count * 8).div_ceil(max)])
+                .collect(),
+            years_per_bar,
+            first_year,
+            last_year: (start + counts.len() as i32) * years_per_bar - 1,
+            max,
+        })
+    }
+}
+
+impl WorldLine {
+    /// The [`sparkline`] of a range of events.
+    pub fn sparkline(&self, start_idx: usize, end_idx: usize, width: usize) -> String {
+        sparkline(&self.events[start_idx..end_idx], width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Date;
+
+    #[test]
+    fn test_sparkline() {
+        let events: Vec<Event> = [1900, 1901, 1902, 1903, 1950, 1999]
+            .into_iter()
+            .map(|year| Event::new(Date::new(year, 0, 0).unwrap(), year.to_string()))
+            .collect();
+        assert_eq!(sparkline(&events, 4), "█ ▂▂");
+        assert_eq!(sparkline(&events, 1), "█");
+        assert_eq!(sparkline(&events[..1], 3), "█  ");
+        assert_eq!(sparkline(&[], 3), "");
+
+        let chart = YearChart::new(&events, 10).unwrap();
+        assert_eq!(chart.bars, "█    ▂   ▂");
+        assert_eq!(chart.years_per_bar, 10);
+        assert_eq!(
+            (chart.first_year, chart.last_year, chart.max),
+            (1900, 1999, 4)
+        );
+        let chart = YearChart::new(&events, 200).unwrap();
+        assert_eq!((chart.years_per_bar, chart.bars.chars().count()), (1, 100));
+        let bce: Vec<Event> = [-44, 14]
+            .into_iter()
+            .map(|year| Event::new(Date::new(year, 0, 0).unwrap(), year.to_string()))
+            .collect();
+        let chart = YearChart::new(&bce, 5).unwrap();
+        assert_eq!(chart.years_per_bar, 20);
+        assert_eq!((chart.first_year, chart.last_year), (-60, 19));
+        assert_eq!(YearChart::new(&[], 5), None);
+    }
+}
@@ -0,0 +1,165 @@
+//! Splitting a worldline into topical extracts, one per tag or category, for keeping a master
+//! timeline while reading or sharing parts of it on their own.
+//!
+//! Each part is a worldline that can be saved like a [`WorldLine::slice`], and [`index`] lists
+//! them as Markdown, linking to each part's file.
+
+use crate::group::GroupBy;
+use crate::query::QueryExpr;
+use crate::WorldLine;
+
+/// What a worldline is split by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SplitBy {
+    #[default]
+    Tag,
+    Category,
+}
+
+impl SplitBy {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "tag" | "tags" => Ok(SplitBy::Tag),
+            "category" | "categories" => Ok(SplitBy::Category),
+            _ => Err(format!("Invalid split '{}', expected tag or category", s)),
+        }
+    }
+}
+
+/// The events with one tag or category, to be written to `file_name`.
+pub struct Part {
+    /// The tag or category
+    pub name: String,
+    /// A file name made from the name, e.g. "cold-war.txt" for "Cold War"
+    pub file_name: String,
+    pub worldline: WorldLine,
+}
+
+/// A file name for a tag or category: lowercase, with each run of characters other than
+/// letters and digits replaced by a "-", e.g. "cold-war" for "Cold War".
+fn file_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.to_lowercase().chars() {
+        if c.is_alphanumeric() {
+            stem.push(c);
+        } else if !stem.is_empty() && !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    match stem.trim_end_matches('-') {
+        "" => "untitled".to_string(),
+        stem => stem.to_string(),
+    }
+}
+
+impl WorldLine {
+    /// A worldline for each tag or category, with the events that have it, sorted by name.
+    /// Events with several tags are in each of their tags' worldlines, and events with none
+    /// are in none. Names that would make the same file name get numbered files.
+    pub fn split(&self, by: SplitBy) -> Vec<Part> {
+        let names: Vec<String> = match by {
+            SplitBy::Tag => self.tags().into_iter().map(str::to_string).collect(),
+            SplitBy::Category => {
+                let mut categories: Vec<String> = self
+                    .events
+                    .iter()
+                    .filter_map(|e| e.category.as_ref().map(|c| c.to_lowercase()))
+                    .collect();
+                categories.sort();
+                categories.dedup();
+                categories
+            }
+        };
+        let mut parts: Vec<Part> = Vec::with_capacity(names.len());
+        for name in names {
+            let filter = match by {
+                SplitBy::Tag => QueryExpr::Tag(name.clone()),
+                SplitBy::Category => QueryExpr::Category(name.clone()),
+            };
+            let mut worldline = self.filtered(&filter);
+            worldline.read_only = false;
+            let stem = file_stem(&name);
+            let mut file_name = format!("{}.txt", stem);
+            for n in 2.. {
+                if !parts.iter().any(|part| part.file_name == file_name) {
+                    break;
+                }
+                file_name = format!("{}-{}.txt", stem, n);
+            }
+            parts.push(Part {
+                name,
+                file_name,
+                worldline,
+            });
+        }
+        parts
+    }
+}
+
+/// A Markdown index of the parts of a split worldline, linking to each part's file with its
+/// number of events and the years they span, to be written to `index.md` beside them.
+pub fn index(parts: &[Part], by: SplitBy) -> String {
+    let heading = match by {
+        SplitBy::Tag => "Events by tag",
+        SplitBy::Category => "Events by category",
+    };
+    let mut out = format!("# {}\n\n", heading);
+    for part in parts {
+        let events = part.worldline.events();
+        let (first, last) = (events[0].date.year, events[events.len() - 1].date.year);
+        let years = if first == last {
+            GroupBy::Year.label(first)
+        } else {
+            format!(
+                "{} to {}",
+                GroupBy::Year.label(first),
+                GroupBy::Year.label(last)
+            )
+        };
+        let plural = if events.len() == 1 { "" } else { "s" };
+        out += &format!(
+            "- [{}]({}): {} event{}, {}\n",
+            part.name,
+            part.file_name,
+            events.len(),
+            plural,
+            years
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let worldline: WorldLine = "#worldline v2\n\
+                                    -44 [war] Caesar crosses the Rubicon\n\
+                                    1947 [politics] Truman Doctrine\ttags=cold war\n\
+                                    1962 [War] Cuban Missile Crisis\ttags=cold war,cold_war\n\
+                                    1969 Moon landing\ttags=space\n"
+            .parse()
+            .unwrap();
+        let parts = worldline.split(SplitBy::Tag);
+        let names: Vec<_> = parts.iter().map(|p| p.file_name.as_str()).collect();
+        assert_eq!(names, ["cold-war.txt", "cold-war-2.txt", "space.txt"]);
+        assert_eq!(parts[0].worldline.len(), 2);
+        assert!(!parts[0].worldline.is_read_only());
+        assert_eq!(
+            index(&parts, SplitBy::Tag),
+            "# Events by tag\n\n\
+             - [cold war](cold-war.txt): 2 events, 1947 to 1962\n\
+             - [cold_war](cold-war-2.txt): 1 event, 1962\n\
+             - [space](space.txt): 1 event, 1969\n"
+        );
+
+        let parts = worldline.split(SplitBy::Category);
+        let names: Vec<_> = parts.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["politics", "war"]);
+        assert_eq!(parts[1].worldline.len(), 2);
+        assert_eq!(file_stem("?!"), "untitled");
+        assert!(SplitBy::parse("person").is_err());
+    }
+}
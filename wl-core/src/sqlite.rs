@@ -0,0 +1,228 @@
+//! SQLite storage for worldlines, behind the `sqlite` feature.
+//!
+//! Each event is stored as its v2 file line, so nothing is lost, alongside its day number
+//! (indexed, for date ranges) and lowercased description (for text queries). Queries are
+//! narrowed down in SQL and then checked exactly with [`QueryExpr::matches`].
+
+use crate::format::{self, FormatVersion};
+use crate::query::QueryExpr;
+use crate::range::DateRange;
+use crate::storage::{check_file_writable, Storage};
+use crate::{Event, WorldLine};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection};
+use std::path::PathBuf;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS front_matter (
+        position INTEGER PRIMARY KEY,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        position INTEGER PRIMARY KEY,
+        day INTEGER NOT NULL,
+        description TEXT NOT NULL,
+        line TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS events_day ON events (day);
+";
+
+/// A worldline stored in a SQLite database, created if it doesn't exist.
+pub struct Database {
+    path: PathBuf,
+    connection: Connection,
+}
+
+impl Database {
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let error = |e: rusqlite::Error| format!("{}: {}", path.display(), e);
+        let connection = Connection::open(&path).map_err(error)?;
+        connection.execute_batch(SCHEMA).map_err(error)?;
+        Ok(Self { path, connection })
+    }
+
+    fn error(&self, e: rusqlite::Error) -> String {
+        format!("{}: {}", self.path.display(), e)
+    }
+
+    fn front_matter(&self) -> Result<Vec<(String, String)>, String> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT key, value FROM front_matter ORDER BY position")
+            .map_err(|e| self.error(e))?;
+        let rows = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| self.error(e))?;
+        rows.collect::<Result<_, _>>().map_err(|e| self.error(e))
+    }
+
+    /// The events matching SQL conditions on the `events` table, in order.
+    fn events_where(
+        &self,
+        conditions: &[String],
+        params: &[SqlValue],
+    ) -> Result<Vec<Event>, String> {
+        let mut sql = "SELECT line FROM events".to_string();
+        if !conditions.is_empty() {
+            sql += " WHERE ";
+            sql += &conditions.join(" AND ");
+        }
+        sql += " ORDER BY position";
+        let mut statement = self.connection.prepare(&sql).map_err(|e| self.error(e))?;
+        let lines = statement
+            .query_map(params_from_iter(params), |row| row.get::<_, String>(0))
+            .map_err(|e| self.error(e))?;
+        lines
+            .map(|line| {
+                let line = line.map_err(|e| self.error(e))?;
+                format::parse_event_v2(&line).map_err(|e| format!("{}: {}", self.path.display(), e))
+            })
+            .collect()
+    }
+}
+
+/// Add SQL conditions that every event matching `expr` satisfies.
+fn narrow(expr: &QueryExpr, conditions: &mut Vec<String>, params: &mut Vec<SqlValue>) {
+    match expr {
+        QueryExpr::Range(range) => {
+            // partial dates share their first day's number, so these bounds are inclusive
+            conditions.push("day >= ? AND day <= ?".to_string());
+            params.push(SqlValue::Integer(range.start().day_number()));
+            params.push(SqlValue::Integer(range.after().day_number()));
+        }
+        QueryExpr::Text(text) => {
+            conditions.push("instr(description, ?) > 0".to_string());
+            params.push(SqlValue::Text(text.clone()));
+        }
+        QueryExpr::And(terms) => {
+            for term in terms {
+                narrow(term, conditions, params);
+            }
+        }
+        QueryExpr::Tag(_)
+        | QueryExpr::Category(_)
+        | QueryExpr::Person(_)
+        | QueryExpr::Series(_)
+        | QueryExpr::Weekday(_)
+        | QueryExpr::Length(_)
+        | QueryExpr::Or(_)
+        | QueryExpr::Not(_) => {}
+    }
+}
+
+impl Storage for Database {
+    fn load(&self) -> Result<WorldLine, String> {
+        Ok(WorldLine {
+            events: self.events_where(&[], &[])?,
+            version: FormatVersion::V2,
+            front_matter: self.front_matter()?,
+            read_only: false,
+        })
+    }
+
+    fn check_writable(&self) -> Result<(), String> {
+        check_file_writable(&self.path)
+    }
+
+    fn save(&self, worldline: &WorldLine) -> Result<(), String> {
+        if worldline.read_only {
+            return Err("the worldline is read-only".to_string());
+        }
+        let transaction = self
+            .connection
+            .unchecked_transaction()
+            .map_err(|e| self.error(e))?;
+        transaction
+            .execute_batch("DELETE FROM front_matter; DELETE FROM events;")
+            .map_err(|e| self.error(e))?;
+        for (position, (key, value)) in worldline.front_matter.iter().enumerate() {
+            transaction
+                .execute(
+                    "INSERT INTO front_matter (position, key, value) VALUES (?, ?, ?)",
+                    params![position as i64, key, value],
+                )
+                .map_err(|e| self.error(e))?;
+        }
+        let mut insert = transaction
+            .prepare("INSERT INTO events (position, day, description, line) VALUES (?, ?, ?, ?)")
+            .map_err(|e| self.error(e))?;
+        for (position, event) in worldline.events.iter().enumerate() {
+            insert
+                .execute(params![
+                    position as i64,
+                    event.date.day_number(),
+                    event.description.to_lowercase(),
+                    format::format_event_v2(event),
+                ])
+                .map_err(|e| self.error(e))?;
+        }
+        drop(insert);
+        transaction.commit().map_err(|e| self.error(e))
+    }
+
+    fn load_range(&self, range: &DateRange) -> Result<WorldLine, String> {
+        Ok(WorldLine {
+            events: self.query(&QueryExpr::Range(range.clone()))?,
+            version: FormatVersion::V2,
+            front_matter: self.front_matter()?,
+            read_only: true,
+        })
+    }
+
+    fn query(&self, expr: &QueryExpr) -> Result<Vec<Event>, String> {
+        let (mut conditions, mut params) = (Vec::new(), Vec::new());
+        narrow(expr, &mut conditions, &mut params);
+        let mut events = self.events_where(&conditions, &params)?;
+        events.retain(|e| expr.matches(e));
+        Ok(events)
+    }
+
+    fn count(&self) -> Result<usize, String> {
+        self.connection
+            .query_row("SELECT COUNT(*) FROM events", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|count| count as usize)
+            .map_err(|e| self.error(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqlite_storage() {
+        let path = std::env::temp_dir().join(format!("wl-sqlite-{}.wl.db", std::process::id()));
+        let database = Database::open(path.clone()).unwrap();
+        let worldline: WorldLine = "#worldline v2\n\
+                                    #title: Wars\n\
+                                    BCE 0044-03-15 Ides of March\n\
+                                    \u{20}CE 1914       [war] WWI\ttags=europe\tend=1918-11-11\n\
+                                    \u{20}CE 1914-07-28 Austria declares war\n\
+                                    \u{20}CE 1939-09-01 WWII\tid=ww2\n"
+            .parse()
+            .unwrap();
+        database.save(&worldline).unwrap();
+        let loaded = database.load().unwrap();
+        assert_eq!(loaded.to_string(), worldline.to_string());
+
+        // both the year and day precision events in 1914
+        let range = database
+            .load_range(&DateRange::parse("1914..1914").unwrap())
+            .unwrap();
+        assert_eq!(range.len(), 2);
+        assert!(range.is_read_only());
+        let expr = QueryExpr::parse("wwi AND tag:europe AND 1900..1950").unwrap();
+        let events = database.query(&expr).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].category.as_deref(), Some("war"));
+
+        // saving replaces everything
+        database.save(&"1066 Hastings\n".parse().unwrap()).unwrap();
+        assert_eq!(database.load().unwrap().len(), 1);
+        assert_eq!(database.count(), Ok(1));
+        std::fs::remove_file(path).unwrap();
+    }
+}
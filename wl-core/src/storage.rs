@@ -0,0 +1,323 @@
+//! Where worldlines are stored: plain text files (see [`crate::format`]), or, with the `sqlite`
+//! feature, SQLite databases, which index events by date so large worldlines can be queried
+//! and partly loaded without reading everything.
+//!
+//! With the `crypto` feature, text files can be encrypted with a passphrase (see
+//! [`crate::crypto`]).
+//!
+//! The backend is chosen by extension: `.wl.db`, `.db` and `.sqlite` files are SQLite
+//! databases, and anything else is text. Encrypted files are recognized by their header,
+//! whatever their extension.
+//!
+//! Any storage can be wrapped in a [`TruncationGuard`], which stops saves that would remove
+//! many events at once, e.g. after a bad merge or a misread file.
+
+use crate::query::QueryExpr;
+use crate::range::DateRange;
+use crate::{Event, WorldLine};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The header of age files, which encrypted worldline files are.
+const ENCRYPTED_HEADER: &[u8] = b"age-encryption.org/v1\n";
+
+/// Whether the file at a path is an encrypted worldline.
+pub fn is_encrypted(path: impl AsRef<Path>) -> bool {
+    let mut header = [0; ENCRYPTED_HEADER.len()];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| header == ENCRYPTED_HEADER)
+}
+
+/// Check that an existing file can be written to, without changing it. Files that don't exist
+/// yet pass, since saving creates them.
+pub(crate) fn check_file_writable(path: &Path) -> Result<(), String> {
+    match std::fs::OpenOptions::new().write(true).open(path) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("{} isn't writable: {}", path.display(), e)),
+    }
+}
+
+pub trait Storage {
+    /// Read the whole worldline.
+    fn load(&self) -> Result<WorldLine, String>;
+
+    /// Replace the stored worldline.
+    fn save(&self, worldline: &WorldLine) -> Result<(), String>;
+
+    /// Check that the worldline could be saved, e.g. that its file isn't read-only, to refuse
+    /// a change before making it rather than failing to save it afterwards.
+    fn check_writable(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// The events dated within a range, as a read-only worldline.
+    fn load_range(&self, range: &DateRange) -> Result<WorldLine, String> {
+        let expr = QueryExpr::Range(range.clone());
+        Ok(self.load()?.filtered(&expr))
+    }
+
+    /// The events matching an expression.
+    fn query(&self, expr: &QueryExpr) -> Result<Vec<Event>, String> {
+        Ok(self.load()?.query_expr(expr).into_iter().cloned().collect())
+    }
+
+    /// The number of events stored.
+    fn count(&self) -> Result<usize, String> {
+        Ok(self.load()?.len())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Text,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+    /// Text encrypted with a passphrase.
+    #[cfg(feature = "crypto")]
+    Encrypted,
+}
+
+impl Backend {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "text" => Ok(Backend::Text),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(Backend::Sqlite),
+            #[cfg(not(feature = "sqlite"))]
+            "sqlite" => Err("SQLite support requires the sqlite feature".to_string()),
+            #[cfg(feature = "crypto")]
+            "encrypted" => Ok(Backend::Encrypted),
+            #[cfg(not(feature = "crypto"))]
+            "encrypted" => Err("Encryption requires the crypto feature".to_string()),
+            _ => Err(format!(
+                "Invalid backend '{}', expected text, sqlite or encrypted",
+                s
+            )),
+        }
+    }
+
+    /// Detect the backend from a file's header or extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        if is_encrypted(&path) {
+            return Self::parse("encrypted");
+        }
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        match extension.as_str() {
+            "db" | "sqlite" => Self::parse("sqlite"),
+            _ => Ok(Backend::Text),
+        }
+    }
+
+    /// Open a worldline stored at a path with this backend. Encrypted worldlines need their
+    /// passphrase, so they're opened with [`crate::crypto::EncryptedFile`] instead.
+    pub fn open(&self, path: impl AsRef<Path>) -> Result<Box<dyn Storage>, String> {
+        let path = path.as_ref().to_path_buf();
+        match self {
+            Backend::Text => Ok(Box::new(TextFile { path })),
+            #[cfg(feature = "sqlite")]
+            Backend::Sqlite => Ok(Box::new(crate::sqlite::Database::open(path)?)),
+            #[cfg(feature = "crypto")]
+            Backend::Encrypted => Err(format!("{} needs a passphrase", path.display())),
+        }
+    }
+}
+
+/// A worldline file in the text format.
+pub struct TextFile {
+    pub path: PathBuf,
+}
+
+impl Storage for TextFile {
+    fn load(&self) -> Result<WorldLine, String> {
+        if is_encrypted(&self.path) {
+            let hint = if cfg!(feature = "crypto") {
+                "open it with the encrypted backend"
+            } else {
+                "reading it requires the crypto feature"
+            };
+            return Err(format!("{} is encrypted; {}", self.path.display(), hint));
+        }
+        WorldLine::from_file(&self.path)
+    }
+
+    fn save(&self, worldline: &WorldLine) -> Result<(), String> {
+        worldline.to_file(&self.path).map_err(|e| e.to_string())
+    }
+
+    fn check_writable(&self) -> Result<(), String> {
+        check_file_writable(&self.path)
+    }
+}
+
+/// The default for the most events a [`TruncationGuard`] lets a save remove.
+pub const DEFAULT_MAX_REMOVED: usize = 50;
+
+/// A save that would remove more events than a [`TruncationGuard`] allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Truncation {
+    /// The events stored
+    pub stored: usize,
+    /// The events in the worldline being saved
+    pub saved: usize,
+    pub max_removed: usize,
+}
+
+impl std::fmt::Display for Truncation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "saving would remove {} of the {} events stored, more than the {} allowed at once",
+            self.stored - self.saved,
+            self.stored,
+            self.max_removed
+        )
+    }
+}
+
+type Confirm = Box<dyn Fn(&Truncation) -> Result<(), String>>;
+
+/// Storage that checks each save against what's stored, and refuses one that would remove more
+/// than `max_removed` events unless it's confirmed (see [`TruncationGuard::confirm_with`]).
+pub struct TruncationGuard {
+    storage: Box<dyn Storage>,
+    max_removed: usize,
+    confirm: Option<Confirm>,
+}
+
+impl TruncationGuard {
+    pub fn new(storage: Box<dyn Storage>, max_removed: usize) -> Self {
+        Self {
+            storage,
+            max_removed,
+            confirm: None,
+        }
+    }
+
+    /// Ask `confirm` about saves removing too many events: they go ahead if it returns Ok, and
+    /// fail with its error otherwise. Without it, they fail with the [`Truncation`].
+    pub fn confirm_with(
+        mut self,
+        confirm: impl Fn(&Truncation) -> Result<(), String> + 'static,
+    ) -> Self {
+        self.confirm = Some(Box::new(confirm));
+        self
+    }
+}
+
+impl Storage for TruncationGuard {
+    fn load(&self) -> Result<WorldLine, String> {
+        self.storage.load()
+    }
+
+    fn save(&self, worldline: &WorldLine) -> Result<(), String> {
+        // a new file, or one too broken to read, has nothing to keep
+        let stored = self.storage.count().unwrap_or(0);
+        if stored > worldline.len() + self.max_removed {
+            let truncation = Truncation {
+                stored,
+                saved: worldline.len(),
+                max_removed: self.max_removed,
+            };
+            match &self.confirm {
+                Some(confirm) => confirm(&truncation)?,
+                None => return Err(truncation.to_string()),
+            }
+        }
+        self.storage.save(worldline)
+    }
+
+    fn check_writable(&self) -> Result<(), String> {
+        self.storage.check_writable()
+    }
+
+    fn load_range(&self, range: &DateRange) -> Result<WorldLine, String> {
+        self.storage.load_range(range)
+    }
+
+    fn query(&self, expr: &QueryExpr) -> Result<Vec<Event>, String> {
+        self.storage.query(expr)
+    }
+
+    fn count(&self) -> Result<usize, String> {
+        self.storage.count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends() {
+        assert_eq!(Backend::from_path("history.txt"), Ok(Backend::Text));
+        assert_eq!(Backend::parse("TEXT"), Ok(Backend::Text));
+        assert!(Backend::parse("postgres").is_err());
+        #[cfg(feature = "sqlite")]
+        assert_eq!(Backend::from_path("history.wl.db"), Ok(Backend::Sqlite));
+        #[cfg(not(feature = "sqlite"))]
+        assert!(Backend::from_path("history.wl.db").is_err());
+
+        let path = std::env::temp_dir().join(format!("wl-storage-{}.wl", std::process::id()));
+        let storage = Backend::Text.open(&path).unwrap();
+        let worldline: WorldLine = "1914 WWI\n1939 WWII\n".parse().unwrap();
+        storage.save(&worldline).unwrap();
+        let range = storage
+            .load_range(&DateRange::parse("1930..1945").unwrap())
+            .unwrap();
+        assert_eq!(range.years(), vec![1939]);
+        assert!(range.is_read_only());
+
+        assert_eq!(storage.check_writable(), Ok(()));
+        let mut permissions = std::fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&path, permissions).unwrap();
+        // root can write to read-only files anyway
+        if std::fs::OpenOptions::new().write(true).open(&path).is_err() {
+            assert!(storage.check_writable().is_err());
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_truncation_guard() {
+        let path = std::env::temp_dir().join(format!("wl-guard-{}.wl", std::process::id()));
+        let guard = TruncationGuard::new(Backend::Text.open(&path).unwrap(), 1);
+        let worldline = |s: &str| s.parse::<WorldLine>().unwrap();
+        guard
+            .save(&worldline("1914 WWI\n1939 WWII\n1969 Moon landing\n"))
+            .unwrap();
+        assert_eq!(guard.count(), Ok(3));
+
+        let one = worldline("1914 WWI\n");
+        assert_eq!(
+            guard.save(&one),
+            Err(
+                "saving would remove 2 of the 3 events stored, more than the 1 allowed at once"
+                    .to_string()
+            )
+        );
+        assert_eq!(guard.count(), Ok(3));
+
+        let guard = guard.confirm_with(|truncation| match truncation.saved {
+            0 => Err("Nothing was saved".to_string()),
+            _ => Ok(()),
+        });
+        assert_eq!(
+            guard.save(&worldline("")),
+            Err("Nothing was saved".to_string())
+        );
+        guard.save(&one).unwrap();
+        assert_eq!(guard.count(), Ok(1));
+        // removing no more than allowed needs no confirmation
+        let guard = TruncationGuard::new(Backend::Text.open(&path).unwrap(), 1);
+        guard.save(&worldline("")).unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,215 @@
+//! Listing events as a table, with their dates, categories, tags and descriptions in aligned
+//! columns under headers, which can be easier to scan than the flat list:
+//!
+//! ```text
+//! Date            Category  Tags         Description
+//! --------------  --------  -----------  -------------------
+//! BCE 0044-03-15  politics  rome         Caesar assassinated
+//!  CE 1914-07-28  war       europe, ww1  WWI begins
+//! ```
+//!
+//! or boxed in with box-drawing characters:
+//!
+//! ```text
+//! ┌────────────────┬──────────┬─────────────┬─────────────────────┐
+//! │ Date           │ Category │ Tags        │ Description         │
+//! ├────────────────┼──────────┼─────────────┼─────────────────────┤
+//! │ BCE 0044-03-15 │ politics │ rome        │ Caesar assassinated │
+//! └────────────────┴──────────┴─────────────┴─────────────────────┘
+//! ```
+//!
+//! The category and tags columns are left out when none of the events have any.
+
+use crate::display::{truncate, wrap, DisplayOptions, DATE_STYLE, MIN_TEXT_WIDTH};
+use crate::Event;
+
+/// The characters a table is drawn with.
+struct Style {
+    /// Before the first column, between columns, and after the last one
+    edges: [&'static str; 3],
+    /// The horizontal line under the headers, with its left edge, crossings and right edge
+    rule: (char, [&'static str; 3]),
+    /// The lines above and below the table, if any
+    borders: Option<[(char, [&'static str; 3]); 2]>,
+}
+
+const PLAIN: Style = Style {
+    edges: ["", "  ", ""],
+    rule: ('-', ["", "  ", ""]),
+    borders: None,
+};
+
+const BOXED: Style = Style {
+    edges: ["│ ", " │ ", " │"],
+    rule: ('─', ["├─", "─┼─", "─┤"]),
+    borders: Some([('─', ["┌─", "─┬─", "─┐"]), ('─', ["└─", "─┴─", "─┘"])]),
+};
+
+/// Render events as a table, plain or boxed. With a width, descriptions are truncated or
+/// wrapped (see [`DisplayOptions`]) to fit the table in it.
+pub fn render(events: &[&Event], options: &DisplayOptions, boxed: bool) -> Vec<String> {
+    let style = if boxed { &BOXED } else { &PLAIN };
+    let show_era = options.show_era(events.iter().copied());
+    let mut columns: Vec<(&str, Vec<String>)> = vec![(
+        "Date",
+        events
+            .iter()
+            .map(|e| options.format_date(&e.date, show_era))
+            .collect(),
+    )];
+    let categories: Vec<String> = events
+        .iter()
+        .map(|e| e.category.clone().unwrap_or_default())
+        .collect();
+    if categories.iter().any(|c| !c.is_empty()) {
+        columns.push(("Category", categories));
+    }
+    let tags: Vec<String> = events.iter().map(|e| e.tags.join(", ")).collect();
+    if tags.iter().any(|t| !t.is_empty()) {
+        columns.push(("Tags", tags));
+    }
+
+    let width = |text: &str| text.chars().count();
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .map(|(header, cells)| {
+            cells
+                .iter()
+                .map(|c| width(c))
+                .fold(width(header), usize::max)
+        })
+        .collect();
+    let descriptions: Vec<String> = events
+        .iter()
+        .map(|e| {
+            let mut description = e.description.clone();
+            if let Some(place) = &e.place {
+                description += &format!(" @{}", place);
+            }
+            description
+        })
+        .collect();
+    let longest = descriptions
+        .iter()
+        .map(|d| width(d))
+        .fold(width("Description"), usize::max);
+    let description_width = match options.width {
+        Some(total) => {
+            let edges = width(style.edges[0])
+                + width(style.edges[1]) * columns.len()
+                + width(style.edges[2]);
+            let taken: usize = widths.iter().sum::<usize>() + edges;
+            longest.min(total.saturating_sub(taken).max(MIN_TEXT_WIDTH))
+        }
+        None => longest,
+    };
+    widths.push(description_width);
+
+    let line = |cells: &[String], date: bool| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (cell, width))| {
+                let cell = format!("{:width$}", cell);
+                if i == 0 && date {
+                    options.paint(DATE_STYLE, &cell)
+                } else {
+                    cell
+                }
+            })
+            .collect();
+        let line = style.edges[0].to_string() + &padded.join(style.edges[1]) + style.edges[2];
+        if boxed {
+            line
+        } else {
+            line.trim_end().to_string()
+        }
+    };
+    let rule = |(c, [left, middle, right]): (char, [&str; 3])| {
+        let segments: Vec<String> = widths.iter().map(|w| c.to_string().repeat(*w)).collect();
+        left.to_string() + &segments.join(middle) + right
+    };
+
+    let mut lines = Vec::new();
+    if let Some([top, _]) = style.borders {
+        lines.push(rule(top));
+    }
+    let headers: Vec<String> = columns
+        .iter()
+        .map(|(header, _)| header.to_string())
+        .chain([truncate("Description", description_width)])
+        .collect();
+    lines.push(line(&headers, false));
+    lines.push(rule(style.rule));
+    for (i, description) in descriptions.iter().enumerate() {
+        let description = match options.width {
+            Some(_) if options.wrap => wrap(description, description_width),
+            _ => vec![truncate(description, description_width)],
+        };
+        for (j, text) in description.into_iter().enumerate() {
+            let mut cells: Vec<String> = columns
+                .iter()
+                .map(|(_, cells)| {
+                    if j == 0 {
+                        cells[i].clone()
+                    } else {
+                        String::new()
+                    }
+                })
+                .collect();
+            cells.push(text);
+            lines.push(line(&cells, j == 0));
+        }
+    }
+    if let Some([_, bottom]) = style.borders {
+        lines.push(rule(bottom));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WorldLine;
+
+    #[test]
+    fn test_render() {
+        let worldline: WorldLine = "#worldline v2\n\
+                                    -44-03-15 [politics] Caesar assassinated\ttags=rome\n\
+                                    1914-07-28 WWI begins\ttags=europe,ww1\n"
+            .parse()
+            .unwrap();
+        let events: Vec<&Event> = worldline.iter().collect();
+        let options = DisplayOptions {
+            ansi: true,
+            ..Default::default()
+        };
+        let date = |d| format!("\u{001B}[34m{}\u{001B}[0m", d);
+        assert_eq!(
+            render(&events, &options, false),
+            [
+                "Date            Category  Tags         Description".to_string(),
+                "--------------  --------  -----------  -------------------".to_string(),
+                date("BCE 0044-03-15") + "  politics  rome         Caesar assassinated",
+                date(" CE 1914-07-28") + "            europe, ww1  WWI begins",
+            ]
+        );
+
+        let options = DisplayOptions {
+            width: Some(40),
+            ..options
+        };
+        let events = &events[1..];
+        assert_eq!(
+            render(events, &options, true),
+            [
+                "┌────────────┬─────────────┬────────────┐".to_string(),
+                "│ Date       │ Tags        │ Descripti… │".to_string(),
+                "├────────────┼─────────────┼────────────┤".to_string(),
+                "│ ".to_string() + &date("1914-07-28") + " │ europe, ww1 │ WWI begins │",
+                "└────────────┴─────────────┴────────────┘".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Templates for starting a worldline with events in it, e.g. `wl init --template
+//! roman-republic`.
+//!
+//! A template pack is a worldline file whose front matter may name and describe it:
+//!
+//! ```text
+//! #worldline v2
+//! #template: Roman Republic
+//! #template_description: From the founding of the Republic to Augustus
+//! BCE 0509 Roman Republic founded
+//! ```
+//!
+//! Applying a pack adds its events and the rest of its front matter to a worldline. A few
+//! packs are bundled (see [`TEMPLATES`]); others are read from files, or with the `net`
+//! feature downloaded.
+
+use crate::WorldLine;
+
+/// The front matter key naming a template pack.
+pub const NAME_KEY: &str = "template";
+
+/// The front matter key describing a template pack.
+pub const DESCRIPTION_KEY: &str = "template_description";
+
+/// A template pack bundled with wl.
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The pack, in the worldline file format.
+    contents: &'static str,
+}
+
+impl Template {
+    pub fn pack(&self) -> Pack {
+        // the bundled packs are tested to be valid
+        Pack::parse(self.contents).unwrap()
+    }
+}
+
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        name: "blank",
+        description: "No events",
+        contents: "",
+    },
+    Template {
+        name: "roman-republic",
+        description: "The Roman Republic, from the expulsion of the kings to Augustus",
+        contents: ROMAN_REPUBLIC,
+    },
+    Template {
+        name: "us-history",
+        description: "United States history, from independence to the 21st century",
+        contents: US_HISTORY,
+    },
+];
+
+const ROMAN_REPUBLIC: &str = "\
+-509 Roman Republic founded after the expulsion of Tarquinius Superbus
+-494 First secession of the plebs; tribunes of the plebs created
+-450 Law of the Twelve Tables
+-390 Gauls sack Rome after the Battle of the Allia
+-264 First Punic War begins
+-218 Hannibal crosses the Alps
+-216-08-02 Battle of Cannae
+-146 Carthage and Corinth destroyed
+-133 Tiberius Gracchus killed
+-88 Sulla marches on Rome
+-73 Revolt of Spartacus begins
+-60 First Triumvirate of Caesar, Pompey and Crassus
+-49-01-10 Caesar crosses the Rubicon
+-44-03-15 Caesar assassinated
+-31-09-02 Battle of Actium
+-27-01-16 Octavian given the name Augustus
+";
+
+const US_HISTORY: &str = "\
+1776-07-04 Declaration of Independence adopted
+1787-09-17 Constitution signed
+1789-04-30 George Washington inaugurated as the first president
+1803-04-30 Louisiana Purchase
+1861-04-12 Civil War begins at Fort Sumter
+1863-01-01 Emancipation Proclamation takes effect
+1865-04-09 Lee surrenders at Appomattox
+1865-04-14 Lincoln shot at Ford's Theatre
+1869-05-10 Transcontinental railroad completed
+1920-08-18 Nineteenth Amendment ratified, giving women the vote
+1929-10-29 Black Tuesday stock market crash
+1941-12-07 Attack on Pearl Harbor
+1954-05-17 Brown v. Board of Education decided
+1964-07-02 Civil Rights Act signed
+1969-07-20 Apollo 11 lands on the Moon
+2001-09-11 September 11 attacks
+";
+
+/// The bundled template with a name.
+pub fn find(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|template| template.name == name)
+}
+
+/// A template pack: events to start a worldline with.
+#[derive(Clone)]
+pub struct Pack {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// The events, and front matter other than the name and description.
+    pub worldline: WorldLine,
+}
+
+impl Pack {
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut worldline: WorldLine = contents.parse()?;
+        let take = |worldline: &mut WorldLine, key: &str| {
+            let value = worldline
+                .front_matter
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.clone());
+            worldline.front_matter.retain(|(k, _)| k != key);
+            value
+        };
+        Ok(Self {
+            name: take(&mut worldline, NAME_KEY),
+            description: take(&mut worldline, DESCRIPTION_KEY),
+            worldline,
+        })
+    }
+
+    /// Add the pack's events to a worldline, along with front matter it doesn't have yet.
+    /// Returns the number of events added.
+    pub fn apply(self, worldline: &mut WorldLine) -> usize {
+        for (key, value) in &self.worldline.front_matter {
+            if !worldline.front_matter.iter().any(|(k, _)| k == key) {
+                worldline.set_front_matter(key, value.clone());
+            }
+        }
+        worldline.import(self.worldline)
+    }
+}
+
+/// The pack a `--template` argument refers to: a bundled template's name, a file, or with
+/// the `net` feature an http(s) URL.
+#[cfg(feature = "std-fs")]
+pub fn load(source: &str) -> Result<Pack, String> {
+    if let Some(template) = find(source) {
+        return Ok(template.pack());
+    }
+    let contents = if source.starts_with("http://") || source.starts_with("https://") {
+        download(source)?
+    } else if std::path::Path::new(source).exists() {
+        std::fs::read_to_string(source).map_err(|e| format!("Could not read {}: {}", source, e))?
+    } else {
+        let names: Vec<&str> = TEMPLATES.iter().map(|template| template.name).collect();
+        return Err(format!(
+            "Unknown template '{}', expected a file or one of {}",
+            source,
+            names.join(", ")
+        ));
+    };
+    Pack::parse(&contents).map_err(|e| format!("Invalid template {}: {}", source, e))
+}
+
+#[cfg(all(feature = "std-fs", feature = "net"))]
+fn download(url: &str) -> Result<String, String> {
+    ureq::get(url)
+        .set(
+            "User-Agent",
+            concat!("worldline/", env!("CARGO_PKG_VERSION")),
+        )
+        .call()
+        .map_err(|e| format!("Could not download {}: {}", url, e))?
+        .into_string()
+        .map_err(|e| format!("Could not read {}: {}", url, e))
+}
+
+#[cfg(all(feature = "std-fs", not(feature = "net")))]
+fn download(url: &str) -> Result<String, String> {
+    Err(format!(
+        "Downloading templates needs the net feature: {}",
+        url
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates() {
+        for template in TEMPLATES {
+            let pack = template.pack();
+            assert!(pack.worldline.validate().is_empty(), "{}", template.name);
+        }
+        assert!(find("blank").unwrap().pack().worldline.is_empty());
+
+        let pack = Pack::parse(
+            "#worldline v2\n\
+             #template: Crusades\n\
+             #template_description: The numbered crusades\n\
+             #same_day_order: insertion\n\
+             1096 First Crusade\ttags=crusades\n\
+             1189 Third Crusade\n",
+        )
+        .unwrap();
+        assert_eq!(pack.name.as_deref(), Some("Crusades"));
+        assert_eq!(pack.description.as_deref(), Some("The numbered crusades"));
+        let mut worldline: WorldLine = "1189 Third Crusade\n".parse().unwrap();
+        assert_eq!(pack.apply(&mut worldline), 1);
+        assert_eq!(worldline.years(), vec![1096, 1189]);
+        assert_eq!(
+            worldline.front_matter(),
+            [("same_day_order".to_string(), "insertion".to_string())]
+        );
+    }
+}
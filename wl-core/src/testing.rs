@@ -0,0 +1,161 @@
+//! Synthetic worldlines for tests, benchmarks, and downstream tools.
+//!
+//! Everything here is deterministic: the same profile always produces the same timeline, so
+//! fixtures can be shared without shipping data files.
+
+use crate::{Date, Event, WorldLine};
+
+/// The shape of a generated timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// A few hundred events, mostly BCE, mostly year precision.
+    AncientHeavy,
+    /// A couple thousand day-precision events in the 20th and 21st centuries.
+    ModernDense,
+    /// Events spread from 3000 BCE to today with a mix of year, month, and day precision.
+    MixedPrecision,
+    /// 100,000 events of mixed precision, for benchmarks.
+    Huge,
+}
+
+impl Profile {
+    /// Number of events generated for this profile.
+    pub fn event_count(&self) -> usize {
+        match self {
+            Profile::AncientHeavy => 500,
+            Profile::ModernDense => 2_000,
+            Profile::MixedPrecision => 1_000,
+            Profile::Huge => 100_000,
+        }
+    }
+
+    /// Range of years (inclusive) events are drawn from.
+    fn years(&self) -> (i32, i32) {
+        match self {
+            Profile::AncientHeavy => (-3000, 500),
+            Profile::ModernDense => (1900, 2024),
+            Profile::MixedPrecision | Profile::Huge => (-3000, 2024),
+        }
+    }
+}
+
+const SUBJECTS: [&str; 12] = [
+    "Rome",
+    "Carthage",
+    "Athens",
+    "Babylon",
+    "Chang'an",
+    "Constantinople",
+    "Paris",
+    "London",
+    "Kyoto",
+    "Tenochtitlan",
+    "Cairo",
+    "Delhi",
+];
+
+const TEMPLATES: [&str; 8] = [
+    "Battle of {}",
+    "Treaty of {} signed",
+    "Founding of {}",
+    "Great fire in {}",
+    "Census taken in {}",
+    "Plague reaches {}",
+    "New temple dedicated in {}",
+    "Embassy arrives in {}",
+];
+
+/// A small deterministic PRNG (SplitMix64), so fixtures don't depend on a rand crate.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in [lo, hi] (inclusive).
+    fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + (self.next() % (hi - lo + 1) as u64) as i64
+    }
+}
+
+fn random_event(profile: Profile, rng: &mut SplitMix64) -> Event {
+    let (lo, hi) = profile.years();
+    let mut year = rng.range(lo as i64, hi as i64) as i32;
+    if year == 0 {
+        year = 1;
+    }
+
+    // percent chance of month and day precision
+    let (month_pct, day_pct) = match profile {
+        Profile::AncientHeavy => (20, 10),
+        Profile::ModernDense => (100, 90),
+        Profile::MixedPrecision | Profile::Huge => (60, 40),
+    };
+    let roll = rng.range(0, 99);
+    let month = if roll < month_pct {
+        rng.range(1, 12) as u8
+    } else {
+        0
+    };
+    let day = if month != 0 && roll < day_pct {
+        rng.range(1, Date::days_in_month(year, month) as i64) as u8
+    } else {
+        0
+    };
+
+    let template = TEMPLATES[rng.range(0, TEMPLATES.len() as i64 - 1) as usize];
+    let subject = SUBJECTS[rng.range(0, SUBJECTS.len() as i64 - 1) as usize];
+    Event::new(
+        Date::new(year, month, day).unwrap(),
+        template.replace("{}", subject),
+    )
+}
+
+/// Generate the (sorted) events for a profile.
+pub fn sample_events(profile: Profile) -> Vec<Event> {
+    let mut rng = SplitMix64(profile as u64 + 1);
+    let mut events: Vec<Event> = (0..profile.event_count())
+        .map(|_| random_event(profile, &mut rng))
+        .collect();
+    events.sort();
+    events
+}
+
+/// Generate a worldline for a profile.
+pub fn sample_worldline(profile: Profile) -> WorldLine {
+    WorldLine {
+        events: sample_events(profile),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_worldlines() {
+        for profile in [
+            Profile::AncientHeavy,
+            Profile::ModernDense,
+            Profile::MixedPrecision,
+        ] {
+            let worldline = sample_worldline(profile);
+            assert_eq!(worldline.len(), profile.event_count());
+            assert!(worldline.events.is_sorted());
+
+            // deterministic
+            assert_eq!(worldline.events, sample_events(profile));
+
+            // every event survives a round trip through the file format
+            for event in &worldline.events {
+                assert_eq!(&Event::parse(&event.format_for_file()).unwrap(), event);
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Times of day on dates, e.g. "2023-12-25 14:30 Flight landed", for worldlines kept as life
+//! logs.
+//!
+//! Times have no time zone: they're what the clock said where the event happened. Only days
+//! can have times, and a timed event sorts after the events on its day without one.
+
+use crate::Date;
+
+/// An hour (0-23) and minute, written as e.g. "14:30".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TimeOfDay {
+    hour: u8,
+    minute: u8,
+}
+
+impl TimeOfDay {
+    pub fn new(hour: u8, minute: u8) -> Result<Self, String> {
+        if hour > 23 || minute > 59 {
+            return Err(format!("Invalid time: {}:{:02}", hour, minute));
+        }
+        Ok(Self { hour, minute })
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// The minute after this one, or None at 23:59.
+    fn next(&self) -> Option<Self> {
+        match (self.hour, self.minute) {
+            (23, 59) => None,
+            (hour, 59) => Some(Self {
+                hour: hour + 1,
+                minute: 0,
+            }),
+            (hour, minute) => Some(Self {
+                hour,
+                minute: minute + 1,
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl Date {
+    /// The time of day, for dates that have one.
+    pub fn time(&self) -> Option<TimeOfDay> {
+        self.time
+    }
+
+    /// This date at a time of day, or without one for None. Only days can have times.
+    pub fn with_time(&self, time: Option<TimeOfDay>) -> Result<Self, String> {
+        if time.is_some() && !self.is_day_precision() {
+            return Err(format!("Only days can have times, not {}", self));
+        }
+        Ok(Self {
+            time,
+            ..self.clone()
+        })
+    }
+
+    /// The minute after a timed date, or None for dates without a time or at 23:59, which are
+    /// followed by the next day.
+    pub(crate) fn next_minute(&self) -> Option<Self> {
+        let time = self.time?.next()?;
+        Some(Self {
+            time: Some(time),
+            ..self.clone()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::parse_whole_date;
+    use crate::range::DateRange;
+    use crate::{Event, WorldLine};
+
+    #[test]
+    fn test_times_of_day() {
+        let date = parse_whole_date("2023-12-25 14:30").unwrap();
+        assert_eq!(date.time(), TimeOfDay::new(14, 30).ok());
+        assert_eq!(date.to_string(), "2023-12-25 14:30");
+        assert_eq!(date.format(true), " CE 2023-12-25 14:30");
+        assert_eq!(parse_whole_date("2023-12-25T14:30"), Ok(date.clone()));
+        assert_eq!(date.next().to_string(), "2023-12-25 14:31");
+        assert_eq!(
+            parse_whole_date("2023-12-25 23:59").unwrap().next(),
+            parse_whole_date("2023-12-26").unwrap()
+        );
+        assert!(parse_whole_date("2023-12-25 24:00").is_err());
+        // only days have times, so this is a year and a description
+        let event = Event::parse("1969 12:00 Noon").unwrap();
+        assert_eq!(event.description, "12:00 Noon");
+
+        // timed events sort after untimed ones on the same day, in time order
+        let mut worldline: WorldLine = "2023-12-25 14:30 Flight landed\n".parse().unwrap();
+        worldline.add_event(Event::parse("2023-12-25 09:05 Flight took off").unwrap());
+        worldline.add_event(Event::parse("2023-12-25 Christmas").unwrap());
+        let descriptions: Vec<&str> = worldline
+            .events()
+            .iter()
+            .map(|e| e.description.as_str())
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec!["Christmas", "Flight took off", "Flight landed"]
+        );
+        assert_eq!(worldline.date_range(&DateRange::of(date)).len(), 1);
+        let christmas = parse_whole_date("2023-12-25").unwrap();
+        assert_eq!(worldline.date_range(&DateRange::of(christmas)).len(), 3);
+    }
+}
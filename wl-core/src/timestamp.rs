@@ -0,0 +1,97 @@
+//! Wall-clock timestamps, for recording when things happened to the worldline itself.
+
+use crate::Date;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A UTC time with second precision, written as e.g. "2025-03-01T14:30:00Z".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp {
+    /// seconds since 1970-01-01T00:00:00Z
+    seconds: i64,
+}
+
+impl Timestamp {
+    pub fn now() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        Self { seconds }
+    }
+
+    pub fn from_seconds(seconds: i64) -> Self {
+        Self { seconds }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    /// The (day precision) date of the timestamp.
+    pub fn date(&self) -> Date {
+        Date::from_day_number(self.seconds.div_euclid(86400))
+    }
+
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid timestamp: {}", s);
+        let (date_part, time) = s
+            .strip_suffix('Z')
+            .and_then(|s| s.split_once('T'))
+            .ok_or_else(invalid)?;
+        let (date, idx) = Date::parse(date_part).map_err(|_| invalid())?;
+        if idx != date_part.len() || !date.is_day_precision() {
+            return Err(invalid());
+        }
+        let parts = time
+            .split(':')
+            .map(|p| p.parse::<i64>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [hours, minutes, seconds] = parts[..] else {
+            return Err(invalid());
+        };
+        if hours > 23 || minutes > 59 || seconds > 59 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            seconds: date.day_number() * 86400 + hours * 3600 + minutes * 60 + seconds,
+        })
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let time = self.seconds.rem_euclid(86400);
+        write!(
+            f,
+            "{}T{:02}:{:02}:{:02}Z",
+            self.date(),
+            time / 3600,
+            time % 3600 / 60,
+            time % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamps() {
+        let t = Timestamp::parse("2025-03-01T14:30:05Z").unwrap();
+        assert_eq!(t.to_string(), "2025-03-01T14:30:05Z");
+        assert_eq!(t.date(), Date::new(2025, 3, 1).unwrap());
+        assert_eq!(
+            Timestamp::from_seconds(0).to_string(),
+            "1970-01-01T00:00:00Z"
+        );
+
+        for invalid in [
+            "2025-03-01",
+            "2025-03T14:30:05Z",
+            "2025-03-01T25:00:00Z",
+            "x",
+        ] {
+            assert!(Timestamp::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Days of the week, computed from the proleptic Gregorian calendar.
+
+use crate::Date;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Parse a weekday name or its three-letter abbreviation, case-insensitively.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|day| {
+                let name = day.to_string().to_lowercase();
+                s == name || s == name[..3]
+            })
+            .ok_or_else(|| format!("Invalid weekday: {}", s))
+    }
+}
+
+impl std::fmt::Display for Weekday {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Date {
+    /// The day of the week, for day precision dates.
+    pub fn weekday(&self) -> Option<Weekday> {
+        if !self.is_day_precision() {
+            return None;
+        }
+        // day 0 (1970-01-01) was a Thursday
+        let idx = (self.day_number() + 3).rem_euclid(7);
+        Some(Weekday::ALL[idx as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weekdays() {
+        let weekday = |y, m, d| Date::new(y, m, d).unwrap().weekday();
+        assert_eq!(weekday(1970, 1, 1), Some(Weekday::Thursday));
+        assert_eq!(weekday(1969, 7, 20), Some(Weekday::Sunday));
+        assert_eq!(weekday(1918, 11, 11), Some(Weekday::Monday));
+        assert_eq!(weekday(2000, 2, 29), Some(Weekday::Tuesday));
+        assert_eq!(weekday(1918, 11, 0), None);
+
+        assert_eq!(Weekday::parse("Fri"), Ok(Weekday::Friday));
+        assert_eq!(Weekday::parse("sunday"), Ok(Weekday::Sunday));
+        assert!(Weekday::parse("someday").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Importing events from Wikidata, by running a SPARQL query on the Wikidata Query Service
+//! (with the `net` feature) and reading each result as an event.
+//!
+//! Each result needs a `?date`, e.g. a point in time (P585), and a label for the description:
+//! `?itemLabel` from the label service, or `?label`. An optional `?precision`
+//! (`wikibase:timePrecision`) keeps dates only known to the year or month from being read as
+//! the 1st of January. For example, for treaties:
+//!
+//! ```sparql
+//! SELECT ?itemLabel ?date ?precision WHERE {
+//!   ?item wdt:P31 wd:Q131569;
+//!         p:P585/psv:P585 [wikibase:timeValue ?date; wikibase:timePrecision ?precision].
+//!   SERVICE wikibase:label { bd:serviceParam wikibase:language "en". }
+//! }
+//! ```
+
+use crate::json::Value;
+use crate::{Date, Event};
+
+/// The Wikidata Query Service endpoint queries are sent to.
+pub const ENDPOINT: &str = "https://query.wikidata.org/sparql";
+
+/// The events read from query results by [`parse_results`].
+#[derive(Debug, Default)]
+pub struct WikidataImport {
+    pub events: Vec<Event>,
+    /// Results that couldn't be read as events, with their (1-based) positions and why.
+    pub skipped: Vec<(usize, String)>,
+}
+
+/// Read a date from a query result, e.g. "1066-10-14T00:00:00Z". Years are astronomical, as
+/// in XSD, so "-0043" is 44 BCE. Wikidata precisions are 9 for years, 10 for months and 11 for
+/// days; coarser ones (decades, centuries...) are read as years.
+fn result_date(value: &str, precision: Option<u8>) -> Result<Date, String> {
+    let invalid = || format!("Invalid date '{}'", value);
+    let (date, _time) = value.split_once('T').ok_or_else(invalid)?;
+    let (negative, date) = match date.strip_prefix('-') {
+        Some(date) => (true, date),
+        None => (false, date),
+    };
+    let mut parts = date.split('-').map(|part| part.parse::<i32>().ok());
+    let (Some(Some(year)), Some(Some(month)), Some(Some(day))) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    let year = if negative { -year } else { year };
+    // there is no year 0 in BCE/CE
+    let year = if year <= 0 { year - 1 } else { year };
+    match precision {
+        Some(0..=9) => Date::new(year, 0, 0),
+        Some(10) => Date::new(year, month as u8, 0),
+        _ => Date::new(year, month as u8, day as u8),
+    }
+}
+
+/// Read one result (a binding of variables to values) as an event.
+fn result_event(result: &Value) -> Result<Event, String> {
+    let var = |name: &str| {
+        result
+            .get(name)
+            .and_then(|binding| binding.get("value"))
+            .and_then(Value::as_str)
+    };
+    let date = var("date").ok_or("No ?date")?;
+    let label = var("itemLabel")
+        .or_else(|| var("label"))
+        .ok_or("No ?itemLabel or ?label")?;
+    let precision = var("precision").and_then(|p| p.parse().ok());
+    Ok(Event::new(
+        result_date(date, precision)?,
+        label.trim().to_string(),
+    ))
+}
+
+/// The events in SPARQL query results, in the JSON format the query service returns.
+pub fn parse_results(json: &str) -> Result<WikidataImport, String> {
+    let results = Value::parse(json)?;
+    let bindings = results
+        .get("results")
+        .and_then(|r| r.get("bindings"))
+        .and_then(Value::as_array)
+        .ok_or("Not SPARQL query results")?;
+    let mut import = WikidataImport::default();
+    for (i, result) in bindings.iter().enumerate() {
+        match result_event(result) {
+            Ok(event) => import.events.push(event),
+            Err(e) => import.skipped.push((i + 1, e)),
+        }
+    }
+    Ok(import)
+}
+
+/// Run a SPARQL query on the Wikidata Query Service, returning the results as JSON for
+/// [`parse_results`].
+#[cfg(feature = "net")]
+pub fn run_query(query: &str) -> Result<String, String> {
+    ureq::get(ENDPOINT)
+        .query("query", query)
+        .set("Accept", "application/sparql-results+json")
+        .set(
+            "User-Agent",
+            concat!("worldline/", env!("CARGO_PKG_VERSION")),
+        )
+        .call()
+        .map_err(|e| format!("Wikidata query failed: {}", e))?
+        .into_string()
+        .map_err(|e| format!("Could not read Wikidata results: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_results() {
+        let json = r#"{
+            "head": {"vars": ["itemLabel", "date", "precision"]},
+            "results": {"bindings": [
+                {
+                    "itemLabel": {"type": "literal", "value": "Treaty of Versailles"},
+                    "date": {"type": "literal", "value": "1919-06-28T00:00:00Z"},
+                    "precision": {"type": "literal", "value": "11"}
+                },
+                {
+                    "itemLabel": {"type": "literal", "value": "Treaty of Kadesh"},
+                    "date": {"type": "literal", "value": "-1258-01-01T00:00:00Z"},
+                    "precision": {"type": "literal", "value": "9"}
+                },
+                {"itemLabel": {"type": "literal", "value": "Undated treaty"}}
+            ]}
+        }"#;
+        let import = parse_results(json).unwrap();
+        let events: Vec<String> = import.events.iter().map(Event::format_for_file).collect();
+        assert_eq!(
+            events,
+            vec![
+                " CE 1919-06-28 Treaty of Versailles",
+                "BCE 1259       Treaty of Kadesh",
+            ]
+        );
+        assert_eq!(import.skipped, vec![(3, "No ?date".to_string())]);
+        assert!(parse_results("[]").is_err());
+    }
+}
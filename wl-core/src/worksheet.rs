@@ -0,0 +1,140 @@
+//! Printable Markdown worksheets for classroom use: a sample of events with their dates or
+//! descriptions blanked out, and an answer key.
+
+use crate::display::DisplayOptions;
+use crate::query::QueryExpr;
+use crate::{Event, WorldLine};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blank {
+    /// Give the descriptions, in random order, and ask for the dates
+    Dates,
+    /// Give the dates, in order, and ask what happened
+    Descriptions,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Worksheet {
+    pub questions: String,
+    pub key: String,
+}
+
+/// A small xorshift generator, so worksheets can be reproduced from a seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // xorshift gets stuck at 0
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+impl WorldLine {
+    /// A worksheet of up to `n` events matching `filter` (or any events), chosen at random
+    /// from `seed`, with dates formatted with `options`.
+    pub fn worksheet(
+        &self,
+        title: &str,
+        filter: Option<&QueryExpr>,
+        n: usize,
+        blank: Blank,
+        seed: u64,
+        options: &DisplayOptions,
+    ) -> Worksheet {
+        let mut events: Vec<&Event> = self
+            .events
+            .iter()
+            .filter(|e| filter.is_none_or(|f| f.matches(e)))
+            .collect();
+        // partial Fisher-Yates shuffle
+        let mut rng = Rng::new(seed);
+        let n = n.min(events.len());
+        for i in 0..n {
+            let j = i + rng.below(events.len() - i);
+            events.swap(i, j);
+        }
+        events.truncate(n);
+        if blank == Blank::Descriptions {
+            events.sort();
+        }
+
+        let show_era = events.iter().any(|e| e.date.year < 0);
+        let date = |e: &Event| {
+            options
+                .format_date(&e.date, show_era)
+                .trim_end()
+                .to_string()
+        };
+        let instructions = match blank {
+            Blank::Dates => "Write the date of each event.",
+            Blank::Descriptions => "Write what happened on each date.",
+        };
+        let mut questions = format!(
+            "# {}\n\nName: ____________________\n\n{}\n\n",
+            title, instructions
+        );
+        let mut key = format!("# {} (answer key)\n\n", title);
+        for (i, event) in events.into_iter().enumerate() {
+            let (question, answer) = match blank {
+                Blank::Dates => (
+                    format!("____________ {}", event.description),
+                    format!("**{}** {}", date(event), event.description),
+                ),
+                Blank::Descriptions => (
+                    format!("{} ________________________________", date(event)),
+                    format!("{} **{}**", date(event), event.description),
+                ),
+            };
+            questions += &format!("{}. {}\n", i + 1, question);
+            key += &format!("{}. {}\n", i + 1, answer);
+        }
+        Worksheet { questions, key }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Date;
+
+    #[test]
+    fn test_worksheet() {
+        let mut worldline = WorldLine::default();
+        for year in 1750..1810 {
+            worldline.add_event(Event::new(
+                Date::new(year, 0, 0).unwrap(),
+                format!("Event {}", year),
+            ));
+        }
+        let range = QueryExpr::range("1750..1800").unwrap();
+        let options = DisplayOptions::default();
+        let sheet = |seed| {
+            worldline.worksheet(
+                "Revolutions",
+                Some(&range),
+                15,
+                Blank::Dates,
+                seed,
+                &options,
+            )
+        };
+        assert_eq!(sheet(7), sheet(7));
+        let sheet = sheet(7);
+        assert!(sheet.questions.starts_with("# Revolutions\n"));
+        assert!(sheet.questions.contains("15. ____________ Event "));
+        assert!(!sheet.questions.contains("16. "));
+        assert!(!sheet.questions.contains("Event 180"));
+        assert!(sheet.key.contains("** Event "));
+
+        let sheet = worldline.worksheet("All", None, 100, Blank::Descriptions, 1, &options);
+        assert!(sheet.questions.contains("60. 1809 ____"));
+        assert!(sheet.key.contains("1. 1750 **Event 1750**\n"));
+    }
+}